@@ -2,14 +2,37 @@
 //!
 //! This CLI provides commands for managing student repositories across
 //! GitHub, GitLab, Gitea, and local filesystem platforms.
+//!
+//! # Exit codes
+//!
+//! | Code | Meaning |
+//! |------|---------|
+//! | 0 | Success |
+//! | 1 | The command ran but failed, e.g. some repositories could not be created or cloned |
+//! | 2 | Invalid settings, arguments or input files; nothing was done |
+//! | 3 | The platform rejected the token or could not be reached |
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use repobee_core::{
-    setup_student_repos, CommonSettings, GuiSettings, Platform, PlatformAPI, SettingsManager,
-    StudentTeam,
+    check_assignments, clear_lms_cache, clone_student_repos, default_lms_cache_dir,
+    delete_single_repo, diff_roster, expected_repos, generate_repobee_yaml_with_progress,
+    list_local_templates, list_templates, open_issue_in_repos, parse_window_date, read_issue_body,
+    read_json_roster, read_roster_mapping, read_student_emails, read_teams_file, read_teams_files,
+    reconcile, repo_activity, resolve_dir, resolve_token, set_git_throttle, set_http_settings,
+    setup_student_repos_with_options, team_notifications, validate_repobee_yaml, write_mapping_csv,
+    write_notifications, write_yaml_file, CloneOptions, CloneResult, CommonSettings, ConfigError,
+    DirectoryLayout, DistributionMode, EmptyTeamPolicy, GitThrottle, GuiSettings, IssueTemplate,
+    MappingColumn, NotificationFormat, Platform, PlatformAPI, PlatformError, RepoNaming,
+    RosterDiff, SettingsManager, SetupOptions, StudentTeam, WorkDirCleanup, YamlConfig,
+    DEFAULT_CLONE_CONCURRENCY, DEFAULT_MAPPING_COLUMNS,
 };
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "repobee")]
@@ -75,7 +98,47 @@ struct Cli {
 
     /// Directory layout (by-team, flat, by-task)
     #[arg(long, global = true, value_name = "LAYOUT")]
-    directory_layout: Option<String>,
+    directory_layout: Option<DirectoryLayout>,
+
+    /// Student repository name template with {team} and {assignment} placeholders
+    #[arg(
+        long,
+        global = true,
+        value_name = "TEMPLATE",
+        conflicts_with = "repo_separator"
+    )]
+    repo_name_template: Option<String>,
+
+    /// Separator between team and assignment in student repository names
+    #[arg(long, global = true, value_name = "SEPARATOR")]
+    repo_separator: Option<String>,
+}
+
+/// Prefix and suffix of the student repository names, e.g. of the repositories of a
+/// trial run
+#[derive(Args)]
+struct RepoAffixes {
+    /// Prefix of student repository names, e.g. `test-` for a trial run
+    #[arg(long, default_value = "")]
+    repo_prefix: String,
+
+    /// Suffix of student repository names
+    #[arg(long, default_value = "")]
+    repo_suffix: String,
+}
+
+impl RepoAffixes {
+    /// Naming scheme of the student repositories, the same for every command
+    ///
+    /// The configured template, or the one given with `--repo-name-template` or
+    /// `--repo-separator`, between `--repo-prefix` and `--repo-suffix`.
+    fn naming(&self, config: &CommonSettings) -> Result<RepoNaming> {
+        RepoNaming::new(format!(
+            "{}{}{}",
+            self.repo_prefix, config.repo_name_template, self.repo_suffix
+        ))
+        .map_err(|e| Failure::Config.wrap(e))
+    }
 }
 
 #[derive(Subcommand)]
@@ -90,21 +153,95 @@ enum Commands {
         #[arg(long = "template")]
         templates: Vec<String>,
 
-        /// Student teams file (JSON/YAML format)
-        #[arg(long)]
-        teams_file: Option<PathBuf>,
+        /// Student teams file (JSON/YAML format); can be specified multiple times to merge
+        /// the rosters of several files
+        #[arg(long = "teams-file")]
+        teams_files: Vec<PathBuf>,
 
         /// Working directory for cloning templates
         #[arg(long)]
         work_dir: Option<PathBuf>,
 
-        /// Create private repositories
+        /// Create private repositories (defaults to the git_private_repos setting)
         #[arg(long)]
         private: Option<bool>,
 
+        /// Create public repositories when the platform does not allow private ones
+        #[arg(long)]
+        public_fallback: bool,
+
         /// Student teams in format "name:member1,member2" (can be specified multiple times)
         #[arg(long = "team")]
         teams: Vec<String>,
+
+        /// Warn when a template's default branch is not this branch
+        #[arg(long, value_name = "BRANCH")]
+        expected_branch: Option<String>,
+
+        /// Fail on archived templates instead of warning about them
+        #[arg(long)]
+        refuse_archived_templates: bool,
+
+        /// Stop at the first error instead of continuing with the remaining repositories
+        #[arg(long)]
+        fail_fast: bool,
+
+        /// Create repositories for teams without members instead of skipping those teams
+        #[arg(long)]
+        create_empty_teams: bool,
+
+        /// Let the platform create the repositories from templates marked as templates
+        /// there, instead of cloning and pushing them; other templates are still pushed
+        #[arg(long)]
+        platform_templates: bool,
+
+        /// Check after the setup that every new repository received the template content
+        #[arg(long)]
+        verify_pushes: bool,
+
+        /// Cancel the setup after this many minutes and report the unfinished repositories
+        #[arg(long, value_name = "MINUTES")]
+        timeout: Option<u64>,
+
+        #[command(flatten)]
+        affixes: RepoAffixes,
+
+        /// Allow work directory paths over the Windows path length limit
+        #[arg(long)]
+        long_paths: bool,
+
+        /// Remove the template working copies from the work directory after a successful setup
+        #[arg(long, conflicts_with = "prune_work_dir")]
+        clean_work_dir: bool,
+
+        /// Remove template working copies not used for this many days after a successful setup
+        #[arg(long, value_name = "DAYS")]
+        prune_work_dir: Option<u64>,
+
+        /// Write a CSV mapping each student to their team and repositories
+        #[arg(long, value_name = "PATH")]
+        mapping_csv: Option<PathBuf>,
+
+        /// Columns of the mapping CSV (git_id, team, assignment, repo, url, status)
+        #[arg(
+            long,
+            value_name = "COLUMNS",
+            value_delimiter = ',',
+            requires = "mapping_csv"
+        )]
+        mapping_columns: Vec<MappingColumn>,
+
+        /// Write each team's repository URLs and member emails for a mail merge
+        #[arg(long, value_name = "PATH", requires = "student_csv")]
+        notify_file: Option<PathBuf>,
+
+        /// Format of the notification file (csv or json)
+        #[arg(long, value_name = "FORMAT", default_value = "csv")]
+        notify_format: NotificationFormat,
+
+        /// Student CSV generated from the LMS, providing the member emails
+        #[arg(long, value_name = "PATH", requires = "notify_file")]
+        student_csv: Option<PathBuf>,
     },
 
     /// Verify platform settings and authentication
@@ -123,9 +260,159 @@ enum Commands {
         /// Specific assignments to clone (overrides settings)
         #[arg(long)]
         assignments: Option<String>,
+
+        /// Maximum number of repositories cloned at the same time, further capped by the
+        /// git_max_network_operations setting
+        #[arg(long, default_value_t = DEFAULT_CLONE_CONCURRENCY, value_name = "N")]
+        concurrency: usize,
+
+        /// Allow target paths over the Windows path length limit
+        #[arg(long)]
+        long_paths: bool,
+
+        /// Show which repositories would be cloned and where, without cloning
+        #[arg(long)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        affixes: RepoAffixes,
+
+        /// Start of the submission window (YYYY-MM-DD, YYYY-MM-DD HH:MM or RFC 3339)
+        #[arg(long, value_name = "DATE")]
+        since: Option<String>,
+
+        /// Deadline: check out the last commit at or before it and report later commits
+        #[arg(long, value_name = "DATE")]
+        until: Option<String>,
+
+        /// Fetch repositories cloned before and fast-forward them to the new commits
+        #[arg(long, conflicts_with = "until")]
+        update: bool,
+    },
+
+    /// Compare the student repositories on the platform with the roster
+    Reconcile {
+        /// Platform to use
+        #[arg(short, long, value_enum)]
+        platform: Option<PlatformType>,
+
+        /// Specific assignments to check (overrides settings)
+        #[arg(long)]
+        assignments: Option<String>,
+
+        /// Only compare repositories whose name contains this text
+        #[arg(long, value_name = "TEXT")]
+        filter: Option<String>,
+
+        #[command(flatten)]
+        affixes: RepoAffixes,
+    },
+
+    /// List the student repositories by last activity, least recently active first
+    Activity {
+        /// Platform to use
+        #[arg(short, long, value_enum)]
+        platform: Option<PlatformType>,
+
+        /// Specific assignments to list (overrides settings)
+        #[arg(long)]
+        assignments: Option<String>,
+
+        /// Setup date: flag repositories without activity after it (YYYY-MM-DD,
+        /// YYYY-MM-DD HH:MM or RFC 3339; a date alone covers the whole day)
+        #[arg(long, value_name = "DATE")]
+        since: Option<String>,
+
+        #[command(flatten)]
+        affixes: RepoAffixes,
+    },
+
+    /// Delete one student repository from the student organization
+    DeleteRepo {
+        /// Name of the repository, e.g. team-1-task-1
+        #[arg(value_name = "NAME")]
+        name: String,
+
+        /// Platform to use
+        #[arg(short, long, value_enum)]
+        platform: Option<PlatformType>,
+
+        /// Delete without asking to type the repository name
+        #[arg(long)]
+        yes: bool,
+    },
+
+    /// List the template repositories in the template group
+    ListTemplates {
+        /// Platform to use
+        #[arg(short, long, value_enum)]
+        platform: Option<PlatformType>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Show pending changes between an old and a new roster without applying them
+    RosterDiff {
+        /// Previously used teams file (JSON/YAML format)
+        #[arg(value_name = "OLD")]
+        old: PathBuf,
+
+        /// Freshly fetched teams file (JSON/YAML format)
+        #[arg(value_name = "NEW")]
+        new: PathBuf,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Check a teams file for problems without running setup
+    ValidateTeams {
+        /// Teams file (JSON/YAML format), defaults to the configured YAML file
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Create a teams file from a JSON roster exported by another system
+    ImportRoster {
+        /// JSON roster file
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// JSON file telling which roster keys hold the name, email, git id and group
+        #[arg(long, value_name = "FILE")]
+        mapping: Option<PathBuf>,
+
+        /// Teams file to write, defaults to the configured YAML file
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Put every student without a group in a team of their own
+        #[arg(long)]
+        individual_teams: bool,
+    },
+
+    /// Remove the LMS data cached by earlier runs
+    ClearLmsCache {
+        /// Only remove the data of this course
+        #[arg(long, value_name = "ID")]
+        course: Option<String>,
+    },
+
+    /// Issues in student repositories
+    Issues {
+        #[command(subcommand)]
+        action: IssuesAction,
     },
 
     /// Settings management commands
+    #[command(alias = "config")]
     Settings {
         #[command(subcommand)]
         action: SettingsAction,
@@ -134,14 +421,22 @@ enum Commands {
 
 #[derive(Subcommand)]
 enum SettingsAction {
-    /// Show current settings
-    Show,
+    /// Show the settings in effect, after the settings file, CLI options and REPOBEE_TOKEN
+    Show {
+        /// Output format; JSON lists every setting, with the tokens redacted
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
 
     /// Show settings file path
     Path,
 
     /// Reset settings to defaults
-    Reset,
+    Reset {
+        /// Only reset the course-specific settings, keeping URLs and tokens
+        #[arg(long)]
+        course_only: bool,
+    },
 
     /// Export settings to a file
     Export {
@@ -158,6 +453,40 @@ enum SettingsAction {
     },
 }
 
+#[derive(Subcommand)]
+enum IssuesAction {
+    /// Open the same issue in every student repository of an assignment
+    Open {
+        /// Platform to use
+        #[arg(short, long, value_enum)]
+        platform: Option<PlatformType>,
+
+        /// Assignment whose repositories get the issue
+        #[arg(long, value_name = "NAME")]
+        assignment: String,
+
+        /// Issue title; like the body it may use {{team}}, {{members}}, {{assignment}},
+        /// {{repo_name}}, {{repo_url}} and the --var variables
+        #[arg(long)]
+        title: String,
+
+        /// Issue body
+        #[arg(long, conflicts_with = "body_file")]
+        body: Option<String>,
+
+        /// Markdown file with the issue body
+        #[arg(long, value_name = "FILE")]
+        body_file: Option<PathBuf>,
+
+        /// Variable for the title and body, e.g. --var due_date=2024-05-01 for {{due_date}}
+        #[arg(long = "var", value_name = "NAME=VALUE")]
+        variables: Vec<String>,
+
+        #[command(flatten)]
+        affixes: RepoAffixes,
+    },
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum PlatformType {
     GitHub,
@@ -166,6 +495,12 @@ enum PlatformType {
     Local,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
 /// Configuration manager for CLI
 struct ConfigManager {
     settings_manager: SettingsManager,
@@ -178,8 +513,11 @@ impl ConfigManager {
         let settings_manager =
             SettingsManager::new().context("Failed to create settings manager")?;
 
-        // Load configuration or use defaults
-        let gui_settings = settings_manager.load().unwrap_or_default();
+        // Load configuration or use defaults, saying why so that a typo is not ignored
+        let gui_settings = settings_manager.load().unwrap_or_else(|e| {
+            eprintln!("⚠ Ignoring the settings file, using defaults: {}", e);
+            GuiSettings::default()
+        });
         let config = gui_settings.common;
 
         Ok(Self {
@@ -217,8 +555,14 @@ impl ConfigManager {
         if let Some(ref assignments) = cli.assignments {
             self.config.assignments = assignments.clone();
         }
-        if let Some(ref layout) = cli.directory_layout {
-            self.config.directory_layout = layout.parse().unwrap_or_default();
+        if let Some(layout) = cli.directory_layout {
+            self.config.directory_layout = layout;
+        }
+        if let Some(ref template) = cli.repo_name_template {
+            self.config.repo_name_template = template.clone();
+        }
+        if let Some(ref separator) = cli.repo_separator {
+            self.config.repo_name_template = format!("{{team}}{}{{assignment}}", separator);
         }
     }
 
@@ -270,6 +614,17 @@ impl ConfigManager {
         Ok(())
     }
 
+    /// Reset the course-specific configuration, keeping URLs and tokens
+    fn reset_course(&mut self) -> Result<()> {
+        self.config.reset_course_settings();
+        let gui_settings = repobee_core::GuiSettings::from_common(self.config.clone());
+        self.settings_manager
+            .save(&gui_settings)
+            .context("Failed to reset course settings")?;
+        println!("Course settings reset to defaults, URLs and tokens were kept");
+        Ok(())
+    }
+
     /// Show current configuration
     fn show(&self) {
         println!("Current Configuration:");
@@ -290,7 +645,16 @@ impl ConfigManager {
         println!("  Target Folder   : {}", self.config.target_folder);
         println!("  Assignments     : {}", self.config.assignments);
         println!("  Directory Layout: {}", self.config.directory_layout);
+        println!("  Repo Names      : {}", self.config.repo_name_template);
         println!();
+        let warnings = self.config.extension_warnings();
+        if !warnings.is_empty() {
+            println!("Warnings:");
+            for warning in warnings {
+                println!("  {}", warning);
+            }
+            println!();
+        }
         println!("Settings File:");
         println!(
             "  Location        : {}",
@@ -298,12 +662,98 @@ impl ConfigManager {
         );
     }
 
+    /// Show every setting in effect as JSON, with the tokens redacted
+    fn show_json(&self) -> Result<()> {
+        println!("{}", serde_json::to_string_pretty(&self.config.redacted())?);
+        Ok(())
+    }
+
     /// Get configuration
     fn config(&self) -> &CommonSettings {
         &self.config
     }
 }
 
+/// Category of a failed run, reported as the exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Failure {
+    /// The command ran but some or all of its work failed (exit code 1)
+    Partial,
+    /// Invalid settings, arguments or input files (exit code 2)
+    Config,
+    /// The token was rejected or the platform could not be reached (exit code 3)
+    Auth,
+}
+
+impl Failure {
+    fn exit_code(self) -> ExitCode {
+        ExitCode::from(match self {
+            Failure::Partial => 1,
+            Failure::Config => 2,
+            Failure::Auth => 3,
+        })
+    }
+
+    /// Error with this category and `message`
+    fn msg(self, message: impl fmt::Display) -> anyhow::Error {
+        self.wrap(anyhow::anyhow!("{}", message))
+    }
+
+    /// `error` with this category, whatever its causes are
+    fn wrap(self, error: impl Into<anyhow::Error>) -> anyhow::Error {
+        anyhow::Error::new(Categorized {
+            failure: self,
+            error: error.into(),
+        })
+    }
+
+    /// Category of `error`, decided by the first cause that has one
+    fn of(error: &anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if let Some(categorized) = cause.downcast_ref::<Categorized>() {
+                return categorized.failure;
+            }
+            if let Some(error) = cause.downcast_ref::<PlatformError>() {
+                return match error {
+                    PlatformError::BadCredentials(_)
+                    | PlatformError::NetworkError(_)
+                    | PlatformError::ServiceNotFound(_) => Failure::Auth,
+                    PlatformError::InvalidUrl(_) | PlatformError::InvalidConfig(_) => {
+                        Failure::Config
+                    }
+                    _ => Failure::Partial,
+                };
+            }
+            if cause.is::<ConfigError>()
+                || cause.is::<serde_json::Error>()
+                || cause.is::<serde_yaml::Error>()
+            {
+                return Failure::Config;
+            }
+        }
+        Failure::Partial
+    }
+}
+
+/// Error with a fixed [`Failure`] category, displayed as the error it wraps
+#[derive(Debug)]
+struct Categorized {
+    failure: Failure,
+    error: anyhow::Error,
+}
+
+impl fmt::Display for Categorized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for Categorized {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
 /// Parse team string in format "name:member1,member2" or "member1,member2" (auto-generated name)
 fn parse_team(team_str: &str) -> Result<StudentTeam> {
     if let Some((name, members_str)) = team_str.split_once(':') {
@@ -320,46 +770,60 @@ fn parse_team(team_str: &str) -> Result<StudentTeam> {
 
 /// Load teams from a JSON or YAML file
 fn load_teams_from_file(path: &PathBuf) -> Result<Vec<StudentTeam>> {
-    let content = std::fs::read_to_string(path)
-        .with_context(|| format!("Failed to read teams file: {}", path.display()))?;
-
-    // Try JSON first, then YAML
-    let teams: Vec<StudentTeam> = serde_json::from_str(&content)
-        .or_else(|_| serde_yaml::from_str(&content))
-        .with_context(|| "Failed to parse teams file (tried JSON and YAML)")?;
+    read_teams_file(path).map_err(|e| Failure::Config.wrap(e))
+}
 
-    Ok(teams)
+/// Load and merge the teams of several files, refusing files that conflict
+fn load_teams_from_files(paths: &[PathBuf]) -> Result<Vec<StudentTeam>> {
+    let merged = read_teams_files(paths).map_err(|e| Failure::Config.wrap(e))?;
+    if !merged.conflicts.is_empty() {
+        for conflict in &merged.conflicts {
+            eprintln!("  - {}", conflict);
+        }
+        return Err(Failure::Config.msg(format!(
+            "The teams files have {} conflicts",
+            merged.conflicts.len()
+        )));
+    }
+    Ok(merged.teams)
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run_setup(
     config: &CommonSettings,
     platform: Option<PlatformType>,
     templates: Vec<String>,
-    teams_file: Option<PathBuf>,
+    teams_files: Vec<PathBuf>,
     team_strings: Vec<String>,
     work_dir: Option<PathBuf>,
     private: Option<bool>,
+    options: SetupOptions,
+    mapping_csv: Option<(PathBuf, Vec<MappingColumn>)>,
+    notify: Option<(PathBuf, NotificationFormat, PathBuf)>,
 ) -> Result<()> {
-    // Load student teams
-    let yaml_path = if let Some(file) = teams_file {
-        file
-    } else if !config.yaml_file.is_empty() {
-        PathBuf::from(&config.yaml_file)
-    } else if !team_strings.is_empty() {
-        // Use team strings directly
-        PathBuf::new()
-    } else {
-        anyhow::bail!("No student teams specified. Use --yaml-file, --teams-file, or --team");
-    };
+    // Read the emails up front so a bad student CSV fails before any repository is created
+    let notify = notify
+        .map(|(path, format, student_csv)| {
+            read_student_emails(&student_csv).map(|emails| (path, format, emails))
+        })
+        .transpose()
+        .map_err(|e| Failure::Config.wrap(e))?;
 
-    let student_teams = if yaml_path.as_os_str().is_empty() {
-        // Parse from --team arguments
-        team_strings
+    // Load student teams
+    let student_teams = match teams_files.as_slice() {
+        [] if !config.yaml_file.is_empty() => {
+            load_teams_from_file(&PathBuf::from(&config.yaml_file))?
+        }
+        [] if !team_strings.is_empty() => team_strings
             .iter()
             .map(|s| parse_team(s))
-            .collect::<Result<Vec<_>>>()?
-    } else {
-        load_teams_from_file(&yaml_path)?
+            .collect::<Result<Vec<_>>>()?,
+        [] => {
+            return Err(Failure::Config
+                .msg("No student teams specified. Use --yaml-file, --teams-file, or --team"))
+        }
+        [file] => load_teams_from_file(file)?,
+        files => load_teams_from_files(files)?,
     };
 
     println!("RepoBee Setup");
@@ -373,7 +837,8 @@ async fn run_setup(
     // Determine platform
     let platform_type = platform.unwrap_or(PlatformType::GitLab);
     let base_url = &config.git_base_url;
-    let token = &config.git_access_token;
+    // The token is also used by git itself, so a `file:` reference is resolved here
+    let token = &resolve_token(&config.git_access_token)?;
     let org = &config.git_student_repos_group;
     let user = &config.git_user;
 
@@ -381,19 +846,22 @@ async fn run_setup(
     let api = match platform_type {
         PlatformType::GitHub => {
             if token.is_empty() {
-                anyhow::bail!("Token required for GitHub. Set with --git-token or REPOBEE_TOKEN");
+                return Err(Failure::Config
+                    .msg("Token required for GitHub. Set with --git-token or REPOBEE_TOKEN"));
             }
             Platform::github(base_url.clone(), token.clone(), org.clone(), user.clone())?
         }
         PlatformType::GitLab => {
             if token.is_empty() {
-                anyhow::bail!("Token required for GitLab. Set with --git-token or REPOBEE_TOKEN");
+                return Err(Failure::Config
+                    .msg("Token required for GitLab. Set with --git-token or REPOBEE_TOKEN"));
             }
             Platform::gitlab(base_url.clone(), token.clone(), org.clone(), user.clone())?
         }
         PlatformType::Gitea => {
             if token.is_empty() {
-                anyhow::bail!("Token required for Gitea. Set with --git-token or REPOBEE_TOKEN");
+                return Err(Failure::Config
+                    .msg("Token required for Gitea. Set with --git-token or REPOBEE_TOKEN"));
             }
             Platform::gitea(base_url.clone(), token.clone(), org.clone(), user.clone())?
         }
@@ -407,8 +875,21 @@ async fn run_setup(
         .context("Failed to verify platform settings")?;
     println!("✓ Platform settings verified\n");
 
-    // Determine work directory
-    let work_dir_path = work_dir.unwrap_or_else(|| PathBuf::from("./repobee-work"));
+    // Determine work directory, the templates are cloned into directories named after them
+    let template_names: Vec<&str> = templates
+        .iter()
+        .filter_map(|t| t.trim_end_matches('/').rsplit('/').next())
+        .map(|t| t.trim_end_matches(".git"))
+        .collect();
+    let work_dir = resolve_dir(
+        &work_dir.unwrap_or_else(|| PathBuf::from("./repobee-work")),
+        &template_names,
+    )?;
+    println!("Work directory: {}", work_dir.path.display());
+    for warning in &work_dir.warnings {
+        println!("⚠ {}", warning);
+    }
+    let work_dir_path = work_dir.path;
 
     // Create work directory
     std::fs::create_dir_all(&work_dir_path).with_context(|| {
@@ -419,18 +900,37 @@ async fn run_setup(
     })?;
 
     // Run setup
-    let result = setup_student_repos(
+    let result = setup_student_repos_with_options(
         &templates,
         &student_teams,
         &api,
         &work_dir_path,
-        private.unwrap_or(true),
+        private.unwrap_or(config.git_private_repos),
         Some(token.as_str()),
+        &options,
     )
     .await?;
 
     // Print summary
     println!("\n=== Final Summary ===");
+    let mut branches: Vec<_> = result.template_branches.iter().collect();
+    branches.sort();
+    for (template, branch) in branches {
+        println!("  Template {} uses branch: {}", template, branch);
+    }
+    for warning in &result.warnings {
+        println!("⚠ {}", warning);
+    }
+    println!("  Time: {}", result.timings.summary());
+    for (assignment, repos) in &result.by_assignment {
+        println!(
+            "  Assignment {}: {} created, {} already existed, {} errors",
+            assignment,
+            repos.created.len(),
+            repos.existing.len(),
+            result.assignment_errors(assignment).len()
+        );
+    }
     println!(
         "✓ Successfully created: {} repositories",
         result.successful_repos.len()
@@ -441,6 +941,30 @@ async fn run_setup(
             result.existing_repos.len()
         );
     }
+    let mut copied: Vec<&str> = result
+        .distribution
+        .iter()
+        .filter(|(_, mode)| **mode == DistributionMode::PlatformTemplate)
+        .map(|(name, _)| name.as_str())
+        .collect();
+    if !copied.is_empty() {
+        copied.sort_unstable();
+        println!("  Created from platform templates: {}", copied.join(", "));
+        println!(
+            "  Cloned and pushed: {} repositories",
+            result.distribution.len() - copied.len()
+        );
+    }
+    if !result.empty_repos.is_empty() {
+        println!("✗ Empty after the setup: {}", result.empty_repos.join(", "));
+    }
+    if !result.skipped_teams.is_empty() {
+        println!(
+            "⚠ Skipped {} teams without members: {}",
+            result.skipped_teams.len(),
+            result.skipped_teams.join(", ")
+        );
+    }
     if !result.errors.is_empty() {
         println!("✗ Errors: {} repositories", result.errors.len());
         for error in &result.errors {
@@ -451,12 +975,231 @@ async fn run_setup(
         }
     }
 
+    if let Some((path, columns)) = mapping_csv {
+        let columns = if columns.is_empty() {
+            DEFAULT_MAPPING_COLUMNS.to_vec()
+        } else {
+            columns
+        };
+        write_mapping_csv(&result, &columns, &path)?;
+        println!("  Mapping written to: {}", path.display());
+    }
+
+    if let Some((path, format, emails)) = notify {
+        let records = team_notifications(&result, &emails);
+        write_notifications(&records, format, &path)?;
+        println!("  Notifications written to: {}", path.display());
+        let missing: Vec<&str> = records
+            .iter()
+            .flat_map(|r| r.missing_emails.iter().map(String::as_str))
+            .collect();
+        if !missing.is_empty() {
+            println!("  ⚠ No email address for: {}", missing.join(", "));
+        }
+    }
+
+    if result.stopped_early {
+        return Err(Failure::Partial.msg("Setup stopped at the first error (--fail-fast)"));
+    }
+    if result.timed_out {
+        println!(
+            "✗ Unfinished: {} repositories",
+            result.unfinished_repos.len()
+        );
+        for name in &result.unfinished_repos {
+            println!("  - {}", name);
+        }
+        return Err(Failure::Partial.msg("Setup timed out (--timeout)"));
+    }
     if result.is_success() {
         println!("\n🎉 Setup completed successfully!");
         Ok(())
     } else {
-        anyhow::bail!("Setup completed with {} errors", result.errors.len());
+        Err(Failure::Partial.msg(format!(
+            "Setup completed with {} errors",
+            result.errors.len()
+        )))
+    }
+}
+
+fn print_clone_plan(result: &CloneResult) {
+    println!("\n=== Clone Plan (dry run) ===");
+    for repo in &result.planned_repos {
+        if let Some(path) = &repo.path {
+            println!("  {} -> {}", repo.name, path.display());
+        }
+    }
+    for repo in &result.existing_repos {
+        if let Some(path) = &repo.path {
+            println!(
+                "  {} -> {} (already present, skipped)",
+                repo.name,
+                path.display()
+            );
+        }
+    }
+    for error in &result.errors {
+        eprintln!(
+            "  ✗ {}/{}: {}",
+            error.team_name, error.repo_name, error.error
+        );
+    }
+    println!(
+        "Would clone {} repositories, {} already present, {} errors",
+        result.planned_repos.len(),
+        result.existing_repos.len(),
+        result.errors.len()
+    );
+}
+
+fn print_commit_windows(result: &CloneResult) {
+    println!(
+        "  Checked out at the deadline: {} repositories, {} with late commits",
+        result.commit_windows.len(),
+        result.late_repos().len()
+    );
+    for (name, window) in &result.commit_windows {
+        match &window.commit {
+            Some(commit) if window.late => {
+                println!("  ⚠ {}: late commits, checked out {:.8}", name, commit)
+            }
+            Some(_) => {}
+            None => println!("  ⚠ {}: no commits before the deadline", name),
+        }
+    }
+}
+
+fn run_roster_diff(old: &PathBuf, new: &PathBuf, format: OutputFormat) -> Result<()> {
+    let old_teams = load_teams_from_file(old)?;
+    let new_teams = load_teams_from_file(new)?;
+    let diff = diff_roster(&old_teams, &new_teams);
+
+    match format {
+        OutputFormat::Json => {
+            let json =
+                serde_json::to_string_pretty(&diff).context("Failed to serialize roster diff")?;
+            println!("{}", json);
+        }
+        OutputFormat::Text => print_roster_diff(&diff),
+    }
+
+    Ok(())
+}
+
+fn run_import_roster(
+    config: &CommonSettings,
+    file: &Path,
+    mapping: Option<&Path>,
+    output: &Path,
+    individual_teams: bool,
+) -> Result<()> {
+    let mapping = mapping
+        .map(read_roster_mapping)
+        .transpose()?
+        .unwrap_or_default();
+    let students = read_json_roster(file, &mapping)?;
+
+    let yaml_config = YamlConfig {
+        member_option: config.lms_member_option.into(),
+        include_group: config.lms_include_group,
+        include_member: config.lms_include_member,
+        include_initials: config.lms_include_initials,
+        full_groups: config.lms_full_groups,
+        grouping: Default::default(),
+        individual_teams,
+        team_name_template: None,
+        course_code: None,
+        member_format: Default::default(),
+    };
+    let generation = generate_repobee_yaml_with_progress(&students, &yaml_config, |_, _, _| {})?;
+    for note in &generation.notes {
+        println!("  ⚠ {}", note);
+    }
+    write_yaml_file(&generation.teams, output)?;
+
+    println!(
+        "✓ Imported {} students into {} teams: {}",
+        students.len(),
+        generation.teams.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+fn run_clear_lms_cache(course: Option<&str>) -> Result<()> {
+    let cache_dir = default_lms_cache_dir();
+    let removed = clear_lms_cache(&cache_dir, course)?;
+    println!(
+        "✓ Removed the cached LMS data of {} courses: {}",
+        removed,
+        cache_dir.display()
+    );
+    Ok(())
+}
+
+fn run_validate_teams(file: &PathBuf, format: OutputFormat) -> Result<()> {
+    let teams = load_teams_from_file(file)?;
+    let problems = validate_repobee_yaml(&teams);
+
+    match format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&problems)
+                .context("Failed to serialize teams problems")?;
+            println!("{}", json);
+        }
+        OutputFormat::Text => {
+            for problem in &problems {
+                println!("  ✗ {}", problem);
+            }
+            if problems.is_empty() {
+                println!(
+                    "✓ {}: {} teams, no problems found",
+                    file.display(),
+                    teams.len()
+                );
+            }
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(Failure::Config.msg(format!(
+            "{} has {} problems",
+            file.display(),
+            problems.len()
+        )));
+    }
+    Ok(())
+}
+
+fn print_roster_diff(diff: &RosterDiff) {
+    if diff.is_empty() {
+        println!("No roster changes.");
+        return;
     }
+
+    println!("Pending roster changes (dry run, nothing is applied):");
+    for team in &diff.teams_added {
+        println!("  + {}: {}", team.name, team.members.join(", "));
+    }
+    for team in &diff.teams_removed {
+        println!("  - {}: {}", team.name, team.members.join(", "));
+    }
+    for change in &diff.teams_changed {
+        let members: Vec<String> = change
+            .members_added
+            .iter()
+            .map(|m| format!("+{}", m))
+            .chain(change.members_removed.iter().map(|m| format!("-{}", m)))
+            .collect();
+        println!("  ~ {}: {}", change.team, members.join(" "));
+    }
+    println!();
+    println!(
+        "Teams to add: {}, teams to remove: {}, teams with member changes: {}",
+        diff.teams_added.len(),
+        diff.teams_removed.len(),
+        diff.teams_changed.len()
+    );
 }
 
 async fn run_verify(config: &CommonSettings, platform: Option<PlatformType>) -> Result<()> {
@@ -491,12 +1234,577 @@ async fn run_verify(config: &CommonSettings, platform: Option<PlatformType>) ->
     Ok(())
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+/// Names of the templates in the template group
+async fn fetch_templates(
+    config: &CommonSettings,
+    platform_type: PlatformType,
+) -> Result<Vec<String>> {
+    let base_url = &config.git_base_url;
+    let token = &config.git_access_token;
+    let group = &config.git_template_group;
+    let user = &config.git_user;
 
-    // Create configuration manager
-    let mut config_mgr = ConfigManager::new()?;
+    let templates = match platform_type {
+        PlatformType::Local => {
+            // Local templates are plain git directories, not platform repos
+            let template_dir = if group.starts_with('/') {
+                PathBuf::from(group)
+            } else {
+                PathBuf::from(base_url).join(group)
+            };
+            list_local_templates(&template_dir)?
+        }
+        PlatformType::GitHub => {
+            let api =
+                Platform::github(base_url.clone(), token.clone(), group.clone(), user.clone())?;
+            list_templates(&api).await?
+        }
+        PlatformType::GitLab => {
+            let api =
+                Platform::gitlab(base_url.clone(), token.clone(), group.clone(), user.clone())?;
+            list_templates(&api).await?
+        }
+        PlatformType::Gitea => {
+            let api =
+                Platform::gitea(base_url.clone(), token.clone(), group.clone(), user.clone())?;
+            list_templates(&api).await?
+        }
+    };
+    Ok(templates)
+}
+
+/// Fail on assignments without a template, suggesting the closest template name
+///
+/// Skipped when no template group is configured or its templates cannot be listed, so the
+/// check never blocks a command that would otherwise work.
+async fn check_assignment_templates(
+    config: &CommonSettings,
+    platform_type: PlatformType,
+    assignments: &[String],
+) -> Result<()> {
+    if config.git_template_group.is_empty() {
+        return Ok(());
+    }
+    match fetch_templates(config, platform_type).await {
+        Ok(templates) if !templates.is_empty() => Ok(check_assignments(assignments, &templates)?),
+        _ => Ok(()),
+    }
+}
+
+async fn run_list_templates(
+    config: &CommonSettings,
+    platform: Option<PlatformType>,
+    format: OutputFormat,
+) -> Result<()> {
+    let group = &config.git_template_group;
+    let templates = fetch_templates(config, platform.unwrap_or(PlatformType::GitLab)).await?;
+
+    match format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&templates)
+                .context("Failed to serialize templates")?;
+            println!("{}", json);
+        }
+        OutputFormat::Text => {
+            if templates.is_empty() {
+                println!("No templates found in {}", group);
+            }
+            for template in &templates {
+                println!("{}", template);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_clone(
+    config: &CommonSettings,
+    platform: Option<PlatformType>,
+    assignments: Option<&str>,
+    options: CloneOptions,
+) -> Result<()> {
+    if config.yaml_file.is_empty() {
+        return Err(Failure::Config.msg("No student teams specified. Use --yaml-file"));
+    }
+    let student_teams = load_teams_from_file(&PathBuf::from(&config.yaml_file))?;
+    let assignments: Vec<String> = assignments
+        .unwrap_or(&config.assignments)
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if assignments.is_empty() {
+        return Err(Failure::Config.msg("No assignments specified. Use --assignments"));
+    }
+    check_assignment_templates(
+        config,
+        platform.unwrap_or(PlatformType::GitLab),
+        &assignments,
+    )
+    .await?;
+    let target_folder = if config.target_folder.is_empty() {
+        PathBuf::from(".")
+    } else {
+        PathBuf::from(&config.target_folder)
+    };
+    // Depending on the layout, entries are named after repositories, teams or assignments
+    let repo_names = assignments
+        .iter()
+        .flat_map(|a| {
+            student_teams
+                .iter()
+                .map(|t| options.naming.repo_name(&t.name, a))
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let expected: Vec<&str> = repo_names
+        .iter()
+        .chain(&assignments)
+        .map(String::as_str)
+        .chain(student_teams.iter().map(|t| t.name.as_str()))
+        .collect();
+    let target_folder = resolve_dir(&target_folder, &expected)?;
+    for warning in &target_folder.warnings {
+        println!("⚠ {}", warning);
+    }
+    let target_folder = target_folder.path;
+
+    println!("RepoBee Clone");
+    println!("=============");
+    println!("Platform: {:?}", platform);
+    println!("Organization: {}", config.git_student_repos_group);
+    println!("Assignments: {:?}", assignments);
+    println!("Teams: {}", student_teams.len());
+    println!("Target folder: {}", target_folder.display());
+    println!("Layout: {}", options.layout);
+    println!();
+
+    let platform_type = platform.unwrap_or(PlatformType::GitLab);
+    let base_url = &config.git_base_url;
+    let token = &resolve_token(&config.git_access_token)?;
+    let org = &config.git_student_repos_group;
+    let user = &config.git_user;
+
+    let api = match platform_type {
+        PlatformType::GitHub => {
+            Platform::github(base_url.clone(), token.clone(), org.clone(), user.clone())?
+        }
+        PlatformType::GitLab => {
+            Platform::gitlab(base_url.clone(), token.clone(), org.clone(), user.clone())?
+        }
+        PlatformType::Gitea => {
+            Platform::gitea(base_url.clone(), token.clone(), org.clone(), user.clone())?
+        }
+        PlatformType::Local => Platform::local(PathBuf::from(base_url), org.clone(), user.clone())?,
+    };
+
+    if options.dry_run {
+        println!("Resolving repositories (dry run)...");
+    } else {
+        println!(
+            "Cloning {} repositories, {} at a time...",
+            assignments.len() * student_teams.len(),
+            options.concurrency.max(1)
+        );
+    }
+    let token = if token.is_empty() {
+        None
+    } else {
+        Some(token.as_str())
+    };
+    let result = clone_student_repos(
+        &assignments,
+        &student_teams,
+        &api,
+        &target_folder,
+        token,
+        &options,
+    )
+    .await
+    .context("Clone failed")?;
+
+    if options.dry_run {
+        print_clone_plan(&result);
+        if !result.is_success() {
+            return Err(Failure::Partial.msg(format!(
+                "{} repositories cannot be cloned",
+                result.errors.len()
+            )));
+        }
+        return Ok(());
+    }
+
+    println!("\n=== Final Summary ===");
+    println!(
+        "✓ Successfully cloned: {} repositories",
+        result.successful_repos.len()
+    );
+    if !result.resumed_repos.is_empty() {
+        println!(
+            "✓ Cloned again after an unfinished clone: {} repositories",
+            result.resumed_repos.len()
+        );
+    }
+    if !result.updated_repos.is_empty() {
+        println!(
+            "✓ Updated to new commits: {} repositories",
+            result.updated_repos.len()
+        );
+    }
+    if !result.existing_repos.is_empty() {
+        println!(
+            "  Already present: {} repositories",
+            result.existing_repos.len()
+        );
+    }
+    if options.until.is_some() {
+        print_commit_windows(&result);
+    }
+    if !result.errors.is_empty() {
+        println!("✗ Errors: {} repositories", result.errors.len());
+        for error in &result.errors {
+            eprintln!(
+                "  - {}/{}: {}",
+                error.team_name, error.repo_name, error.error
+            );
+        }
+    }
+
+    if result.is_success() {
+        println!("\n🎉 Clone completed successfully!");
+        Ok(())
+    } else {
+        Err(Failure::Partial.msg(format!(
+            "Clone completed with {} errors",
+            result.errors.len()
+        )))
+    }
+}
+
+async fn run_reconcile(
+    config: &CommonSettings,
+    platform: Option<PlatformType>,
+    assignments: Option<&str>,
+    filter: Option<&str>,
+    naming: &RepoNaming,
+) -> Result<()> {
+    if config.yaml_file.is_empty() {
+        return Err(Failure::Config.msg("No student teams specified. Use --yaml-file"));
+    }
+    let student_teams = load_teams_from_file(&PathBuf::from(&config.yaml_file))?;
+    let assignments: Vec<String> = assignments
+        .unwrap_or(&config.assignments)
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if assignments.is_empty() {
+        return Err(Failure::Config.msg("No assignments specified. Use --assignments"));
+    }
+    check_assignment_templates(
+        config,
+        platform.unwrap_or(PlatformType::GitLab),
+        &assignments,
+    )
+    .await?;
+
+    println!("RepoBee Reconcile");
+    println!("=================");
+    println!("Platform: {:?}", platform);
+    println!("Organization: {}", config.git_student_repos_group);
+    println!("Assignments: {:?}", assignments);
+    println!("Teams: {}", student_teams.len());
+    println!();
+
+    let platform_type = platform.unwrap_or(PlatformType::GitLab);
+    let base_url = &config.git_base_url;
+    let token = &resolve_token(&config.git_access_token)?;
+    let org = &config.git_student_repos_group;
+    let user = &config.git_user;
+
+    let api = match platform_type {
+        PlatformType::GitHub => {
+            Platform::github(base_url.clone(), token.clone(), org.clone(), user.clone())?
+        }
+        PlatformType::GitLab => {
+            Platform::gitlab(base_url.clone(), token.clone(), org.clone(), user.clone())?
+        }
+        PlatformType::Gitea => {
+            Platform::gitea(base_url.clone(), token.clone(), org.clone(), user.clone())?
+        }
+        PlatformType::Local => Platform::local(PathBuf::from(base_url), org.clone(), user.clone())?,
+    };
+
+    let result = reconcile(&assignments, &student_teams, &api, naming, filter)
+        .await
+        .context("Reconcile failed")?;
+
+    println!("✓ Present: {} repositories", result.matched.len());
+    if !result.missing.is_empty() {
+        println!("✗ Missing: {} repositories", result.missing.len());
+        for repo in &result.missing {
+            println!("  - {} ({}, {})", repo.name, repo.team, repo.assignment);
+        }
+    }
+    if !result.extra.is_empty() {
+        println!("⚠ Not in the roster: {} repositories", result.extra.len());
+        for repo in &result.extra {
+            println!("  - {}", repo.name);
+        }
+    }
+
+    if result.is_complete() {
+        println!("\n🎉 All expected repositories exist");
+        Ok(())
+    } else {
+        Err(Failure::Partial.msg(format!(
+            "{} expected repositories are missing",
+            result.missing.len()
+        )))
+    }
+}
+
+async fn run_activity(
+    config: &CommonSettings,
+    platform: Option<PlatformType>,
+    assignments: Option<&str>,
+    since: Option<&str>,
+    naming: &RepoNaming,
+) -> Result<()> {
+    if config.yaml_file.is_empty() {
+        return Err(Failure::Config.msg("No student teams specified. Use --yaml-file"));
+    }
+    let since = since
+        .map(|date| parse_window_date(date, true))
+        .transpose()
+        .map_err(|e| Failure::Config.wrap(e))?;
+    let student_teams = load_teams_from_file(&PathBuf::from(&config.yaml_file))?;
+    let assignments: Vec<String> = assignments
+        .unwrap_or(&config.assignments)
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if assignments.is_empty() {
+        return Err(Failure::Config.msg("No assignments specified. Use --assignments"));
+    }
+    let repos = expected_repos(&assignments, &student_teams, naming)
+        .map_err(|e| Failure::Config.wrap(e))?;
+
+    println!("RepoBee Activity");
+    println!("================");
+    println!("Organization: {}", config.git_student_repos_group);
+    println!("Assignments: {:?}", assignments);
+    println!("Repositories: {}", repos.len());
+    println!();
+
+    let platform_type = platform.unwrap_or(PlatformType::GitLab);
+    let base_url = &config.git_base_url;
+    let token = &resolve_token(&config.git_access_token)?;
+    let org = &config.git_student_repos_group;
+    let user = &config.git_user;
+
+    let api = match platform_type {
+        PlatformType::GitHub => {
+            Platform::github(base_url.clone(), token.clone(), org.clone(), user.clone())?
+        }
+        PlatformType::GitLab => {
+            Platform::gitlab(base_url.clone(), token.clone(), org.clone(), user.clone())?
+        }
+        PlatformType::Gitea => {
+            Platform::gitea(base_url.clone(), token.clone(), org.clone(), user.clone())?
+        }
+        PlatformType::Local => Platform::local(PathBuf::from(base_url), org.clone(), user.clone())?,
+    };
+
+    let report = repo_activity(&api, &repos).await;
+
+    for activity in &report.repos {
+        let marker = if activity.is_inactive(since) {
+            "⚠"
+        } else {
+            " "
+        };
+        let last = match activity.last_activity {
+            Some(time) => time.format("%Y-%m-%d %H:%M UTC").to_string(),
+            None => "never".to_string(),
+        };
+        println!(
+            "{} {:<20} {} ({})",
+            marker, last, activity.repo.name, activity.repo.team
+        );
+    }
+    let inactive = report.inactive(since).len();
+    if inactive > 0 {
+        match since {
+            Some(since) => println!(
+                "\n⚠ No activity since {}: {} repositories",
+                since.format("%Y-%m-%d %H:%M UTC"),
+                inactive
+            ),
+            None => println!("\n⚠ No activity: {} repositories", inactive),
+        }
+    }
+    if !report.errors.is_empty() {
+        println!("✗ Failed: {} repositories", report.errors.len());
+        for error in &report.errors {
+            println!("  - {}: {}", error.repo_name, error.error);
+        }
+        return Err(Failure::Partial.msg(format!(
+            "The activity of {} repositories could not be looked up",
+            report.errors.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Parse `--var NAME=VALUE` options
+fn parse_issue_variables(variables: &[String]) -> Result<BTreeMap<String, String>> {
+    variables
+        .iter()
+        .map(|variable| {
+            let (name, value) = variable
+                .split_once('=')
+                .filter(|(name, _)| !name.trim().is_empty())
+                .ok_or_else(|| {
+                    Failure::Config.msg(format!("Invalid --var '{}', use NAME=VALUE", variable))
+                })?;
+            Ok((name.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+async fn run_open_issue(
+    config: &CommonSettings,
+    platform: Option<PlatformType>,
+    assignment: &str,
+    issue: &IssueTemplate,
+    naming: &RepoNaming,
+) -> Result<()> {
+    if config.yaml_file.is_empty() {
+        return Err(Failure::Config.msg("No student teams specified. Use --yaml-file"));
+    }
+    let student_teams = load_teams_from_file(&PathBuf::from(&config.yaml_file))?;
+    let repos = expected_repos(&[assignment.to_string()], &student_teams, naming)
+        .map_err(|e| Failure::Config.wrap(e))?;
+
+    println!("RepoBee Issues");
+    println!("==============");
+    println!("Organization: {}", config.git_student_repos_group);
+    println!("Assignment: {}", assignment);
+    println!("Title: {}", issue.title);
+    println!("Repositories: {}", repos.len());
+    println!();
+
+    let platform_type = platform.unwrap_or(PlatformType::GitLab);
+    let base_url = &config.git_base_url;
+    let token = &resolve_token(&config.git_access_token)?;
+    let org = &config.git_student_repos_group;
+    let user = &config.git_user;
+
+    let api = match platform_type {
+        PlatformType::GitHub => {
+            Platform::github(base_url.clone(), token.clone(), org.clone(), user.clone())?
+        }
+        PlatformType::GitLab => {
+            Platform::gitlab(base_url.clone(), token.clone(), org.clone(), user.clone())?
+        }
+        PlatformType::Gitea => {
+            Platform::gitea(base_url.clone(), token.clone(), org.clone(), user.clone())?
+        }
+        PlatformType::Local => Platform::local(PathBuf::from(base_url), org.clone(), user.clone())?,
+    };
+
+    let result = open_issue_in_repos(&api, &repos, issue)
+        .await
+        .map_err(|e| Failure::Config.wrap(e))?;
+
+    println!("✓ Opened: {} issues", result.opened.len());
+    if !result.unknown_variables.is_empty() {
+        println!(
+            "⚠ Unknown variables left as written: {}",
+            result.unknown_variables.join(", ")
+        );
+    }
+    if !result.errors.is_empty() {
+        println!("✗ Failed: {} repositories", result.errors.len());
+        for error in &result.errors {
+            println!("  - {}: {}", error.repo_name, error.error);
+        }
+    }
+
+    if result.is_success() {
+        Ok(())
+    } else {
+        Err(Failure::Partial.msg(format!(
+            "The issue could not be opened in {} repositories",
+            result.errors.len()
+        )))
+    }
+}
+
+async fn run_delete_repo(
+    config: &CommonSettings,
+    platform: Option<PlatformType>,
+    name: &str,
+    yes: bool,
+) -> Result<()> {
+    let platform_type = platform.unwrap_or(PlatformType::GitLab);
+    let base_url = &config.git_base_url;
+    let token = &resolve_token(&config.git_access_token)?;
+    let org = &config.git_student_repos_group;
+    let user = &config.git_user;
+
+    let api = match platform_type {
+        PlatformType::GitHub => {
+            Platform::github(base_url.clone(), token.clone(), org.clone(), user.clone())?
+        }
+        PlatformType::GitLab => {
+            Platform::gitlab(base_url.clone(), token.clone(), org.clone(), user.clone())?
+        }
+        PlatformType::Gitea => {
+            Platform::gitea(base_url.clone(), token.clone(), org.clone(), user.clone())?
+        }
+        PlatformType::Local => Platform::local(PathBuf::from(base_url), org.clone(), user.clone())?,
+    };
+
+    if !yes {
+        if !io::stdin().is_terminal() {
+            return Err(Failure::Config.msg(format!(
+                "Deleting '{}' needs confirmation; pass --yes when not running interactively",
+                name
+            )));
+        }
+        println!("This permanently deletes {}/{} and its history.", org, name);
+        print!("Type the repository name to confirm: ");
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if answer.trim() != name {
+            println!("Cancelled, nothing was deleted");
+            return Ok(());
+        }
+    }
+
+    let repo = delete_single_repo(&api, name).await?;
+    println!("✓ Deleted {}/{}", org, repo.name);
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Cli::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            Failure::of(&e).exit_code()
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    // Create configuration manager
+    let mut config_mgr = ConfigManager::new()?;
 
     // Load configuration from specific file if requested
     if let Some(ref load_path) = cli.load {
@@ -510,6 +1818,8 @@ async fn main() -> Result<()> {
 
     // Apply CLI overrides
     config_mgr.apply_overrides(&cli);
+    set_git_throttle(GitThrottle::from_settings(config_mgr.config()));
+    set_http_settings(config_mgr.config()).map_err(|e| Failure::Config.wrap(e))?;
 
     // Handle show settings
     if cli.show {
@@ -519,14 +1829,17 @@ async fn main() -> Result<()> {
 
     // If no command was provided, error
     let Some(ref command) = cli.command else {
-        anyhow::bail!("No command specified. Use --help to see available commands or --show to display current settings");
+        return Err(Failure::Config.msg("No command specified. Use --help to see available commands or --show to display current settings"));
     };
 
     // Handle settings subcommand
     if let Commands::Settings { action } = command {
         match action {
-            SettingsAction::Show => {
-                config_mgr.show();
+            SettingsAction::Show { format } => {
+                match format {
+                    OutputFormat::Text => config_mgr.show(),
+                    OutputFormat::Json => config_mgr.show_json()?,
+                }
                 return Ok(());
             }
             SettingsAction::Path => {
@@ -536,8 +1849,12 @@ async fn main() -> Result<()> {
                 );
                 return Ok(());
             }
-            SettingsAction::Reset => {
-                config_mgr.reset()?;
+            SettingsAction::Reset { course_only } => {
+                if *course_only {
+                    config_mgr.reset_course()?;
+                } else {
+                    config_mgr.reset()?;
+                }
                 return Ok(());
             }
             SettingsAction::Export { path } => {
@@ -556,26 +1873,200 @@ async fn main() -> Result<()> {
         Commands::Setup {
             platform,
             templates,
-            teams_file,
+            teams_files,
             work_dir,
             private,
+            public_fallback,
             teams,
+            expected_branch,
+            refuse_archived_templates,
+            fail_fast,
+            create_empty_teams,
+            platform_templates,
+            verify_pushes,
+            timeout,
+            affixes,
+            long_paths,
+            clean_work_dir,
+            prune_work_dir,
+            mapping_csv,
+            mapping_columns,
+            notify_file,
+            notify_format,
+            student_csv,
         } => {
+            let naming = affixes.naming(config_mgr.config())?;
+            let options = SetupOptions {
+                expected_branch: expected_branch.clone(),
+                fail_fast: *fail_fast,
+                naming,
+                long_paths: *long_paths,
+                work_dir_cleanup: match (*clean_work_dir, *prune_work_dir) {
+                    (true, _) => WorkDirCleanup::Remove,
+                    (false, Some(days)) => {
+                        WorkDirCleanup::PruneOlderThan(Duration::from_secs(days * 24 * 60 * 60))
+                    }
+                    (false, None) => WorkDirCleanup::Keep,
+                },
+                public_fallback: *public_fallback || config_mgr.config().git_public_fallback,
+                refuse_archived_templates: *refuse_archived_templates,
+                empty_teams: if *create_empty_teams {
+                    EmptyTeamPolicy::Create
+                } else {
+                    EmptyTeamPolicy::Skip
+                },
+                distribution: if *platform_templates {
+                    DistributionMode::PlatformTemplate
+                } else {
+                    DistributionMode::ClonePush
+                },
+                verify_pushes: *verify_pushes,
+                timeout: timeout.map(|minutes| Duration::from_secs(minutes * 60)),
+                ..Default::default()
+            };
             run_setup(
                 config_mgr.config(),
                 *platform,
                 templates.clone(),
-                teams_file.clone(),
+                teams_files.clone(),
                 teams.clone(),
                 work_dir.clone(),
                 *private,
+                options,
+                mapping_csv
+                    .clone()
+                    .map(|path| (path, mapping_columns.clone())),
+                notify_file
+                    .clone()
+                    .zip(student_csv.clone())
+                    .map(|(path, student_csv)| (path, *notify_format, student_csv)),
             )
             .await
         }
         Commands::Verify { platform } => run_verify(config_mgr.config(), *platform).await,
-        Commands::Clone { .. } => {
-            anyhow::bail!("Clone command not yet implemented")
+        Commands::ListTemplates { platform, format } => {
+            run_list_templates(config_mgr.config(), *platform, *format).await
+        }
+        Commands::RosterDiff { old, new, format } => run_roster_diff(old, new, *format),
+        Commands::ValidateTeams { file, format } => {
+            let file = file
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(&config_mgr.config().yaml_file));
+            run_validate_teams(&file, *format)
+        }
+        Commands::ImportRoster {
+            file,
+            mapping,
+            output,
+            individual_teams,
+        } => {
+            let config = config_mgr.config();
+            let output = output
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(&config.yaml_file));
+            run_import_roster(config, file, mapping.as_deref(), &output, *individual_teams)
+        }
+        Commands::ClearLmsCache { course } => run_clear_lms_cache(course.as_deref()),
+        Commands::Clone {
+            platform,
+            assignments,
+            concurrency,
+            long_paths,
+            dry_run,
+            since,
+            until,
+            update,
+            affixes,
+        } => {
+            let config = config_mgr.config();
+            let since = since
+                .as_deref()
+                .map(|date| parse_window_date(date, false))
+                .transpose()
+                .map_err(|e| Failure::Config.wrap(e))?;
+            let until = until
+                .as_deref()
+                .map(|date| parse_window_date(date, true))
+                .transpose()
+                .map_err(|e| Failure::Config.wrap(e))?;
+            if let (Some(since), Some(until)) = (since, until) {
+                if since > until {
+                    return Err(Failure::Config.msg("--since must not be after --until"));
+                }
+            }
+            let options = CloneOptions {
+                concurrency: *concurrency,
+                layout: config.directory_layout,
+                long_paths: *long_paths,
+                dry_run: *dry_run,
+                since,
+                until,
+                update_existing: *update,
+                naming: affixes.naming(config)?,
+                ..Default::default()
+            };
+            run_clone(config, *platform, assignments.as_deref(), options).await
+        }
+        Commands::Reconcile {
+            platform,
+            assignments,
+            filter,
+            affixes,
+        } => {
+            let naming = affixes.naming(config_mgr.config())?;
+            run_reconcile(
+                config_mgr.config(),
+                *platform,
+                assignments.as_deref(),
+                filter.as_deref(),
+                &naming,
+            )
+            .await
         }
+        Commands::Activity {
+            platform,
+            assignments,
+            since,
+            affixes,
+        } => {
+            let naming = affixes.naming(config_mgr.config())?;
+            run_activity(
+                config_mgr.config(),
+                *platform,
+                assignments.as_deref(),
+                since.as_deref(),
+                &naming,
+            )
+            .await
+        }
+        Commands::DeleteRepo {
+            name,
+            platform,
+            yes,
+        } => run_delete_repo(config_mgr.config(), *platform, name, *yes).await,
+        Commands::Issues { action } => match action {
+            IssuesAction::Open {
+                platform,
+                assignment,
+                title,
+                body,
+                body_file,
+                variables,
+                affixes,
+            } => {
+                let body = match body_file {
+                    Some(path) => read_issue_body(path).map_err(|e| Failure::Config.wrap(e))?,
+                    None => body.clone().unwrap_or_default(),
+                };
+                let issue = IssueTemplate {
+                    title: title.clone(),
+                    body,
+                    variables: parse_issue_variables(variables)?,
+                };
+                let naming = affixes.naming(config_mgr.config())?;
+                run_open_issue(config_mgr.config(), *platform, assignment, &issue, &naming).await
+            }
+        },
         Commands::Settings { .. } => {
             // Already handled above
             Ok(())
@@ -39,6 +39,29 @@ fn test_settings_show() {
         .stdout(predicate::str::contains("Repository Settings"));
 }
 
+#[test]
+fn test_config_show_json_redacts_token() {
+    let output = cli()
+        .arg("config")
+        .arg("show")
+        .arg("--format")
+        .arg("json")
+        .arg("--git-token")
+        .arg("secret-token")
+        .arg("--git-user")
+        .arg("teacher")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let settings: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(settings["git_access_token"], "***");
+    assert_eq!(settings["git_user"], "teacher");
+    assert!(!String::from_utf8_lossy(&output).contains("secret-token"));
+}
+
 #[test]
 fn test_settings_path() {
     cli()
@@ -276,7 +299,57 @@ fn test_setup_help() {
         .success()
         .stdout(predicate::str::contains("Set up student repositories"))
         .stdout(predicate::str::contains("--platform"))
-        .stdout(predicate::str::contains("--template"));
+        .stdout(predicate::str::contains("--template"))
+        .stdout(predicate::str::contains("--fail-fast"))
+        .stdout(predicate::str::contains("--repo-separator"))
+        .stdout(predicate::str::contains("--mapping-csv"))
+        .stdout(predicate::str::contains("--notify-file"))
+        .stdout(predicate::str::contains("--platform-templates"))
+        .stdout(predicate::str::contains("--verify-pushes"))
+        .stdout(predicate::str::contains("--timeout"));
+}
+
+#[test]
+fn test_reconcile_help() {
+    cli()
+        .arg("reconcile")
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Compare the student repositories"))
+        .stdout(predicate::str::contains("--filter"));
+}
+
+#[test]
+fn test_reconcile_uses_repo_prefix_and_name_template() {
+    let temp_dir = TempDir::new().unwrap();
+    let roster = create_roster(
+        &temp_dir,
+        "students.yaml",
+        "- name: team-1\n  members: [alice]\n",
+    );
+
+    cli()
+        .arg("reconcile")
+        .arg("--platform")
+        .arg("local")
+        .arg("--git-base-url")
+        .arg(temp_dir.path().join("platform"))
+        .arg("--student-org")
+        .arg("course")
+        .arg("--assignments")
+        .arg("task-1")
+        .arg("--yaml-file")
+        .arg(&roster)
+        .arg("--repo-name-template")
+        .arg("{assignment}_{team}")
+        .arg("--repo-prefix")
+        .arg("test-")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "test-task-1_team-1 (team-1, task-1)",
+        ));
 }
 
 #[test]
@@ -289,6 +362,33 @@ fn test_verify_help() {
         .stdout(predicate::str::contains("Verify platform settings"));
 }
 
+#[test]
+fn test_clone_help() {
+    cli()
+        .arg("clone")
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Clone student repositories"))
+        .stdout(predicate::str::contains("--concurrency"))
+        .stdout(predicate::str::contains("--dry-run"))
+        .stdout(predicate::str::contains("--until <DATE>"))
+        .stdout(predicate::str::contains("--update"))
+        .stdout(predicate::str::contains("[default: 4]"));
+}
+
+#[test]
+fn test_clone_update_conflicts_with_until() {
+    cli()
+        .arg("clone")
+        .arg("--update")
+        .arg("--until")
+        .arg("2024-05-01")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
 #[test]
 fn test_settings_help() {
     cli()
@@ -316,6 +416,17 @@ fn test_directory_layout_option() {
         .stdout(predicate::str::contains("by-team"));
 }
 
+#[test]
+fn test_invalid_directory_layout_is_rejected() {
+    cli()
+        .arg("--directory-layout")
+        .arg("by-tema")
+        .arg("--show")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown directory layout"));
+}
+
 #[test]
 fn test_yaml_file_option() {
     cli()
@@ -370,6 +481,47 @@ fn test_settings_reset_workflow() {
     }
 }
 
+#[test]
+fn test_settings_reset_course_only_keeps_credentials() {
+    let config_dir = TempDir::new().unwrap();
+    Command::cargo_bin("repobee-cli")
+        .unwrap()
+        .env("REPOBEE_CONFIG_DIR", config_dir.path())
+        .arg("--git-user")
+        .arg("teacher1")
+        .arg("--student-org")
+        .arg("cs101-2024")
+        .arg("settings")
+        .arg("reset")
+        .arg("--course-only")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Course settings reset"));
+
+    let saved = fs::read_to_string(config_dir.path().join("repobee.json")).unwrap();
+    let saved: serde_json::Value = serde_json::from_str(&saved).unwrap();
+    assert_eq!(saved["git_user"], "teacher1");
+    assert_eq!(saved["git_student_repos_group"], "");
+}
+
+#[test]
+fn test_settings_show_warns_about_extension_mismatch() {
+    let config_dir = TempDir::new().unwrap();
+    Command::cargo_bin("repobee-cli")
+        .unwrap()
+        .env("REPOBEE_CONFIG_DIR", config_dir.path())
+        .arg("--yaml-file")
+        .arg("students.csv")
+        .arg("settings")
+        .arg("show")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Warnings:"))
+        .stdout(predicate::str::contains(
+            "yaml_file: students.csv does not have the .yaml extension",
+        ));
+}
+
 #[test]
 fn test_show_displays_defaults() {
     // Just verify that show works and displays configuration
@@ -382,3 +534,732 @@ fn test_show_displays_defaults() {
         .stdout(predicate::str::contains("Git Settings"))
         .stdout(predicate::str::contains("Repository Settings"));
 }
+
+// ===== Roster Diff Tests =====
+
+fn create_roster(dir: &TempDir, name: &str, content: &str) -> std::path::PathBuf {
+    let path = dir.path().join(name);
+    fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn test_roster_diff_json() {
+    let temp_dir = TempDir::new().unwrap();
+    let old = create_roster(
+        &temp_dir,
+        "old.yaml",
+        "- name: team-1\n  members: [alice, bob]\n- name: team-2\n  members: [carol]\n",
+    );
+    let new = create_roster(
+        &temp_dir,
+        "new.yaml",
+        "- name: team-1\n  members: [alice, dave]\n- name: team-3\n  members: [erin]\n",
+    );
+
+    cli()
+        .arg("roster-diff")
+        .arg(&old)
+        .arg(&new)
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"teams_added\""))
+        .stdout(predicate::str::contains("\"team-3\""))
+        .stdout(predicate::str::contains(
+            "\"members_added\": [\n        \"dave\"",
+        ))
+        .stdout(predicate::str::contains(
+            "\"members_removed\": [\n        \"bob\"",
+        ));
+}
+
+#[test]
+fn test_roster_diff_text_without_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let roster = create_roster(
+        &temp_dir,
+        "roster.yaml",
+        "- name: team-1\n  members: [alice, bob]\n",
+    );
+
+    cli()
+        .arg("roster-diff")
+        .arg(&roster)
+        .arg(&roster)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No roster changes."));
+}
+
+// ===== Validate Teams Tests =====
+
+#[test]
+fn test_validate_teams_ok() {
+    let temp_dir = TempDir::new().unwrap();
+    let roster = create_roster(
+        &temp_dir,
+        "students.json",
+        r#"[{"name": "team-1", "members": ["alice", "bob"]}]"#,
+    );
+
+    cli()
+        .arg("validate-teams")
+        .arg(&roster)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 teams, no problems found"));
+}
+
+#[test]
+fn test_validate_teams_reports_problems() {
+    let temp_dir = TempDir::new().unwrap();
+    let roster = create_roster(
+        &temp_dir,
+        "students.yaml",
+        "- name: team-1\n  members: [alice]\n- name: team-2\n  members: [alice]\n",
+    );
+
+    cli()
+        .arg("validate-teams")
+        .arg(&roster)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "Team team-2: alice is also a member of team team-1",
+        ))
+        .stderr(predicate::str::contains("has 1 problems"));
+}
+
+// ===== Import Roster Tests =====
+
+#[test]
+fn test_import_roster_with_mapping() {
+    let temp_dir = TempDir::new().unwrap();
+    let roster = create_roster(
+        &temp_dir,
+        "export.json",
+        r#"{"students": [
+            {"fullName": "Alice Smith", "mail": "alice@uni.edu", "github": "alice", "lab": "Lab 1"},
+            {"fullName": "Bob Jones", "mail": "bob@uni.edu", "github": "bob", "lab": "Lab 1"},
+            {"fullName": "Carol White", "mail": "carol@uni.edu", "github": "carol", "lab": "Lab 2"}
+        ]}"#,
+    );
+    let mapping = create_roster(
+        &temp_dir,
+        "mapping.json",
+        r#"{
+            "records": "students",
+            "name": "fullName",
+            "email": "mail",
+            "git_id": "github",
+            "group": "lab"
+        }"#,
+    );
+    let output = temp_dir.path().join("students.yaml");
+
+    cli()
+        .arg("import-roster")
+        .arg(&roster)
+        .arg("--mapping")
+        .arg(&mapping)
+        .arg("--output")
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Imported 3 students into 2 teams"));
+
+    cli()
+        .arg("validate-teams")
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 teams, no problems found"));
+}
+
+#[test]
+fn test_import_roster_reports_missing_fields() {
+    let temp_dir = TempDir::new().unwrap();
+    let roster = create_roster(
+        &temp_dir,
+        "export.json",
+        r#"[{"name": "Alice Smith", "email": "alice@uni.edu"}]"#,
+    );
+
+    cli()
+        .arg("import-roster")
+        .arg(&roster)
+        .arg("--output")
+        .arg(temp_dir.path().join("students.yaml"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("record 1: missing 'git_id'"));
+    assert!(!temp_dir.path().join("students.yaml").exists());
+}
+
+// ===== Clear LMS Cache Tests =====
+
+#[test]
+fn test_clear_lms_cache_of_one_course() {
+    let config_dir = TempDir::new().unwrap();
+    let cache_dir = config_dir.path().join("lms-cache");
+    for course in ["1234", "5678"] {
+        fs::create_dir_all(cache_dir.join(course)).unwrap();
+        fs::write(cache_dir.join(course).join("groups.json"), "[]").unwrap();
+    }
+
+    cli()
+        .env("REPOBEE_CONFIG_DIR", config_dir.path())
+        .arg("clear-lms-cache")
+        .arg("--course")
+        .arg("1234")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cached LMS data of 1 courses"));
+    assert!(!cache_dir.join("1234").exists());
+    assert!(cache_dir.join("5678").exists());
+
+    cli()
+        .env("REPOBEE_CONFIG_DIR", config_dir.path())
+        .arg("clear-lms-cache")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cached LMS data of 1 courses"));
+    assert!(!cache_dir.join("5678").exists());
+}
+
+// ===== List Templates Tests =====
+
+#[test]
+fn test_list_templates_local() {
+    let temp_dir = TempDir::new().unwrap();
+    let template_dir = temp_dir.path().join("templates");
+    fs::create_dir_all(template_dir.join("task-2")).unwrap();
+    fs::create_dir_all(template_dir.join("task-1")).unwrap();
+
+    cli()
+        .arg("list-templates")
+        .arg("--platform")
+        .arg("local")
+        .arg("--template-org")
+        .arg(template_dir.to_str().unwrap())
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "[\n  \"task-1\",\n  \"task-2\"\n]",
+        ));
+}
+
+#[test]
+fn test_list_templates_local_missing_dir() {
+    let temp_dir = TempDir::new().unwrap();
+
+    cli()
+        .arg("list-templates")
+        .arg("--platform")
+        .arg("local")
+        .arg("--template-org")
+        .arg(temp_dir.path().join("missing").to_str().unwrap())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Failed to read template directory",
+        ));
+}
+
+#[test]
+fn test_clone_suggests_template_for_unknown_assignment() {
+    let temp_dir = TempDir::new().unwrap();
+    let template_dir = temp_dir.path().join("templates");
+    fs::create_dir_all(template_dir.join("task-1")).unwrap();
+    let roster = create_roster(
+        &temp_dir,
+        "students.yaml",
+        "- name: team-1\n  members: [alice]\n",
+    );
+
+    cli()
+        .arg("clone")
+        .arg("--platform")
+        .arg("local")
+        .arg("--assignments")
+        .arg("taks-1")
+        .arg("--template-org")
+        .arg(template_dir.to_str().unwrap())
+        .arg("--yaml-file")
+        .arg(&roster)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "No template for assignment(s) taks-1 (did you mean 'task-1'?)",
+        ));
+}
+
+#[test]
+fn test_clone_rejects_invalid_window() {
+    cli()
+        .arg("clone")
+        .arg("--until")
+        .arg("next friday")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid date 'next friday'"));
+
+    cli()
+        .arg("clone")
+        .arg("--since")
+        .arg("2024-05-02")
+        .arg("--until")
+        .arg("2024-05-01")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--since must not be after --until",
+        ));
+}
+
+#[test]
+fn test_clone_dry_run_uses_repo_prefix() {
+    let temp_dir = TempDir::new().unwrap();
+    let roster = create_roster(
+        &temp_dir,
+        "students.yaml",
+        "- name: team-1\n  members: [alice]\n",
+    );
+
+    cli()
+        .arg("clone")
+        .arg("--platform")
+        .arg("local")
+        .arg("--git-base-url")
+        .arg(temp_dir.path().join("platform"))
+        .arg("--student-org")
+        .arg("course")
+        .arg("--assignments")
+        .arg("task-1")
+        .arg("--yaml-file")
+        .arg(&roster)
+        .arg("--target-folder")
+        .arg(temp_dir.path().join("target"))
+        .arg("--repo-prefix")
+        .arg("test-")
+        .arg("--dry-run")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("team-1/test-team-1-task-1"));
+}
+
+#[test]
+fn test_clone_dry_run_uses_repo_name_template() {
+    let temp_dir = TempDir::new().unwrap();
+    let roster = create_roster(
+        &temp_dir,
+        "students.yaml",
+        "- name: team-1\n  members: [alice]\n",
+    );
+
+    cli()
+        .arg("clone")
+        .arg("--platform")
+        .arg("local")
+        .arg("--git-base-url")
+        .arg(temp_dir.path().join("platform"))
+        .arg("--student-org")
+        .arg("course")
+        .arg("--assignments")
+        .arg("task-1")
+        .arg("--yaml-file")
+        .arg(&roster)
+        .arg("--target-folder")
+        .arg(temp_dir.path().join("target"))
+        .arg("--repo-name-template")
+        .arg("{assignment}_{team}")
+        .arg("--dry-run")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("team-1/task-1_team-1"));
+}
+
+// ===== Setup Tests =====
+
+#[test]
+fn test_setup_rejects_invalid_repo_name_template() {
+    cli()
+        .arg("setup")
+        .arg("--template")
+        .arg("task-1")
+        .arg("--team")
+        .arg("team-1:alice")
+        .arg("--repo-name-template")
+        .arg("{assignment}")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid repository name template '{assignment}'",
+        ));
+}
+
+#[test]
+fn test_setup_repo_prefix_wraps_name_template() {
+    cli()
+        .arg("setup")
+        .arg("--template")
+        .arg("task-1")
+        .arg("--team")
+        .arg("team-1:alice")
+        .arg("--repo-name-template")
+        .arg("{assignment}")
+        .arg("--repo-prefix")
+        .arg("test-")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid repository name template 'test-{assignment}'",
+        ));
+}
+
+#[test]
+fn test_setup_rejects_unknown_mapping_column() {
+    cli()
+        .arg("setup")
+        .arg("--mapping-csv")
+        .arg("mapping.csv")
+        .arg("--mapping-columns")
+        .arg("git_id,grade")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown mapping column: grade"));
+}
+
+#[test]
+fn test_setup_notify_file_requires_student_csv() {
+    cli()
+        .arg("setup")
+        .arg("--notify-file")
+        .arg("notify.csv")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--student-csv"));
+}
+
+#[test]
+fn test_setup_clean_and_prune_work_dir_conflict() {
+    cli()
+        .arg("setup")
+        .arg("--clean-work-dir")
+        .arg("--prune-work-dir")
+        .arg("30")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_setup_rejects_invalid_timeout() {
+    cli()
+        .arg("setup")
+        .arg("--timeout")
+        .arg("1h")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("--timeout"));
+}
+
+#[test]
+fn test_setup_merges_teams_files_and_reports_conflicts() {
+    let temp_dir = TempDir::new().unwrap();
+    let ta1 = create_roster(
+        &temp_dir,
+        "ta1.yaml",
+        "- name: team-1\n  members: [alice, bob]\n",
+    );
+    let ta2 = create_roster(
+        &temp_dir,
+        "ta2.yaml",
+        "- name: team-2\n  members: [bob, carol]\n",
+    );
+
+    cli()
+        .arg("setup")
+        .arg("--template")
+        .arg("task-1")
+        .arg("--teams-file")
+        .arg(&ta1)
+        .arg("--teams-file")
+        .arg(&ta2)
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(format!(
+            "bob is in team-1 ({}) and team-2 ({})",
+            ta1.display(),
+            ta2.display()
+        )))
+        .stderr(predicate::str::contains("The teams files have 1 conflicts"));
+}
+
+// ===== Issues Tests =====
+
+#[test]
+fn test_issues_open_help() {
+    cli()
+        .arg("issues")
+        .arg("open")
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Open the same issue"))
+        .stdout(predicate::str::contains("--body-file"));
+}
+
+#[test]
+fn test_issues_open_missing_body_file() {
+    cli()
+        .arg("issues")
+        .arg("open")
+        .arg("--assignment")
+        .arg("task-1")
+        .arg("--title")
+        .arg("Deadline moved")
+        .arg("--body-file")
+        .arg("/nonexistent/announcement.md")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Failed to read issue body"));
+}
+
+#[test]
+fn test_issues_open_invalid_variable() {
+    cli()
+        .arg("issues")
+        .arg("open")
+        .arg("--assignment")
+        .arg("task-1")
+        .arg("--title")
+        .arg("Due {{due_date}}")
+        .arg("--var")
+        .arg("due_date")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("use NAME=VALUE"));
+}
+
+#[test]
+fn test_issues_open_reports_missing_repos() {
+    let temp_dir = TempDir::new().unwrap();
+    let roster = create_roster(
+        &temp_dir,
+        "students.yaml",
+        "- name: team-1\n  members: [alice]\n- name: team-2\n  members: [bob]\n",
+    );
+    let body = temp_dir.path().join("announcement.md");
+    fs::write(&body, "The deadline is now **Friday**.").unwrap();
+
+    cli()
+        .arg("issues")
+        .arg("open")
+        .arg("--platform")
+        .arg("local")
+        .arg("--git-base-url")
+        .arg(temp_dir.path().join("platform"))
+        .arg("--student-org")
+        .arg("course")
+        .arg("--yaml-file")
+        .arg(&roster)
+        .arg("--assignment")
+        .arg("task-1")
+        .arg("--title")
+        .arg("Deadline moved")
+        .arg("--body-file")
+        .arg(&body)
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("Failed: 2 repositories"))
+        .stdout(predicate::str::contains("team-1-task-1"));
+}
+
+#[test]
+fn test_issues_open_uses_repo_prefix_and_separator() {
+    let temp_dir = TempDir::new().unwrap();
+    let roster = create_roster(
+        &temp_dir,
+        "students.yaml",
+        "- name: team-1\n  members: [alice]\n",
+    );
+
+    cli()
+        .arg("issues")
+        .arg("open")
+        .arg("--platform")
+        .arg("local")
+        .arg("--git-base-url")
+        .arg(temp_dir.path().join("platform"))
+        .arg("--student-org")
+        .arg("course")
+        .arg("--yaml-file")
+        .arg(&roster)
+        .arg("--assignment")
+        .arg("task-1")
+        .arg("--title")
+        .arg("Deadline moved")
+        .arg("--repo-prefix")
+        .arg("test-")
+        .arg("--repo-separator")
+        .arg("_")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("test-team-1_task-1"));
+}
+
+// ===== Delete Repo Tests =====
+
+#[test]
+fn test_delete_repo_needs_confirmation() {
+    let temp_dir = TempDir::new().unwrap();
+
+    cli()
+        .arg("delete-repo")
+        .arg("team-1-task-1")
+        .arg("--platform")
+        .arg("local")
+        .arg("--git-base-url")
+        .arg(temp_dir.path().join("platform"))
+        .arg("--student-org")
+        .arg("course")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("pass --yes"));
+}
+
+#[test]
+fn test_delete_missing_repo() {
+    let temp_dir = TempDir::new().unwrap();
+
+    cli()
+        .arg("delete-repo")
+        .arg("team-1-task-1")
+        .arg("--platform")
+        .arg("local")
+        .arg("--git-base-url")
+        .arg(temp_dir.path().join("platform"))
+        .arg("--student-org")
+        .arg("course")
+        .arg("--yes")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("does not exist in 'course'"));
+}
+
+// ===== Activity Tests =====
+
+#[test]
+fn test_activity_invalid_since() {
+    cli()
+        .arg("activity")
+        .arg("--yaml-file")
+        .arg("students.yaml")
+        .arg("--since")
+        .arg("May 1st")
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_activity_reports_missing_repos() {
+    let temp_dir = TempDir::new().unwrap();
+    let roster = create_roster(
+        &temp_dir,
+        "students.yaml",
+        "- name: team-1\n  members: [alice]\n- name: team-2\n  members: [bob]\n",
+    );
+
+    cli()
+        .arg("activity")
+        .arg("--platform")
+        .arg("local")
+        .arg("--git-base-url")
+        .arg(temp_dir.path().join("platform"))
+        .arg("--student-org")
+        .arg("course")
+        .arg("--yaml-file")
+        .arg(&roster)
+        .arg("--assignments")
+        .arg("task-1")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("Failed: 2 repositories"))
+        .stdout(predicate::str::contains("team-2-task-1"));
+}
+
+// ===== Exit Code Tests =====
+
+#[test]
+fn test_exit_code_for_config_errors() {
+    cli().assert().code(2);
+
+    cli()
+        .arg("clone")
+        .arg("--since")
+        .arg("2024-05-02")
+        .arg("--until")
+        .arg("2024-05-01")
+        .assert()
+        .code(2);
+
+    let temp_dir = TempDir::new().unwrap();
+    let roster = create_roster(
+        &temp_dir,
+        "students.yaml",
+        "- name: team-1\n  members: [alice]\n- name: team-2\n  members: [alice]\n",
+    );
+    cli().arg("validate-teams").arg(&roster).assert().code(2);
+}
+
+#[test]
+fn test_exit_code_for_failed_repos() {
+    let temp_dir = TempDir::new().unwrap();
+    let roster = create_roster(
+        &temp_dir,
+        "students.yaml",
+        "- name: team-1\n  members: [alice]\n",
+    );
+
+    cli()
+        .arg("clone")
+        .arg("--platform")
+        .arg("local")
+        .arg("--git-base-url")
+        .arg(temp_dir.path().join("platform"))
+        .arg("--student-org")
+        .arg("course")
+        .arg("--assignments")
+        .arg("task-1")
+        .arg("--yaml-file")
+        .arg(&roster)
+        .arg("--target-folder")
+        .arg(temp_dir.path().join("target"))
+        .arg("--dry-run")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("repositories cannot be cloned"));
+}
+
+#[test]
+fn test_exit_code_for_unreachable_platform() {
+    cli()
+        .arg("verify")
+        .arg("--platform")
+        .arg("git-hub")
+        .arg("--git-base-url")
+        .arg("http://127.0.0.1:9")
+        .arg("--git-token")
+        .arg("token")
+        .arg("--git-user")
+        .arg("teacher")
+        .arg("--student-org")
+        .arg("course")
+        .assert()
+        .code(3);
+}
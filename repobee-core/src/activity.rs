@@ -0,0 +1,177 @@
+//! Last activity in the student repositories
+//!
+//! Students who have not started on an assignment show up as repositories nothing was
+//! pushed to since setup. [`repo_activity`] looks up when each expected repository was
+//! last active, and sorts them from the least to the most recently active so the
+//! repositories to follow up on come first.
+
+use crate::platform::PlatformAPI;
+use crate::reconcile::ExpectedRepo;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, StreamExt};
+
+/// Repositories looked up at the same time
+const ACTIVITY_CONCURRENCY: usize = 8;
+
+/// Last activity of one student repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoActivity {
+    pub repo: ExpectedRepo,
+    /// `None` when nothing was pushed to the repository
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+impl RepoActivity {
+    /// Whether nothing happened in the repository after `since`, e.g. the setup date
+    ///
+    /// Without `since`, only repositories nothing was pushed to are inactive.
+    pub fn is_inactive(&self, since: Option<DateTime<Utc>>) -> bool {
+        match (self.last_activity, since) {
+            (None, _) => true,
+            (Some(last), Some(since)) => last <= since,
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Repository whose activity could not be looked up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityError {
+    pub repo_name: String,
+    pub error: String,
+}
+
+/// Outcome of [`repo_activity`]
+#[derive(Debug, Clone, Default)]
+pub struct ActivityReport {
+    /// Repositories, least recently active first; the ones without activity lead
+    pub repos: Vec<RepoActivity>,
+    /// Repositories that do not exist or could not be looked up
+    pub errors: Vec<ActivityError>,
+}
+
+impl ActivityReport {
+    /// Repositories without activity after `since`, see [`RepoActivity::is_inactive`]
+    pub fn inactive(&self, since: Option<DateTime<Utc>>) -> Vec<&RepoActivity> {
+        self.repos
+            .iter()
+            .filter(|activity| activity.is_inactive(since))
+            .collect()
+    }
+}
+
+/// Look up the last activity of each of the repositories `repos`
+///
+/// A repository that cannot be looked up, such as one that does not exist, ends up in
+/// [`ActivityReport::errors`] and does not stop the others.
+pub async fn repo_activity<P: PlatformAPI>(api: &P, repos: &[ExpectedRepo]) -> ActivityReport {
+    let results: Vec<_> = stream::iter(repos)
+        .map(|repo| async move { (repo, api.repo_last_activity(&repo.name).await) })
+        .buffered(ACTIVITY_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut report = ActivityReport::default();
+    for (repo, result) in results {
+        match result {
+            Ok(last_activity) => report.repos.push(RepoActivity {
+                repo: repo.clone(),
+                last_activity,
+            }),
+            Err(e) => {
+                log::warn!("Failed to look up activity of {}: {}", repo.name, e);
+                report.errors.push(ActivityError {
+                    repo_name: repo.name.clone(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+    report.repos.sort_by(|a, b| {
+        a.last_activity
+            .cmp(&b.last_activity)
+            .then_with(|| a.repo.name.cmp(&b.repo.name))
+    });
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Platform;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn expected(name: &str) -> ExpectedRepo {
+        ExpectedRepo {
+            name: name.to_string(),
+            team: name.split('-').next().unwrap().to_string(),
+            assignment: "task1".to_string(),
+            members: Vec::new(),
+        }
+    }
+
+    /// Commit an empty tree at `time` to the bare repository at `path`
+    fn commit_at(path: &std::path::Path, time: DateTime<Utc>) {
+        let repo = git2::Repository::open_bare(path).unwrap();
+        let signature = git2::Signature::new(
+            "alice",
+            "alice@example.com",
+            &git2::Time::new(time.timestamp(), 0),
+        )
+        .unwrap();
+        let tree_id = repo.treebuilder(None).unwrap().write().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        repo.commit(
+            Some("refs/heads/main"),
+            &signature,
+            &signature,
+            "Start",
+            &tree,
+            &[],
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_repo_activity() {
+        let temp_dir = TempDir::new().unwrap();
+        let api = Platform::local(
+            temp_dir.path().to_path_buf(),
+            "course".to_string(),
+            "teacher".to_string(),
+        )
+        .unwrap();
+        let setup = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        for name in ["alice-task1", "bob-task1", "carol-task1"] {
+            api.create_repo(name, "", true, None).await.unwrap();
+        }
+        let org_dir = temp_dir.path().join("orgs").join("course");
+        commit_at(
+            &org_dir.join("alice-task1"),
+            setup + chrono::Duration::days(3),
+        );
+        commit_at(&org_dir.join("bob-task1"), setup);
+
+        let repos: Vec<ExpectedRepo> = ["alice-task1", "bob-task1", "carol-task1", "dave-task1"]
+            .into_iter()
+            .map(expected)
+            .collect();
+        let report = repo_activity(&api, &repos).await;
+
+        let order: Vec<&str> = report.repos.iter().map(|a| a.repo.name.as_str()).collect();
+        assert_eq!(order, vec!["carol-task1", "bob-task1", "alice-task1"]);
+        assert_eq!(report.repos[0].last_activity, None);
+        assert_eq!(report.repos[1].last_activity, Some(setup));
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].repo_name, "dave-task1");
+
+        let inactive: Vec<&str> = report
+            .inactive(Some(setup))
+            .iter()
+            .map(|a| a.repo.name.as_str())
+            .collect();
+        assert_eq!(inactive, vec!["carol-task1", "bob-task1"]);
+        assert_eq!(report.inactive(None).len(), 1);
+    }
+}
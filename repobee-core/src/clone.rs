@@ -0,0 +1,1045 @@
+//! Cloning student repositories
+//!
+//! Student repositories are cloned in parallel, with at most [`CloneOptions::concurrency`]
+//! clones running at a time. The destination of every repository is resolved before the
+//! first clone starts, so no two clones ever write to the same directory, and each clone
+//! runs on a blocking thread with its own git2 repository handle.
+//!
+//! With [`CloneOptions::since`] and [`CloneOptions::until`], every clone is checked out at
+//! the last commit before the deadline, and commits after it are reported as late in
+//! [`CloneResult::commit_windows`].
+//!
+//! An interrupted run can simply be started again: complete clones are skipped, and
+//! clones that did not finish are removed and cloned again, see [`unfinished_clone`].
+//! With [`CloneOptions::update_existing`], complete clones are fast-forwarded to the
+//! latest commits instead of skipped, see [`fast_forward_clone`].
+
+use crate::error::{PlatformError, Result};
+use crate::naming::RepoNaming;
+use crate::paths::work_path;
+use crate::platform::PlatformAPI;
+use crate::progress::{CloneProgress, Progress};
+use crate::settings::DirectoryLayout;
+use crate::setup::{clone_template, fetch_origin};
+use crate::types::{StudentRepo, StudentTeam};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use git2::{build::CheckoutBuilder, Repository, Sort};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Number of clones running at the same time when not configured
+pub const DEFAULT_CLONE_CONCURRENCY: usize = 4;
+
+/// Optional behaviour of [`clone_student_repos`]
+#[derive(Debug, Clone)]
+pub struct CloneOptions {
+    /// Maximum number of clones running at the same time, at least 1; the crate-wide
+    /// [`throttle`](crate::throttle) may allow fewer
+    pub concurrency: usize,
+    /// Directory structure below the target folder
+    pub layout: DirectoryLayout,
+    /// Naming scheme of the student repositories
+    pub naming: RepoNaming,
+    /// Use `\\?\` paths on Windows for destinations over the path length limit,
+    /// instead of refusing them
+    pub long_paths: bool,
+    /// Only resolve the repositories and their destinations, without cloning anything
+    /// or creating directories
+    pub dry_run: bool,
+    /// Start of the submission window, only used to count the commits in the window
+    pub since: Option<DateTime<Utc>>,
+    /// Deadline: clones are checked out at the last commit at or before it
+    pub until: Option<DateTime<Utc>>,
+    /// Fetch existing clones and fast-forward them, instead of leaving them untouched
+    pub update_existing: bool,
+}
+
+impl Default for CloneOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: DEFAULT_CLONE_CONCURRENCY,
+            layout: DirectoryLayout::default(),
+            naming: RepoNaming::default(),
+            long_paths: false,
+            dry_run: false,
+            since: None,
+            until: None,
+            update_existing: false,
+        }
+    }
+}
+
+/// Result of the clone operation
+#[derive(Debug, Clone, Default)]
+pub struct CloneResult {
+    /// Newly cloned repositories, with their local path
+    pub successful_repos: Vec<StudentRepo>,
+    /// Repositories whose destination already existed and was left untouched
+    pub existing_repos: Vec<StudentRepo>,
+    /// Repositories cloned again because an earlier clone did not finish
+    pub resumed_repos: Vec<StudentRepo>,
+    /// Existing clones fast-forwarded to new commits, see [`CloneOptions::update_existing`];
+    /// up-to-date clones stay in [`existing_repos`](Self::existing_repos)
+    pub updated_repos: Vec<StudentRepo>,
+    /// Repositories a dry run would clone, with their destination
+    pub planned_repos: Vec<StudentRepo>,
+    /// Errors that occurred while resolving or cloning repositories
+    pub errors: Vec<CloneError>,
+    /// Directory the repositories were cloned into
+    pub target_dir: PathBuf,
+    /// Commits of each newly cloned repository relative to the submission window, by
+    /// repository name; empty without [`CloneOptions::since`] or [`CloneOptions::until`]
+    pub commit_windows: BTreeMap<String, CommitWindow>,
+}
+
+/// Commits of a cloned repository relative to the submission window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitWindow {
+    /// Commit that was checked out, the last one at or before the deadline; `None` if
+    /// every commit is after the deadline, in which case the clone is left at its head
+    pub commit: Option<String>,
+    /// Whether the repository has commits after the deadline
+    pub late: bool,
+    /// Number of commits between the start of the window and the deadline
+    pub commits_in_window: usize,
+}
+
+/// Error that occurred while cloning a student repository
+#[derive(Debug, Clone)]
+pub struct CloneError {
+    pub repo_name: String,
+    pub team_name: String,
+    pub error: String,
+}
+
+impl CloneResult {
+    pub fn total_repos(&self) -> usize {
+        self.successful_repos.len()
+            + self.existing_repos.len()
+            + self.resumed_repos.len()
+            + self.updated_repos.len()
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Names of the repositories with commits after the deadline
+    pub fn late_repos(&self) -> Vec<&str> {
+        self.commit_windows
+            .iter()
+            .filter(|(_, window)| window.late)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    fn push_error(&mut self, repo_name: &str, team_name: &str, error: impl ToString) {
+        self.errors.push(CloneError {
+            repo_name: repo_name.to_string(),
+            team_name: team_name.to_string(),
+            error: error.to_string(),
+        });
+    }
+}
+
+/// Local directory of a student repository below `target_dir`
+///
+/// * `Flat` - `target_dir/<repo>`
+/// * `ByTeam` - `target_dir/<team>/<repo>`
+/// * `ByTask` - `target_dir/<assignment>/<repo>`
+pub fn clone_path(
+    target_dir: &Path,
+    layout: DirectoryLayout,
+    team: &str,
+    assignment: &str,
+    repo_name: &str,
+    long_paths: bool,
+) -> Result<PathBuf> {
+    match layout {
+        DirectoryLayout::Flat => work_path(target_dir, &[repo_name], long_paths),
+        DirectoryLayout::ByTeam => work_path(target_dir, &[team, repo_name], long_paths),
+        DirectoryLayout::ByTask => work_path(target_dir, &[assignment, repo_name], long_paths),
+    }
+}
+
+/// Parse a `--since`/`--until` date
+///
+/// Accepts RFC 3339 (`2024-05-01T12:00:00+02:00`), or `YYYY-MM-DD HH:MM` and `YYYY-MM-DD`
+/// in local time. A bare date is the start of that day, or its end with `end_of_day`, so
+/// `--until 2024-05-01` includes commits made on May 1st.
+pub fn parse_window_date(value: &str, end_of_day: bool) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    let local = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M")
+        .ok()
+        .or_else(|| {
+            let time = if end_of_day {
+                NaiveTime::from_hms_opt(23, 59, 59)?
+            } else {
+                NaiveTime::MIN
+            };
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+            Some(date.and_time(time))
+        })
+        .ok_or_else(|| {
+            PlatformError::Other(format!(
+                "Invalid date '{}'. Expected YYYY-MM-DD, YYYY-MM-DD HH:MM or RFC 3339",
+                value
+            ))
+        })?;
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|date| date.with_timezone(&Utc))
+        .ok_or_else(|| PlatformError::Other(format!("Date '{}' does not exist locally", value)))
+}
+
+/// Check out the last commit at or before `until` in the repository at `path`
+///
+/// Commits are ordered by commit time, following every parent from `HEAD`. Without
+/// `until`, the repository is left at its head and no commit is late.
+pub fn checkout_commit_window(
+    path: &Path,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Result<CommitWindow> {
+    let repo = Repository::open(path)?;
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TIME)?;
+    revwalk.push_head()?;
+
+    let mut window = CommitWindow {
+        commit: None,
+        late: false,
+        commits_in_window: 0,
+    };
+    let mut checkout = None;
+    for oid in revwalk {
+        let oid = oid?;
+        let time = repo.find_commit(oid)?.time().seconds();
+        if until.is_some_and(|until| time > until.timestamp()) {
+            window.late = true;
+            continue;
+        }
+        if checkout.is_none() {
+            checkout = Some(oid);
+        }
+        if since.is_some_and(|since| time < since.timestamp()) {
+            // Every remaining commit is older
+            break;
+        }
+        window.commits_in_window += 1;
+    }
+
+    if let Some(oid) = checkout {
+        if window.late {
+            repo.set_head_detached(oid)?;
+            repo.checkout_head(Some(CheckoutBuilder::new().force()))?;
+        }
+        window.commit = Some(oid.to_string());
+    }
+    Ok(window)
+}
+
+/// Fetch the clone at `path` and fast-forward its checked-out branch to `origin`
+///
+/// Returns whether new commits were checked out. Local work is never discarded: a
+/// detached head, such as one left at a deadline by [`checkout_commit_window`], a branch
+/// with commits that are not on `origin`, and local changes the new commits touch are
+/// errors that leave the clone as it was.
+pub fn fast_forward_clone(path: &Path, token: Option<&str>) -> Result<bool> {
+    let repo = Repository::open(path)?;
+    fetch_origin(&repo, token, None)?;
+
+    let head = repo.head()?;
+    let branch = head
+        .is_branch()
+        .then(|| (head.name(), head.shorthand(), head.target()));
+    let Some((Some(head_name), Some(branch), Some(local))) = branch else {
+        return Err(PlatformError::Other(format!(
+            "{} is not on a branch, check out a branch to update it",
+            path.display()
+        )));
+    };
+    let Ok(upstream) = repo.refname_to_id(&format!("refs/remotes/origin/{}", branch)) else {
+        // The branch only exists locally, there is nothing to update it from
+        return Ok(false);
+    };
+    if local == upstream {
+        return Ok(false);
+    }
+    let (ahead, _) = repo.graph_ahead_behind(local, upstream)?;
+    if ahead > 0 {
+        return Err(PlatformError::Other(format!(
+            "{} has {} commits that are not on origin/{}, it cannot be fast-forwarded",
+            path.display(),
+            ahead,
+            branch
+        )));
+    }
+
+    let target = repo.find_object(upstream, None)?;
+    repo.checkout_tree(&target, Some(CheckoutBuilder::new().safe()))?;
+    repo.reference(head_name, upstream, true, "fast-forward to origin")?;
+    Ok(true)
+}
+
+/// Clone the student repositories of the given assignments
+///
+/// # Arguments
+/// * `assignments` - Assignment (template) names
+/// * `student_teams` - Teams whose repositories are cloned
+/// * `api` - Platform the student repositories live on
+/// * `target_dir` - Directory the repositories are cloned into
+/// * `token` - Optional authentication token
+/// * `options` - Concurrency, layout and naming, see [`CloneOptions`]
+///
+/// Repositories that cannot be found or cloned are recorded in [`CloneResult::errors`]
+/// and do not stop the others. Destinations that already exist are never overwritten,
+/// but fast-forwarded with [`CloneOptions::update_existing`].
+/// With [`CloneOptions::dry_run`], the repositories that would be cloned are returned in
+/// [`CloneResult::planned_repos`] and nothing is written to disk. With a submission
+/// window, see [`checkout_commit_window`].
+pub async fn clone_student_repos<P: PlatformAPI>(
+    assignments: &[String],
+    student_teams: &[StudentTeam],
+    api: &P,
+    target_dir: &Path,
+    token: Option<&str>,
+    options: &CloneOptions,
+) -> Result<CloneResult> {
+    clone_student_repos_with_progress(
+        assignments,
+        student_teams,
+        api,
+        target_dir,
+        token,
+        options,
+        |_| {},
+    )
+    .await
+}
+
+/// Same as [`clone_student_repos`], reporting every finished clone to `progress`
+pub async fn clone_student_repos_with_progress<P: PlatformAPI, F: FnMut(Progress)>(
+    assignments: &[String],
+    student_teams: &[StudentTeam],
+    api: &P,
+    target_dir: &Path,
+    token: Option<&str>,
+    options: &CloneOptions,
+    mut progress: F,
+) -> Result<CloneResult> {
+    if !options.dry_run {
+        std::fs::create_dir_all(target_dir).map_err(|e| {
+            PlatformError::FileError(format!(
+                "Failed to create target folder {}: {}",
+                target_dir.display(),
+                e
+            ))
+        })?;
+    }
+
+    let mut result = CloneResult {
+        target_dir: target_dir.to_path_buf(),
+        ..Default::default()
+    };
+    let mut jobs = Vec::new();
+    // Indices of the jobs that replace an unfinished clone, and of those that update one
+    let mut resumed = HashSet::new();
+    let mut updates = HashSet::new();
+    let mut destinations = HashSet::new();
+
+    // Resolve every repository and destination up front, so the clones below only ever
+    // touch their own directory
+    progress(
+        CloneProgress::Resolving {
+            count: assignments.len() * student_teams.len(),
+        }
+        .into(),
+    );
+    for assignment in assignments {
+        for team in student_teams {
+            let repo_name = match options.naming.repo_name(&team.name, assignment) {
+                Ok(name) => name,
+                Err(e) => {
+                    result.push_error(&format!("{}/{}", team.name, assignment), &team.name, e);
+                    continue;
+                }
+            };
+            let repo = match api.get_repo(&repo_name, Some(&team.name)).await {
+                Ok(repo) => repo,
+                Err(e) => {
+                    result.push_error(&repo_name, &team.name, e);
+                    continue;
+                }
+            };
+            let path = match clone_path(
+                target_dir,
+                options.layout,
+                &team.name,
+                assignment,
+                &repo_name,
+                options.long_paths,
+            ) {
+                Ok(path) => path,
+                Err(e) => {
+                    result.push_error(&repo_name, &team.name, e);
+                    continue;
+                }
+            };
+            if !destinations.insert(path.clone()) {
+                result.push_error(
+                    &repo_name,
+                    &team.name,
+                    format!(
+                        "{} is also the destination of another repository",
+                        path.display()
+                    ),
+                );
+                continue;
+            }
+
+            let student_repo = StudentRepo::new(repo_name, team.clone(), repo.url);
+            let unfinished = if path.exists() {
+                match unfinished_clone(&path) {
+                    Ok(None) if options.update_existing && !options.dry_run => {
+                        updates.insert(jobs.len());
+                        jobs.push(student_repo.with_path(path));
+                        continue;
+                    }
+                    Ok(None) => {
+                        result.existing_repos.push(student_repo.with_path(path));
+                        continue;
+                    }
+                    Ok(Some(reason)) => Some(reason),
+                    Err(e) => {
+                        result.push_error(&student_repo.name, &team.name, e);
+                        continue;
+                    }
+                }
+            } else {
+                None
+            };
+            if options.dry_run {
+                result.planned_repos.push(student_repo.with_path(path));
+                continue;
+            }
+            if let Some(reason) = unfinished {
+                log::info!("Cloning {} again: {}", student_repo.name, reason);
+                if let Err(e) = std::fs::remove_dir_all(&path) {
+                    result.push_error(
+                        &student_repo.name,
+                        &team.name,
+                        format!(
+                            "Failed to remove unfinished clone {}: {}",
+                            path.display(),
+                            e
+                        ),
+                    );
+                    continue;
+                }
+                resumed.insert(jobs.len());
+            }
+            if let Some(parent) = path.parent() {
+                if let Err(e) = std::fs::create_dir_all(parent) {
+                    result.push_error(
+                        &student_repo.name,
+                        &team.name,
+                        format!("Failed to create {}: {}", parent.display(), e),
+                    );
+                    continue;
+                }
+            }
+            jobs.push(student_repo.with_path(path));
+        }
+    }
+
+    let semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
+    let mut tasks = JoinSet::new();
+    let mut task_repos = HashMap::new();
+    for (index, repo) in jobs.iter().enumerate() {
+        let permit = Arc::clone(&semaphore)
+            .acquire_owned()
+            .await
+            .map_err(|e| PlatformError::unexpected(e.to_string()))?;
+        let url = repo.url.clone();
+        let path = repo.path.clone().unwrap_or_default();
+        let token = token.map(str::to_string);
+        let (since, until) = (options.since, options.until);
+        let update = updates.contains(&index);
+        // Whether the repository changed on disk, with its submission window
+        let handle = tasks.spawn_blocking(move || {
+            let _permit = permit;
+            if update {
+                return fast_forward_clone(&path, token.as_deref()).map(|moved| (moved, None));
+            }
+            clone_template(&url, &path, token.as_deref())?;
+            if since.is_none() && until.is_none() {
+                return Ok((true, None));
+            }
+            checkout_commit_window(&path, since, until).map(|window| (true, Some(window)))
+        });
+        task_repos.insert(handle.id(), index);
+    }
+
+    let mut outcomes: Vec<Option<Result<(bool, Option<CommitWindow>)>>> =
+        std::iter::repeat_with(|| None).take(jobs.len()).collect();
+    let mut finished = 0;
+    while let Some(joined) = tasks.join_next_with_id().await {
+        let (id, outcome) = match joined {
+            Ok((id, outcome)) => (id, outcome),
+            Err(e) => (
+                e.id(),
+                Err(PlatformError::unexpected(format!(
+                    "Clone task failed: {}",
+                    e
+                ))),
+            ),
+        };
+        let index = task_repos[&id];
+        outcomes[index] = Some(outcome);
+        finished += 1;
+        progress(
+            CloneProgress::Cloned {
+                current: finished,
+                total: jobs.len(),
+                repo_name: jobs[index].name.clone(),
+            }
+            .into(),
+        );
+    }
+
+    // Report in the order the repositories were resolved, not the order they finished
+    for (index, (repo, outcome)) in jobs.into_iter().zip(outcomes).enumerate() {
+        match outcome {
+            Some(Ok((changed, window))) => {
+                if let Some(window) = window {
+                    result.commit_windows.insert(repo.name.clone(), window);
+                }
+                if updates.contains(&index) {
+                    if changed {
+                        result.updated_repos.push(repo);
+                    } else {
+                        result.existing_repos.push(repo);
+                    }
+                } else if resumed.contains(&index) {
+                    result.resumed_repos.push(repo);
+                } else {
+                    result.successful_repos.push(repo);
+                }
+            }
+            Some(Err(e)) => {
+                // A failed clone leaves a partial directory, a failed update the old clone
+                if let Some(path) = repo.path.as_ref().filter(|_| !updates.contains(&index)) {
+                    let _ = std::fs::remove_dir_all(path);
+                }
+                result.push_error(&repo.name, &repo.team.name, e);
+            }
+            None => result.push_error(&repo.name, &repo.team.name, "Clone did not finish"),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Why the clone at `path` did not finish, or `None` when it is complete
+///
+/// An interrupted clone leaves an empty directory, a git directory without commits, or a
+/// checkout that never wrote its index. A non-empty directory without a git directory may
+/// hold other files and is an error rather than something to replace.
+pub fn unfinished_clone(path: &Path) -> Result<Option<&'static str>> {
+    let mut entries = std::fs::read_dir(path)
+        .map_err(|e| PlatformError::FileError(format!("Cannot read {}: {}", path.display(), e)))?;
+    if entries.next().is_none() {
+        return Ok(Some("the directory is empty"));
+    }
+    if !path.join(".git").exists() {
+        return Err(PlatformError::FileError(format!(
+            "{} exists but is not a git repository; move it away to clone the repository",
+            path.display()
+        )));
+    }
+
+    let Ok(repo) = Repository::open(path) else {
+        return Ok(Some("the git directory is damaged"));
+    };
+    let Ok(head) = repo.head() else {
+        return Ok(Some("no commits were fetched"));
+    };
+    let Ok(tree) = head.peel_to_tree() else {
+        return Ok(Some("the git directory is damaged"));
+    };
+    if !tree.is_empty() && !repo.path().join("index").exists() {
+        return Ok(Some("the checkout did not finish"));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Platform;
+    use crate::setup::setup_student_repos;
+    use git2::Repository;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn create_template(path: &Path) {
+        fs::create_dir_all(path).unwrap();
+        let repo = Repository::init(path).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test User").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        fs::write(path.join("README.md"), "# Task\n").unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("README.md")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = repo.signature().unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_clone_path_layouts() {
+        let target = Path::new("grading");
+        let path = |layout| clone_path(target, layout, "alice", "task1", "alice-task1", false);
+        assert_eq!(
+            path(DirectoryLayout::Flat).unwrap(),
+            target.join("alice-task1")
+        );
+        assert_eq!(
+            path(DirectoryLayout::ByTeam).unwrap(),
+            target.join("alice").join("alice-task1")
+        );
+        assert_eq!(
+            path(DirectoryLayout::ByTask).unwrap(),
+            target.join("task1").join("alice-task1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clone_student_repos_concurrently() {
+        let temp_dir = TempDir::new().unwrap();
+        let work_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+
+        let assignments = vec!["task1".to_string(), "task2".to_string()];
+        let template_urls: Vec<String> = assignments
+            .iter()
+            .map(|assignment| {
+                let path = temp_dir.path().join("templates").join(assignment);
+                create_template(&path);
+                format!("file://{}", path.display())
+            })
+            .collect();
+
+        let api = Platform::local(
+            temp_dir.path().join("platform"),
+            "test-org".to_string(),
+            "teacher".to_string(),
+        )
+        .unwrap();
+        let student_teams: Vec<StudentTeam> = ["alice", "bob", "carol"]
+            .iter()
+            .map(|member| StudentTeam::new(vec![member.to_string()]))
+            .collect();
+        let setup = setup_student_repos(
+            &template_urls,
+            &student_teams,
+            &api,
+            work_dir.path(),
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(setup.is_success());
+
+        let options = CloneOptions {
+            concurrency: 2,
+            layout: DirectoryLayout::ByTeam,
+            ..Default::default()
+        };
+        let mut events = Vec::new();
+        let result = clone_student_repos_with_progress(
+            &assignments,
+            &student_teams,
+            &api,
+            target_dir.path(),
+            None,
+            &options,
+            |event| events.push(event),
+        )
+        .await
+        .unwrap();
+
+        assert!(result.is_success(), "{:?}", result.errors);
+        assert_eq!(result.successful_repos.len(), 6);
+        assert!(matches!(
+            events.first(),
+            Some(Progress::Clone(CloneProgress::Resolving { count: 6 }))
+        ));
+        assert!(matches!(
+            events.last(),
+            Some(Progress::Clone(CloneProgress::Cloned {
+                current: 6,
+                total: 6,
+                ..
+            }))
+        ));
+        let names: Vec<&str> = result
+            .successful_repos
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "alice-task1",
+                "bob-task1",
+                "carol-task1",
+                "alice-task2",
+                "bob-task2",
+                "carol-task2"
+            ]
+        );
+        for repo in &result.successful_repos {
+            let path = target_dir.path().join(&repo.team.name).join(&repo.name);
+            assert_eq!(repo.path.as_deref(), Some(path.as_path()));
+            assert!(path.join("README.md").exists());
+            assert!(Repository::open(&path).is_ok());
+        }
+
+        // A second run leaves the existing clones alone
+        let result = clone_student_repos(
+            &assignments,
+            &student_teams,
+            &api,
+            target_dir.path(),
+            None,
+            &options,
+        )
+        .await
+        .unwrap();
+        assert!(result.successful_repos.is_empty());
+        assert_eq!(result.existing_repos.len(), 6);
+
+        // After an interrupted run only the unfinished clones are cloned again
+        let alice = target_dir.path().join("alice").join("alice-task1");
+        fs::remove_file(alice.join(".git").join("index")).unwrap();
+        fs::remove_file(alice.join("README.md")).unwrap();
+        let bob = target_dir.path().join("bob").join("bob-task2");
+        fs::remove_dir_all(&bob).unwrap();
+        fs::create_dir(&bob).unwrap();
+        let result = clone_student_repos(
+            &assignments,
+            &student_teams,
+            &api,
+            target_dir.path(),
+            None,
+            &options,
+        )
+        .await
+        .unwrap();
+        assert!(result.is_success(), "{:?}", result.errors);
+        assert!(result.successful_repos.is_empty());
+        assert_eq!(result.existing_repos.len(), 4);
+        let resumed: Vec<&str> = result
+            .resumed_repos
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(resumed, vec!["alice-task1", "bob-task2"]);
+        assert!(alice.join("README.md").exists());
+        assert!(bob.join("README.md").exists());
+    }
+
+    /// Commit a new file to the working copy at `path`
+    fn commit_file(path: &Path, name: &str) {
+        let repo = Repository::open(path).unwrap();
+        fs::write(path.join(name), "work\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(name)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = git2::Signature::now("Student", "student@example.com").unwrap();
+        let parent = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "Work", &tree, &[&parent])
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_update_existing_clones() {
+        let temp_dir = TempDir::new().unwrap();
+        let work_dir = TempDir::new().unwrap();
+        let template_dir = temp_dir.path().join("templates").join("task1");
+        create_template(&template_dir);
+
+        let api = Platform::local(
+            temp_dir.path().join("platform"),
+            "test-org".to_string(),
+            "teacher".to_string(),
+        )
+        .unwrap();
+        let student_teams = vec![
+            StudentTeam::new(vec!["alice".to_string()]),
+            StudentTeam::new(vec!["bob".to_string()]),
+            StudentTeam::new(vec!["carol".to_string()]),
+        ];
+        setup_student_repos(
+            &[format!("file://{}", template_dir.display())],
+            &student_teams,
+            &api,
+            work_dir.path(),
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+        let assignments = vec!["task1".to_string()];
+        let target_dir = temp_dir.path().join("target");
+        let cloned = clone_student_repos(
+            &assignments,
+            &student_teams,
+            &api,
+            &target_dir,
+            None,
+            &CloneOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(cloned.successful_repos.len(), 3);
+
+        // Alice pushes new work, bob's clone has a commit of its own
+        let student_copy = temp_dir.path().join("alice-copy");
+        clone_template(&cloned.successful_repos[0].url, &student_copy, None).unwrap();
+        commit_file(&student_copy, "solution.txt");
+        crate::setup::push_to_repo(&student_copy, &cloned.successful_repos[0].url, None).unwrap();
+        commit_file(&target_dir.join("bob-task1"), "notes.txt");
+
+        let options = CloneOptions {
+            update_existing: true,
+            ..Default::default()
+        };
+        let result = clone_student_repos(
+            &assignments,
+            &student_teams,
+            &api,
+            &target_dir,
+            None,
+            &options,
+        )
+        .await
+        .unwrap();
+
+        let updated: Vec<&str> = result
+            .updated_repos
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(updated, vec!["alice-task1"]);
+        assert!(target_dir.join("alice-task1").join("solution.txt").exists());
+        assert_eq!(result.existing_repos.len(), 1);
+        assert_eq!(result.existing_repos[0].name, "carol-task1");
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].repo_name, "bob-task1");
+        assert!(result.errors[0].error.contains("cannot be fast-forwarded"));
+        assert!(target_dir.join("bob-task1").join("notes.txt").exists());
+    }
+
+    #[test]
+    fn test_unfinished_clone() {
+        let temp_dir = TempDir::new().unwrap();
+        let template = temp_dir.path().join("template");
+        create_template(&template);
+        let clone = temp_dir.path().join("clone");
+        clone_template(&format!("file://{}", template.display()), &clone, None).unwrap();
+        assert_eq!(unfinished_clone(&clone).unwrap(), None);
+
+        fs::remove_file(clone.join(".git").join("index")).unwrap();
+        assert_eq!(
+            unfinished_clone(&clone).unwrap(),
+            Some("the checkout did not finish")
+        );
+
+        let empty = temp_dir.path().join("empty");
+        fs::create_dir(&empty).unwrap();
+        assert_eq!(
+            unfinished_clone(&empty).unwrap(),
+            Some("the directory is empty")
+        );
+
+        let fetched_nothing = temp_dir.path().join("fetched-nothing");
+        Repository::init(&fetched_nothing).unwrap();
+        assert_eq!(
+            unfinished_clone(&fetched_nothing).unwrap(),
+            Some("no commits were fetched")
+        );
+
+        // Someone else's files are never replaced
+        let notes = temp_dir.path().join("notes");
+        fs::create_dir(&notes).unwrap();
+        fs::write(notes.join("grades.txt"), "8").unwrap();
+        assert!(unfinished_clone(&notes).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_clone_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let work_dir = TempDir::new().unwrap();
+        let template_dir = temp_dir.path().join("templates").join("task1");
+        create_template(&template_dir);
+
+        let api = Platform::local(
+            temp_dir.path().join("platform"),
+            "test-org".to_string(),
+            "teacher".to_string(),
+        )
+        .unwrap();
+        let student_teams = vec![
+            StudentTeam::new(vec!["alice".to_string()]),
+            StudentTeam::new(vec!["bob".to_string()]),
+        ];
+        setup_student_repos(
+            &[format!("file://{}", template_dir.display())],
+            &student_teams,
+            &api,
+            work_dir.path(),
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let target_dir = temp_dir.path().join("target");
+        let options = CloneOptions {
+            layout: DirectoryLayout::ByTask,
+            dry_run: true,
+            ..Default::default()
+        };
+        let result = clone_student_repos(
+            &["task1".to_string()],
+            &student_teams,
+            &api,
+            &target_dir,
+            None,
+            &options,
+        )
+        .await
+        .unwrap();
+
+        assert!(result.is_success());
+        assert!(result.successful_repos.is_empty());
+        let planned: Vec<PathBuf> = result
+            .planned_repos
+            .iter()
+            .filter_map(|r| r.path.clone())
+            .collect();
+        assert_eq!(
+            planned,
+            vec![
+                target_dir.join("task1").join("alice-task1"),
+                target_dir.join("task1").join("bob-task1")
+            ]
+        );
+        assert!(!target_dir.exists());
+    }
+
+    fn commit_at(repo: &Repository, file: &str, time: i64) -> git2::Oid {
+        let path = repo.workdir().unwrap().join(file);
+        fs::write(&path, file).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(file)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig =
+            git2::Signature::new("Student", "s@example.com", &git2::Time::new(time, 0)).unwrap();
+        let parent = repo.head().ok().map(|h| h.peel_to_commit().unwrap());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, file, &tree, &parents)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_window_date() {
+        let rfc = parse_window_date("2024-05-01T12:00:00+02:00", false).unwrap();
+        assert_eq!(rfc.to_rfc3339(), "2024-05-01T10:00:00+00:00");
+
+        let start = parse_window_date("2024-05-01", false).unwrap();
+        let end = parse_window_date("2024-05-01", true).unwrap();
+        assert_eq!((end - start).num_seconds(), 24 * 3600 - 1);
+        let minute = parse_window_date("2024-05-01 13:30", true).unwrap();
+        assert_eq!((minute - start).num_minutes(), 13 * 60 + 30);
+
+        let err = parse_window_date("May 1st", false).unwrap_err();
+        assert!(err.to_string().contains("Invalid date 'May 1st'"));
+    }
+
+    #[test]
+    fn test_checkout_commit_window_flags_late_commits() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        commit_at(&repo, "early.txt", at("2024-04-01T10:00:00Z").timestamp());
+        let on_time = commit_at(&repo, "on_time.txt", at("2024-04-20T10:00:00Z").timestamp());
+        commit_at(&repo, "late.txt", at("2024-05-02T10:00:00Z").timestamp());
+
+        let window = checkout_commit_window(
+            temp_dir.path(),
+            Some(at("2024-04-10T00:00:00Z")),
+            Some(at("2024-05-01T00:00:00Z")),
+        )
+        .unwrap();
+        assert_eq!(
+            window,
+            CommitWindow {
+                commit: Some(on_time.to_string()),
+                late: true,
+                commits_in_window: 1,
+            }
+        );
+        assert_eq!(repo.head().unwrap().target(), Some(on_time));
+        assert!(temp_dir.path().join("on_time.txt").exists());
+        assert!(!temp_dir.path().join("late.txt").exists());
+
+        // The late commit is no longer reachable from the checked out commit
+        let window =
+            checkout_commit_window(temp_dir.path(), None, Some(at("2024-06-01T00:00:00Z")))
+                .unwrap();
+        assert!(!window.late);
+        assert_eq!(window.commits_in_window, 2);
+    }
+
+    #[tokio::test]
+    async fn test_clone_missing_repo_is_reported() {
+        let temp_dir = TempDir::new().unwrap();
+        let api = Platform::local(
+            temp_dir.path().join("platform"),
+            "test-org".to_string(),
+            "teacher".to_string(),
+        )
+        .unwrap();
+        let student_teams = vec![StudentTeam::new(vec!["alice".to_string()])];
+
+        let result = clone_student_repos(
+            &["task1".to_string()],
+            &student_teams,
+            &api,
+            &temp_dir.path().join("target"),
+            None,
+            &CloneOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert!(!result.is_success());
+        assert_eq!(result.errors[0].repo_name, "alice-task1");
+    }
+}
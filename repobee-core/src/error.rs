@@ -24,6 +24,10 @@ pub enum PlatformError {
     #[error("Invalid URL: {0}")]
     InvalidUrl(String),
 
+    /// Platform settings that cannot work, e.g. an empty organization
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
     /// File operation error
     #[error("File error: {0}")]
     FileError(String),
@@ -61,6 +65,11 @@ impl PlatformError {
         Self::InvalidUrl(msg.into())
     }
 
+    /// Create an InvalidConfig error
+    pub fn invalid_config(msg: impl Into<String>) -> Self {
+        Self::InvalidConfig(msg.into())
+    }
+
     /// Create an Unexpected error
     pub fn unexpected(msg: impl Into<String>) -> Self {
         Self::Unexpected(msg.into())
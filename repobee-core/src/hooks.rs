@@ -0,0 +1,214 @@
+//! Lifecycle hooks for the setup workflow
+//!
+//! Hooks are user-provided executables in a hooks directory, named after the stage they run
+//! at (`pre-setup`, `repo-created`, `post-setup`), optionally with an extension such as
+//! `pre-setup.sh`. Context is passed through `REPOBEE_*` environment variables.
+//!
+//! **Security:** hooks run arbitrary programs with the privileges of the user running
+//! RepoBee, and they receive repository URLs that may contain credentials. Hooks are
+//! disabled unless a hooks directory is explicitly configured; only point it at a
+//! directory you control.
+
+use crate::error::{PlatformError, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Point in the setup workflow at which a hook runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStage {
+    /// Before any template is cloned; a failing hook aborts the setup
+    PreSetup,
+    /// After a student repository was created and the template was pushed to it
+    RepoCreated,
+    /// After all repositories were processed
+    PostSetup,
+}
+
+impl HookStage {
+    /// File name (without extension) of the hook script for this stage
+    pub fn file_stem(&self) -> &'static str {
+        match self {
+            Self::PreSetup => "pre-setup",
+            Self::RepoCreated => "repo-created",
+            Self::PostSetup => "post-setup",
+        }
+    }
+}
+
+/// Context passed to a hook via environment variables
+#[derive(Debug, Clone, Default)]
+pub struct HookContext {
+    /// `REPOBEE_TEAM`
+    pub team: Option<String>,
+    /// `REPOBEE_TEAM_MEMBERS` (comma separated)
+    pub members: Vec<String>,
+    /// `REPOBEE_REPO`
+    pub repo: Option<String>,
+    /// `REPOBEE_REPO_URL`
+    pub repo_url: Option<String>,
+    /// `REPOBEE_ASSIGNMENT`
+    pub assignment: Option<String>,
+}
+
+/// Captured outcome of running a hook
+#[derive(Debug, Clone)]
+pub struct HookRun {
+    pub stage: HookStage,
+    pub script: PathBuf,
+    /// Repository the hook ran for, if any
+    pub repo: Option<String>,
+    /// Exit code, `None` when the hook was killed by a signal
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl HookRun {
+    pub fn is_success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Find the hook script for a stage in the hooks directory
+pub fn find_hook(hooks_dir: &Path, stage: HookStage) -> Option<PathBuf> {
+    let entries = std::fs::read_dir(hooks_dir).ok()?;
+    let mut candidates: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file() && path.file_stem().and_then(|s| s.to_str()) == Some(stage.file_stem())
+        })
+        .collect();
+    // Make the choice deterministic when e.g. both `pre-setup` and `pre-setup.sh` exist
+    candidates.sort();
+    candidates.into_iter().next()
+}
+
+/// Run the hook for a stage, if the hooks directory contains one
+///
+/// Returns `Ok(None)` when there is no hook for the stage. A hook exiting with a non-zero
+/// code is not an error; check [`HookRun::is_success`].
+pub fn run_hook(
+    hooks_dir: &Path,
+    stage: HookStage,
+    context: &HookContext,
+    work_dir: &Path,
+) -> Result<Option<HookRun>> {
+    let Some(script) = find_hook(hooks_dir, stage) else {
+        return Ok(None);
+    };
+
+    let mut command = Command::new(&script);
+    command
+        .current_dir(work_dir)
+        .env("REPOBEE_HOOK_STAGE", stage.file_stem())
+        .env("REPOBEE_WORK_DIR", work_dir)
+        .env("REPOBEE_TEAM_MEMBERS", context.members.join(","));
+    for (name, value) in [
+        ("REPOBEE_TEAM", &context.team),
+        ("REPOBEE_REPO", &context.repo),
+        ("REPOBEE_REPO_URL", &context.repo_url),
+        ("REPOBEE_ASSIGNMENT", &context.assignment),
+    ] {
+        if let Some(value) = value {
+            command.env(name, value);
+        }
+    }
+
+    let output = command.output().map_err(|e| {
+        PlatformError::Other(format!(
+            "Failed to run {} hook {}: {}",
+            stage.file_stem(),
+            script.display(),
+            e
+        ))
+    })?;
+
+    Ok(Some(HookRun {
+        stage,
+        script,
+        repo: context.repo.clone(),
+        exit_code: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    fn write_script(dir: &Path, name: &str, body: &str) {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_missing_hook_is_skipped() {
+        let hooks_dir = TempDir::new().unwrap();
+        let run = run_hook(
+            hooks_dir.path(),
+            HookStage::PreSetup,
+            &HookContext::default(),
+            hooks_dir.path(),
+        )
+        .unwrap();
+        assert!(run.is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hook_receives_context() {
+        let hooks_dir = TempDir::new().unwrap();
+        write_script(
+            hooks_dir.path(),
+            "repo-created.sh",
+            "echo \"$REPOBEE_HOOK_STAGE $REPOBEE_TEAM $REPOBEE_REPO $REPOBEE_ASSIGNMENT\"",
+        );
+        let context = HookContext {
+            team: Some("team1".to_string()),
+            members: vec!["alice".to_string()],
+            repo: Some("team1-task1".to_string()),
+            repo_url: None,
+            assignment: Some("task1".to_string()),
+        };
+
+        let run = run_hook(
+            hooks_dir.path(),
+            HookStage::RepoCreated,
+            &context,
+            hooks_dir.path(),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert!(run.is_success());
+        assert_eq!(run.stdout.trim(), "repo-created team1 team1-task1 task1");
+        assert_eq!(run.repo.as_deref(), Some("team1-task1"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failing_hook_output_is_captured() {
+        let hooks_dir = TempDir::new().unwrap();
+        write_script(hooks_dir.path(), "post-setup", "echo oops >&2; exit 3");
+
+        let run = run_hook(
+            hooks_dir.path(),
+            HookStage::PostSetup,
+            &HookContext::default(),
+            hooks_dir.path(),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert!(!run.is_success());
+        assert_eq!(run.exit_code, Some(3));
+        assert_eq!(run.stderr.trim(), "oops");
+    }
+}
@@ -0,0 +1,270 @@
+//! HTTP clients for the LMS and git platform APIs
+//!
+//! Some institutional API gateways only let requests through that carry an extra header,
+//! such as a gateway key or an allowlisted `User-Agent`. The headers configured with
+//! [`set_extra_headers`], usually from the `http_headers` setting, are sent on every
+//! request of the clients built by [`http_client`]: the GitHub, GitLab and Gitea APIs,
+//! Moodle, and the Canvas sections, group sets and enrollments.
+//!
+//! The unified LMS client builds its own HTTP client, so the Canvas course requests it
+//! makes do not carry the extra headers.
+//!
+//! Requests identify themselves as `repobee-tauri/<version>`, so server logs show which
+//! tool and release sent them. Servers that only accept allowlisted agents can be served
+//! another one with [`set_user_agent`]; a `User-Agent` among the extra headers wins over
+//! both.
+//!
+//! Header values that look like credentials are marked sensitive, so they print as
+//! `Sensitive` in debug output, and [`describe_headers`] redacts them for logging.
+
+use crate::error::{PlatformError, Result};
+use crate::settings::CommonSettings;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// User agent sent unless the settings replace it
+pub const DEFAULT_USER_AGENT: &str = concat!("repobee-tauri/", env!("CARGO_PKG_VERSION"));
+
+/// Parts of header names whose values are redacted in logs
+const SENSITIVE_NAME_PARTS: &[&str] = &[
+    "auth",
+    "key",
+    "token",
+    "secret",
+    "password",
+    "cookie",
+    "session",
+    "signature",
+];
+
+static EXTRA_HEADERS: Mutex<Option<HeaderMap>> = Mutex::new(None);
+static USER_AGENT: Mutex<Option<HeaderValue>> = Mutex::new(None);
+
+/// Whether the value of header `name` should be kept out of logs
+fn is_sensitive(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_NAME_PARTS.iter().any(|part| name.contains(part))
+}
+
+/// Parse header names and values, rejecting the ones HTTP does not allow
+fn parse_headers(headers: &BTreeMap<String, String>) -> Result<HeaderMap> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let header_name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| {
+            PlatformError::invalid_config(format!("'{}' is not a valid HTTP header name", name))
+        })?;
+        let mut header_value = HeaderValue::from_str(value.trim()).map_err(|_| {
+            PlatformError::invalid_config(format!(
+                "The value of HTTP header '{}' contains characters HTTP does not allow",
+                name
+            ))
+        })?;
+        header_value.set_sensitive(is_sensitive(name));
+        map.insert(header_name, header_value);
+    }
+    Ok(map)
+}
+
+/// Send `headers` on every request of the clients built after this call
+///
+/// An empty map removes the extra headers. Invalid names or values are an
+/// [`PlatformError::InvalidConfig`] and leave the previous headers in place.
+pub fn set_extra_headers(headers: &BTreeMap<String, String>) -> Result<()> {
+    let map = parse_headers(headers)?;
+    if !map.is_empty() {
+        log::info!("Sending extra HTTP headers: {}", describe_headers(headers));
+    }
+    let map = Some(map).filter(|m| !m.is_empty());
+    *EXTRA_HEADERS.lock().unwrap_or_else(|e| e.into_inner()) = map;
+    Ok(())
+}
+
+/// Identify requests of the clients built after this call as `user_agent`
+///
+/// An empty value restores [`DEFAULT_USER_AGENT`]. A value HTTP does not allow is an
+/// [`PlatformError::InvalidConfig`] and leaves the previous user agent in place.
+pub fn set_user_agent(user_agent: &str) -> Result<()> {
+    let user_agent = user_agent.trim();
+    let value = if user_agent.is_empty() {
+        None
+    } else {
+        Some(HeaderValue::from_str(user_agent).map_err(|_| {
+            PlatformError::invalid_config(format!(
+                "User agent '{}' contains characters HTTP does not allow",
+                user_agent
+            ))
+        })?)
+    };
+    *USER_AGENT.lock().unwrap_or_else(|e| e.into_inner()) = value;
+    Ok(())
+}
+
+/// The user agent sent on requests, without a `User-Agent` from the extra headers
+pub fn user_agent() -> String {
+    USER_AGENT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or(DEFAULT_USER_AGENT)
+        .to_string()
+}
+
+/// Apply the `http_user_agent` and `http_headers` settings
+///
+/// Nothing changes when either setting is invalid.
+pub fn set_http_settings(settings: &CommonSettings) -> Result<()> {
+    parse_headers(&settings.http_headers)?;
+    set_user_agent(&settings.http_user_agent)?;
+    set_extra_headers(&settings.http_headers)
+}
+
+/// `name: value` list of `headers` for logs, with credential-like values redacted
+pub fn describe_headers(headers: &BTreeMap<String, String>) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            if is_sensitive(name) {
+                format!("{}: <redacted>", name)
+            } else {
+                format!("{}: {}", name, value)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// HTTP client with the repobee user agent and the configured extra headers
+pub(crate) fn http_client() -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().user_agent(user_agent());
+    if let Some(headers) = EXTRA_HEADERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+    {
+        // Added after the user agent, so an allowlisted `User-Agent` replaces ours
+        builder = builder.default_headers(headers);
+    }
+    Ok(builder.build()?)
+}
+
+/// Extract the `rel="next"` URL from the `Link` header of a paginated response, as GitHub,
+/// GitLab and Canvas send it
+pub(crate) fn next_link(headers: &HeaderMap) -> Option<String> {
+    let link = headers.get(reqwest::header::LINK)?.to_str().ok()?;
+    parse_next_link(link)
+}
+
+fn parse_next_link(link: &str) -> Option<String> {
+    link.split(',').find_map(|part| {
+        let (url, params) = part.split_once(';')?;
+        params
+            .split(';')
+            .any(|p| p.trim() == "rel=\"next\"")
+            .then(|| {
+                url.trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string()
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::http::{MockServer, Response};
+
+    fn headers(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_invalid_headers_are_rejected() {
+        let result = parse_headers(&headers(&[("X Gateway", "1")]));
+        assert!(matches!(result, Err(PlatformError::InvalidConfig(_))));
+        let result = parse_headers(&headers(&[("X-Gateway", "a\nb")]));
+        assert!(matches!(result, Err(PlatformError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_sensitive_values_are_redacted() {
+        let headers = headers(&[("X-Api-Key", "s3cret"), ("X-Campus", "tue")]);
+        assert_eq!(
+            describe_headers(&headers),
+            "X-Api-Key: <redacted>, X-Campus: tue"
+        );
+        let map = parse_headers(&headers).unwrap();
+        assert!(map["x-api-key"].is_sensitive());
+        assert!(!format!("{:?}", map).contains("s3cret"));
+    }
+
+    // The headers are global, so one test sets them to keep parallel tests apart
+    #[tokio::test]
+    async fn test_extra_headers_are_sent() {
+        let server = MockServer::sequence(vec![Response::status(200)]);
+
+        set_extra_headers(&headers(&[
+            ("X-Gateway-Key", "s3cret"),
+            ("User-Agent", "campus-allowlisted"),
+        ]))
+        .unwrap();
+        let client = http_client().unwrap();
+        set_extra_headers(&BTreeMap::new()).unwrap();
+        client.get(server.url()).send().await.unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request.header("X-Gateway-Key"), Some("s3cret"));
+        assert_eq!(request.header("User-Agent"), Some("campus-allowlisted"));
+        assert!(!request
+            .headers
+            .values()
+            .any(|v| v.contains("repobee-tauri")));
+    }
+
+    #[test]
+    fn test_next_link() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(next_link(&headers), None);
+        headers.insert(
+            reqwest::header::LINK,
+            HeaderValue::from_static(
+                "<https://api.github.com/orgs/c/repos?page=3>; rel=\"next\", \
+                 <https://api.github.com/orgs/c/repos?page=9>; rel=\"last\"",
+            ),
+        );
+        assert_eq!(
+            next_link(&headers).as_deref(),
+            Some("https://api.github.com/orgs/c/repos?page=3")
+        );
+    }
+
+    #[test]
+    fn test_parse_next_link() {
+        let link = "<https://canvas.example/api/v1/courses/1/sections?page=1>; rel=\"current\",\
+                    <https://canvas.example/api/v1/courses/1/sections?page=2>; rel=\"next\",\
+                    <https://canvas.example/api/v1/courses/1/sections?page=3>; rel=\"last\"";
+        assert_eq!(
+            parse_next_link(link).as_deref(),
+            Some("https://canvas.example/api/v1/courses/1/sections?page=2")
+        );
+    }
+
+    #[test]
+    fn test_parse_next_link_last_page() {
+        let link = "<https://canvas.example/api/v1/courses/1/sections?page=3>; rel=\"last\"";
+        assert_eq!(parse_next_link(link), None);
+    }
+
+    #[test]
+    fn test_user_agent() {
+        assert!(DEFAULT_USER_AGENT.starts_with("repobee-tauri/"));
+        assert!(DEFAULT_USER_AGENT.ends_with(env!("CARGO_PKG_VERSION")));
+        let result = set_user_agent("campus\nagent");
+        assert!(matches!(result, Err(PlatformError::InvalidConfig(_))));
+    }
+}
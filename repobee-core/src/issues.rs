@@ -0,0 +1,277 @@
+//! Opening the same issue in many student repositories
+//!
+//! Announcements and tasks are posted as an issue in every student repository of an
+//! assignment. Issues are opened one repository at a time, since platforms limit how
+//! fast issues may be created; a failure in one repository does not stop the others.
+//!
+//! The title and body are templates: `{{team}}`, `{{members}}`, `{{assignment}}`,
+//! `{{repo_name}}` and `{{repo_url}}` are filled in per repository, next to the
+//! variables of the [`IssueTemplate`] such as a `{{due_date}}`. Unknown variables are
+//! left as they are and reported in [`IssueResult::unknown_variables`].
+
+use crate::error::{PlatformError, Result};
+use crate::platform::PlatformAPI;
+use crate::progress::{IssueProgress, Progress};
+use crate::reconcile::ExpectedRepo;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Variables filled in per repository
+pub const REPO_VARIABLES: &[&str] = &["team", "members", "assignment", "repo_name", "repo_url"];
+
+/// Issue to open, with `{{variable}}` placeholders in its title and body
+#[derive(Debug, Clone, Default)]
+pub struct IssueTemplate {
+    pub title: String,
+    /// Markdown body
+    pub body: String,
+    /// Variables shared by all repositories, e.g. `due_date`; the per-repository
+    /// variables in [`REPO_VARIABLES`] take precedence
+    pub variables: BTreeMap<String, String>,
+}
+
+/// Outcome of [`open_issue_in_repos`]
+#[derive(Debug, Clone, Default)]
+pub struct IssueResult {
+    /// Repositories the issue was opened in
+    pub opened: Vec<OpenedIssue>,
+    /// Repositories the issue could not be opened in
+    pub errors: Vec<IssueError>,
+    /// Placeholders without a value, left in the issue as written, sorted
+    pub unknown_variables: Vec<String>,
+}
+
+/// Issue opened in one repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenedIssue {
+    pub repo_name: String,
+    /// Issue number on the platform, if it reports one
+    pub number: Option<u32>,
+}
+
+/// Repository the issue could not be opened in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueError {
+    pub repo_name: String,
+    pub error: String,
+}
+
+impl IssueResult {
+    /// Whether the issue was opened in every repository
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Read an issue body from a Markdown file
+pub fn read_issue_body(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|e| {
+        PlatformError::FileError(format!(
+            "Failed to read issue body {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Replace the `{{variable}}` placeholders of `template` with their values
+///
+/// Whitespace inside the braces is ignored. Placeholders without a value are kept as
+/// written; their names are returned next to the rendered text.
+pub fn render_template(
+    template: &str,
+    variables: &BTreeMap<String, String>,
+) -> (String, Vec<String>) {
+    let mut rendered = String::with_capacity(template.len());
+    let mut unknown = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(length) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + length + 2;
+        let name = rest[start + 2..end - 2].trim();
+        rendered.push_str(&rest[..start]);
+        match variables.get(name) {
+            Some(value) => rendered.push_str(value),
+            None => {
+                rendered.push_str(&rest[start..end]);
+                unknown.push(name.to_string());
+            }
+        }
+        rest = &rest[end..];
+    }
+    rendered.push_str(rest);
+    (rendered, unknown)
+}
+
+/// Open `issue` in each of the repositories `repos`
+pub async fn open_issue_in_repos<P: PlatformAPI>(
+    api: &P,
+    repos: &[ExpectedRepo],
+    issue: &IssueTemplate,
+) -> Result<IssueResult> {
+    open_issue_in_repos_with_progress(api, repos, issue, |_| {}).await
+}
+
+/// [`open_issue_in_repos`], reporting each repository it is done with
+///
+/// Only an empty title fails the whole run; problems with single repositories, such as
+/// a repository that does not exist, end up in [`IssueResult::errors`].
+pub async fn open_issue_in_repos_with_progress<P: PlatformAPI, F: FnMut(Progress)>(
+    api: &P,
+    repos: &[ExpectedRepo],
+    issue: &IssueTemplate,
+    mut progress: F,
+) -> Result<IssueResult> {
+    if issue.title.trim().is_empty() {
+        return Err(PlatformError::Other("The issue needs a title".to_string()));
+    }
+
+    let mut result = IssueResult::default();
+    let mut unknown = BTreeSet::new();
+    for (index, expected) in repos.iter().enumerate() {
+        let opened = match api.get_repo(&expected.name, None).await {
+            Ok(repo) => {
+                let mut variables = issue.variables.clone();
+                variables.insert("team".to_string(), expected.team.clone());
+                variables.insert("members".to_string(), expected.members.join(", "));
+                variables.insert("assignment".to_string(), expected.assignment.clone());
+                variables.insert("repo_name".to_string(), repo.name.clone());
+                variables.insert("repo_url".to_string(), repo.url.clone());
+                let (title, unknown_in_title) = render_template(issue.title.trim(), &variables);
+                let (body, unknown_in_body) = render_template(&issue.body, &variables);
+                unknown.extend(unknown_in_title);
+                unknown.extend(unknown_in_body);
+                api.create_issue(&title, &body, &repo, None).await
+            }
+            Err(PlatformError::NotFound(_)) => Err(PlatformError::not_found(format!(
+                "Repository '{}' does not exist",
+                expected.name
+            ))),
+            Err(e) => Err(e),
+        };
+        match opened {
+            Ok(opened) => result.opened.push(OpenedIssue {
+                repo_name: expected.name.clone(),
+                number: opened.number,
+            }),
+            Err(e) => {
+                log::warn!("Failed to open issue in {}: {}", expected.name, e);
+                result.errors.push(IssueError {
+                    repo_name: expected.name.clone(),
+                    error: e.to_string(),
+                });
+            }
+        }
+        progress(Progress::from(IssueProgress::Opened {
+            current: index + 1,
+            total: repos.len(),
+            repo_name: expected.name.clone(),
+        }));
+    }
+
+    if !unknown.is_empty() {
+        log::warn!(
+            "Unknown issue variables left as written: {}",
+            unknown.iter().cloned().collect::<Vec<_>>().join(", ")
+        );
+    }
+    result.unknown_variables = unknown.into_iter().collect();
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Platform;
+    use crate::types::IssueState;
+    use tempfile::TempDir;
+
+    fn expected(name: &str, team: &str) -> ExpectedRepo {
+        ExpectedRepo {
+            name: name.to_string(),
+            team: team.to_string(),
+            assignment: "task-1".to_string(),
+            members: vec!["alice".to_string(), "bob".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_render_template() {
+        let variables: BTreeMap<String, String> = [
+            ("team".to_string(), "team1".to_string()),
+            ("due_date".to_string(), "Friday".to_string()),
+        ]
+        .into_iter()
+        .collect();
+
+        let (text, unknown) =
+            render_template("Hi {{team}}, due {{ due_date }} at {{time}}.", &variables);
+        assert_eq!(text, "Hi team1, due Friday at {{time}}.");
+        assert_eq!(unknown, vec!["time"]);
+
+        // Unclosed braces and single braces are text
+        let (text, unknown) = render_template("{team} {{team", &variables);
+        assert_eq!(text, "{team} {{team");
+        assert!(unknown.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_open_issue_in_repos() {
+        let temp_dir = TempDir::new().unwrap();
+        let api = Platform::local(
+            temp_dir.path().to_path_buf(),
+            "test-org".to_string(),
+            "teacher".to_string(),
+        )
+        .unwrap();
+        let repo = api
+            .create_repo("team1-task-1", "", true, None)
+            .await
+            .unwrap();
+
+        let repos = vec![
+            expected("team1-task-1", "team1"),
+            expected("team2-task-1", "team2"),
+        ];
+        let issue = IssueTemplate {
+            title: "Deadline moved for {{team}}".to_string(),
+            body: "{{members}}: {{assignment}} is due **{{due_date}}** in {{repo_url}}. {{room}}"
+                .to_string(),
+            variables: [("due_date".to_string(), "Friday".to_string())]
+                .into_iter()
+                .collect(),
+        };
+        let mut events = 0;
+        let result = open_issue_in_repos_with_progress(&api, &repos, &issue, |_| events += 1)
+            .await
+            .unwrap();
+
+        assert_eq!(events, 2);
+        assert!(!result.is_success());
+        assert_eq!(result.opened.len(), 1);
+        assert_eq!(result.opened[0].repo_name, "team1-task-1");
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].repo_name, "team2-task-1");
+        assert!(result.errors[0].error.contains("does not exist"));
+        assert_eq!(result.unknown_variables, vec!["room"]);
+
+        let issues = api.get_repo_issues(&repo, IssueState::Open).await.unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].title, "Deadline moved for team1");
+        assert_eq!(
+            issues[0].body,
+            format!(
+                "alice, bob: task-1 is due **Friday** in {}. {{{{room}}}}",
+                repo.url
+            )
+        );
+
+        let untitled = IssueTemplate {
+            title: " ".to_string(),
+            ..Default::default()
+        };
+        assert!(open_issue_in_repos(&api, &repos, &untitled).await.is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! Git LFS support for templates
+//!
+//! libgit2 does not understand Git LFS, so a cloned LFS template only contains pointer
+//! files. The actual objects are fetched and pushed by shelling out to `git lfs`.
+
+use crate::error::{PlatformError, Result};
+use crate::throttle;
+use std::path::Path;
+use std::process::Command;
+
+/// Whether the repository's `.gitattributes` tracks any files with Git LFS
+pub fn uses_lfs(repo_path: &Path) -> bool {
+    std::fs::read_to_string(repo_path.join(".gitattributes"))
+        .map(|attributes| {
+            attributes.lines().any(|line| {
+                let line = line.trim();
+                !line.starts_with('#') && line.split_whitespace().any(|a| a == "filter=lfs")
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// Whether the `git lfs` command is installed
+pub fn lfs_available() -> bool {
+    Command::new("git")
+        .args(["lfs", "version"])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Error for a template that needs Git LFS when it is not installed
+pub fn lfs_unavailable_error(template_name: &str) -> PlatformError {
+    PlatformError::Other(format!(
+        "Template {} uses Git LFS, but git-lfs is not installed. Install it from \
+         https://git-lfs.com and run `git lfs install`, then retry the setup.",
+        template_name
+    ))
+}
+
+/// Download the LFS objects of a cloned repository and replace the pointer files
+pub fn lfs_pull(repo_path: &Path, remote_url: &str, token: Option<&str>) -> Result<()> {
+    {
+        let _slot = throttle::network_slot();
+        run_git_lfs(
+            repo_path,
+            &["fetch", remote_url, "--all"],
+            remote_url,
+            token,
+        )?;
+    }
+    run_git_lfs(repo_path, &["checkout"], remote_url, None)
+}
+
+/// Upload all LFS objects of a repository to another remote
+pub fn lfs_push(repo_path: &Path, remote_url: &str, token: Option<&str>) -> Result<()> {
+    let _slot = throttle::network_slot();
+    run_git_lfs(repo_path, &["push", "--all", remote_url], remote_url, token)
+}
+
+fn run_git_lfs(
+    repo_path: &Path,
+    args: &[&str],
+    remote_url: &str,
+    token: Option<&str>,
+) -> Result<()> {
+    let mut command = Command::new("git");
+    command.arg("lfs").args(args).current_dir(repo_path);
+    // Pass the token through the environment so it does not show up in the process list,
+    // and only send it to the remote's host
+    if let Some(token) = token {
+        command
+            .env("GIT_CONFIG_COUNT", "1")
+            .env(
+                "GIT_CONFIG_KEY_0",
+                format!("http.{}/.extraHeader", url_origin(remote_url)),
+            )
+            .env(
+                "GIT_CONFIG_VALUE_0",
+                format!(
+                    "Authorization: Basic {}",
+                    base64_encode(format!("oauth2:{}", token).as_bytes())
+                ),
+            );
+    }
+
+    let output = command
+        .output()
+        .map_err(|e| PlatformError::Other(format!("Failed to run git lfs: {}", e)))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(PlatformError::Other(format!(
+            "git lfs {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// Scheme and host of a URL, e.g. `https://gitlab.com` for `https://gitlab.com/org/repo.git`
+fn url_origin(url: &str) -> &str {
+    let Some(scheme_end) = url.find("://") else {
+        return url;
+    };
+    let host_start = scheme_end + 3;
+    match url[host_start..].find('/') {
+        Some(path_start) => &url[..host_start + path_start],
+        None => url,
+    }
+}
+
+fn base64_encode(input: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_uses_lfs() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(!uses_lfs(temp_dir.path()));
+
+        let attributes = temp_dir.path().join(".gitattributes");
+        std::fs::write(&attributes, "*.txt text eol=lf\n# *.bin filter=lfs\n").unwrap();
+        assert!(!uses_lfs(temp_dir.path()));
+
+        std::fs::write(&attributes, "*.csv filter=lfs diff=lfs merge=lfs -text\n").unwrap();
+        assert!(uses_lfs(temp_dir.path()));
+    }
+
+    #[test]
+    fn test_url_origin() {
+        assert_eq!(
+            url_origin("https://gitlab.tue.nl/course/repo.git"),
+            "https://gitlab.tue.nl"
+        );
+        assert_eq!(url_origin("https://gitlab.tue.nl"), "https://gitlab.tue.nl");
+    }
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"oauth2:token"), "b2F1dGgyOnRva2Vu");
+    }
+}
@@ -3,26 +3,107 @@
 //! This crate provides the core abstractions and types for RepoBee,
 //! including platform API abstraction for GitHub, GitLab, and Gitea.
 
+pub mod activity;
+pub mod clone;
 pub mod error;
+pub mod hooks;
+pub mod http;
+pub mod issues;
+pub mod lfs;
 pub mod lms;
+pub mod mapping;
+pub mod naming;
+pub mod notify;
+pub mod paths;
 pub mod platform;
+pub mod preflight;
+pub mod progress;
+pub mod reconcile;
+pub mod retry;
+pub mod roster;
+pub mod secrets;
 pub mod settings;
 pub mod setup;
+pub mod suggest;
+pub mod templates;
+pub mod throttle;
 pub mod types;
 
+#[cfg(test)]
+mod test_support;
+
 // Re-export commonly used items
+pub use activity::{repo_activity, ActivityError, ActivityReport, RepoActivity};
+pub use clone::{
+    checkout_commit_window, clone_path, clone_student_repos, clone_student_repos_with_progress,
+    fast_forward_clone, parse_window_date, CloneError, CloneOptions, CloneResult, CommitWindow,
+    DEFAULT_CLONE_CONCURRENCY,
+};
 pub use error::{PlatformError, Result};
-pub use platform::{Platform, PlatformAPI};
-pub use setup::{setup_student_repos, SetupError, SetupResult};
+pub use hooks::{HookContext, HookRun, HookStage};
+pub use http::{
+    describe_headers, set_extra_headers, set_http_settings, set_user_agent, user_agent,
+    DEFAULT_USER_AGENT,
+};
+pub use issues::{
+    open_issue_in_repos, open_issue_in_repos_with_progress, read_issue_body, render_template,
+    IssueError, IssueResult, IssueTemplate, OpenedIssue, REPO_VARIABLES,
+};
+pub use lfs::{lfs_available, uses_lfs};
+pub use mapping::{write_mapping_csv, MappingColumn, DEFAULT_MAPPING_COLUMNS};
+pub use naming::{repo_name, validate_repo_name, RepoNaming};
+pub use notify::{
+    read_student_emails, team_notifications, write_notifications, NotificationFormat, NotifiedRepo,
+    TeamNotification,
+};
+pub use paths::{
+    default_work_dir, resolve_dir, resolve_output_file, sanitize_dir_name, work_path, ResolvedDir,
+};
+pub use platform::{
+    delete_single_repo, git_token_generation_instructions, git_token_generation_url,
+    missing_token_scopes, validate_config, validate_local_config, GitPlatformKind, PageStyle,
+    PagedStream, Platform, PlatformAPI,
+};
+pub use preflight::{preflight_setup, CheckStatus, PreflightCheck, PreflightReport};
+pub use progress::{CloneProgress, IssueProgress, Progress, SetupProgress};
+pub use reconcile::{expected_repos, reconcile, ExpectedRepo, Reconciliation};
+pub use retry::RetryPolicy;
+pub use roster::{diff_roster, RosterDiff, TeamMemberChanges};
+pub use secrets::{check_token_file, resolve_token, token_file};
+pub use setup::{
+    clean_work_dir, clone_or_update_template, find_empty_repos, push_to_repo_with_retry,
+    set_ci_variables, setup_student_repos, setup_student_repos_with_options,
+    setup_student_repos_with_progress, template_default_branch, working_tree_size, AssignmentRepos,
+    CiVariableResult, CleanupReport, DistributionMode, EmptyTeamPolicy, SetupError, SetupOptions,
+    SetupResult, SetupTimings, TemplateSizeCheck, WorkDirCleanup,
+};
+pub use suggest::{closest_match, did_you_mean};
+pub use templates::{
+    check_assignments, list_local_template_files, list_local_templates, list_templates,
+};
+pub use throttle::{git_throttle, set_git_throttle, GitThrottle};
 pub use types::{
-    Issue, IssueState, Repo, StudentRepo, StudentTeam, Team, TeamPermission, TemplateRepo,
+    CiVariable, Issue, IssueState, Repo, RepoFile, RepoFileKind, StudentRepo, StudentTeam, Team,
+    TeamPermission, TemplateRepo,
 };
 
 // LMS re-exports
 pub use lms::{
-    create_lms_client_with_params, generate_repobee_yaml, generate_repobee_yaml_with_progress,
-    get_student_info, get_student_info_with_progress, write_csv_file, write_yaml_file,
-    FetchProgress, MemberOption as LmsMemberOption, StudentInfo, YamlConfig,
+    clear_lms_cache, course_name_differs, create_lms_client_with_params, create_moodle_client,
+    debug_student, default_lms_cache_dir, generate_repobee_yaml,
+    generate_repobee_yaml_with_progress, get_course_sections, get_course_sections_with_paging,
+    get_course_users_by_role, get_group_sets, get_group_sets_with_paging, get_student_info,
+    get_student_info_by_sections, get_student_info_by_sections_with_progress,
+    get_student_info_with_progress, group_set_file, org_from_course, preview_repobee_yaml,
+    read_json_roster, read_roster_mapping, read_teams_file, read_teams_files, render_org_template,
+    sample_students, students_from_json, trace_student, validate_group_path, validate_repobee_yaml,
+    validate_student_info, validate_team_name_template, write_csv_file, write_xlsx_file,
+    write_yaml_file, CachedLmsClient, CanvasEnrollmentClient, CanvasGroupSet, CanvasPaging,
+    CanvasSection, CourseDataSource, CourseUser, EnrollmentRole, FetchProgress, FixtureLmsClient,
+    GenerationResult, GroupingSource, LmsDataSource, MemberFormat, MemberOption as LmsMemberOption,
+    MemberOrder, MergedTeams, MoodleClient, RosterFieldMapping, StudentFetchOptions,
+    StudentFetchResult, StudentGroup, StudentInfo, StudentTrace, StudentWarning, YamlConfig,
+    YamlPreview, DEFAULT_GROUP_FETCH_CONCURRENCY, DEFAULT_LMS_CACHE_TTL,
 };
 
 // Re-export lms-common types (used throughout the app)
@@ -37,9 +118,11 @@ pub use lms_client::{LmsAuth, LmsClient, LmsType};
 // Settings re-exports
 pub use settings::{
     atomic_write, atomic_write_json, atomic_write_string, ActiveTab, CLIConfig, CommonSettings,
-    ConfigError, ConfigResult, DirectoryLayout, GuiSettings, Interface, LocationManager,
-    LmsUrlOption, MemberOption, Normalize, PathValidationMode, SettingsLocation,
-    SettingsManager, Validate, ValidationErrors, join_comma_separated, normalize_path,
+    ConfigError, ConfigResult, DirectoryLayout, ExtensionCheck, FileFormat, GitIdSource,
+    GuiSettings, Interface, NameExtraction, SurnameSource,
+    LocationManager, LmsUrlOption, MemberOption, Normalize, PathValidationMode, RecentCourse,
+    SettingsLocation, SettingsManager, Validate, ValidationErrors, MAX_RECENT_COURSES,
+    join_comma_separated, normalize_path,
     normalize_paths, normalize_string, normalize_string_vec, normalize_url,
     parse_comma_separated, path_to_posix_string, validate_date, validate_date_range,
     validate_glob_pattern, validate_path,
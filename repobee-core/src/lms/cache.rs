@@ -0,0 +1,265 @@
+//! On-disk cache of fetched LMS data
+//!
+//! Teachers regenerating the student files of a large course fetch the same users, groups
+//! and memberships over and over. [`CachedLmsClient`] keeps every response in a JSON file
+//! per course for a day, so repeated runs only hit the LMS once:
+//!
+//! ```text
+//! lms-cache/
+//! └── <course id>/
+//!     ├── users-StudentEnrollment.json
+//!     ├── groups.json
+//!     └── members/<group id>.json
+//! ```
+//!
+//! Memberships are stored as soon as each group is fetched, so a fetch that was
+//! interrupted, e.g. by the LMS rate limit, continues with the groups it did not get to.
+
+use super::types::{CourseUser, EnrollmentRole, Group, GroupMembership, User};
+use super::LmsDataSource;
+use crate::error::{PlatformError, Result};
+use crate::settings::atomic_write_json;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How long cached LMS data is used before it is fetched again
+pub const DEFAULT_LMS_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Default cache directory, `lms-cache` in the config directory
+///
+/// Follows `REPOBEE_CONFIG_DIR` like the settings do, and falls back to the system
+/// temporary directory when there is no config directory.
+pub fn default_lms_cache_dir() -> PathBuf {
+    if let Ok(config_dir) = std::env::var("REPOBEE_CONFIG_DIR") {
+        return PathBuf::from(config_dir).join("lms-cache");
+    }
+    directories::ProjectDirs::from("", "", "repobee-tauri")
+        .map(|dirs| dirs.config_dir().join("lms-cache"))
+        .unwrap_or_else(|| std::env::temp_dir().join("repobee-lms-cache"))
+}
+
+/// Remove the cached data of one course, or of all courses
+///
+/// Returns the number of courses whose data was removed.
+pub fn clear_lms_cache(cache_dir: &Path, course_id: Option<&str>) -> Result<usize> {
+    let course_dirs: Vec<PathBuf> = match course_id {
+        Some(course_id) => vec![cache_dir.join(file_key(course_id))],
+        None => match std::fs::read_dir(cache_dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.is_dir())
+                .collect(),
+            Err(_) => Vec::new(),
+        },
+    };
+
+    let mut removed = 0;
+    for dir in course_dirs.iter().filter(|dir| dir.exists()) {
+        std::fs::remove_dir_all(dir).map_err(|e| {
+            PlatformError::FileError(format!("Failed to remove {}: {}", dir.display(), e))
+        })?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// LMS data source that reuses the responses of an earlier run from disk
+///
+/// Wraps another source for a single course. Cached data older than the TTL is fetched
+/// again; with [`refresh`](Self::refresh) everything is fetched again and the cache
+/// updated. Failing to write the cache is logged and does not fail the fetch.
+#[derive(Debug, Clone)]
+pub struct CachedLmsClient<C> {
+    inner: C,
+    dir: PathBuf,
+    ttl: Duration,
+    refresh: bool,
+}
+
+impl<C: LmsDataSource> CachedLmsClient<C> {
+    /// Cache the data of `course_id` in a subdirectory of `cache_dir`
+    pub fn new(inner: C, cache_dir: &Path, course_id: &str) -> Self {
+        Self {
+            inner,
+            dir: cache_dir.join(file_key(course_id)),
+            ttl: DEFAULT_LMS_CACHE_TTL,
+            refresh: false,
+        }
+    }
+
+    /// Use cached data for `ttl` instead of [`DEFAULT_LMS_CACHE_TTL`]
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Ignore the cached data, fetch everything and update the cache
+    pub fn refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Cached value of `file`, or the fetched one when missing, stale or refreshing
+    async fn cached<T, F>(&self, file: &str, fetch: F) -> Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Future<Output = Result<T>>,
+    {
+        let path = self.dir.join(file);
+        if !self.refresh {
+            if let Some(value) = self.read_fresh(&path) {
+                return Ok(value);
+            }
+        }
+
+        let value = fetch.await?;
+        if let Err(e) = atomic_write_json(&path, &value) {
+            log::warn!("Could not cache LMS data in {}: {}", path.display(), e);
+        }
+        Ok(value)
+    }
+
+    fn read_fresh<T: DeserializeOwned>(&self, path: &Path) -> Option<T> {
+        let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if age >= self.ttl {
+            return None;
+        }
+        let content = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+}
+
+impl<C: LmsDataSource> LmsDataSource for CachedLmsClient<C> {
+    async fn get_users(&self, course_id: &str) -> Result<Vec<User>> {
+        self.cached("users.json", self.inner.get_users(course_id))
+            .await
+    }
+
+    async fn get_users_with_role(
+        &self,
+        course_id: &str,
+        role: EnrollmentRole,
+    ) -> Result<Vec<User>> {
+        let users = self.get_course_users(course_id, role).await?;
+        Ok(users.into_iter().map(|user| user.user).collect())
+    }
+
+    async fn get_course_users(
+        &self,
+        course_id: &str,
+        role: EnrollmentRole,
+    ) -> Result<Vec<CourseUser>> {
+        let file = format!("users-{}.json", role.canvas_name());
+        self.cached(&file, self.inner.get_course_users(course_id, role))
+            .await
+    }
+
+    async fn get_groups(&self, course_id: &str) -> Result<Vec<Group>> {
+        self.cached("groups.json", self.inner.get_groups(course_id))
+            .await
+    }
+
+    async fn get_group_members(&self, group_id: &str) -> Result<Vec<GroupMembership>> {
+        let file = format!("members/{}.json", file_key(group_id));
+        self.cached(&file, self.inner.get_group_members(group_id))
+            .await
+    }
+}
+
+/// File name safe version of an LMS id
+fn file_key(id: &str) -> String {
+    let key: String = id
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if key.is_empty() {
+        "_".to_string()
+    } else {
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lms::FixtureLmsClient;
+    use std::cell::Cell;
+    use tempfile::TempDir;
+
+    /// Fixture client that counts the group fetches
+    #[derive(Default)]
+    struct CountingClient {
+        inner: FixtureLmsClient,
+        group_fetches: Cell<usize>,
+    }
+
+    impl LmsDataSource for &CountingClient {
+        async fn get_users(&self, course_id: &str) -> Result<Vec<User>> {
+            self.inner.get_users(course_id).await
+        }
+
+        async fn get_groups(&self, course_id: &str) -> Result<Vec<Group>> {
+            self.group_fetches.set(self.group_fetches.get() + 1);
+            self.inner.get_groups(course_id).await
+        }
+
+        async fn get_group_members(&self, group_id: &str) -> Result<Vec<GroupMembership>> {
+            self.inner.get_group_members(group_id).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_data_is_reused_until_refreshed() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = CountingClient::default();
+
+        let client = CachedLmsClient::new(&source, temp_dir.path(), "1234");
+        client.get_groups("1234").await.unwrap();
+        client.get_groups("1234").await.unwrap();
+        assert_eq!(source.group_fetches.get(), 1);
+        assert!(temp_dir.path().join("1234").join("groups.json").exists());
+
+        let client = client.refresh(true);
+        client.get_groups("1234").await.unwrap();
+        assert_eq!(source.group_fetches.get(), 2);
+
+        let client =
+            CachedLmsClient::new(&source, temp_dir.path(), "1234").with_ttl(Duration::ZERO);
+        client.get_groups("1234").await.unwrap();
+        assert_eq!(source.group_fetches.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_clear_lms_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = CountingClient::default();
+        for course in ["1234", "5678"] {
+            CachedLmsClient::new(&source, temp_dir.path(), course)
+                .get_users(course)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(clear_lms_cache(temp_dir.path(), Some("1234")).unwrap(), 1);
+        assert!(!temp_dir.path().join("1234").exists());
+        assert!(temp_dir.path().join("5678").exists());
+        assert_eq!(clear_lms_cache(temp_dir.path(), None).unwrap(), 1);
+        assert_eq!(
+            clear_lms_cache(&temp_dir.path().join("none"), None).unwrap(),
+            0
+        );
+    }
+}
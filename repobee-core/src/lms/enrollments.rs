@@ -0,0 +1,712 @@
+//! Canvas users by enrollment role
+//!
+//! Users are fetched directly from the Canvas REST API, including the students: the course
+//! users endpoint returns up to 100 users per request with their email and enrollments, so
+//! the separate profile request per user that the unified LMS client makes is not needed.
+//! Profiles are only fetched for the users the list leaves without an email or login id.
+//! For a course of 300 students this is 3 requests instead of 303.
+//!
+//! Groups and group memberships are fetched from the Canvas API as well, so that every list
+//! follows the `Link` header to its last page instead of stopping after 100 items. The page
+//! size and an optional cap on the number of items come from a [`CanvasPaging`].
+//!
+//! Canvas throttles large fetches with 429 or 403 "Rate Limit Exceeded" responses and
+//! occasionally answers with a 5xx. These requests are retried with the backoff of a
+//! [`RetryPolicy`], or after the delay of a `Retry-After` header when Canvas sends one.
+
+use super::sections::course_sections;
+use super::types::{
+    CanvasPaging, CanvasSection, CourseUser, EnrollmentRole, Group, GroupMembership, User,
+};
+use super::LmsDataSource;
+use crate::error::{PlatformError, Result};
+use crate::http::{http_client, next_link};
+use crate::retry::RetryPolicy;
+use futures_util::stream::{self, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Number of profile requests sent at the same time for incomplete users
+const PROFILE_CONCURRENCY: usize = 8;
+
+/// Longest `Retry-After` delay that is waited for
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Deserialize)]
+struct CanvasUserResponse {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    login_id: Option<String>,
+    #[serde(default)]
+    sis_user_id: Option<String>,
+    /// "Last, First", so that surnames with a particle stay intact
+    #[serde(default)]
+    sortable_name: Option<String>,
+}
+
+impl CanvasUserResponse {
+    /// Whether the list left out fields that the profile has
+    fn is_incomplete(&self) -> bool {
+        self.email.is_none() || self.login_id.is_none()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CanvasGroupResponse {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    members_count: Option<u64>,
+    #[serde(default)]
+    max_membership: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CanvasMembershipResponse {
+    id: u64,
+    group_id: u64,
+    user_id: u64,
+}
+
+/// Response of the Canvas user profile endpoint
+#[derive(Debug, Deserialize)]
+struct CanvasProfileResponse {
+    #[serde(default)]
+    primary_email: Option<String>,
+    #[serde(default)]
+    login_id: Option<String>,
+    #[serde(default)]
+    sis_user_id: Option<String>,
+    #[serde(default)]
+    sortable_name: Option<String>,
+}
+
+/// Fetch the users of a Canvas course that have the given enrollment role
+///
+/// Only users the list returns without an email or login id cost an extra profile
+/// request; when that fails as well, the user is kept as the list returned them.
+pub async fn get_course_users_by_role(
+    base_url: &str,
+    access_token: &str,
+    course_id: &str,
+    role: EnrollmentRole,
+) -> Result<Vec<User>> {
+    let users = users_by_role(
+        base_url,
+        access_token,
+        course_id,
+        role,
+        &RetryPolicy::default(),
+        &CanvasPaging::default(),
+    )
+    .await?;
+    Ok(users.into_iter().map(|user| user.user).collect())
+}
+
+/// [`get_course_users_by_role`] with the sortable names, retrying throttled and failed
+/// requests with `retry` and paging as `paging` says
+async fn users_by_role(
+    base_url: &str,
+    access_token: &str,
+    course_id: &str,
+    role: EnrollmentRole,
+    retry: &RetryPolicy,
+    paging: &CanvasPaging,
+) -> Result<Vec<CourseUser>> {
+    let client = http_client()?;
+    let base_url = base_url.trim_end_matches('/');
+
+    let url = format!(
+        "{}/api/v1/courses/{}/users?enrollment_type[]={}\
+         &include[]=email&include[]=enrollments&per_page={}",
+        base_url,
+        course_id,
+        role.enrollment_type(),
+        paging.page_size()
+    );
+    let users: Vec<CanvasUserResponse> = get_all_pages(&client, &url, access_token, retry, paging)
+        .await
+        .map_err(|e| match e {
+            PlatformError::BadCredentials(text) => {
+                PlatformError::bad_credentials(format!("Failed to fetch {} users: {}", role, text))
+            }
+            PlatformError::NotFound(_) => {
+                PlatformError::not_found(format!("Course '{}' not found", course_id))
+            }
+            e => e,
+        })?;
+
+    let users: Vec<CanvasUserResponse> = stream::iter(users)
+        .map(|mut user| {
+            let client = &client;
+            async move {
+                if user.is_incomplete() {
+                    let url = format!("{}/api/v1/users/{}/profile", base_url, user.id);
+                    match get_json::<CanvasProfileResponse>(client, &url, access_token, retry).await
+                    {
+                        Ok((profile, _)) => {
+                            user.email = user.email.or(profile.primary_email);
+                            user.login_id = user.login_id.or(profile.login_id);
+                            user.sis_user_id = user.sis_user_id.or(profile.sis_user_id);
+                            user.sortable_name = user.sortable_name.or(profile.sortable_name);
+                        }
+                        Err(e) => log::debug!("No profile for Canvas user {}: {}", user.id, e),
+                    }
+                }
+                user
+            }
+        })
+        .buffered(PROFILE_CONCURRENCY)
+        .collect()
+        .await;
+
+    users.into_iter().map(into_user).collect()
+}
+
+/// GET a Canvas API URL, returning the parsed body and the URL of the next page
+///
+/// Throttled requests, server errors and failed connections are retried as `retry` says.
+async fn get_json<T: DeserializeOwned>(
+    client: &reqwest::Client,
+    url: &str,
+    access_token: &str,
+    retry: &RetryPolicy,
+) -> Result<(T, Option<String>)> {
+    let mut retries = 0;
+    loop {
+        let sent = client
+            .get(url)
+            .header("Authorization", format!("Bearer {}", access_token))
+            .send()
+            .await;
+        let (reason, retry_after) = match sent {
+            Ok(response) if response.status().is_success() => return read_page(response).await,
+            Ok(response) => {
+                let status = response.status().as_u16();
+                let retry_after = retry_after(response.headers());
+                let text = response.text().await.unwrap_or_default();
+                if retries >= retry.max_retries || !is_transient_status(status, &text) {
+                    return Err(match status {
+                        401 | 403 => PlatformError::bad_credentials(text),
+                        404 => PlatformError::not_found(text),
+                        code => PlatformError::unexpected(format!("HTTP {}: {}", code, text)),
+                    });
+                }
+                (format!("HTTP {}", status), retry_after)
+            }
+            Err(e) if retries < retry.max_retries && (e.is_connect() || e.is_timeout()) => {
+                (e.to_string(), None)
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        retries += 1;
+        let delay = retry_after.unwrap_or_else(|| retry.delay(retries));
+        log::warn!(
+            "Canvas request failed ({}), retrying in {:?} ({}/{})",
+            reason,
+            delay,
+            retries,
+            retry.max_retries
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Whether a Canvas error response is worth retrying: throttling or a server error
+///
+/// Canvas throttles with a 403 whose body says "Rate Limit Exceeded"; any other 403 is a
+/// missing permission.
+fn is_transient_status(status: u16, body: &str) -> bool {
+    match status {
+        429 | 500 | 502 | 503 | 504 => true,
+        403 => body.to_ascii_lowercase().contains("rate limit exceeded"),
+        _ => false,
+    }
+}
+
+/// Delay asked for by a `Retry-After` header in seconds, at most [`MAX_RETRY_AFTER`]
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let seconds: u64 = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds).min(MAX_RETRY_AFTER))
+}
+
+/// Whether a response is an HTML page, e.g. the login page Canvas serves an expired token
+fn is_html(headers: &reqwest::header::HeaderMap) -> bool {
+    headers
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.trim_start().starts_with("text/html"))
+}
+
+/// Parse a successful Canvas response and the URL of the next page
+async fn read_page<T: DeserializeOwned>(
+    response: reqwest::Response,
+) -> Result<(T, Option<String>)> {
+    if is_html(response.headers()) {
+        return Err(PlatformError::bad_credentials(
+            "Canvas returned an HTML page instead of JSON; \
+             the access token appears invalid or expired",
+        ));
+    }
+
+    let next = next_link(response.headers());
+    let body = response
+        .json()
+        .await
+        .map_err(|e| PlatformError::unexpected(format!("JSON parse error: {}", e)))?;
+    Ok((body, next))
+}
+
+/// GET every page of a Canvas list, following the `rel="next"` URL of the `Link` header
+///
+/// When `paging.max_results` is exceeded, paging stops with a warning and only the first
+/// `max_results` items are returned. The page size is part of `url`.
+pub(super) async fn get_all_pages<T: DeserializeOwned>(
+    client: &reqwest::Client,
+    url: &str,
+    access_token: &str,
+    retry: &RetryPolicy,
+    paging: &CanvasPaging,
+) -> Result<Vec<T>> {
+    let mut items = Vec::new();
+    let mut next_url = Some(url.to_string());
+    while let Some(page_url) = next_url.take() {
+        let (page, next): (Vec<T>, _) = get_json(client, &page_url, access_token, retry).await?;
+        items.extend(page);
+        next_url = next;
+
+        if let Some(max_results) = paging.max_results {
+            if paging.is_exceeded(items.len(), next_url.is_some()) {
+                log::warn!(
+                    "{} has more than {} results, ignoring the rest",
+                    url,
+                    max_results
+                );
+                items.truncate(max_results);
+                break;
+            }
+        }
+    }
+    Ok(items)
+}
+
+/// Convert a Canvas user into the unified LMS user type, keeping the sortable name next to it
+fn into_user(user: CanvasUserResponse) -> Result<CourseUser> {
+    let unified = serde_json::from_value(serde_json::json!({
+        "id": user.id.to_string(),
+        "name": user.name,
+        "email": user.email,
+        "login_id": user.login_id,
+        "sis_user_id": user.sis_user_id,
+    }))
+    .map_err(|e| PlatformError::unexpected(format!("Unexpected Canvas user: {}", e)))?;
+    Ok(CourseUser {
+        user: unified,
+        sortable_name: user.sortable_name,
+    })
+}
+
+/// Convert a Canvas group into the unified LMS group type
+fn into_group(group: CanvasGroupResponse) -> Result<Group> {
+    serde_json::from_value(serde_json::json!({
+        "id": group.id.to_string(),
+        "name": group.name,
+        "members_count": group.members_count,
+        "max_membership": group.max_membership,
+    }))
+    .map_err(|e| PlatformError::unexpected(format!("Unexpected Canvas group: {}", e)))
+}
+
+/// Convert a Canvas group membership into the unified LMS membership type
+fn into_membership(membership: CanvasMembershipResponse) -> Result<GroupMembership> {
+    serde_json::from_value(serde_json::json!({
+        "id": membership.id.to_string(),
+        "group_id": membership.group_id.to_string(),
+        "user_id": membership.user_id.to_string(),
+    }))
+    .map_err(|e| PlatformError::unexpected(format!("Unexpected Canvas membership: {}", e)))
+}
+
+/// Canvas client that reads users, groups and memberships from the Canvas API, following
+/// every list to its last page
+pub struct CanvasEnrollmentClient<'a> {
+    base_url: &'a str,
+    access_token: &'a str,
+    retry: RetryPolicy,
+    paging: CanvasPaging,
+}
+
+impl<'a> CanvasEnrollmentClient<'a> {
+    pub fn new(base_url: &'a str, access_token: &'a str) -> Self {
+        Self {
+            base_url,
+            access_token,
+            retry: RetryPolicy::default(),
+            paging: CanvasPaging::default(),
+        }
+    }
+
+    /// Retry throttled and failed requests with `retry` instead of the default policy
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Page every list with `paging` instead of the default 100 items per page, unlimited
+    pub fn paging(mut self, paging: CanvasPaging) -> Self {
+        self.paging = paging;
+        self
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!("{}/api/v1/{}", self.base_url.trim_end_matches('/'), path)
+    }
+
+    /// Fetch the sections of a course, including the ids of their students
+    pub async fn get_sections(&self, course_id: &str) -> Result<Vec<CanvasSection>> {
+        course_sections(
+            self.base_url,
+            self.access_token,
+            course_id,
+            &self.retry,
+            &self.paging,
+        )
+        .await
+    }
+
+    async fn get_all_pages<T: DeserializeOwned>(&self, url: &str) -> Result<Vec<T>> {
+        let client = http_client()?;
+        get_all_pages(&client, url, self.access_token, &self.retry, &self.paging).await
+    }
+}
+
+impl LmsDataSource for CanvasEnrollmentClient<'_> {
+    async fn get_users(&self, course_id: &str) -> Result<Vec<User>> {
+        self.get_users_with_role(course_id, EnrollmentRole::Student)
+            .await
+    }
+
+    async fn get_users_with_role(
+        &self,
+        course_id: &str,
+        role: EnrollmentRole,
+    ) -> Result<Vec<User>> {
+        let users = self.get_course_users(course_id, role).await?;
+        Ok(users.into_iter().map(|user| user.user).collect())
+    }
+
+    async fn get_course_users(
+        &self,
+        course_id: &str,
+        role: EnrollmentRole,
+    ) -> Result<Vec<CourseUser>> {
+        users_by_role(
+            self.base_url,
+            self.access_token,
+            course_id,
+            role,
+            &self.retry,
+            &self.paging,
+        )
+        .await
+    }
+
+    async fn get_groups(&self, course_id: &str) -> Result<Vec<Group>> {
+        let url = self.api_url(&format!(
+            "courses/{}/groups?per_page={}",
+            course_id,
+            self.paging.page_size()
+        ));
+        let groups: Vec<CanvasGroupResponse> = self.get_all_pages(&url).await?;
+        groups.into_iter().map(into_group).collect()
+    }
+
+    async fn get_group_members(&self, group_id: &str) -> Result<Vec<GroupMembership>> {
+        let url = self.api_url(&format!(
+            "groups/{}/memberships?per_page={}",
+            group_id,
+            self.paging.page_size()
+        ));
+        let memberships: Vec<CanvasMembershipResponse> = self.get_all_pages(&url).await?;
+        memberships.into_iter().map(into_membership).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::http::{MockServer, Response};
+
+    #[tokio::test]
+    async fn test_token_is_sent_in_authorization_header() {
+        let server = MockServer::sequence(vec![Response::json(
+            r#"[{"id": 3, "name": "Tom Ta", "login_id": "tta"}]"#,
+        )]);
+
+        let users = get_course_users_by_role(server.url(), "secret", "42", EnrollmentRole::Ta)
+            .await
+            .unwrap();
+        assert_eq!(users.len(), 1);
+
+        let request = &server.requests()[0];
+        assert_eq!(request.header("Authorization"), Some("Bearer secret"));
+        assert!(request.path.contains("enrollment_type[]=ta"));
+        assert!(!request.path.contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_profiles_only_for_incomplete_users() {
+        let server = MockServer::sequence(vec![
+            Response::json(
+                r#"[{"id": 1, "name": "Alice", "email": "alice@uni.nl", "login_id": "alice"},
+                    {"id": 2, "name": "Bob", "login_id": "bob"}]"#,
+            ),
+            Response::json(r#"{"id": 2, "primary_email": "bob@uni.nl", "login_id": "bob"}"#),
+        ]);
+
+        let users = get_course_users_by_role(server.url(), "secret", "42", EnrollmentRole::Student)
+            .await
+            .unwrap();
+
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[1].email.as_deref(), Some("bob@uni.nl"));
+        let requests = server.request_lines();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains("include[]=email&include[]=enrollments"));
+        assert_eq!(requests[1], "GET /api/v1/users/2/profile");
+    }
+
+    #[tokio::test]
+    async fn test_groups_and_memberships_follow_next_links() {
+        let server = MockServer::sequence(vec![
+            Response::json(r#"[{"id": 10, "name": "Group 1", "members_count": 2}]"#)
+                .next_page("/api/v1/courses/42/groups?page=2&per_page=100"),
+            Response::json(r#"[{"id": 11, "name": "Group 2", "members_count": 1}]"#),
+            Response::json(r#"[{"id": 100, "group_id": 10, "user_id": 1}]"#)
+                .next_page("/api/v1/groups/10/memberships?page=2&per_page=100"),
+            Response::json(r#"[{"id": 101, "group_id": 10, "user_id": 2}]"#),
+        ]);
+        let client = CanvasEnrollmentClient::new(server.url(), "secret");
+
+        let groups = client.get_groups("42").await.unwrap();
+        let names: Vec<&str> = groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec!["Group 1", "Group 2"]);
+        assert_eq!(groups[1].id, "11");
+
+        let members = client.get_group_members("10").await.unwrap();
+        let user_ids: Vec<&str> = members.iter().map(|m| m.user_id.as_str()).collect();
+        assert_eq!(user_ids, vec!["1", "2"]);
+
+        let requests = server.request_lines();
+        assert_eq!(requests.len(), 4);
+        assert!(requests[1].starts_with("GET /api/v1/courses/42/groups?page=2"));
+        assert!(requests[3].starts_with("GET /api/v1/groups/10/memberships?page=2"));
+    }
+
+    #[tokio::test]
+    async fn test_paging_limits_apply_to_every_list() {
+        let server = MockServer::sequence(vec![
+            Response::json(r#"[{"id": 1, "name": "Alice", "email": "a@uni.nl", "login_id": "a"}]"#)
+                .next_page("/api/v1/courses/42/users?page=2&per_page=1"),
+            Response::json(r#"[{"id": 10, "name": "Group 1"}, {"id": 11, "name": "Group 2"}]"#),
+        ]);
+        let paging = CanvasPaging {
+            per_page: 1,
+            max_results: Some(1),
+        };
+        let client = CanvasEnrollmentClient::new(server.url(), "secret").paging(paging);
+
+        let users = client.get_users("42").await.unwrap();
+        assert_eq!(users.len(), 1);
+        let groups = client.get_groups("42").await.unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].name, "Group 1");
+
+        let requests = server.request_lines();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|r| r.contains("per_page=1")));
+    }
+
+    #[tokio::test]
+    async fn test_throttled_requests_are_retried() {
+        let server = MockServer::sequence(vec![
+            Response::status(429).header("Retry-After", "0"),
+            Response::status(429),
+            Response::json(r#"[{"id": 10, "name": "Group 1"}]"#),
+        ]);
+        let retry = RetryPolicy {
+            max_retries: 2,
+            initial_delay: Duration::from_millis(1),
+        };
+        let client = CanvasEnrollmentClient::new(server.url(), "secret").retry(retry);
+
+        let groups = client.get_groups("42").await.unwrap();
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[test]
+    fn test_is_transient_status() {
+        assert!(is_transient_status(429, ""));
+        assert!(is_transient_status(503, "Service Unavailable"));
+        assert!(is_transient_status(
+            403,
+            "403 Forbidden (Rate Limit Exceeded)"
+        ));
+        assert!(!is_transient_status(
+            403,
+            "user not authorized to perform that action"
+        ));
+        assert!(!is_transient_status(404, ""));
+    }
+
+    #[tokio::test]
+    async fn test_student_email_from_user_list() {
+        use crate::lms::get_student_info;
+
+        let server = MockServer::route(|request| {
+            if request.path.starts_with("/api/v1/courses/42/users") {
+                Response::json(
+                    r#"[{"id": 1, "name": "Alice Doe", "email": "a.doe@uni.nl",
+                         "login_id": "adoe"},
+                        {"id": 2, "name": "Bob Roe", "email": "b.roe@uni.nl",
+                         "login_id": "broe"}]"#,
+                )
+            } else {
+                Response::json("[]")
+            }
+        });
+        let client = CanvasEnrollmentClient::new(server.url(), "secret");
+
+        let result = get_student_info(&client, "42").await.unwrap();
+
+        let emails: Vec<&str> = result.students.iter().map(|s| s.email.as_str()).collect();
+        assert_eq!(emails, vec!["a.doe@uni.nl", "b.roe@uni.nl"]);
+        let requests = server.request_lines();
+        assert!(requests.iter().all(|r| !r.contains("/profile")));
+        assert!(requests
+            .iter()
+            .any(|r| r.contains("/users?") && r.contains("include[]=email")));
+    }
+
+    #[tokio::test]
+    async fn test_student_surname_from_sortable_name() {
+        use crate::lms::{get_student_info_with_progress, StudentFetchOptions};
+        use crate::settings::SurnameSource;
+
+        let server = MockServer::route(|request| {
+            if request.path.starts_with("/api/v1/courses/42/users") {
+                Response::json(
+                    r#"[{"id": 1, "name": "Anna van der Berg",
+                         "sortable_name": "van der Berg, Anna",
+                         "email": "anna.van.der.berg@uni.nl", "login_id": "aberg"}]"#,
+                )
+            } else {
+                Response::json("[]")
+            }
+        });
+        let client = CanvasEnrollmentClient::new(server.url(), "secret");
+
+        let surname = |surname_source| {
+            let options = StudentFetchOptions {
+                surname_source,
+                ..Default::default()
+            };
+            let client = &client;
+            async move {
+                let result = get_student_info_with_progress(client, "42", &options, |_| {})
+                    .await
+                    .unwrap();
+                result.students[0].name.clone()
+            }
+        };
+        assert_eq!(surname(SurnameSource::SortableName).await, "van der Berg");
+        assert_eq!(surname(SurnameSource::Email).await, "berg");
+    }
+
+    #[tokio::test]
+    async fn test_student_info_fetches_memberships_concurrently() {
+        use crate::lms::{get_student_info_with_progress, StudentFetchOptions};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        /// Body of the Canvas response to a request path
+        fn canvas_body(path: &str) -> String {
+            if path.starts_with("/api/v1/courses/42/users") {
+                let users: Vec<String> = (1..=4)
+                    .map(|i| {
+                        format!(
+                            r#"{{"id": {0}, "name": "Student {0}", "email": "s{0}@uni.nl",
+                                "login_id": "s{0}", "sis_user_id": "s{0}"}}"#,
+                            i
+                        )
+                    })
+                    .collect();
+                format!("[{}]", users.join(","))
+            } else if path.starts_with("/api/v1/courses/42/groups") {
+                let groups: Vec<String> = (1..=4)
+                    .map(|i| format!(r#"{{"id": {0}, "name": "Group {0}"}}"#, 10 + i))
+                    .collect();
+                format!("[{}]", groups.join(","))
+            } else {
+                // `/api/v1/groups/1<i>/memberships`: student <i> is the only member
+                let group_id: u64 = path.split('/').nth(4).unwrap().parse().unwrap();
+                format!(
+                    r#"[{{"id": {0}, "group_id": {0}, "user_id": {1}}}]"#,
+                    group_id,
+                    group_id - 10
+                )
+            }
+        }
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let (server_in_flight, server_max) = (in_flight.clone(), max_in_flight.clone());
+        let server = MockServer::route(move |request| {
+            if request.path.contains("/memberships") {
+                let now = server_in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                server_max.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(50));
+                server_in_flight.fetch_sub(1, Ordering::SeqCst);
+            }
+            Response::json(canvas_body(&request.path))
+        });
+
+        let client = CanvasEnrollmentClient::new(server.url(), "secret");
+        let options = StudentFetchOptions {
+            group_concurrency: 2,
+            ..Default::default()
+        };
+        let result = get_student_info_with_progress(&client, "42", &options, |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+        let groups: Vec<(&str, &str)> = result
+            .students
+            .iter()
+            .map(|s| (s.email.as_str(), s.group.as_ref().unwrap().name.as_str()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("s1@uni.nl", "Group 11"),
+                ("s2@uni.nl", "Group 12"),
+                ("s3@uni.nl", "Group 13"),
+                ("s4@uni.nl", "Group 14"),
+            ]
+        );
+    }
+}
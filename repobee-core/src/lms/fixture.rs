@@ -0,0 +1,257 @@
+//! Fixture-backed LMS data for demos and tests
+//!
+//! A fixture directory contains the JSON files
+//!
+//! ```text
+//! fixture_dir/
+//! ├── users.json        # [User]
+//! ├── groups.json       # [Group]
+//! ├── memberships.json  # {group_id: [GroupMembership]}, optional
+//! └── enrollments.json  # {role: [User]} for roles other than students, optional
+//! ```
+//!
+//! The course id passed to the fetch functions is ignored, a fixture describes one course.
+
+use super::types::{EnrollmentRole, Group, GroupMembership, User};
+use super::LmsDataSource;
+use crate::error::{PlatformError, Result};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// LMS client that serves users, groups and memberships from local JSON files
+#[derive(Debug, Clone, Default)]
+pub struct FixtureLmsClient {
+    users: Vec<User>,
+    groups: Vec<Group>,
+    memberships: HashMap<String, Vec<GroupMembership>>,
+    enrollments: HashMap<EnrollmentRole, Vec<User>>,
+}
+
+impl FixtureLmsClient {
+    /// Load a fixture directory, see the [module documentation](self) for its layout
+    pub fn from_dir(dir: &Path) -> Result<Self> {
+        Ok(Self {
+            users: read_fixture(&dir.join("users.json"))?,
+            groups: read_fixture(&dir.join("groups.json"))?,
+            memberships: read_optional_fixture(&dir.join("memberships.json"))?,
+            enrollments: read_optional_fixture(&dir.join("enrollments.json"))?,
+        })
+    }
+}
+
+impl LmsDataSource for FixtureLmsClient {
+    async fn get_users(&self, _course_id: &str) -> Result<Vec<User>> {
+        Ok(self.users.clone())
+    }
+
+    async fn get_users_with_role(
+        &self,
+        course_id: &str,
+        role: EnrollmentRole,
+    ) -> Result<Vec<User>> {
+        match role {
+            EnrollmentRole::Student => self.get_users(course_id).await,
+            role => Ok(self.enrollments.get(&role).cloned().unwrap_or_default()),
+        }
+    }
+
+    async fn get_groups(&self, _course_id: &str) -> Result<Vec<Group>> {
+        Ok(self.groups.clone())
+    }
+
+    async fn get_group_members(&self, group_id: &str) -> Result<Vec<GroupMembership>> {
+        Ok(self.memberships.get(group_id).cloned().unwrap_or_default())
+    }
+}
+
+fn read_fixture<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        PlatformError::FileError(format!("Failed to read fixture {}: {}", path.display(), e))
+    })?;
+    serde_json::from_str(&content)
+        .map_err(|e| PlatformError::FileError(format!("Invalid fixture {}: {}", path.display(), e)))
+}
+
+fn read_optional_fixture<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    if path.exists() {
+        read_fixture(path)
+    } else {
+        Ok(T::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lms::{
+        generate_repobee_yaml, get_student_info, get_student_info_with_progress, MemberOption,
+        StudentFetchOptions, YamlConfig,
+    };
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write_fixture(dir: &Path) {
+        fs::write(
+            dir.join("users.json"),
+            r#"[
+                {"id": "1", "name": "Alice Doe", "email": "a.doe@uni.nl",
+                 "login_id": "adoe", "sis_user_id": "20201234"},
+                {"id": "2", "name": "Bob Roe", "email": "b.roe@uni.nl",
+                 "login_id": "broe", "sis_user_id": "20205678"}
+            ]"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("groups.json"),
+            r#"[{"id": "10", "name": "Group 1", "members_count": 2, "max_membership": 2}]"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("memberships.json"),
+            r#"{"10": [
+                {"id": "100", "group_id": "10", "user_id": "1"},
+                {"id": "101", "group_id": "10", "user_id": "2"}
+            ]}"#,
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_student_info_from_fixture() {
+        let temp_dir = TempDir::new().unwrap();
+        write_fixture(temp_dir.path());
+        let client = FixtureLmsClient::from_dir(temp_dir.path()).unwrap();
+
+        let result = get_student_info(&client, "demo").await.unwrap();
+        assert_eq!(result.students.len(), 2);
+        assert!(result.warnings.is_empty());
+        assert!(result
+            .students
+            .iter()
+            .all(|s| s.group.as_ref().map(|g| g.name.as_str()) == Some("Group 1")));
+
+        let config = YamlConfig {
+            member_option: MemberOption::GitId,
+            include_group: true,
+            include_member: false,
+            include_initials: false,
+            full_groups: true,
+            grouping: Default::default(),
+            individual_teams: false,
+            team_name_template: None,
+            course_code: None,
+            member_format: Default::default(),
+        };
+        let teams = generate_repobee_yaml(&result.students, &config).unwrap();
+        assert_eq!(teams.len(), 1);
+        assert_eq!(teams[0].members, vec!["20201234", "20205678"]);
+    }
+
+    #[tokio::test]
+    async fn test_include_ta_enrollments() {
+        let temp_dir = TempDir::new().unwrap();
+        write_fixture(temp_dir.path());
+        fs::write(
+            temp_dir.path().join("enrollments.json"),
+            r#"{"TaEnrollment": [
+                {"id": "3", "name": "Carol Ta", "email": "c.ta@uni.nl",
+                 "login_id": "cta", "sis_user_id": "20209999"},
+                {"id": "2", "name": "Bob Roe", "email": "b.roe@uni.nl",
+                 "login_id": "broe", "sis_user_id": "20205678"}
+            ]}"#,
+        )
+        .unwrap();
+        let client = FixtureLmsClient::from_dir(temp_dir.path()).unwrap();
+
+        // Students only by default
+        let result = get_student_info(&client, "demo").await.unwrap();
+        assert_eq!(result.students.len(), 2);
+
+        let options = StudentFetchOptions {
+            include_roles: vec![EnrollmentRole::Student, EnrollmentRole::Ta],
+            ..Default::default()
+        };
+        let result = get_student_info_with_progress(&client, "demo", &options, |_| {})
+            .await
+            .unwrap();
+        let names: Vec<&str> = result
+            .students
+            .iter()
+            .map(|s| s.full_name.as_str())
+            .collect();
+        // Bob is both student and TA, and is only listed once
+        assert_eq!(names, vec!["Alice Doe", "Bob Roe", "Carol Ta"]);
+    }
+
+    #[tokio::test]
+    async fn test_test_student_is_excluded() {
+        let temp_dir = TempDir::new().unwrap();
+        write_fixture(temp_dir.path());
+        fs::write(
+            temp_dir.path().join("users.json"),
+            r#"[
+                {"id": "1", "name": "Alice Doe", "email": "a.doe@uni.nl",
+                 "login_id": "adoe", "sis_user_id": "20201234"},
+                {"id": "2", "name": "Bob Roe", "email": "b.roe@uni.nl",
+                 "login_id": "broe", "sis_user_id": "20205678"},
+                {"id": "3", "name": "Test Student",
+                 "login_id": "9f2c1e0b7d6a5c4b3a2f1e0d9c8b7a6f5e4d3c2b"}
+            ]"#,
+        )
+        .unwrap();
+        let client = FixtureLmsClient::from_dir(temp_dir.path()).unwrap();
+
+        let result = get_student_info(&client, "demo").await.unwrap();
+        assert_eq!(result.students.len(), 2);
+        assert_eq!(result.excluded_test_students, 1);
+        assert!(result
+            .students
+            .iter()
+            .all(|s| s.full_name != "Test Student"));
+
+        let options = StudentFetchOptions {
+            include_test_students: true,
+            ..Default::default()
+        };
+        let result = get_student_info_with_progress(&client, "demo", &options, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(result.students.len(), 3);
+        assert_eq!(result.excluded_test_students, 0);
+    }
+
+    #[tokio::test]
+    async fn test_group_ids_limit_the_groups() {
+        let temp_dir = TempDir::new().unwrap();
+        write_fixture(temp_dir.path());
+        let client = FixtureLmsClient::from_dir(temp_dir.path()).unwrap();
+
+        // A group set without Group 1 leaves both students without a group
+        let options = StudentFetchOptions {
+            group_ids: Some(["11".to_string()].into_iter().collect()),
+            ..Default::default()
+        };
+        let result = get_student_info_with_progress(&client, "demo", &options, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(result.students.len(), 2);
+        assert!(result.students.iter().all(|s| s.group.is_none()));
+
+        let options = StudentFetchOptions {
+            group_ids: Some(["10".to_string()].into_iter().collect()),
+            ..Default::default()
+        };
+        let result = get_student_info_with_progress(&client, "demo", &options, |_| {})
+            .await
+            .unwrap();
+        assert!(result.students.iter().all(|s| s.group.is_some()));
+    }
+
+    #[test]
+    fn test_missing_fixture_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = FixtureLmsClient::from_dir(temp_dir.path());
+        assert!(matches!(result, Err(PlatformError::FileError(_))));
+    }
+}
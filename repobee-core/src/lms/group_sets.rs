@@ -0,0 +1,119 @@
+//! Canvas group sets
+//!
+//! A Canvas course can have several group sets (group categories), e.g. one for project
+//! teams and one for lab pairs. The unified LMS client returns the groups of all sets
+//! together, so the sets themselves are fetched directly from the Canvas REST API, and a
+//! set is selected with [`StudentFetchOptions::group_ids`](super::StudentFetchOptions).
+
+use super::enrollments::get_all_pages;
+use super::types::{group_slug, CanvasGroupSet, CanvasPaging};
+use crate::error::Result;
+use crate::http::http_client;
+use crate::retry::RetryPolicy;
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Debug, Deserialize)]
+struct CanvasGroupCategoryResponse {
+    id: u64,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CanvasGroupResponse {
+    id: u64,
+}
+
+/// Fetch all group sets of a Canvas course, including the ids of their groups
+pub async fn get_group_sets(
+    base_url: &str,
+    access_token: &str,
+    course_id: &str,
+) -> Result<Vec<CanvasGroupSet>> {
+    get_group_sets_with_paging(base_url, access_token, course_id, &CanvasPaging::default()).await
+}
+
+/// Fetch the group sets of a Canvas course with explicit paging limits
+///
+/// `paging.max_results` caps the group sets and the groups of each set separately.
+pub async fn get_group_sets_with_paging(
+    base_url: &str,
+    access_token: &str,
+    course_id: &str,
+    paging: &CanvasPaging,
+) -> Result<Vec<CanvasGroupSet>> {
+    let client = http_client()?;
+    let retry = RetryPolicy::default();
+    let base_url = base_url.trim_end_matches('/');
+
+    let url = format!(
+        "{}/api/v1/courses/{}/group_categories?per_page={}",
+        base_url,
+        course_id,
+        paging.page_size()
+    );
+    let categories: Vec<CanvasGroupCategoryResponse> =
+        get_all_pages(&client, &url, access_token, &retry, paging).await?;
+
+    let mut group_sets = Vec::with_capacity(categories.len());
+    for category in categories {
+        let url = format!(
+            "{}/api/v1/group_categories/{}/groups?per_page={}",
+            base_url,
+            category.id,
+            paging.page_size()
+        );
+        let groups: Vec<CanvasGroupResponse> =
+            get_all_pages(&client, &url, access_token, &retry, paging).await?;
+        let group_ids = groups.into_iter().map(|g| g.id.to_string()).collect();
+        group_sets.push(CanvasGroupSet {
+            id: category.id.to_string(),
+            name: category.name,
+            group_ids,
+        });
+    }
+
+    Ok(group_sets)
+}
+
+/// Output file for one group set, e.g. `students-projects.yaml` for `students.yaml` and
+/// the set `Projects`
+pub fn group_set_file(file: &str, set_name: &str) -> String {
+    let path = Path::new(file);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!(
+            "{}-{}.{}",
+            stem,
+            group_slug(set_name),
+            ext.to_string_lossy()
+        ),
+        None => format!("{}-{}", stem, group_slug(set_name)),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_set_file() {
+        assert_eq!(
+            group_set_file("students.yaml", "Projects"),
+            "students-projects.yaml"
+        );
+        assert_eq!(
+            group_set_file("students.yaml", "Lab Pairs (week 1)"),
+            "students-lab-pairs-week-1.yaml"
+        );
+        assert_eq!(group_set_file("students", "Labs"), "students-labs");
+        assert_eq!(
+            group_set_file("out/students.yaml", "Labs"),
+            "out/students-labs.yaml"
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Student rosters exported as JSON by other systems
+//!
+//! Institutions whose LMS is not supported can often export the course roster as JSON
+//! from their student information system. A [`RosterFieldMapping`] tells which keys of
+//! each record hold the student's name, email, git username and group, so the roster
+//! becomes the same [`StudentInfo`] list an LMS fetch produces:
+//!
+//! ```json
+//! {
+//!   "records": "data.students",
+//!   "name": "displayName",
+//!   "email": "contact.email",
+//!   "git_id": "githubUser",
+//!   "group": "projectGroup"
+//! }
+//! ```
+//!
+//! Keys are dotted paths into nested objects. Without `records`, the file itself is the
+//! array of students.
+
+use super::types::{group_slug, unique_group_slugs, StudentGroup, StudentInfo};
+use crate::error::{PlatformError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Keys of an external JSON roster that hold the student fields
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RosterFieldMapping {
+    /// Path of the array of student records, `None` when the document is the array
+    pub records: Option<String>,
+    /// Full name of the student, required
+    pub name: String,
+    /// Email address of the student, required
+    pub email: String,
+    /// Git platform username of the student, required
+    pub git_id: String,
+    /// Group the student belongs to, optional per record; `None` ignores groups
+    pub group: Option<String>,
+    /// Identifier of the student in the exporting system, the git username when unset
+    pub id: Option<String>,
+}
+
+impl Default for RosterFieldMapping {
+    fn default() -> Self {
+        Self {
+            records: None,
+            name: "name".to_string(),
+            email: "email".to_string(),
+            git_id: "git_id".to_string(),
+            group: Some("group".to_string()),
+            id: None,
+        }
+    }
+}
+
+/// Read a field mapping from a JSON file, see the [module documentation](self)
+pub fn read_roster_mapping(path: &Path) -> Result<RosterFieldMapping> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        PlatformError::FileError(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    serde_json::from_str(&content).map_err(|e| {
+        PlatformError::FileError(format!("Invalid field mapping {}: {}", path.display(), e))
+    })
+}
+
+/// Read the students of a JSON roster file
+pub fn read_json_roster(path: &Path, mapping: &RosterFieldMapping) -> Result<Vec<StudentInfo>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        PlatformError::FileError(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    let json: Value = serde_json::from_str(&content).map_err(|e| {
+        PlatformError::FileError(format!("Invalid JSON in {}: {}", path.display(), e))
+    })?;
+    students_from_json(&json, mapping)
+}
+
+/// Map the records of a JSON roster to students
+///
+/// Every record must have a non-empty name, email and git username; strings and numbers
+/// are accepted. All records are checked before failing, so the error lists every
+/// missing field at once. Students with the same group value share a group, which
+/// [`generate_repobee_yaml`](super::generate_repobee_yaml) turns into one team.
+pub fn students_from_json(json: &Value, mapping: &RosterFieldMapping) -> Result<Vec<StudentInfo>> {
+    let records = match &mapping.records {
+        Some(path) => lookup(json, path)
+            .ok_or_else(|| PlatformError::Other(format!("Roster has no '{}' field", path)))?,
+        None => json,
+    };
+    let records = records.as_array().ok_or_else(|| {
+        PlatformError::Other("Roster records must be a JSON array of objects".to_string())
+    })?;
+
+    let mut problems = Vec::new();
+    let mut students = Vec::new();
+    let mut group_names = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        let mut required = |key: &str| {
+            let value = text(record, key);
+            if value.is_none() {
+                problems.push(format!("record {}: missing '{}'", index + 1, key));
+            }
+            value
+        };
+        let (Some(full_name), Some(email), Some(git_id)) = (
+            required(&mapping.name),
+            required(&mapping.email),
+            required(&mapping.git_id),
+        ) else {
+            continue;
+        };
+
+        let group = mapping.group.as_deref().and_then(|key| text(record, key));
+        let id = mapping.id.as_deref().and_then(|key| text(record, key));
+        students.push(StudentInfo {
+            group: None,
+            name: full_name
+                .split_whitespace()
+                .last()
+                .unwrap_or_default()
+                .to_string(),
+            full_name,
+            canvas_id: git_id.clone(),
+            lms_id: id.unwrap_or_else(|| git_id.clone()),
+            git_id,
+            email,
+            sis_user_id: None,
+        });
+        group_names.push(group);
+    }
+    if !problems.is_empty() {
+        return Err(PlatformError::Other(format!(
+            "Invalid roster: {}",
+            problems.join("; ")
+        )));
+    }
+
+    // The group value is the group's identity, its members are the students sharing it
+    let mut counts: BTreeMap<&str, u64> = BTreeMap::new();
+    for name in group_names.iter().flatten() {
+        *counts.entry(name).or_default() += 1;
+    }
+    let mut groups: Vec<StudentGroup> = counts
+        .into_iter()
+        .map(|(name, count)| StudentGroup {
+            id: name.to_string(),
+            name: name.to_string(),
+            slug: group_slug(name),
+            members_count: Some(count),
+            max_membership: None,
+        })
+        .collect();
+    unique_group_slugs(&mut groups);
+    for (student, name) in students.iter_mut().zip(&group_names) {
+        student.group = name
+            .as_deref()
+            .and_then(|name| groups.iter().find(|g| g.id == name))
+            .cloned();
+    }
+
+    Ok(students)
+}
+
+/// Value at a dotted path such as `contact.email`
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(value, |value, key| value.as_object()?.get(key))
+}
+
+/// Non-empty text of a string or number field
+fn text(record: &Value, path: &str) -> Option<String> {
+    let text = match lookup(record, path)? {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) => n.to_string(),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_students_from_nested_json() {
+        let roster = json!({
+            "data": {
+                "students": [
+                    {
+                        "displayName": "Alice Smith",
+                        "contact": {"email": "alice@uni.edu"},
+                        "github": "alice-s",
+                        "team": "Lab 1",
+                        "studentNo": 1001
+                    },
+                    {
+                        "displayName": "Bob Jones",
+                        "contact": {"email": "bob@uni.edu"},
+                        "github": "bobj",
+                        "team": "Lab 1",
+                        "studentNo": 1002
+                    },
+                    {
+                        "displayName": "Carol",
+                        "contact": {"email": "carol@uni.edu"},
+                        "github": "carol"
+                    }
+                ]
+            }
+        });
+        let mapping = RosterFieldMapping {
+            records: Some("data.students".to_string()),
+            name: "displayName".to_string(),
+            email: "contact.email".to_string(),
+            git_id: "github".to_string(),
+            group: Some("team".to_string()),
+            id: Some("studentNo".to_string()),
+        };
+
+        let students = students_from_json(&roster, &mapping).unwrap();
+        assert_eq!(students.len(), 3);
+        assert_eq!(students[0].full_name, "Alice Smith");
+        assert_eq!(students[0].name, "Smith");
+        assert_eq!(students[0].email, "alice@uni.edu");
+        assert_eq!(students[0].git_id, "alice-s");
+        assert_eq!(students[0].lms_id, "1001");
+        let group = students[0].group.as_ref().unwrap();
+        assert_eq!(group.slug, "lab-1");
+        assert_eq!(group.members_count, Some(2));
+        assert_eq!(students[1].group.as_ref().unwrap().id, group.id);
+        assert!(students[2].group.is_none());
+        assert_eq!(students[2].lms_id, "carol");
+    }
+
+    #[test]
+    fn test_missing_required_fields_are_all_reported() {
+        let roster = json!([
+            {"name": "Alice", "email": "alice@uni.edu", "git_id": "alice"},
+            {"name": "Bob", "email": "", "git_id": "bob"},
+            {"email": "carol@uni.edu"}
+        ]);
+
+        let err = students_from_json(&roster, &RosterFieldMapping::default()).unwrap_err();
+        assert!(err.to_string().ends_with(
+            "Invalid roster: record 2: missing 'email'; record 3: missing 'name'; \
+             record 3: missing 'git_id'"
+        ));
+
+        let err = students_from_json(&json!({"students": []}), &RosterFieldMapping::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("must be a JSON array"));
+    }
+}
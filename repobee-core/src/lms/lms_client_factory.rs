@@ -1,12 +1,22 @@
 ///! Factory for creating unified LMS clients from settings
 use crate::error::{PlatformError, Result};
-use crate::lms::types::StudentInfo;
-use crate::settings::CommonSettings;
+use crate::lms::types::{
+    unique_group_slugs, CanvasSection, CourseUser, EnrollmentRole, Group, GroupMembership,
+    StudentFetchOptions, StudentFetchResult, StudentGroup, StudentInfo, StudentWarning, User,
+};
+use crate::lms::{CanvasEnrollmentClient, MoodleClient};
+use crate::secrets::resolve_token;
+use crate::settings::{CommonSettings, GitIdSource, LmsUrlOption, NameExtraction, SurnameSource};
+use futures_util::{stream, StreamExt};
 use lms_client::{LmsAuth, LmsClient, LmsType};
-use lms_common::LmsClient as _; // Import trait to call its methods
-use std::collections::HashMap;
+use regex::Regex;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::pin::pin;
 
 /// Create an LMS client based on settings
+///
+/// The access token may be a `file:` reference, see [`crate::secrets`].
 pub fn create_lms_client(settings: &CommonSettings) -> Result<LmsClient> {
     // Determine LMS type from settings
     let lms_type = match settings.lms_type.as_str() {
@@ -20,28 +30,60 @@ pub fn create_lms_client(settings: &CommonSettings) -> Result<LmsClient> {
         }
     };
 
-    // Determine base URL (Canvas allows TUE shortcut or custom)
-    let base_url = if settings.lms_type == "Canvas" {
-        if settings.lms_url_option == crate::settings::LmsUrlOption::TUE {
-            settings.lms_base_url.clone()
-        } else {
-            settings.lms_custom_url.clone()
+    // Canvas allows the TUE shortcut or a custom URL, Moodle has its own fields
+    let (base_url, token) = match lms_type {
+        LmsType::Moodle => moodle_connection(settings),
+        _ => {
+            let url = if settings.lms_url_option == LmsUrlOption::TUE {
+                &settings.lms_base_url
+            } else {
+                &settings.lms_custom_url
+            };
+            (url.as_str(), settings.lms_access_token.as_str())
         }
-    } else {
-        // For Moodle and future LMSes, rely on the custom URL field
-        settings.lms_custom_url.clone()
     };
 
     // Create authentication (both Canvas and Moodle use token auth)
     let auth = LmsAuth::Token {
-        url: base_url,
-        token: settings.lms_access_token.clone(),
+        url: base_url.to_string(),
+        token: resolve_token(token)?,
     };
 
     // Create the unified client
     LmsClient::new(lms_type, auth).map_err(|e| PlatformError::Other(e.to_string()))
 }
 
+/// Create a Moodle Web Services client from the Moodle settings
+///
+/// The access token may be a `file:` reference, see [`crate::secrets`].
+pub fn create_moodle_client(settings: &CommonSettings) -> Result<MoodleClient> {
+    let (base_url, token) = moodle_connection(settings);
+    if base_url.trim().is_empty() {
+        return Err(PlatformError::invalid_config(
+            "No Moodle base URL configured",
+        ));
+    }
+    MoodleClient::new(base_url, &resolve_token(token)?)
+}
+
+/// Base URL and access token of the Moodle site
+///
+/// Settings saved before the Moodle fields existed have the Moodle site in the custom URL
+/// and access token fields, which are used while the Moodle fields are empty.
+fn moodle_connection(settings: &CommonSettings) -> (&str, &str) {
+    fn or<'a>(value: &'a str, fallback: &'a str) -> &'a str {
+        if value.trim().is_empty() {
+            fallback
+        } else {
+            value
+        }
+    }
+    (
+        or(&settings.moodle_base_url, &settings.lms_custom_url),
+        or(&settings.moodle_access_token, &settings.lms_access_token),
+    )
+}
+
 /// Create an LMS client with explicit parameters (for Tauri commands)
 pub fn create_lms_client_with_params(
     lms_type: &str,
@@ -61,12 +103,133 @@ pub fn create_lms_client_with_params(
 
     let auth = LmsAuth::Token {
         url: base_url,
-        token: access_token,
+        token: resolve_token(&access_token)?,
     };
 
     LmsClient::new(lms_type, auth).map_err(|e| PlatformError::Other(e.to_string()))
 }
 
+/// LMS calls needed to fetch student information
+///
+/// Implemented by the unified [`LmsClient`] and by [`FixtureLmsClient`](super::FixtureLmsClient),
+/// so [`get_student_info`] works against either.
+#[allow(async_fn_in_trait)]
+pub trait LmsDataSource {
+    /// Get all users enrolled in a course
+    async fn get_users(&self, course_id: &str) -> Result<Vec<User>>;
+
+    /// Get the users of a course that have an enrollment role
+    ///
+    /// [`get_users`](Self::get_users) returns the students; by default other roles are not
+    /// supported.
+    async fn get_users_with_role(
+        &self,
+        course_id: &str,
+        role: EnrollmentRole,
+    ) -> Result<Vec<User>> {
+        match role {
+            EnrollmentRole::Student => self.get_users(course_id).await,
+            role => Err(PlatformError::Other(format!(
+                "Fetching users with role {} is not supported by this LMS",
+                role
+            ))),
+        }
+    }
+
+    /// Get the users of a course that have an enrollment role, with their sortable names
+    ///
+    /// Only Canvas provides sortable names; by default the users of
+    /// [`get_users_with_role`](Self::get_users_with_role) are returned without one.
+    async fn get_course_users(
+        &self,
+        course_id: &str,
+        role: EnrollmentRole,
+    ) -> Result<Vec<CourseUser>> {
+        let users = self.get_users_with_role(course_id, role).await?;
+        Ok(users.into_iter().map(CourseUser::from).collect())
+    }
+
+    /// Get all groups of a course
+    async fn get_groups(&self, course_id: &str) -> Result<Vec<Group>>;
+
+    /// Get the memberships of a group
+    async fn get_group_members(&self, group_id: &str) -> Result<Vec<GroupMembership>>;
+}
+
+impl LmsDataSource for LmsClient {
+    async fn get_users(&self, course_id: &str) -> Result<Vec<User>> {
+        lms_common::LmsClient::get_users(self, course_id)
+            .await
+            .map_err(|e| PlatformError::Other(format!("Failed to fetch course data: {}", e)))
+    }
+
+    async fn get_groups(&self, course_id: &str) -> Result<Vec<Group>> {
+        lms_common::LmsClient::get_groups(self, course_id)
+            .await
+            .map_err(|e| PlatformError::Other(format!("Failed to fetch course data: {}", e)))
+    }
+
+    async fn get_group_members(&self, group_id: &str) -> Result<Vec<GroupMembership>> {
+        lms_common::LmsClient::get_group_members(self, group_id)
+            .await
+            .map_err(|e| PlatformError::Other(format!("Failed to fetch group memberships: {}", e)))
+    }
+}
+
+/// Course data source of the configured LMS
+///
+/// Canvas is read from the Canvas REST API directly; Moodle goes through its Web Services
+/// API.
+pub enum CourseDataSource<'a> {
+    Canvas(CanvasEnrollmentClient<'a>),
+    Moodle(MoodleClient),
+}
+
+impl LmsDataSource for CourseDataSource<'_> {
+    async fn get_users(&self, course_id: &str) -> Result<Vec<User>> {
+        match self {
+            Self::Canvas(client) => client.get_users(course_id).await,
+            Self::Moodle(client) => client.get_users(course_id).await,
+        }
+    }
+
+    async fn get_users_with_role(
+        &self,
+        course_id: &str,
+        role: EnrollmentRole,
+    ) -> Result<Vec<User>> {
+        match self {
+            Self::Canvas(client) => client.get_users_with_role(course_id, role).await,
+            Self::Moodle(client) => client.get_users_with_role(course_id, role).await,
+        }
+    }
+
+    async fn get_course_users(
+        &self,
+        course_id: &str,
+        role: EnrollmentRole,
+    ) -> Result<Vec<CourseUser>> {
+        match self {
+            Self::Canvas(client) => client.get_course_users(course_id, role).await,
+            Self::Moodle(client) => client.get_course_users(course_id, role).await,
+        }
+    }
+
+    async fn get_groups(&self, course_id: &str) -> Result<Vec<Group>> {
+        match self {
+            Self::Canvas(client) => client.get_groups(course_id).await,
+            Self::Moodle(client) => client.get_groups(course_id).await,
+        }
+    }
+
+    async fn get_group_members(&self, group_id: &str) -> Result<Vec<GroupMembership>> {
+        match self {
+            Self::Canvas(client) => client.get_group_members(group_id).await,
+            Self::Moodle(client) => client.get_group_members(group_id).await,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum FetchProgress {
     FetchingUsers,
@@ -82,84 +245,688 @@ pub enum FetchProgress {
         total: usize,
         group_name: String,
     },
+    FetchedSections {
+        count: usize,
+    },
 }
 
 /// Fetch all student information for a course using the unified LMS client
-pub async fn get_student_info(client: &LmsClient, course_id: &str) -> Result<Vec<StudentInfo>> {
-    get_student_info_with_progress(client, course_id, |_| {}).await
+///
+/// Students missing a git username or email are still returned, but are also listed in
+/// the result's warnings.
+pub async fn get_student_info<C: LmsDataSource>(
+    client: &C,
+    course_id: &str,
+) -> Result<StudentFetchResult> {
+    get_student_info_with_progress(client, course_id, &StudentFetchOptions::default(), |_| {}).await
 }
 
 /// Same as [`get_student_info`] but reports progress via callback
-pub async fn get_student_info_with_progress<F>(
-    client: &LmsClient,
+pub async fn get_student_info_with_progress<C: LmsDataSource, F>(
+    client: &C,
     course_id: &str,
+    options: &StudentFetchOptions,
     mut progress_callback: F,
-) -> Result<Vec<StudentInfo>>
+) -> Result<StudentFetchResult>
 where
     F: FnMut(FetchProgress),
 {
+    let surnames = SurnameExtractor::new(&options.name_extraction)?;
     progress_callback(FetchProgress::FetchingUsers);
     progress_callback(FetchProgress::FetchingGroups);
 
     // Fetch users and groups in parallel
-    let (users, groups) =
-        tokio::try_join!(client.get_users(course_id), client.get_groups(course_id))
-            .map_err(|e| PlatformError::Other(format!("Failed to fetch course data: {}", e)))?;
+    let (users, groups) = tokio::try_join!(
+        get_users_with_roles(client, course_id, &options.include_roles),
+        client.get_groups(course_id)
+    )?;
+    let groups: Vec<Group> = match &options.group_ids {
+        Some(ids) => groups.into_iter().filter(|g| ids.contains(&g.id)).collect(),
+        None => groups,
+    };
 
+    let (users, excluded_test_students) = without_test_students(users, options);
     progress_callback(FetchProgress::FetchedUsers { count: users.len() });
     progress_callback(FetchProgress::FetchedGroups {
         count: groups.len(),
     });
 
-    // Build a map of user_id -> group, reporting progress per group
-    let mut user_to_group = HashMap::new();
+    let mut student_groups: Vec<StudentGroup> = groups.iter().map(StudentGroup::from).collect();
+    unique_group_slugs(&mut student_groups);
+
+    // Fetch the memberships of several groups at a time. They arrive in group order, so a
+    // user in more than one group always ends up in the first of them, like with sections.
     let total_groups = groups.len();
-    for (idx, group) in groups.iter().enumerate() {
+    let mut memberships = pin!(stream::iter(groups.iter().zip(&student_groups))
+        .map(|(group, student_group)| async move {
+            (
+                group,
+                student_group,
+                client.get_group_members(&group.id).await,
+            )
+        })
+        .buffered(options.group_concurrency.max(1))
+        .enumerate());
+
+    // Build a map of user_id -> group, reporting progress per group
+    let mut user_to_group: HashMap<String, StudentGroup> = HashMap::new();
+    while let Some((idx, (group, student_group, members))) = memberships.next().await {
         progress_callback(FetchProgress::FetchingGroupMembers {
             current: idx + 1,
             total: total_groups.max(1),
             group_name: group.name.clone(),
         });
 
-        let memberships = client.get_group_members(&group.id).await.map_err(|e| {
-            PlatformError::Other(format!("Failed to fetch group memberships: {}", e))
-        })?;
+        for membership in members? {
+            match user_to_group.entry(membership.user_id) {
+                Entry::Occupied(entry) => log::warn!(
+                    "User {} is in groups {} and {}, using {}",
+                    entry.key(),
+                    entry.get().name,
+                    student_group.name,
+                    entry.get().name
+                ),
+                Entry::Vacant(entry) => {
+                    entry.insert(student_group.clone());
+                }
+            }
+        }
+    }
+
+    Ok(into_fetch_result(
+        build_student_infos(users, &user_to_group, options, &surnames),
+        excluded_test_students,
+    ))
+}
+
+/// Fetch all student information for a course, using Canvas sections as the grouping
+///
+/// Sections are fetched separately with [`get_course_sections`](super::get_course_sections)
+/// because the unified LMS client does not expose them.
+pub async fn get_student_info_by_sections<C: LmsDataSource>(
+    client: &C,
+    course_id: &str,
+    sections: &[CanvasSection],
+) -> Result<StudentFetchResult> {
+    get_student_info_by_sections_with_progress(
+        client,
+        course_id,
+        sections,
+        &StudentFetchOptions::default(),
+        |_| {},
+    )
+    .await
+}
+
+/// Same as [`get_student_info_by_sections`] but reports progress via callback
+pub async fn get_student_info_by_sections_with_progress<C: LmsDataSource, F>(
+    client: &C,
+    course_id: &str,
+    sections: &[CanvasSection],
+    options: &StudentFetchOptions,
+    mut progress_callback: F,
+) -> Result<StudentFetchResult>
+where
+    F: FnMut(FetchProgress),
+{
+    let surnames = SurnameExtractor::new(&options.name_extraction)?;
+    progress_callback(FetchProgress::FetchingUsers);
+
+    let users = get_users_with_roles(client, course_id, &options.include_roles).await?;
+    let (users, excluded_test_students) = without_test_students(users, options);
+
+    progress_callback(FetchProgress::FetchedUsers { count: users.len() });
+    progress_callback(FetchProgress::FetchedSections {
+        count: sections.len(),
+    });
+
+    let mut section_groups: Vec<StudentGroup> = sections.iter().map(StudentGroup::from).collect();
+    unique_group_slugs(&mut section_groups);
+
+    let mut user_to_group = HashMap::new();
+    for (section, group) in sections.iter().zip(section_groups) {
+        for student_id in &section.student_ids {
+            // A student enrolled in several sections is grouped by the first one
+            user_to_group
+                .entry(student_id.clone())
+                .or_insert_with(|| group.clone());
+        }
+    }
 
-        for membership in memberships {
-            user_to_group.insert(membership.user_id.clone(), group.clone());
+    Ok(into_fetch_result(
+        build_student_infos(users, &user_to_group, options, &surnames),
+        excluded_test_students,
+    ))
+}
+
+/// Fetch the users of all requested enrollment roles
+///
+/// A user with several of the roles is only returned once.
+async fn get_users_with_roles<C: LmsDataSource>(
+    client: &C,
+    course_id: &str,
+    roles: &[EnrollmentRole],
+) -> Result<Vec<CourseUser>> {
+    let mut seen = HashSet::new();
+    let mut users = Vec::new();
+    for role in roles {
+        for user in client.get_course_users(course_id, *role).await? {
+            if seen.insert(user.user.id.clone()) {
+                users.push(user);
+            }
         }
     }
+    Ok(users)
+}
 
-    // Build student info from users
-    let mut student_infos = Vec::new();
-    for user in users {
-        let email = user.email.clone().unwrap_or_default();
-        let git_id = user.login_id.clone().unwrap_or_default();
-        let name = extract_lastname_from_email(&email);
+/// Build student info from LMS users and their group assignment
+fn build_student_infos(
+    users: Vec<CourseUser>,
+    user_to_group: &HashMap<String, StudentGroup>,
+    options: &StudentFetchOptions,
+    surnames: &SurnameExtractor,
+) -> Vec<StudentInfo> {
+    users
+        .into_iter()
+        .map(|course_user| {
+            let user = course_user.user;
+            StudentInfo {
+                group: user_to_group.get(&user.id).cloned(),
+                name: select_surname(
+                    options.surname_source,
+                    course_user.sortable_name.as_deref(),
+                    user.email.as_deref().unwrap_or_default(),
+                    surnames,
+                ),
+                ..StudentInfo::from_user(user, options.git_id_source)
+            }
+        })
+        .collect()
+}
 
-        let student_info = StudentInfo {
-            group: user_to_group.get(&user.id).cloned(),
-            full_name: user.name.clone(),
-            name,
+impl StudentInfo {
+    /// Student info of an LMS user, without a group, with the git id taken from `source`
+    ///
+    /// A [`User`] has no sortable name, so the surname comes from the email address.
+    pub fn from_user(user: User, source: GitIdSource) -> Self {
+        let email = user.email.unwrap_or_default();
+        let git_id = select_git_id(
+            source,
+            user.sis_user_id.as_deref(),
+            user.login_id.as_deref(),
+            &email,
+        );
+        Self {
+            group: None,
+            full_name: user.name,
+            name: SurnameExtractor::default().extract(&email),
             canvas_id: user.login_id.unwrap_or_default(),
             git_id,
             email,
-        };
+            lms_id: user.id,
+            sis_user_id: user.sis_user_id,
+        }
+    }
+}
+
+/// Uses the default git id source, the SIS user id
+impl From<User> for StudentInfo {
+    fn from(user: User) -> Self {
+        Self::from_user(user, GitIdSource::default())
+    }
+}
+
+/// The LMS user a student was fetched as; the group and git id are not part of a user
+impl TryFrom<&StudentInfo> for User {
+    type Error = PlatformError;
+
+    fn try_from(student: &StudentInfo) -> Result<Self> {
+        let non_empty = |value: &str| Some(value.to_string()).filter(|v| !v.is_empty());
+        serde_json::from_value(serde_json::json!({
+            "id": student.lms_id,
+            "name": student.full_name,
+            "email": non_empty(&student.email),
+            "login_id": non_empty(&student.canvas_id),
+            "sis_user_id": student.sis_user_id,
+        }))
+        .map_err(|e| PlatformError::unexpected(format!("Cannot convert student to user: {}", e)))
+    }
+}
+
+/// Pick the git username of a user according to the configured source
+fn select_git_id(
+    source: GitIdSource,
+    sis_user_id: Option<&str>,
+    login_id: Option<&str>,
+    email: &str,
+) -> String {
+    let non_empty = |id: Option<&str>| id.map(str::trim).filter(|id| !id.is_empty());
+    match source {
+        GitIdSource::SisUserId => non_empty(sis_user_id).or(non_empty(login_id)),
+        GitIdSource::LoginId => non_empty(login_id),
+        GitIdSource::EmailLocalPart => email.split_once('@').map(|(local, _)| local.trim()),
+    }
+    .unwrap_or_default()
+    .to_string()
+}
+
+/// Pick the surname of a user according to the configured source
+///
+/// A sortable name is "Last, First", so everything before the comma is the surname, particles
+/// included. Without a usable sortable name the surname is derived from the email address.
+fn select_surname(
+    source: SurnameSource,
+    sortable_name: Option<&str>,
+    email: &str,
+    surnames: &SurnameExtractor,
+) -> String {
+    let from_sortable = sortable_name
+        .and_then(|name| name.split_once(','))
+        .map(|(last, _)| last.trim())
+        .filter(|last| !last.is_empty());
+    match (source, from_sortable) {
+        (SurnameSource::SortableName, Some(last)) => last.to_string(),
+        _ => surnames.extract(email),
+    }
+}
+
+fn into_fetch_result(
+    students: Vec<StudentInfo>,
+    excluded_test_students: usize,
+) -> StudentFetchResult {
+    let warnings = validate_student_info(&students);
+    StudentFetchResult {
+        students,
+        warnings,
+        excluded_test_students,
+    }
+}
+
+/// Whether a user is the test student Canvas creates when a teacher opens the Student View
+///
+/// That account is named "Test Student" and has no email address.
+pub fn is_test_student(user: &User) -> bool {
+    user.name.trim().eq_ignore_ascii_case("test student")
+        && user.email.as_deref().is_none_or(|e| e.trim().is_empty())
+}
+
+/// Drop test students unless the options keep them, returning how many were dropped
+fn without_test_students(
+    users: Vec<CourseUser>,
+    options: &StudentFetchOptions,
+) -> (Vec<CourseUser>, usize) {
+    if options.include_test_students {
+        return (users, 0);
+    }
+    let total = users.len();
+    let users: Vec<CourseUser> = users
+        .into_iter()
+        .filter(|u| !is_test_student(&u.user))
+        .collect();
+    let excluded = total - users.len();
+    (users, excluded)
+}
+
+/// Collect students that have no git username or email
+///
+/// Such students would otherwise end up as empty team members and break setup later on.
+pub fn validate_student_info(students: &[StudentInfo]) -> Vec<StudentWarning> {
+    students
+        .iter()
+        .filter_map(|student| {
+            let missing_git_id = student.git_id.trim().is_empty();
+            let missing_email = student.email.trim().is_empty();
+            (missing_git_id || missing_email).then(|| StudentWarning {
+                full_name: student.full_name.clone(),
+                missing_git_id,
+                missing_email,
+            })
+        })
+        .collect()
+}
+
+/// Extracts surnames from email addresses as a [`NameExtraction`] says
+///
+/// A custom pattern is compiled once, when the extractor is created.
+#[derive(Default)]
+struct SurnameExtractor {
+    extraction: NameExtraction,
+    pattern: Option<Regex>,
+}
 
-        student_infos.push(student_info);
+impl SurnameExtractor {
+    fn new(extraction: &NameExtraction) -> Result<Self> {
+        let pattern = match extraction {
+            NameExtraction::Custom(pattern) => Some(Regex::new(pattern).map_err(|e| {
+                PlatformError::invalid_config(format!("Invalid name extraction pattern: {}", e))
+            })?),
+            _ => None,
+        };
+        Ok(Self {
+            extraction: extraction.clone(),
+            pattern,
+        })
     }
 
-    Ok(student_infos)
+    /// Surname in the local part of `email`, e.g. "doe" for "john.doe@uni.nl" after the last
+    /// dot
+    ///
+    /// A custom pattern that does not match leaves the whole local part.
+    fn extract(&self, email: &str) -> String {
+        let local = email.split('@').next().unwrap_or_default();
+        let surname = match (&self.extraction, &self.pattern) {
+            (NameExtraction::AfterLastDot, _) => local.rsplit('.').next(),
+            (NameExtraction::BeforeFirstDot, _) => local.split('.').next(),
+            (NameExtraction::FullLocalPart, _) => Some(local),
+            (NameExtraction::Custom(_), Some(pattern)) => pattern
+                .captures(local)
+                .and_then(|captures| captures.get(1).or_else(|| captures.get(0)))
+                .map(|m| m.as_str()),
+            (NameExtraction::Custom(_), None) => None,
+        };
+        surname.unwrap_or(local).to_string()
+    }
 }
 
-/// Extract lastname from email (e.g., "john.doe@uni.nl" -> "doe")
-fn extract_lastname_from_email(email: &str) -> String {
-    email
-        .split('@')
-        .next()
-        .unwrap_or("")
-        .split('.')
-        .last()
-        .unwrap_or("")
-        .to_string()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn student(full_name: &str, git_id: &str, email: &str) -> StudentInfo {
+        StudentInfo {
+            group: None,
+            full_name: full_name.to_string(),
+            name: SurnameExtractor::default().extract(email),
+            canvas_id: git_id.to_string(),
+            git_id: git_id.to_string(),
+            email: email.to_string(),
+            lms_id: git_id.to_string(),
+            sis_user_id: None,
+        }
+    }
+
+    #[test]
+    fn test_select_git_id_sis_user_id() {
+        let source = GitIdSource::SisUserId;
+        assert_eq!(
+            select_git_id(source, Some("20201234"), Some("adoe"), "a.doe@uni.nl"),
+            "20201234"
+        );
+        // Falls back to the login id when no SIS id is set
+        assert_eq!(
+            select_git_id(source, None, Some("adoe"), "a.doe@uni.nl"),
+            "adoe"
+        );
+        assert_eq!(
+            select_git_id(source, Some(""), Some("adoe"), "a.doe@uni.nl"),
+            "adoe"
+        );
+    }
+
+    #[test]
+    fn test_select_git_id_login_id() {
+        let source = GitIdSource::LoginId;
+        assert_eq!(
+            select_git_id(source, Some("20201234"), Some("adoe"), "a.doe@uni.nl"),
+            "adoe"
+        );
+        assert_eq!(
+            select_git_id(source, Some("20201234"), None, "a.doe@uni.nl"),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_select_git_id_email_local_part() {
+        let source = GitIdSource::EmailLocalPart;
+        assert_eq!(
+            select_git_id(source, Some("20201234"), Some("adoe"), "a.doe@uni.nl"),
+            "a.doe"
+        );
+        assert_eq!(select_git_id(source, None, None, ""), "");
+    }
+
+    #[test]
+    fn test_select_surname_with_particle() {
+        let surnames = SurnameExtractor::default();
+        let sortable = Some("van der Berg, Jan");
+        let email = "jan.vanderberg@student.uni.nl";
+        assert_eq!(
+            select_surname(SurnameSource::SortableName, sortable, email, &surnames),
+            "van der Berg"
+        );
+        let source = SurnameSource::Email;
+        assert_eq!(
+            select_surname(source, sortable, email, &surnames),
+            "vanderberg"
+        );
+
+        // Dotted particles in the address leave only the last part of the surname
+        let email = "jan.van.der.berg@student.uni.nl";
+        assert_eq!(select_surname(source, sortable, email, &surnames), "berg");
+    }
+
+    #[test]
+    fn test_select_surname_falls_back_to_email() {
+        let email = "a.doe@uni.nl";
+        let source = SurnameSource::SortableName;
+        let surnames = SurnameExtractor::default();
+        assert_eq!(select_surname(source, None, email, &surnames), "doe");
+        assert_eq!(
+            select_surname(source, Some("Alice Doe"), email, &surnames),
+            "doe"
+        );
+        assert_eq!(
+            select_surname(source, Some(" , Alice"), email, &surnames),
+            "doe"
+        );
+    }
+
+    #[test]
+    fn test_surname_extraction_strategies() {
+        let extract = |extraction: NameExtraction, email: &str| {
+            SurnameExtractor::new(&extraction).unwrap().extract(email)
+        };
+        assert_eq!(
+            extract(NameExtraction::AfterLastDot, "john.doe@uni.nl"),
+            "doe"
+        );
+        assert_eq!(extract(NameExtraction::AfterLastDot, "jdoe@uni.nl"), "jdoe");
+        assert_eq!(
+            extract(NameExtraction::BeforeFirstDot, "doe.john@uni.nl"),
+            "doe"
+        );
+        assert_eq!(
+            extract(NameExtraction::FullLocalPart, "s1234567@student.uni.nl"),
+            "s1234567"
+        );
+
+        // "j.a.doe2@" keeps the letters after the last dot, without the digits
+        let custom = NameExtraction::Custom(r"\.([a-z]+)\d*$".to_string());
+        assert_eq!(extract(custom.clone(), "j.a.doe2@uni.nl"), "doe");
+        // Without a capture group the whole match is the surname
+        let custom_match = NameExtraction::Custom(r"^[a-z]+".to_string());
+        assert_eq!(extract(custom_match, "doe42@uni.nl"), "doe");
+        // A pattern that does not match leaves the whole local part
+        assert_eq!(extract(custom, "s1234567@uni.nl"), "s1234567");
+
+        let invalid = NameExtraction::Custom("(unclosed".to_string());
+        assert!(SurnameExtractor::new(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_parse_enrollment_roles() {
+        assert_eq!(
+            EnrollmentRole::parse_list::<&str>(&[]).unwrap(),
+            vec![EnrollmentRole::Student]
+        );
+        assert_eq!(
+            EnrollmentRole::parse_list(&["StudentEnrollment", "TaEnrollment", "TaEnrollment"])
+                .unwrap(),
+            vec![EnrollmentRole::Student, EnrollmentRole::Ta]
+        );
+        let error = EnrollmentRole::parse_list(&["TAEnrollment"]).unwrap_err();
+        assert!(error.contains("Unknown enrollment role 'TAEnrollment'"));
+        assert!(error.contains("TaEnrollment"));
+    }
+
+    #[test]
+    fn test_validate_student_info_reports_missing_fields() {
+        let students = vec![
+            student("Alice Doe", "alice", "alice.doe@uni.nl"),
+            student("Bob Roe", "", ""),
+        ];
+
+        let warnings = validate_student_info(&students);
+
+        assert_eq!(
+            warnings,
+            vec![StudentWarning {
+                full_name: "Bob Roe".to_string(),
+                missing_git_id: true,
+                missing_email: true,
+            }]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "Bob Roe has no git username and email"
+        );
+    }
+
+    #[test]
+    fn test_student_info_user_conversion_preserves_fields() {
+        let user: User = serde_json::from_value(serde_json::json!({
+            "id": "17",
+            "name": "Alice Doe",
+            "email": "alice.doe@uni.nl",
+            "login_id": "adoe",
+            "sis_user_id": "20201234",
+        }))
+        .unwrap();
+
+        let student = StudentInfo::from(user.clone());
+        assert!(student.group.is_none());
+        assert_eq!(student.full_name, "Alice Doe");
+        assert_eq!(student.name, "doe");
+        assert_eq!(student.canvas_id, "adoe");
+        assert_eq!(student.git_id, "20201234");
+        assert_eq!(student.email, "alice.doe@uni.nl");
+        assert_eq!(student.lms_id, "17");
+        assert_eq!(student.sis_user_id.as_deref(), Some("20201234"));
+
+        // The SIS id stays available when the git id comes from elsewhere
+        let student = StudentInfo::from_user(user.clone(), GitIdSource::LoginId);
+        assert_eq!(student.git_id, "adoe");
+        assert_eq!(student.sis_user_id.as_deref(), Some("20201234"));
+
+        let back = User::try_from(&student).unwrap();
+        assert_eq!(back.id, user.id);
+        assert_eq!(back.name, user.name);
+        assert_eq!(back.email, user.email);
+        assert_eq!(back.login_id, user.login_id);
+        assert_eq!(back.sis_user_id, user.sis_user_id);
+    }
+
+    /// Data source whose group memberships take a while, counting the concurrent requests
+    struct SlowGroups {
+        groups: usize,
+        in_flight: std::sync::atomic::AtomicUsize,
+        max_in_flight: std::sync::atomic::AtomicUsize,
+    }
+
+    impl LmsDataSource for SlowGroups {
+        async fn get_users(&self, _course_id: &str) -> Result<Vec<User>> {
+            Ok((0..self.groups)
+                .map(|i| {
+                    serde_json::from_value(serde_json::json!({
+                        "id": i.to_string(),
+                        "name": format!("Student {}", i),
+                        "email": format!("s{}@uni.nl", i),
+                        "sis_user_id": format!("s{}", i),
+                    }))
+                    .unwrap()
+                })
+                .collect())
+        }
+
+        async fn get_groups(&self, _course_id: &str) -> Result<Vec<Group>> {
+            Ok((0..self.groups)
+                .map(|i| {
+                    serde_json::from_value(serde_json::json!({
+                        "id": format!("g{}", i),
+                        "name": format!("Group {}", i),
+                        "members_count": 2,
+                        "max_membership": 2,
+                    }))
+                    .unwrap()
+                })
+                .collect())
+        }
+
+        async fn get_group_members(&self, group_id: &str) -> Result<Vec<GroupMembership>> {
+            use std::sync::atomic::Ordering;
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            // Later groups answer sooner, so completion order differs from group order
+            let index: u64 = group_id[1..].parse().unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(40 - index * 4)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            // Every group has its own student, and student 0 is also in every group
+            let member = |user_id: String| {
+                serde_json::from_value(serde_json::json!({
+                    "id": format!("{}-{}", group_id, user_id),
+                    "group_id": group_id,
+                    "user_id": user_id,
+                }))
+                .unwrap()
+            };
+            Ok(vec![member(index.to_string()), member("0".to_string())])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_group_memberships_fetched_concurrently() {
+        let client = SlowGroups {
+            groups: 6,
+            in_flight: Default::default(),
+            max_in_flight: Default::default(),
+        };
+        let options = StudentFetchOptions {
+            group_concurrency: 3,
+            ..Default::default()
+        };
+        let mut reported = Vec::new();
+        let result = get_student_info_with_progress(&client, "demo", &options, |progress| {
+            if let FetchProgress::FetchingGroupMembers { current, .. } = progress {
+                reported.push(current);
+            }
+        })
+        .await
+        .unwrap();
+
+        let max_in_flight = client
+            .max_in_flight
+            .load(std::sync::atomic::Ordering::SeqCst);
+        assert!(max_in_flight > 1 && max_in_flight <= 3, "{}", max_in_flight);
+        assert_eq!(reported, vec![1, 2, 3, 4, 5, 6]);
+
+        let groups: Vec<(&str, &str)> = result
+            .students
+            .iter()
+            .map(|s| (s.git_id.as_str(), s.group.as_ref().unwrap().name.as_str()))
+            .collect();
+        // Student 0 is in every group and is deterministically put in the first one
+        assert_eq!(
+            groups,
+            vec![
+                ("s0", "Group 0"),
+                ("s1", "Group 1"),
+                ("s2", "Group 2"),
+                ("s3", "Group 3"),
+                ("s4", "Group 4"),
+                ("s5", "Group 5"),
+            ]
+        );
+    }
 }
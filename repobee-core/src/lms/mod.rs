@@ -1,7 +1,25 @@
+mod cache;
+mod enrollments;
+mod fixture;
+mod group_sets;
+mod import;
 mod lms_client_factory;
+mod moodle;
+mod org;
+mod sections;
+mod trace;
 mod types;
 mod yaml;
 
+pub use cache::*;
+pub use enrollments::*;
+pub use fixture::*;
+pub use group_sets::*;
+pub use import::*;
 pub use lms_client_factory::*;
+pub use moodle::*;
+pub use org::*;
+pub use sections::*;
+pub use trace::*;
 pub use types::*;
 pub use yaml::*;
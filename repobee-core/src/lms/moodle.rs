@@ -0,0 +1,434 @@
+//! Moodle Web Services client
+//!
+//! Moodle exposes its data through the Web Services API: every call is a request to
+//! `webservice/rest/server.php` with the token in `wstoken` and the function in
+//! `wsfunction`. Errors are reported with status 200 and an error object:
+//!
+//! ```json
+//! {"exception": "moodle_exception", "errorcode": "invalidtoken", "message": "Invalid token"}
+//! ```
+//!
+//! [`MoodleClient`] calls `core_enrol_get_enrolled_users`, `core_group_get_course_groups`
+//! and `core_group_get_group_members`, and maps the results to the common LMS types.
+
+use super::types::{EnrollmentRole, Group, GroupMembership, User};
+use super::LmsDataSource;
+use crate::error::{PlatformError, Result};
+use crate::http::http_client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Query parameter Moodle reads the web service token from
+const TOKEN_PARAM: &str = "wstoken";
+
+#[derive(Debug, Deserialize)]
+struct MoodleUser {
+    id: u64,
+    fullname: String,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    idnumber: Option<String>,
+    #[serde(default)]
+    roles: Vec<MoodleRole>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MoodleRole {
+    shortname: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MoodleGroup {
+    id: u64,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MoodleGroupMembers {
+    groupid: u64,
+    userids: Vec<u64>,
+}
+
+/// LMS client for Moodle's Web Services API
+#[derive(Debug, Clone)]
+pub struct MoodleClient {
+    client: reqwest::Client,
+    base_url: String,
+    token: String,
+}
+
+impl MoodleClient {
+    /// Create a client for the Moodle site at `base_url` with a web service token
+    pub fn new(base_url: &str, token: &str) -> Result<Self> {
+        let client = http_client()?;
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+        })
+    }
+
+    /// Call a web service function, unwrapping Moodle's error object
+    ///
+    /// Moodle reads the token from the query instead of an `Authorization` header, so it
+    /// is part of the URL; the URL is only logged or reported with the token redacted.
+    async fn call<T: DeserializeOwned>(
+        &self,
+        function: &str,
+        params: &[(&str, &str)],
+    ) -> Result<T> {
+        let url = format!("{}/webservice/rest/server.php", self.base_url);
+        let request = self
+            .client
+            .get(&url)
+            .query(&[
+                (TOKEN_PARAM, self.token.as_str()),
+                ("wsfunction", function),
+                ("moodlewsrestformat", "json"),
+            ])
+            .query(params)
+            .build()
+            .map_err(redact_error)?;
+        log::debug!("Moodle request: GET {}", redact_url(request.url()));
+        let response = self.client.execute(request).await.map_err(redact_error)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(match status.as_u16() {
+                401 | 403 => PlatformError::bad_credentials(format!("{}: {}", function, text)),
+                404 => {
+                    PlatformError::not_found(format!("No Moodle web services at {}", self.base_url))
+                }
+                code => PlatformError::unexpected(format!("HTTP {}: {}", code, text)),
+            });
+        }
+
+        let value: Value = response.json().await.map_err(|e| {
+            PlatformError::unexpected(format!("JSON parse error: {}", redact_error(e)))
+        })?;
+        if let Some(error) = moodle_error(function, &value) {
+            return Err(error);
+        }
+        serde_json::from_value(value).map_err(|e| {
+            PlatformError::unexpected(format!("Unexpected {} response: {}", function, e))
+        })
+    }
+
+    /// All users enrolled in a course, with their roles
+    async fn enrolled_users(&self, course_id: &str) -> Result<Vec<MoodleUser>> {
+        self.call("core_enrol_get_enrolled_users", &[("courseid", course_id)])
+            .await
+    }
+}
+
+impl LmsDataSource for MoodleClient {
+    async fn get_users(&self, course_id: &str) -> Result<Vec<User>> {
+        self.get_users_with_role(course_id, EnrollmentRole::Student)
+            .await
+    }
+
+    async fn get_users_with_role(
+        &self,
+        course_id: &str,
+        role: EnrollmentRole,
+    ) -> Result<Vec<User>> {
+        let shortname = moodle_role(role).ok_or_else(|| {
+            PlatformError::Other(format!("Moodle has no enrollment role for {}", role))
+        })?;
+        self.enrolled_users(course_id)
+            .await?
+            .into_iter()
+            .filter(|user| user.roles.iter().any(|r| r.shortname == shortname))
+            .map(into_user)
+            .collect()
+    }
+
+    async fn get_groups(&self, course_id: &str) -> Result<Vec<Group>> {
+        let groups: Vec<MoodleGroup> = self
+            .call("core_group_get_course_groups", &[("courseid", course_id)])
+            .await?;
+        groups
+            .into_iter()
+            .map(|group| {
+                serde_json::from_value(json!({
+                    "id": group.id.to_string(),
+                    "name": group.name,
+                    "members_count": null,
+                    "max_membership": null,
+                }))
+                .map_err(|e| PlatformError::unexpected(format!("Unexpected Moodle group: {}", e)))
+            })
+            .collect()
+    }
+
+    async fn get_group_members(&self, group_id: &str) -> Result<Vec<GroupMembership>> {
+        let groups: Vec<MoodleGroupMembers> = self
+            .call("core_group_get_group_members", &[("groupids[0]", group_id)])
+            .await?;
+        groups
+            .into_iter()
+            .flat_map(|group| {
+                let group_id = group.groupid;
+                group.userids.into_iter().map(move |user_id| {
+                    serde_json::from_value(json!({
+                        "id": format!("{}-{}", group_id, user_id),
+                        "group_id": group_id.to_string(),
+                        "user_id": user_id.to_string(),
+                    }))
+                    .map_err(|e| {
+                        PlatformError::unexpected(format!("Unexpected Moodle membership: {}", e))
+                    })
+                })
+            })
+            .collect()
+    }
+}
+
+/// `url` with the value of the token parameter replaced by `REDACTED`
+fn redact_url(url: &reqwest::Url) -> reqwest::Url {
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| {
+            let value = if name == TOKEN_PARAM {
+                "REDACTED".to_string()
+            } else {
+                value.into_owned()
+            };
+            (name.into_owned(), value)
+        })
+        .collect();
+    let mut redacted = url.clone();
+    if !pairs.is_empty() {
+        redacted.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    redacted
+}
+
+/// Redact the token in the URL reqwest includes in its error messages
+fn redact_error(mut error: reqwest::Error) -> reqwest::Error {
+    if let Some(url) = error.url_mut() {
+        *url = redact_url(url);
+    }
+    error
+}
+
+/// Short name of the Moodle role matching an enrollment role
+///
+/// Moodle's non-editing teacher is the closest match for a Canvas TA.
+fn moodle_role(role: EnrollmentRole) -> Option<&'static str> {
+    match role {
+        EnrollmentRole::Student => Some("student"),
+        EnrollmentRole::Ta => Some("teacher"),
+        EnrollmentRole::Teacher => Some("editingteacher"),
+        EnrollmentRole::Designer => Some("manager"),
+        EnrollmentRole::Observer => None,
+    }
+}
+
+/// Error reported in a Moodle error object, if the response is one
+fn moodle_error(function: &str, value: &Value) -> Option<PlatformError> {
+    let object = value.as_object()?;
+    object.get("exception")?;
+    let code = object
+        .get("errorcode")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let message = object
+        .get("message")
+        .and_then(Value::as_str)
+        .unwrap_or(code);
+    let message = format!("{}: {}", function, message);
+    Some(match code {
+        "invalidtoken"
+        | "accessexception"
+        | "webservicesnotenabled"
+        | "nopermissions"
+        | "requireloginerror" => PlatformError::bad_credentials(message),
+        "invalidrecord" | "invalidrecordunknown" | "invalidparameter" => {
+            PlatformError::not_found(message)
+        }
+        _ => PlatformError::unexpected(message),
+    })
+}
+
+/// Convert a Moodle user into the unified LMS user type
+///
+/// The Moodle username becomes the login id and the ID number the SIS id.
+fn into_user(user: MoodleUser) -> Result<User> {
+    serde_json::from_value(json!({
+        "id": user.id.to_string(),
+        "name": user.fullname,
+        "email": user.email.filter(|e| !e.is_empty()),
+        "login_id": user.username,
+        "sis_user_id": user.idnumber.filter(|n| !n.is_empty()),
+    }))
+    .map_err(|e| PlatformError::unexpected(format!("Unexpected Moodle user: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::http::{closed_url, MockServer, Response};
+
+    /// Serve one canned JSON body per connection
+    fn serve(bodies: Vec<&str>) -> MockServer {
+        MockServer::sequence(bodies.into_iter().map(Response::json).collect())
+    }
+
+    const ENROLLED_USERS: &str = r#"[
+        {"id": 7, "fullname": "Alice Doe", "email": "a.doe@uni.nl", "username": "adoe",
+         "idnumber": "20201234", "roles": [{"roleid": 5, "shortname": "student"}]},
+        {"id": 8, "fullname": "Tom Ta", "email": "t.ta@uni.nl", "username": "tta",
+         "idnumber": "", "roles": [{"roleid": 4, "shortname": "teacher"}]}
+    ]"#;
+
+    #[tokio::test]
+    async fn test_enrolled_users_by_role() {
+        let server = serve(vec![ENROLLED_USERS, ENROLLED_USERS]);
+        let client = MoodleClient::new(server.url(), "secret").unwrap();
+
+        let students = client.get_users("42").await.unwrap();
+        assert_eq!(students.len(), 1);
+        assert_eq!(students[0].id, "7");
+        assert_eq!(students[0].name, "Alice Doe");
+        assert_eq!(students[0].login_id.as_deref(), Some("adoe"));
+        assert_eq!(students[0].sis_user_id.as_deref(), Some("20201234"));
+
+        let tas = client
+            .get_users_with_role("42", EnrollmentRole::Ta)
+            .await
+            .unwrap();
+        assert_eq!(tas.len(), 1);
+        assert_eq!(tas[0].sis_user_id, None);
+
+        // The token is a query parameter, not a header
+        let request = &server.requests()[0];
+        let path = &request.path;
+        assert!(path.starts_with("/webservice/rest/server.php?wstoken=secret&"));
+        assert!(path.contains("wsfunction=core_enrol_get_enrolled_users"));
+        assert!(path.contains("moodlewsrestformat=json"));
+        assert!(path.ends_with("&courseid=42"));
+        assert_eq!(request.header("Authorization"), None);
+    }
+
+    #[tokio::test]
+    async fn test_student_info_with_client_from_settings() {
+        use crate::lms::{create_moodle_client, get_student_info};
+        use crate::settings::CommonSettings;
+
+        let server = serve(vec![
+            ENROLLED_USERS,
+            r#"[{"id": 3, "courseid": 42, "name": "Lab 1", "description": ""}]"#,
+            r#"[{"groupid": 3, "userids": [7]}]"#,
+        ]);
+        let settings = CommonSettings {
+            lms_type: "Moodle".to_string(),
+            lms_custom_url: "https://canvas.example.com".to_string(),
+            lms_access_token: "canvas-token".to_string(),
+            moodle_base_url: server.url().to_string(),
+            moodle_access_token: "moodle-token".to_string(),
+            ..Default::default()
+        };
+        let client = create_moodle_client(&settings).unwrap();
+
+        let result = get_student_info(&client, "42").await.unwrap();
+        assert_eq!(result.students.len(), 1);
+        assert_eq!(result.students[0].git_id, "20201234");
+        assert_eq!(result.students[0].group.as_ref().unwrap().name, "Lab 1");
+
+        let requests = server.request_lines();
+        assert_eq!(requests.len(), 3);
+        assert!(requests
+            .iter()
+            .all(|r| r.starts_with("GET /webservice/rest/server.php?wstoken=moodle-token&")));
+    }
+
+    #[test]
+    fn test_client_from_older_settings() {
+        use crate::lms::create_moodle_client;
+        use crate::settings::CommonSettings;
+
+        // Before the Moodle fields, the site was kept in the custom URL
+        let mut settings = CommonSettings {
+            lms_type: "Moodle".to_string(),
+            lms_custom_url: "https://moodle.uni.nl/".to_string(),
+            lms_access_token: "secret".to_string(),
+            ..Default::default()
+        };
+        let client = create_moodle_client(&settings).unwrap();
+        assert_eq!(client.base_url, "https://moodle.uni.nl");
+        assert_eq!(client.token, "secret");
+
+        settings.lms_custom_url.clear();
+        assert!(create_moodle_client(&settings).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_course_groups() {
+        let server = serve(vec![
+            r#"[{"id": 3, "courseid": 42, "name": "Lab 1", "description": ""}]"#,
+        ]);
+        let client = MoodleClient::new(server.url(), "secret").unwrap();
+
+        let groups = client.get_groups("42").await.unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].id, "3");
+        assert_eq!(groups[0].name, "Lab 1");
+        assert!(server.requests()[0]
+            .path
+            .contains("wsfunction=core_group_get_course_groups"));
+    }
+
+    #[tokio::test]
+    async fn test_group_members() {
+        let server = serve(vec![r#"[{"groupid": 3, "userids": [7, 9]}]"#]);
+        let client = MoodleClient::new(server.url(), "secret").unwrap();
+
+        let members = client.get_group_members("3").await.unwrap();
+        let users: Vec<&str> = members.iter().map(|m| m.user_id.as_str()).collect();
+        assert_eq!(users, vec!["7", "9"]);
+        assert_eq!(members[0].group_id, "3");
+        let path = &server.requests()[0].path;
+        assert!(path.contains("wsfunction=core_group_get_group_members"));
+        assert!(path.ends_with("&groupids%5B0%5D=3"));
+    }
+
+    #[tokio::test]
+    async fn test_error_object_is_reported() {
+        let server = serve(vec![
+            r#"{"exception": "moodle_exception", "errorcode": "invalidtoken",
+                "message": "Invalid token - token not found"}"#,
+            r#"{"exception": "dml_missing_record_exception", "errorcode": "invalidrecord",
+                "message": "Can't find data record in database table course."}"#,
+        ]);
+        let client = MoodleClient::new(server.url(), "expired").unwrap();
+
+        match client.get_groups("42").await {
+            Err(PlatformError::BadCredentials(message)) => {
+                assert_eq!(
+                    message,
+                    "core_group_get_course_groups: Invalid token - token not found"
+                )
+            }
+            other => panic!("expected BadCredentials, got {:?}", other),
+        }
+        assert!(matches!(
+            client.get_users("999").await,
+            Err(PlatformError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_token_is_redacted_in_errors() {
+        let client = MoodleClient::new(&closed_url(), "secret").unwrap();
+
+        let message = client.get_groups("42").await.unwrap_err().to_string();
+        assert!(message.contains("wstoken=REDACTED"), "{}", message);
+        assert!(!message.contains("secret"), "{}", message);
+    }
+}
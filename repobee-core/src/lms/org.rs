@@ -0,0 +1,203 @@
+//! Deriving the student repository group from an LMS course, and checking the course
+//! against the settings
+
+use super::types::Course;
+use crate::error::{PlatformError, Result};
+
+/// Placeholders supported in organization templates
+const ORG_TEMPLATE_PLACEHOLDERS: &[&str] = &["course_code", "course_id", "term"];
+
+/// Maximum length of a group path, as enforced by GitLab
+const MAX_GROUP_PATH_LENGTH: usize = 255;
+
+/// Git group path for a course, e.g. `{course_code}-{term}` gives `2WF50-2024`
+///
+/// Whitespace in the result is replaced by `-`, and the result must be a valid group path
+/// (see [`validate_group_path`]).
+pub fn org_from_course(course: &Course, template: &str, term: &str) -> Result<String> {
+    render_org_template(
+        template,
+        course.course_code.as_deref(),
+        &course.id.to_string(),
+        term,
+    )
+}
+
+/// Fill in an organization template and validate the result
+pub fn render_org_template(
+    template: &str,
+    course_code: Option<&str>,
+    course_id: &str,
+    term: &str,
+) -> Result<String> {
+    let mut org = String::new();
+    let mut rest = template.trim();
+    if rest.is_empty() {
+        return Err(PlatformError::Other(
+            "Organization template must not be empty".to_string(),
+        ));
+    }
+
+    while let Some(start) = rest.find('{') {
+        org.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after.find('}').ok_or_else(|| {
+            PlatformError::Other(format!(
+                "Unclosed placeholder in organization template '{}'",
+                template
+            ))
+        })?;
+        let value = match &after[..end] {
+            "course_code" => match course_code {
+                Some(code) if !code.trim().is_empty() => code,
+                _ => {
+                    return Err(missing_value(
+                        "course_code",
+                        "the course has no course code",
+                    ))
+                }
+            },
+            "course_id" => course_id,
+            "term" if term.trim().is_empty() => {
+                return Err(missing_value("term", "no term is set"));
+            }
+            "term" => term,
+            placeholder => {
+                return Err(PlatformError::Other(format!(
+                    "Unknown placeholder '{{{}}}' in organization template (expected one of: {})",
+                    placeholder,
+                    ORG_TEMPLATE_PLACEHOLDERS
+                        .iter()
+                        .map(|p| format!("{{{}}}", p))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )));
+            }
+        };
+        org.push_str(value.trim());
+        rest = &after[end + 1..];
+    }
+    org.push_str(rest);
+
+    let org = org.split_whitespace().collect::<Vec<_>>().join("-");
+    validate_group_path(&org)?;
+    Ok(org)
+}
+
+fn missing_value(placeholder: &str, reason: &str) -> PlatformError {
+    PlatformError::Other(format!(
+        "Organization template uses {{{}}} but {}",
+        placeholder, reason
+    ))
+}
+
+/// Check that a group path is valid on the git platforms
+///
+/// Subgroups are separated by `/`. Every part may only contain ASCII letters, digits, `_`,
+/// `-` and `.`, must not start with `-` or `.`, and must not end with `.`, `.git` or
+/// `.atom`.
+pub fn validate_group_path(path: &str) -> Result<()> {
+    let problem = if path.is_empty() {
+        Some("it is empty".to_string())
+    } else if path.len() > MAX_GROUP_PATH_LENGTH {
+        Some(format!(
+            "it is longer than {} characters",
+            MAX_GROUP_PATH_LENGTH
+        ))
+    } else {
+        path.split('/').find_map(|part| {
+            if part.is_empty() {
+                Some("it has an empty part".to_string())
+            } else if let Some(c) = part
+                .chars()
+                .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')))
+            {
+                Some(format!("it contains '{}'", c))
+            } else if part.starts_with(['.', '-']) {
+                Some(format!("'{}' starts with '.' or '-'", part))
+            } else if part.ends_with('.') || part.ends_with(".git") || part.ends_with(".atom") {
+                Some(format!("'{}' ends with '.', '.git' or '.atom'", part))
+            } else {
+                None
+            }
+        })
+    };
+
+    match problem {
+        Some(problem) => Err(PlatformError::Other(format!(
+            "Invalid group path '{}': {}",
+            path, problem
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Whether a course name stored in the settings no longer matches the course's actual name
+///
+/// The id and name in the settings drift apart when a course is renamed or the id is
+/// edited by hand, e.g. after copying it from another course. Case and whitespace
+/// differences are ignored, and an empty stored name never differs.
+pub fn course_name_differs(stored: &str, fetched: &str) -> bool {
+    let normalize = |name: &str| {
+        name.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    !stored.trim().is_empty() && normalize(stored) != normalize(fetched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_org_template() {
+        assert_eq!(
+            render_org_template("{course_code}-{term}", Some("2WF50"), "123", "2024").unwrap(),
+            "2WF50-2024"
+        );
+        assert_eq!(
+            render_org_template(
+                "courses/{term}/{course_code}",
+                Some("TDA 357"),
+                "1",
+                "2024-Q3"
+            )
+            .unwrap(),
+            "courses/2024-Q3/TDA-357"
+        );
+        assert_eq!(
+            render_org_template("course-{course_id}", None, "123", "").unwrap(),
+            "course-123"
+        );
+    }
+
+    #[test]
+    fn test_render_org_template_errors() {
+        assert!(render_org_template("{course_code}", None, "1", "").is_err());
+        assert!(render_org_template("{course_code}-{term}", Some("2WF50"), "1", " ").is_err());
+        assert!(render_org_template("{course}", Some("2WF50"), "1", "").is_err());
+        assert!(render_org_template("{course_code", Some("2WF50"), "1", "").is_err());
+        assert!(render_org_template("{course_code}!", Some("2WF50"), "1", "").is_err());
+    }
+
+    #[test]
+    fn test_validate_group_path() {
+        assert!(validate_group_path("courses/2024/2wf50").is_ok());
+        assert!(validate_group_path("2WF50_2024.students").is_ok());
+        assert!(validate_group_path("").is_err());
+        assert!(validate_group_path("courses//2wf50").is_err());
+        assert!(validate_group_path("-2wf50").is_err());
+        assert!(validate_group_path("2wf50.git").is_err());
+        assert!(validate_group_path("2wf50 2024").is_err());
+    }
+
+    #[test]
+    fn test_course_name_differs() {
+        assert!(!course_name_differs("", "Calculus"));
+        assert!(!course_name_differs("Calculus  2024", " calculus 2024"));
+        assert!(course_name_differs("Calculus 2023", "Calculus 2024"));
+        assert!(course_name_differs("Calculus", "Linear Algebra"));
+    }
+}
@@ -0,0 +1,154 @@
+//! Canvas course sections
+//!
+//! Sections are not part of the unified LMS client, so they are fetched directly
+//! from the Canvas REST API, with the same retries and paging as the other Canvas lists.
+
+use super::enrollments::get_all_pages;
+use super::types::{CanvasPaging, CanvasSection};
+use crate::error::{PlatformError, Result};
+use crate::http::http_client;
+use crate::retry::RetryPolicy;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct CanvasSectionResponse {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    students: Option<Vec<CanvasSectionStudent>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CanvasSectionStudent {
+    id: u64,
+}
+
+/// Fetch all sections of a Canvas course, including the ids of their students
+pub async fn get_course_sections(
+    base_url: &str,
+    access_token: &str,
+    course_id: &str,
+) -> Result<Vec<CanvasSection>> {
+    get_course_sections_with_paging(base_url, access_token, course_id, &CanvasPaging::default())
+        .await
+}
+
+/// Fetch the sections of a Canvas course with explicit paging limits
+///
+/// When `paging.max_results` is exceeded, paging stops with a warning and only the first
+/// `max_results` sections are returned.
+pub async fn get_course_sections_with_paging(
+    base_url: &str,
+    access_token: &str,
+    course_id: &str,
+    paging: &CanvasPaging,
+) -> Result<Vec<CanvasSection>> {
+    course_sections(
+        base_url,
+        access_token,
+        course_id,
+        &RetryPolicy::default(),
+        paging,
+    )
+    .await
+}
+
+/// [`get_course_sections_with_paging`], retrying throttled and failed requests with `retry`
+pub(super) async fn course_sections(
+    base_url: &str,
+    access_token: &str,
+    course_id: &str,
+    retry: &RetryPolicy,
+    paging: &CanvasPaging,
+) -> Result<Vec<CanvasSection>> {
+    let url = format!(
+        "{}/api/v1/courses/{}/sections?include[]=students&per_page={}",
+        base_url.trim_end_matches('/'),
+        course_id,
+        paging.page_size()
+    );
+    let sections: Vec<CanvasSectionResponse> =
+        get_all_pages(&http_client()?, &url, access_token, retry, paging)
+            .await
+            .map_err(|e| match e {
+                PlatformError::BadCredentials(text) => {
+                    PlatformError::bad_credentials(format!("Failed to fetch sections: {}", text))
+                }
+                PlatformError::NotFound(_) => {
+                    PlatformError::not_found(format!("Course '{}' not found", course_id))
+                }
+                e => e,
+            })?;
+
+    Ok(sections
+        .into_iter()
+        .map(|s| CanvasSection {
+            id: s.id.to_string(),
+            name: s.name,
+            student_ids: s
+                .students
+                .unwrap_or_default()
+                .into_iter()
+                .map(|student| student.id.to_string())
+                .collect(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::http::{MockServer, Response};
+
+    #[tokio::test]
+    async fn test_html_login_page_is_reported_as_bad_credentials() {
+        let server = MockServer::sequence(vec![Response::status(200)
+            .header("Content-Type", "text/html; charset=utf-8")
+            .body("<html><body>Log in to Canvas</body></html>")]);
+
+        let result = get_course_sections(server.url(), "expired", "1").await;
+        match result {
+            Err(PlatformError::BadCredentials(message)) => {
+                assert!(message.contains("invalid or expired"))
+            }
+            other => panic!("expected BadCredentials, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_throttled_request_is_retried() {
+        let server = MockServer::sequence(vec![
+            Response::status(429).header("Retry-After", "0"),
+            Response::json(r#"[{"id": 1, "name": "A", "students": [{"id": 7}]}]"#),
+        ]);
+
+        let sections = get_course_sections(server.url(), "token", "1")
+            .await
+            .unwrap();
+
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].student_ids, vec!["7"]);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_max_results_stops_paging() {
+        let server = MockServer::sequence(vec![
+            Response::json(r#"[{"id": 1, "name": "A"}, {"id": 2, "name": "B"}]"#)
+                .next_page("/api/v1/courses/1/sections?page=2"),
+            Response::json(r#"[{"id": 3, "name": "C"}, {"id": 4, "name": "D"}]"#)
+                .next_page("/api/v1/courses/1/sections?page=3"),
+        ]);
+
+        let paging = CanvasPaging {
+            per_page: 2,
+            max_results: Some(3),
+        };
+        let sections = get_course_sections_with_paging(server.url(), "token", "1", &paging)
+            .await
+            .unwrap();
+
+        let names: Vec<&str> = sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["A", "B", "C"]);
+    }
+}
@@ -0,0 +1,279 @@
+//! Tracing a single student from the LMS to their repositories
+//!
+//! When a student ends up without a repository, [`debug_student`] shows every step that
+//! student goes through: the LMS record, the derived git username, the team generated for
+//! them, and the repository names setup would create.
+
+use super::lms_client_factory::{get_student_info_with_progress, LmsDataSource};
+use super::types::{StudentFetchOptions, StudentInfo, YamlConfig};
+use super::yaml::{format_member, generate_repobee_yaml};
+use crate::error::{PlatformError, Result};
+use crate::naming::RepoNaming;
+use crate::types::StudentTeam;
+use std::fmt;
+
+/// Everything the pipeline derives for one student
+#[derive(Debug, Clone)]
+pub struct StudentTrace {
+    /// The student as fetched from the LMS
+    pub student: StudentInfo,
+    /// Team member entry written to the YAML file, i.e. the resolved git username
+    pub member: String,
+    /// Team the student was put in; `None` if YAML generation left them out
+    pub team: Option<StudentTeam>,
+    /// Repository name per assignment, or why the name is invalid
+    pub repos: Vec<(String, std::result::Result<String, String>)>,
+    /// Reasons the student may not get a (working) repository
+    pub problems: Vec<String>,
+}
+
+/// Fetch a course and trace the student matching `identifier`
+///
+/// See [`trace_student`] for how the student is looked up.
+pub async fn debug_student<C: LmsDataSource>(
+    client: &C,
+    course_id: &str,
+    identifier: &str,
+    options: &StudentFetchOptions,
+    config: &YamlConfig,
+    assignments: &[String],
+    naming: &RepoNaming,
+) -> Result<StudentTrace> {
+    let fetched = get_student_info_with_progress(client, course_id, options, |_| {}).await?;
+    trace_student(&fetched.students, identifier, config, assignments, naming)
+}
+
+/// Trace the student matching `identifier` through YAML generation and repository naming
+///
+/// The identifier is compared case-insensitively with the login id, git id, email and
+/// full name of every student. All students are needed because teams depend on the other
+/// members of a group.
+pub fn trace_student(
+    students: &[StudentInfo],
+    identifier: &str,
+    config: &YamlConfig,
+    assignments: &[String],
+    naming: &RepoNaming,
+) -> Result<StudentTrace> {
+    let student = find_student(students, identifier)?.clone();
+    let member = format_member(&student, config);
+    let mut problems = Vec::new();
+
+    if student.git_id.trim().is_empty() {
+        problems.push("The LMS record has no git username".to_string());
+    }
+    if student.email.trim().is_empty() {
+        problems.push("The LMS record has no email".to_string());
+    }
+
+    let teams = generate_repobee_yaml(students, config)?;
+    let team = if student.git_id.trim().is_empty() && student.email.trim().is_empty() {
+        None
+    } else {
+        teams.into_iter().find(|t| t.members.contains(&member))
+    };
+    if team.is_none() {
+        problems.push(match &student.group {
+            Some(group)
+                if config.full_groups
+                    && matches!(
+                        (group.members_count, group.max_membership),
+                        (Some(count), Some(max)) if count < max
+                    ) =>
+            {
+                format!(
+                    "Not in any team: group '{}' is not full and only full groups are included",
+                    group.name
+                )
+            }
+            None if !config.individual_teams => {
+                "Not in any team: the student has no group and individual teams are off".to_string()
+            }
+            _ => "Not in any team".to_string(),
+        });
+    }
+
+    let repos = match &team {
+        Some(team) => assignments
+            .iter()
+            .map(|assignment| {
+                let name = naming
+                    .repo_name(&team.name, assignment)
+                    .map_err(|e| e.to_string());
+                if let Err(e) = &name {
+                    problems.push(e.clone());
+                }
+                (assignment.clone(), name)
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    Ok(StudentTrace {
+        student,
+        member,
+        team,
+        repos,
+        problems,
+    })
+}
+
+fn find_student<'a>(students: &'a [StudentInfo], identifier: &str) -> Result<&'a StudentInfo> {
+    let identifier = identifier.trim();
+    let matches: Vec<&StudentInfo> = students
+        .iter()
+        .filter(|s| {
+            [&s.canvas_id, &s.git_id, &s.email, &s.full_name]
+                .iter()
+                .any(|value| !value.is_empty() && value.eq_ignore_ascii_case(identifier))
+        })
+        .collect();
+
+    match matches.as_slice() {
+        [student] => Ok(student),
+        [] => Err(PlatformError::not_found(format!(
+            "No student matches '{}' (compared with login id, git id, email and name)",
+            identifier
+        ))),
+        several => Err(PlatformError::Other(format!(
+            "'{}' matches {} students ({}); use their login id or email",
+            identifier,
+            several.len(),
+            several
+                .iter()
+                .map(|s| s.full_name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
+
+impl fmt::Display for StudentTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let student = &self.student;
+        writeln!(f, "Student: {}", student.full_name)?;
+        writeln!(f, "  Login ID: {}", student.canvas_id)?;
+        writeln!(f, "  Git ID: {}", student.git_id)?;
+        writeln!(f, "  Email: {}", student.email)?;
+        writeln!(f, "  Last name: {}", student.name)?;
+        match &student.group {
+            Some(group) => writeln!(
+                f,
+                "  Group: {} (id {}, slug {})",
+                group.name, group.id, group.slug
+            )?,
+            None => writeln!(f, "  Group: (none)")?,
+        }
+        writeln!(f, "  Team member entry: {}", self.member)?;
+        match &self.team {
+            Some(team) => writeln!(f, "  Team: {} ({})", team.name, team.members.join(", "))?,
+            None => writeln!(f, "  Team: (none)")?,
+        }
+        for (assignment, repo) in &self.repos {
+            match repo {
+                Ok(name) => writeln!(f, "  Repository for {}: {}", assignment, name)?,
+                Err(e) => writeln!(f, "  Repository for {}: ✗ {}", assignment, e)?,
+            }
+        }
+        if self.problems.is_empty() {
+            write!(f, "No problems found")
+        } else {
+            write!(f, "Problems:")?;
+            for problem in &self.problems {
+                write!(f, "\n  - {}", problem)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lms::{group_slug, GroupingSource, MemberOption, StudentGroup};
+
+    fn student(git_id: &str, group: Option<(&str, u64, u64)>) -> StudentInfo {
+        StudentInfo {
+            group: group.map(|(name, members_count, max_membership)| StudentGroup {
+                id: name.to_string(),
+                name: name.to_string(),
+                slug: group_slug(name),
+                members_count: Some(members_count),
+                max_membership: Some(max_membership),
+            }),
+            full_name: format!("{} Student", git_id),
+            name: git_id.to_string(),
+            canvas_id: git_id.to_string(),
+            git_id: git_id.to_string(),
+            email: format!("{}@uni.nl", git_id),
+            lms_id: git_id.to_string(),
+            sis_user_id: None,
+        }
+    }
+
+    fn config() -> YamlConfig {
+        YamlConfig {
+            member_option: MemberOption::GitId,
+            include_group: true,
+            include_member: false,
+            include_initials: false,
+            full_groups: true,
+            grouping: GroupingSource::Groups,
+            individual_teams: false,
+            team_name_template: None,
+            course_code: None,
+            member_format: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_trace_student_in_full_group() {
+        let students = vec![
+            student("alice", Some(("Group 1", 2, 2))),
+            student("bob", Some(("Group 1", 2, 2))),
+        ];
+
+        let trace = trace_student(
+            &students,
+            "ALICE@uni.nl",
+            &config(),
+            &["task1".to_string()],
+            &RepoNaming::default(),
+        )
+        .unwrap();
+
+        assert_eq!(trace.member, "alice");
+        assert_eq!(trace.team.as_ref().unwrap().name, "team-group-1");
+        assert_eq!(
+            trace.repos,
+            vec![("task1".to_string(), Ok("team-group-1-task1".to_string()))]
+        );
+        assert!(trace.problems.is_empty());
+        assert!(trace.to_string().contains("No problems found"));
+    }
+
+    #[test]
+    fn test_trace_student_in_partial_group() {
+        let students = vec![student("carol", Some(("Group 2", 1, 2)))];
+
+        let trace = trace_student(
+            &students,
+            "carol",
+            &config(),
+            &["task1".to_string()],
+            &RepoNaming::default(),
+        )
+        .unwrap();
+
+        assert!(trace.team.is_none());
+        assert!(trace.repos.is_empty());
+        assert!(trace.problems[0].contains("'Group 2' is not full"));
+    }
+
+    #[test]
+    fn test_trace_unknown_student() {
+        let students = vec![student("alice", None)];
+        let result = trace_student(&students, "mallory", &config(), &[], &RepoNaming::default());
+        assert!(matches!(result, Err(PlatformError::NotFound(_))));
+    }
+}
@@ -1,17 +1,334 @@
+use crate::settings::{GitIdSource, NameExtraction, SurnameSource};
+use crate::types::StudentTeam;
 use serde::{Deserialize, Serialize};
 // Use lms-client re-exported types (from lms-common)
 pub use lms_client::{Course, Group, GroupMembership, User};
 
 /// Student information mapped from LMS
 /// This is domain-specific to repobee and combines LMS data with Git identifiers
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StudentInfo {
-    pub group: Option<Group>, // Now uses lms-common::Group with String ID
+    pub group: Option<StudentGroup>, // LMS group or section the student belongs to
     pub full_name: String,
     pub name: String,      // Last name
     pub canvas_id: String, // login_id (keeping name for compatibility)
     pub git_id: String,    // sis_user_id or external identifier
     pub email: String,
+    pub lms_id: String,              // LMS user id
+    pub sis_user_id: Option<String>, // Canvas SIS id, whatever the git id source
+}
+
+/// Number of groups whose memberships are fetched at the same time when not configured
+pub const DEFAULT_GROUP_FETCH_CONCURRENCY: usize = 8;
+
+/// Options controlling how LMS users are turned into [`StudentInfo`]
+#[derive(Debug, Clone)]
+pub struct StudentFetchOptions {
+    /// LMS user field used as the student's git username
+    pub git_id_source: GitIdSource,
+    /// LMS user field the student's surname is taken from
+    pub surname_source: SurnameSource,
+    /// How the surname is extracted from the email address when the email is used
+    pub name_extraction: NameExtraction,
+    /// Course enrollments whose users are fetched as students, e.g. TAs who join a team
+    pub include_roles: Vec<EnrollmentRole>,
+    /// Keep the "Test Student" account Canvas creates for its Student View
+    pub include_test_students: bool,
+    /// Maximum number of groups whose memberships are fetched at the same time, at least 1
+    pub group_concurrency: usize,
+    /// Only use the groups with these ids, e.g. the groups of one Canvas group set;
+    /// `None` uses all groups of the course
+    pub group_ids: Option<std::collections::HashSet<String>>,
+}
+
+impl Default for StudentFetchOptions {
+    fn default() -> Self {
+        Self {
+            git_id_source: GitIdSource::default(),
+            surname_source: SurnameSource::default(),
+            name_extraction: NameExtraction::default(),
+            include_roles: vec![EnrollmentRole::Student],
+            include_test_students: false,
+            group_concurrency: DEFAULT_GROUP_FETCH_CONCURRENCY,
+            group_ids: None,
+        }
+    }
+}
+
+/// Canvas course enrollment role
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EnrollmentRole {
+    #[serde(rename = "StudentEnrollment")]
+    Student,
+    #[serde(rename = "TaEnrollment")]
+    Ta,
+    #[serde(rename = "TeacherEnrollment")]
+    Teacher,
+    #[serde(rename = "DesignerEnrollment")]
+    Designer,
+    #[serde(rename = "ObserverEnrollment")]
+    Observer,
+}
+
+impl EnrollmentRole {
+    pub const ALL: [EnrollmentRole; 5] = [
+        Self::Student,
+        Self::Ta,
+        Self::Teacher,
+        Self::Designer,
+        Self::Observer,
+    ];
+
+    /// Canvas name of the role, e.g. `TaEnrollment`
+    pub fn canvas_name(&self) -> &'static str {
+        match self {
+            Self::Student => "StudentEnrollment",
+            Self::Ta => "TaEnrollment",
+            Self::Teacher => "TeacherEnrollment",
+            Self::Designer => "DesignerEnrollment",
+            Self::Observer => "ObserverEnrollment",
+        }
+    }
+
+    /// Value of the Canvas `enrollment_type[]` filter for the role
+    pub fn enrollment_type(&self) -> &'static str {
+        match self {
+            Self::Student => "student",
+            Self::Ta => "ta",
+            Self::Teacher => "teacher",
+            Self::Designer => "designer",
+            Self::Observer => "observer",
+        }
+    }
+
+    /// Parse a list of Canvas role names, rejecting unknown roles
+    ///
+    /// An empty list means students only.
+    pub fn parse_list<S: AsRef<str>>(roles: &[S]) -> Result<Vec<Self>, String> {
+        if roles.is_empty() {
+            return Ok(vec![Self::Student]);
+        }
+        let mut parsed = Vec::new();
+        for role in roles {
+            let role: Self = role.as_ref().parse()?;
+            if !parsed.contains(&role) {
+                parsed.push(role);
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+impl std::str::FromStr for EnrollmentRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|role| role.canvas_name() == s.trim())
+            .ok_or_else(|| {
+                let known: Vec<&str> = Self::ALL.iter().map(|r| r.canvas_name()).collect();
+                format!(
+                    "Unknown enrollment role '{}'. Known roles: {}",
+                    s,
+                    known.join(", ")
+                )
+            })
+    }
+}
+
+impl std::fmt::Display for EnrollmentRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.canvas_name())
+    }
+}
+
+/// LMS user with the details the unified [`User`] has no field for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CourseUser {
+    pub user: User,
+    /// "Last, First" as Canvas reports it; other LMSs have no sortable name
+    #[serde(default)]
+    pub sortable_name: Option<String>,
+}
+
+impl From<User> for CourseUser {
+    fn from(user: User) -> Self {
+        Self {
+            user,
+            sortable_name: None,
+        }
+    }
+}
+
+/// Students fetched from the LMS, with warnings about incomplete records
+#[derive(Debug, Clone, Default)]
+pub struct StudentFetchResult {
+    pub students: Vec<StudentInfo>,
+    pub warnings: Vec<StudentWarning>,
+    /// Number of Canvas test student accounts that were left out
+    pub excluded_test_students: usize,
+}
+
+/// A student whose LMS record lacks data needed to set up repositories
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StudentWarning {
+    pub full_name: String,
+    pub missing_git_id: bool,
+    pub missing_email: bool,
+}
+
+impl std::fmt::Display for StudentWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let missing = match (self.missing_git_id, self.missing_email) {
+            (true, true) => "git username and email",
+            (true, false) => "git username",
+            _ => "email",
+        };
+        write!(f, "{} has no {}", self.full_name, missing)
+    }
+}
+
+/// Grouping a student belongs to, derived from either an LMS group or a Canvas section
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StudentGroup {
+    pub id: String,
+    /// Name as shown in the LMS, for display
+    pub name: String,
+    /// Repository-safe form of the name used for team and repository names, e.g.
+    /// `team-1-lab` for `Team #1 (Lab)`. Unique within a fetch, see [`unique_group_slugs`].
+    pub slug: String,
+    pub members_count: Option<u64>,
+    pub max_membership: Option<u64>,
+}
+
+impl From<&Group> for StudentGroup {
+    fn from(group: &Group) -> Self {
+        Self {
+            id: group.id.clone(),
+            name: group.name.clone(),
+            slug: group_slug(&group.name),
+            members_count: group.members_count.map(|c| c as u64),
+            max_membership: group.max_membership.map(|m| m as u64),
+        }
+    }
+}
+
+impl From<&CanvasSection> for StudentGroup {
+    fn from(section: &CanvasSection) -> Self {
+        Self {
+            id: section.id.clone(),
+            name: section.name.clone(),
+            slug: group_slug(&section.name),
+            members_count: Some(section.student_ids.len() as u64),
+            // Sections have no capacity, so they always count as "full"
+            max_membership: None,
+        }
+    }
+}
+
+/// Repository-safe slug of a group name
+///
+/// Lowercase ASCII letters and digits are kept, every run of other characters becomes a
+/// single `-`, e.g. `Team #1 (Lab)` becomes `team-1-lab`. A name without any letters or
+/// digits becomes `group`.
+pub fn group_slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "group".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Make the slugs of distinct groups unique
+///
+/// Groups whose names slugify to the same value, such as `Team 1` and `team-1`, get a
+/// numeric suffix (`team-1`, `team-1-2`, ...). Groups are numbered in order of their id,
+/// so the same groups always get the same slugs regardless of the order they were fetched.
+pub fn unique_group_slugs(groups: &mut [StudentGroup]) {
+    let mut order: Vec<usize> = (0..groups.len()).collect();
+    order.sort_by(|&a, &b| {
+        (groups[a].id.len(), &groups[a].id).cmp(&(groups[b].id.len(), &groups[b].id))
+    });
+
+    let mut taken = std::collections::HashSet::new();
+    for index in order {
+        let base = group_slug(&groups[index].name);
+        let mut slug = base.clone();
+        let mut n = 1;
+        while !taken.insert(slug.clone()) {
+            n += 1;
+            slug = format!("{}-{}", base, n);
+        }
+        groups[index].slug = slug;
+    }
+}
+
+/// Canvas course section with the ids of its enrolled students
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanvasSection {
+    pub id: String,
+    pub name: String,
+    pub student_ids: Vec<String>,
+}
+
+/// Canvas group set (group category) with the ids of its groups
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanvasGroupSet {
+    pub id: String,
+    pub name: String,
+    pub group_ids: Vec<String>,
+}
+
+/// Paging limits for Canvas list requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CanvasPaging {
+    /// Items requested per page; Canvas caps this at 100
+    pub per_page: u32,
+    /// Stop paging once this many items were fetched; `None` fetches everything
+    pub max_results: Option<usize>,
+}
+
+impl Default for CanvasPaging {
+    fn default() -> Self {
+        Self {
+            per_page: 100,
+            max_results: None,
+        }
+    }
+}
+
+impl CanvasPaging {
+    /// Page size to request, within the 1 to 100 that Canvas accepts
+    pub fn page_size(&self) -> u32 {
+        self.per_page.clamp(1, 100)
+    }
+
+    /// Whether paging stops after `fetched` items, `more` telling whether pages remain
+    pub(super) fn is_exceeded(&self, fetched: usize, more: bool) -> bool {
+        self.max_results
+            .is_some_and(|max| fetched > max || (fetched == max && more))
+    }
+}
+
+/// Which LMS structure is used to group students into teams
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupingSource {
+    /// Canvas/Moodle groups
+    #[default]
+    Groups,
+    /// Canvas course sections
+    Sections,
 }
 
 /// Configuration for YAML generation
@@ -22,6 +339,84 @@ pub struct YamlConfig {
     pub include_member: bool,
     pub include_initials: bool,
     pub full_groups: bool,
+    #[serde(default)]
+    pub grouping: GroupingSource,
+    /// Put every student without a group in a team of their own
+    #[serde(default)]
+    pub individual_teams: bool,
+    /// Pattern for group team names, e.g. `ex1-{group}` or `{course_code}-{group}`
+    ///
+    /// Supported placeholders are `{group}`, `{group_id}` and `{course_code}`.
+    /// When set, it replaces the `include_group`/`include_member` naming scheme.
+    #[serde(default)]
+    pub team_name_template: Option<String>,
+    /// Course code substituted for `{course_code}` in `team_name_template`
+    #[serde(default)]
+    pub course_code: Option<String>,
+    /// How members are written, e.g. the `(email, gitid)` form of [`MemberOption::Both`]
+    #[serde(default)]
+    pub member_format: MemberFormat,
+}
+
+/// Which identifier comes first in a combined member entry
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberOrder {
+    #[default]
+    EmailFirst,
+    GitIdFirst,
+}
+
+/// Formatting of team members in the generated YAML
+///
+/// The default writes [`MemberOption::Both`] as `(email, gitid)`, which is what RepoBee
+/// expects; other RepoBee versions parse e.g. `gitid email`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct MemberFormat {
+    /// Text between the email and the git id
+    pub separator: String,
+    pub order: MemberOrder,
+    /// Wrap the combined entry in parentheses
+    pub parentheses: bool,
+    /// Lowercase every member entry, as git platforms compare usernames case-insensitively
+    pub lowercase: bool,
+}
+
+impl Default for MemberFormat {
+    fn default() -> Self {
+        Self {
+            separator: ", ".to_string(),
+            order: MemberOrder::EmailFirst,
+            parentheses: true,
+            lowercase: false,
+        }
+    }
+}
+
+/// Teams produced by YAML generation, with notes about decisions made along the way
+#[derive(Debug, Clone, Default)]
+pub struct GenerationResult {
+    pub teams: Vec<StudentTeam>,
+    pub notes: Vec<String>,
+}
+
+/// Teams of a few students as YAML, for previewing the effect of the YAML options
+#[derive(Debug, Clone, Serialize)]
+pub struct YamlPreview {
+    pub yaml: String,
+    /// Notes of the generation, e.g. groups that are left out
+    pub notes: Vec<String>,
+}
+
+/// Teams merged from several teams files
+#[derive(Debug, Clone, Default)]
+pub struct MergedTeams {
+    /// Distinct teams, sorted by name, with sorted members
+    pub teams: Vec<StudentTeam>,
+    /// Team names with different members in different files and students in more than
+    /// one team, each naming the files involved
+    pub conflicts: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -35,6 +430,15 @@ pub enum MemberOption {
 }
 
 impl MemberOption {
+    /// Parse a member option as written in the settings, e.g. `(email, gitid)`
+    ///
+    /// Unknown values are an error, so that a typo does not silently change the members.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        s.parse::<crate::settings::MemberOption>().map(Self::from)
+    }
+
+    /// Parse a member option, falling back to [`MemberOption::Both`] for unknown values
+    #[deprecated(note = "use `MemberOption::parse`, which reports unknown values")]
     pub fn from_str(s: &str) -> Self {
         match s {
             "(email, gitid)" => Self::Both,
@@ -44,3 +448,32 @@ impl MemberOption {
         }
     }
 }
+
+impl From<crate::settings::MemberOption> for MemberOption {
+    fn from(option: crate::settings::MemberOption) -> Self {
+        match option {
+            crate::settings::MemberOption::EmailAndGitId => Self::Both,
+            crate::settings::MemberOption::Email => Self::Email,
+            crate::settings::MemberOption::GitId => Self::GitId,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_member_option_parse() {
+        assert_eq!(
+            MemberOption::parse("(email, gitid)"),
+            Ok(MemberOption::Both)
+        );
+        assert_eq!(MemberOption::parse("email"), Ok(MemberOption::Email));
+        assert_eq!(MemberOption::parse(" git_id "), Ok(MemberOption::GitId));
+
+        let error = MemberOption::parse("gitid_email").unwrap_err();
+        assert!(error.contains("gitid_email"), "{}", error);
+        assert!(MemberOption::parse("").is_err());
+    }
+}
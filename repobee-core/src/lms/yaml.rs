@@ -1,28 +1,66 @@
 use super::types::*;
 use crate::error::*;
+use crate::naming::validate_repo_name;
 use crate::types::StudentTeam;
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 /// Generate RepoBee-compatible YAML from LMS student information
 pub fn generate_repobee_yaml(
     students: &[StudentInfo],
     config: &YamlConfig,
 ) -> Result<Vec<StudentTeam>> {
-    generate_repobee_yaml_with_progress(students, config, |_, _, _| {})
+    generate_repobee_yaml_with_progress(students, config, |_, _, _| {}).map(|r| r.teams)
 }
 
 /// Generate RepoBee-compatible YAML from LMS student information with progress callback
+///
+/// Every LMS group becomes one team holding all of its members, whatever the size of the
+/// group. With `full_groups`, a group is left out when the LMS reports fewer members than
+/// its capacity; groups without a reported capacity are always kept. For example:
+///
+/// - a full group of 3 gives `{name: team-group-1, members: [alice, bob, carol]}`
+/// - a group of 1 gives `{name: team-group-2, members: [dave]}`, unless it has room for
+///   more members and `full_groups` is set
+/// - a student without a group gets `{name: erin, members: [erin]}` with
+///   `individual_teams`; otherwise they are left out with `full_groups`, or put in a shared
+///   `team-no-group` team without it
+///
+/// Groups and students that are left out are reported in the notes of the result. When
+/// none of the students belong to a group, every student is put in a team of their own (as
+/// if `individual_teams` was set) and a note is added to the result.
+///
+/// Distinct groups never share a team, even when their names slugify to the same value.
+/// Teams that would get the same name are renamed with [`unique_team_names`] and the
+/// renames are reported in the notes.
 pub fn generate_repobee_yaml_with_progress<F>(
     students: &[StudentInfo],
     config: &YamlConfig,
     mut progress_callback: F,
-) -> Result<Vec<StudentTeam>>
+) -> Result<GenerationResult>
 where
     F: FnMut(usize, usize, &str),
 {
-    // Group students by their LMS group
-    let mut group_map: HashMap<String, Vec<&StudentInfo>> = HashMap::new();
+    if let Some(template) = &config.team_name_template {
+        validate_team_name_template(template, config.course_code.as_deref())?;
+    }
+
+    let mut notes = Vec::new();
+
+    let has_groups = students.iter().any(|s| s.group.is_some());
+    let individual_teams = config.individual_teams || !has_groups;
+    if !has_groups && !config.individual_teams && !students.is_empty() {
+        notes.push(format!(
+            "No group data found; created an individual team for each of the {} students",
+            students.len()
+        ));
+    }
+
+    // Group students by their LMS group, keyed by slug and id
+    let mut group_map: HashMap<(String, String), Vec<&StudentInfo>> = HashMap::new();
+    let mut individual_students = Vec::new();
+    let mut partial_groups = BTreeSet::new();
+    let mut left_out_students = 0;
 
     for student in students {
         if let Some(group) = &student.group {
@@ -30,55 +68,298 @@ where
             if config.full_groups {
                 if let (Some(count), Some(max)) = (group.members_count, group.max_membership) {
                     if count < max {
+                        partial_groups.insert(group.name.as_str());
                         continue; // Skip non-full groups
                     }
                 }
             }
 
-            let group_name = group.name.clone();
+            // Keyed by id as well, so distinct groups with similar names stay separate teams
             group_map
-                .entry(group_name)
+                .entry((group.slug.clone(), group.id.clone()))
                 .or_insert_with(Vec::new)
                 .push(student);
+        } else if individual_teams {
+            individual_students.push(student);
         } else if !config.full_groups {
             // Include groupless students if not filtering for full groups
             let group_name = "no-group".to_string();
             group_map
-                .entry(group_name)
+                .entry((group_name, String::new()))
                 .or_insert_with(Vec::new)
                 .push(student);
+        } else {
+            left_out_students += 1;
         }
     }
 
-    // Generate teams
+    if !partial_groups.is_empty() {
+        notes.push(format!(
+            "Left out {} groups that are not full: {}",
+            partial_groups.len(),
+            partial_groups.into_iter().collect::<Vec<_>>().join(", ")
+        ));
+    }
+    if left_out_students > 0 {
+        notes.push(format!(
+            "Left out {} students without a group; enable individual teams to give them a team of their own",
+            left_out_students
+        ));
+    }
+
+    // Generate teams, each with a label and a stable key to tell apart equally named ones
     let mut teams = Vec::new();
-    let total_groups = group_map.len();
+    let total_groups = group_map.len() + individual_students.len();
     let mut processed_groups = 0;
-    for (group_name, group_students) in group_map {
+    for ((group_slug, group_id), group_students) in group_map {
         processed_groups += 1;
-        progress_callback(processed_groups, total_groups, &group_name);
+        let group_name = group_students[0]
+            .group
+            .as_ref()
+            .map_or(group_slug.as_str(), |g| g.name.as_str());
+        progress_callback(processed_groups, total_groups, group_name);
 
-        let team_name = generate_team_name(&group_name, group_students.as_slice(), config);
+        let team_name = match &config.team_name_template {
+            Some(template) => render_team_name_template(
+                template,
+                &group_slug,
+                &group_id,
+                config.course_code.as_deref().unwrap_or_default(),
+            ),
+            None => generate_team_name(&group_slug, group_students.as_slice(), config),
+        };
 
         let members: Vec<String> = group_students
             .iter()
-            .map(|s| format_member(s, &config.member_option))
+            .map(|s| format_member(s, config))
             .collect();
 
-        teams.push(StudentTeam {
+        let team = StudentTeam {
             name: team_name,
             members,
-        });
+            notes: None,
+        };
+        teams.push((format!("group {}", group_name), group_id, team));
+    }
+
+    for student in individual_students {
+        let team_name = individual_team_name(student);
+        processed_groups += 1;
+        progress_callback(processed_groups, total_groups, &team_name);
+
+        let team = StudentTeam {
+            name: team_name,
+            members: vec![format_member(student, config)],
+            notes: None,
+        };
+        teams.push((student.full_name.clone(), student.lms_id.clone(), team));
     }
 
+    notes.extend(unique_team_names(&mut teams));
+    let mut teams: Vec<StudentTeam> = teams.into_iter().map(|(_, _, team)| team).collect();
+
     // Sort by team name for consistency
     teams.sort_by(|a, b| a.name.cmp(&b.name));
+    notes.extend(validate_repobee_yaml(&teams));
+
+    Ok(GenerationResult { teams, notes })
+}
+
+/// Give teams that would share a name a numeric suffix, returning a note per shared name
+///
+/// Each team comes with a label for the notes and a key, the group id or the LMS id of
+/// the student. Of the teams named `team-1`, the one with the lowest key keeps the name
+/// and the others become `team-1-2`, `team-1-3`, ..., skipping names already in use. The
+/// same teams therefore always get the same names, whatever order they are in.
+fn unique_team_names(teams: &mut [(String, String, StudentTeam)]) -> Vec<String> {
+    let mut order: Vec<usize> = (0..teams.len()).collect();
+    order.sort_by(|&a, &b| {
+        let key = |i: usize| (&teams[i].2.name, teams[i].1.len(), &teams[i].1);
+        key(a).cmp(&key(b))
+    });
+
+    let runs: Vec<Vec<usize>> = order
+        .chunk_by(|&a, &b| teams[a].2.name == teams[b].2.name)
+        .filter(|run| run.len() > 1)
+        .map(<[usize]>::to_vec)
+        .collect();
+
+    let mut taken: HashSet<String> = teams.iter().map(|(_, _, t)| t.name.clone()).collect();
+    let mut notes = Vec::new();
+    for run in runs {
+        let base = teams[run[0]].2.name.clone();
+        let mut n = 1;
+        let mut renamed = Vec::new();
+        for &index in &run[1..] {
+            let name = loop {
+                n += 1;
+                let name = format!("{}-{}", base, n);
+                if taken.insert(name.clone()) {
+                    break name;
+                }
+            };
+            renamed.push(format!("{} to {}", teams[index].0, name));
+            teams[index].2.name = name;
+        }
+        notes.push(format!(
+            "{} teams would be named {}; kept it for {} and renamed {}",
+            run.len(),
+            base,
+            teams[run[0]].0,
+            renamed.join(", ")
+        ));
+    }
+    notes
+}
+
+/// Problems that would make setup fail or misbehave for these teams
+///
+/// Every team needs a name that can be used in repository names and at least one
+/// member, team names must be unique and no student may be in more than one team. An
+/// empty list means the teams are fine.
+pub fn validate_repobee_yaml(teams: &[StudentTeam]) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut team_names = HashMap::new();
+    let mut member_teams: HashMap<&str, &str> = HashMap::new();
+
+    for (index, team) in teams.iter().enumerate() {
+        let label = if team.name.is_empty() {
+            format!("Team {}", index + 1)
+        } else {
+            format!("Team {}", team.name)
+        };
+        if let Err(e) = validate_repo_name(&team.name) {
+            problems.push(format!("{}: {}", label, e));
+        }
+        let first = *team_names.entry(team.name.as_str()).or_insert(index);
+        if first != index {
+            problems.push(format!(
+                "{}: the name is also used by team {}",
+                label,
+                first + 1
+            ));
+        }
+        if team.members.is_empty() {
+            problems.push(format!("{}: has no members", label));
+        }
+        for member in &team.members {
+            if member.trim().is_empty() {
+                problems.push(format!("{}: has an empty member name", label));
+            } else if let Some(other) = member_teams.insert(member, &team.name) {
+                if other != team.name {
+                    problems.push(format!(
+                        "{}: {} is also a member of team {}",
+                        label, member, other
+                    ));
+                } else {
+                    problems.push(format!("{}: {} is listed twice", label, member));
+                }
+            }
+        }
+    }
+
+    problems
+}
+
+/// Read teams from a JSON or YAML file
+pub fn read_teams_file(path: &Path) -> Result<Vec<StudentTeam>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        PlatformError::FileError(format!(
+            "Failed to read teams file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    // Try JSON first, then YAML
+    serde_json::from_str(&content)
+        .or_else(|_| serde_yaml::from_str(&content))
+        .map_err(|e| {
+            PlatformError::Other(format!(
+                "Failed to parse teams file {} (tried JSON and YAML): {}",
+                path.display(),
+                e
+            ))
+        })
+}
+
+/// Read and merge the teams of several files, e.g. the rosters kept by different TAs
+///
+/// A team listed with the same members in more than one file is kept once. A team name
+/// with different members in different files, and a student in more than one team, are
+/// reported as conflicts; all variants of the teams are kept so nothing is silently lost.
+/// The files are read in sorted order and the teams are sorted by name, so the result
+/// does not depend on the order of `paths`.
+pub fn read_teams_files(paths: &[PathBuf]) -> Result<MergedTeams> {
+    let mut paths: Vec<&PathBuf> = paths.iter().collect();
+    paths.sort();
+    paths.dedup();
+
+    // Distinct teams with the files that list them
+    let mut sources: BTreeMap<(String, Vec<String>), (StudentTeam, BTreeSet<String>)> =
+        BTreeMap::new();
+    for path in paths {
+        let file = path.display().to_string();
+        for mut team in read_teams_file(path)? {
+            team.members.sort();
+            team.members.dedup();
+            sources
+                .entry((team.name.clone(), team.members.clone()))
+                .or_insert_with(|| (team, BTreeSet::new()))
+                .1
+                .insert(file.clone());
+        }
+    }
+
+    let files = |files: &BTreeSet<String>| files.iter().cloned().collect::<Vec<_>>().join(", ");
+    let mut conflicts = Vec::new();
+    let mut variants: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    let mut member_teams: BTreeMap<&str, BTreeMap<&str, BTreeSet<String>>> = BTreeMap::new();
+    for ((name, members), (_, in_files)) in &sources {
+        let variant = format!("{} ({})", files(in_files), members.join(", "));
+        variants.entry(name).or_default().push(variant);
+        for member in members {
+            member_teams
+                .entry(member)
+                .or_default()
+                .entry(name)
+                .or_default()
+                .extend(in_files.iter().cloned());
+        }
+    }
+    for (name, variants) in variants.into_iter().filter(|(_, v)| v.len() > 1) {
+        conflicts.push(format!(
+            "Team {} has different members in {}",
+            name,
+            variants.join(" and ")
+        ));
+    }
+    for (member, teams) in member_teams.into_iter().filter(|(_, t)| t.len() > 1) {
+        let teams: Vec<String> = teams
+            .iter()
+            .map(|(team, in_files)| format!("{} ({})", team, files(in_files)))
+            .collect();
+        conflicts.push(format!("{} is in {}", member, teams.join(" and ")));
+    }
 
-    Ok(teams)
+    let teams = sources.into_values().map(|(team, _)| team).collect();
+    Ok(MergedTeams { teams, conflicts })
+}
+
+/// Name of a single-student team: the student's git id, falling back to their LMS login
+fn individual_team_name(student: &StudentInfo) -> String {
+    let id = if !student.git_id.is_empty() {
+        &student.git_id
+    } else if !student.canvas_id.is_empty() {
+        &student.canvas_id
+    } else {
+        student.email.split('@').next().unwrap_or("")
+    };
+    sanitize_name_part(id)
 }
 
 /// Generate team name based on configuration
-fn generate_team_name(group_name: &str, students: &[&StudentInfo], config: &YamlConfig) -> String {
+fn generate_team_name(group_slug: &str, students: &[&StudentInfo], config: &YamlConfig) -> String {
     let mut parts = Vec::new();
 
     // Add "team" prefix
@@ -86,7 +367,7 @@ fn generate_team_name(group_name: &str, students: &[&StudentInfo], config: &Yaml
 
     // Add group name if configured
     if config.include_group {
-        parts.push(sanitize_name_part(group_name));
+        parts.push(group_slug.to_string());
     }
 
     // Add member names if configured
@@ -108,12 +389,114 @@ fn generate_team_name(group_name: &str, students: &[&StudentInfo], config: &Yaml
     parts.join("-")
 }
 
-/// Format a member according to the member option
-fn format_member(student: &StudentInfo, option: &MemberOption) -> String {
-    match option {
-        MemberOption::Both => format!("({}, {})", student.email, student.git_id),
+/// Placeholders supported in team name templates
+const TEAM_NAME_PLACEHOLDERS: &[&str] = &["group", "group_id", "course_code"];
+
+/// Check that a team name template only uses known placeholders
+pub fn validate_team_name_template(template: &str, course_code: Option<&str>) -> Result<()> {
+    if template.trim().is_empty() {
+        return Err(PlatformError::Other(
+            "Team name template must not be empty".to_string(),
+        ));
+    }
+
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        if rest[..start].contains('}') {
+            return Err(unmatched_brace(template));
+        }
+        let after = &rest[start + 1..];
+        let end = after.find('}').ok_or_else(|| {
+            PlatformError::Other(format!(
+                "Unclosed placeholder in team name template '{}'",
+                template
+            ))
+        })?;
+        let placeholder = &after[..end];
+        if !TEAM_NAME_PLACEHOLDERS.contains(&placeholder) {
+            return Err(PlatformError::Other(format!(
+                "Unknown placeholder '{{{}}}' in team name template (expected one of: {})",
+                placeholder,
+                TEAM_NAME_PLACEHOLDERS
+                    .iter()
+                    .map(|p| format!("{{{}}}", p))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+        if placeholder == "course_code" && course_code.is_none_or(str::is_empty) {
+            return Err(PlatformError::Other(
+                "Team name template uses {course_code} but the course has no course code"
+                    .to_string(),
+            ));
+        }
+        rest = &after[end + 1..];
+    }
+
+    if rest.contains('}') {
+        return Err(unmatched_brace(template));
+    }
+
+    Ok(())
+}
+
+fn unmatched_brace(template: &str) -> PlatformError {
+    PlatformError::Other(format!(
+        "Unmatched '}}' in team name template '{}'",
+        template
+    ))
+}
+
+/// Fill in a (validated) team name template and slugify the result
+fn render_team_name_template(
+    template: &str,
+    group_name: &str,
+    group_id: &str,
+    course_code: &str,
+) -> String {
+    let name = template
+        .replace("{group_id}", group_id)
+        .replace("{group}", group_name)
+        .replace("{course_code}", course_code);
+    slugify(&name)
+}
+
+/// Lowercase a name and collapse every run of other characters into a single `-`
+fn slugify(s: &str) -> String {
+    let mut slug = String::new();
+    for c in s.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Format a member according to the member option and member format
+pub(super) fn format_member(student: &StudentInfo, config: &YamlConfig) -> String {
+    let format = &config.member_format;
+    let member = match config.member_option {
+        MemberOption::Both => {
+            let (first, second) = match format.order {
+                MemberOrder::EmailFirst => (&student.email, &student.git_id),
+                MemberOrder::GitIdFirst => (&student.git_id, &student.email),
+            };
+            let combined = format!("{}{}{}", first, format.separator, second);
+            if format.parentheses {
+                format!("({})", combined)
+            } else {
+                combined
+            }
+        }
         MemberOption::Email => student.email.clone(),
         MemberOption::GitId => student.git_id.clone(),
+    };
+    if format.lowercase {
+        member.to_lowercase()
+    } else {
+        member
     }
 }
 
@@ -141,6 +524,53 @@ fn sanitize_name_part(s: &str) -> String {
         .collect()
 }
 
+/// Students covering every kind of team for [`preview_repobee_yaml`]
+///
+/// A full group of two, a group with one of its two places taken and a student without a
+/// group, so the effect of `full_groups` and `individual_teams` shows as well.
+pub fn sample_students() -> Vec<StudentInfo> {
+    let group = |name: &str, members_count| StudentGroup {
+        id: name.to_lowercase().replace(' ', "-"),
+        name: name.to_string(),
+        slug: group_slug(name),
+        members_count: Some(members_count),
+        max_membership: Some(2),
+    };
+    let student = |full_name: &str, git_id: &str, group: Option<StudentGroup>| {
+        let name = full_name.rsplit(' ').next().unwrap_or(full_name);
+        StudentInfo {
+            group,
+            full_name: full_name.to_string(),
+            name: name.to_string(),
+            canvas_id: git_id.to_string(),
+            git_id: git_id.to_string(),
+            email: format!("{}@student.uni.nl", git_id),
+            lms_id: git_id.to_string(),
+            sis_user_id: None,
+        }
+    };
+
+    vec![
+        student("Alice Jansen", "ajansen", Some(group("Group 1", 2))),
+        student("Bob de Vries", "bdevries", Some(group("Group 1", 2))),
+        student("Carol Bakker", "cbakker", Some(group("Group 2", 1))),
+        student("Dave Visser", "dvisser", None),
+    ]
+}
+
+/// Render the teams `students` get with `config`, without writing a file
+///
+/// Meant for a handful of students, e.g. [`sample_students`] or one fetched team.
+pub fn preview_repobee_yaml(students: &[StudentInfo], config: &YamlConfig) -> Result<YamlPreview> {
+    let generation = generate_repobee_yaml_with_progress(students, config, |_, _, _| {})?;
+    let yaml = serde_yaml::to_string(&generation.teams)
+        .map_err(|e| PlatformError::Other(format!("Failed to serialize YAML: {}", e)))?;
+    Ok(YamlPreview {
+        yaml,
+        notes: generation.notes,
+    })
+}
+
 /// Write teams to YAML file
 pub fn write_yaml_file(teams: &[StudentTeam], file_path: &Path) -> Result<()> {
     let yaml = serde_yaml::to_string(teams)
@@ -186,3 +616,556 @@ pub fn write_csv_file(students: &[StudentInfo], file_path: &Path) -> Result<()>
 
     Ok(())
 }
+
+/// Write students to an Excel workbook with the same columns as [`write_csv_file`]
+pub fn write_xlsx_file(students: &[StudentInfo], file_path: &Path) -> Result<()> {
+    use rust_xlsxwriter::{Format, Workbook};
+
+    let xlsx_error = |e: rust_xlsxwriter::XlsxError| {
+        PlatformError::Other(format!("Failed to write Excel file: {}", e))
+    };
+
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+
+    let bold = Format::new().set_bold();
+    for (col, header) in ["Group", "FullName", "Name", "ID", "GitID", "Mail"]
+        .into_iter()
+        .enumerate()
+    {
+        worksheet
+            .write_string_with_format(0, col as u16, header, &bold)
+            .map_err(xlsx_error)?;
+    }
+
+    for (index, student) in students.iter().enumerate() {
+        let row = index as u32 + 1;
+        let group_name = student
+            .group
+            .as_ref()
+            .map(|g| g.name.as_str())
+            .unwrap_or_default();
+        let cells: [&str; 6] = [
+            group_name,
+            &student.full_name,
+            &student.name,
+            &student.canvas_id,
+            &student.git_id,
+            &student.email,
+        ];
+        for (col, value) in cells.into_iter().enumerate() {
+            worksheet
+                .write_string(row, col as u16, value)
+                .map_err(xlsx_error)?;
+        }
+    }
+
+    workbook.save(file_path).map_err(xlsx_error)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn student(git_id: &str, group: Option<&str>) -> StudentInfo {
+        StudentInfo {
+            group: group.map(|name| StudentGroup {
+                id: name.to_string(),
+                name: name.to_string(),
+                slug: group_slug(name),
+                members_count: None,
+                max_membership: None,
+            }),
+            full_name: format!("{} Student", git_id),
+            name: git_id.to_string(),
+            canvas_id: git_id.to_string(),
+            git_id: git_id.to_string(),
+            email: format!("{}@uni.nl", git_id),
+            lms_id: git_id.to_string(),
+            sis_user_id: None,
+        }
+    }
+
+    fn config() -> YamlConfig {
+        YamlConfig {
+            member_option: MemberOption::GitId,
+            include_group: true,
+            include_member: false,
+            include_initials: false,
+            full_groups: true,
+            grouping: GroupingSource::Groups,
+            individual_teams: false,
+            team_name_template: None,
+            course_code: None,
+            member_format: MemberFormat::default(),
+        }
+    }
+
+    #[test]
+    fn test_no_groups_falls_back_to_individual_teams() {
+        let students = vec![student("alice", None), student("bob", None)];
+
+        let result =
+            generate_repobee_yaml_with_progress(&students, &config(), |_, _, _| {}).unwrap();
+
+        assert_eq!(result.teams.len(), 2);
+        assert_eq!(result.teams[0].name, "alice");
+        assert_eq!(result.teams[0].members, vec!["alice".to_string()]);
+        assert_eq!(result.teams[1].name, "bob");
+        assert_eq!(result.notes.len(), 1);
+    }
+
+    #[test]
+    fn test_individual_teams_alongside_groups() {
+        let students = vec![
+            student("alice", Some("Group 1")),
+            student("bob", Some("Group 1")),
+            student("carol", None),
+        ];
+        let mut config = config();
+        config.individual_teams = true;
+
+        let result = generate_repobee_yaml_with_progress(&students, &config, |_, _, _| {}).unwrap();
+
+        assert_eq!(result.teams.len(), 2);
+        assert_eq!(result.teams[0].name, "carol");
+        assert_eq!(result.teams[1].name, "team-group-1");
+        assert_eq!(result.teams[1].members.len(), 2);
+        assert!(result.notes.is_empty());
+    }
+
+    #[test]
+    fn test_ungrouped_students_dropped_with_full_groups() {
+        let students = vec![student("alice", Some("Group 1")), student("carol", None)];
+
+        let teams = generate_repobee_yaml(&students, &config()).unwrap();
+
+        assert_eq!(teams.len(), 1);
+        assert_eq!(teams[0].name, "team-group-1");
+    }
+
+    fn sized_student(git_id: &str, group: &str, members_count: u64, max: u64) -> StudentInfo {
+        let mut student = student(git_id, Some(group));
+        if let Some(group) = &mut student.group {
+            group.members_count = Some(members_count);
+            group.max_membership = Some(max);
+        }
+        student
+    }
+
+    #[test]
+    fn test_full_groups_team_shapes() {
+        let students = vec![
+            sized_student("alice", "Group 1", 3, 3),
+            sized_student("bob", "Group 1", 3, 3),
+            sized_student("carol", "Group 1", 3, 3),
+            sized_student("dave", "Group 2", 1, 1),
+            student("erin", None),
+        ];
+
+        let result =
+            generate_repobee_yaml_with_progress(&students, &config(), |_, _, _| {}).unwrap();
+
+        assert_eq!(
+            result.teams,
+            vec![
+                StudentTeam {
+                    name: "team-group-1".to_string(),
+                    members: vec!["alice".to_string(), "bob".to_string(), "carol".to_string()],
+                    notes: None,
+                },
+                StudentTeam {
+                    name: "team-group-2".to_string(),
+                    members: vec!["dave".to_string()],
+                    notes: None,
+                },
+            ]
+        );
+        assert_eq!(result.notes.len(), 1);
+        assert!(result.notes[0].contains("Left out 1 students without a group"));
+
+        let mut config = config();
+        config.individual_teams = true;
+        let result = generate_repobee_yaml_with_progress(&students, &config, |_, _, _| {}).unwrap();
+        assert_eq!(result.teams.len(), 3);
+        assert_eq!(result.teams[0].name, "erin");
+        assert_eq!(result.teams[0].members, vec!["erin".to_string()]);
+        assert!(result.notes.is_empty());
+    }
+
+    #[test]
+    fn test_partial_groups_are_reported() {
+        let students = vec![
+            sized_student("alice", "Group 1", 1, 3),
+            sized_student("bob", "Group 2", 2, 2),
+        ];
+
+        let result =
+            generate_repobee_yaml_with_progress(&students, &config(), |_, _, _| {}).unwrap();
+
+        assert_eq!(result.teams.len(), 1);
+        assert_eq!(result.teams[0].name, "team-group-2");
+        assert_eq!(
+            result.notes,
+            vec!["Left out 1 groups that are not full: Group 1".to_string()]
+        );
+    }
+
+    fn member_with(format: MemberFormat) -> String {
+        let mut config = config();
+        config.member_option = MemberOption::Both;
+        config.member_format = format;
+        let teams = generate_repobee_yaml(&[student("Alice", Some("Group 1"))], &config).unwrap();
+        serde_yaml::to_string(&teams[0].members).unwrap()
+    }
+
+    #[test]
+    fn test_default_member_format() {
+        assert_eq!(
+            member_with(MemberFormat::default()),
+            "- (Alice@uni.nl, Alice)\n"
+        );
+    }
+
+    #[test]
+    fn test_member_format_options() {
+        let format = MemberFormat {
+            separator: " ".to_string(),
+            order: MemberOrder::GitIdFirst,
+            parentheses: false,
+            lowercase: false,
+        };
+        assert_eq!(member_with(format.clone()), "- Alice Alice@uni.nl\n");
+
+        let format = MemberFormat {
+            separator: ";".to_string(),
+            ..format
+        };
+        assert_eq!(member_with(format.clone()), "- Alice;Alice@uni.nl\n");
+
+        let format = MemberFormat {
+            parentheses: true,
+            lowercase: true,
+            ..format
+        };
+        assert_eq!(member_with(format), "- (alice;alice@uni.nl)\n");
+    }
+
+    #[test]
+    fn test_member_format_deserializes_with_defaults() {
+        let format: MemberFormat = serde_json::from_str(r#"{"order": "git_id_first"}"#).unwrap();
+        assert_eq!(format.order, MemberOrder::GitIdFirst);
+        assert_eq!(format.separator, ", ");
+        assert!(format.parentheses);
+    }
+
+    #[test]
+    fn test_team_name_template() {
+        let students = vec![
+            student("alice", Some("Group 1")),
+            student("bob", Some("Group 1")),
+        ];
+        let mut config = config();
+        config.team_name_template = Some("{course_code}-Ex1_{group}".to_string());
+        config.course_code = Some("TDA 357".to_string());
+
+        let teams = generate_repobee_yaml(&students, &config).unwrap();
+
+        assert_eq!(teams.len(), 1);
+        assert_eq!(teams[0].name, "tda-357-ex1-group-1");
+    }
+
+    #[test]
+    fn test_team_name_template_rejects_unknown_placeholder() {
+        assert!(validate_team_name_template("ex1-{group}", None).is_ok());
+        assert!(validate_team_name_template("ex1-{team}", None).is_err());
+        assert!(validate_team_name_template("ex1-{group", None).is_err());
+        assert!(validate_team_name_template("ex1}-{group}", None).is_err());
+        assert!(validate_team_name_template("{course_code}-{group}", None).is_err());
+        assert!(validate_team_name_template("{course_code}-{group}", Some("TDA357")).is_ok());
+    }
+
+    #[test]
+    fn test_group_names_are_slugged_for_teams() {
+        let students = vec![
+            student("alice", Some("Team #1 (Lab)")),
+            student("bob", Some("Team #1 (Lab)")),
+        ];
+
+        let teams = generate_repobee_yaml(&students, &config()).unwrap();
+
+        assert_eq!(teams.len(), 1);
+        assert_eq!(teams[0].name, "team-team-1-lab");
+    }
+
+    #[test]
+    fn test_colliding_group_slugs_stay_separate_teams() {
+        let mut groups = vec![
+            StudentGroup {
+                id: "12".to_string(),
+                name: "team-1".to_string(),
+                slug: String::new(),
+                members_count: None,
+                max_membership: None,
+            },
+            StudentGroup {
+                id: "9".to_string(),
+                name: "Team 1".to_string(),
+                slug: String::new(),
+                members_count: None,
+                max_membership: None,
+            },
+        ];
+        unique_group_slugs(&mut groups);
+        // Numbered by id, not by the order the groups were fetched in
+        assert_eq!(groups[1].slug, "team-1");
+        assert_eq!(groups[0].slug, "team-1-2");
+        assert_eq!(groups[1].name, "Team 1");
+
+        let mut alice = student("alice", None);
+        alice.group = Some(groups[0].clone());
+        let mut bob = student("bob", None);
+        bob.group = Some(groups[1].clone());
+        let teams = generate_repobee_yaml(&[alice, bob], &config()).unwrap();
+
+        let names: Vec<&str> = teams.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["team-team-1", "team-team-1-2"]);
+    }
+
+    #[test]
+    fn test_colliding_team_names_are_disambiguated() {
+        // Same name in two group sets, and a name differing only in case and whitespace
+        let in_group = |git_id: &str, id: &str, name: &str| {
+            let mut s = student(git_id, Some(name));
+            s.group.as_mut().unwrap().id = id.to_string();
+            s
+        };
+        let students = vec![
+            in_group("alice", "31", "Group 1"),
+            in_group("bob", "7", "Group 1"),
+            in_group("carol", "12", "group  1 "),
+        ];
+
+        let result =
+            generate_repobee_yaml_with_progress(&students, &config(), |_, _, _| {}).unwrap();
+        let teams: Vec<(&str, &str)> = result
+            .teams
+            .iter()
+            .map(|t| (t.name.as_str(), t.members[0].as_str()))
+            .collect();
+        assert_eq!(
+            teams,
+            vec![
+                ("team-group-1", "bob"),
+                ("team-group-1-2", "carol"),
+                ("team-group-1-3", "alice"),
+            ]
+        );
+        assert_eq!(
+            result.notes,
+            vec![
+                "3 teams would be named team-group-1; kept it for group Group 1 and renamed \
+                 group group  1  to team-group-1-2, group Group 1 to team-group-1-3"
+            ]
+        );
+
+        // The same groups get the same names in any order
+        let reversed: Vec<StudentInfo> = students.into_iter().rev().collect();
+        let again = generate_repobee_yaml(&reversed, &config()).unwrap();
+        assert_eq!(again, result.teams);
+    }
+
+    #[test]
+    fn test_validate_repobee_yaml() {
+        let team = |name: &str, members: &[&str]| StudentTeam {
+            name: name.to_string(),
+            members: members.iter().map(|m| m.to_string()).collect(),
+            notes: None,
+        };
+        assert!(validate_repobee_yaml(&[team("team-1", &["alice", "bob"])]).is_empty());
+
+        let problems = validate_repobee_yaml(&[
+            team("team-1", &["alice", "bob"]),
+            team("team-1", &["carol"]),
+            team("team 3", &["alice"]),
+            team("team-4", &[]),
+            team("team-5", &["dave", "dave", ""]),
+        ]);
+        assert_eq!(
+            problems,
+            vec![
+                "Team team-1: the name is also used by team 1",
+                "Team team 3: Invalid repository name 'team 3': it contains ' '",
+                "Team team 3: alice is also a member of team team-1",
+                "Team team-4: has no members",
+                "Team team-5: dave is listed twice",
+                "Team team-5: has an empty member name",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_teams_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let yaml = dir.path().join("students.yaml");
+        std::fs::write(&yaml, "- name: team-1\n  members: [alice, bob]\n").unwrap();
+        let json = dir.path().join("students.json");
+        std::fs::write(
+            &json,
+            r#"[{"name": "team-1", "members": ["alice", "bob"]}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_teams_file(&yaml).unwrap(),
+            read_teams_file(&json).unwrap()
+        );
+        assert_eq!(
+            read_teams_file(&yaml).unwrap()[0].members,
+            vec!["alice", "bob"]
+        );
+
+        std::fs::write(&yaml, "- name: team-1\n  members: alice\n").unwrap();
+        assert!(read_teams_file(&yaml).is_err());
+        assert!(read_teams_file(&dir.path().join("missing.yaml")).is_err());
+    }
+
+    #[test]
+    fn test_read_teams_files_merges_and_reports_conflicts() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ta1 = dir.path().join("ta1.yaml");
+        std::fs::write(
+            &ta1,
+            "- name: team-1\n  members: [bob, alice]\n- name: team-2\n  members: [carol]\n",
+        )
+        .unwrap();
+        let ta2 = dir.path().join("ta2.json");
+        std::fs::write(
+            &ta2,
+            r#"[{"name": "team-2", "members": ["carol"]}, {"name": "team-3", "members": ["bob", "dave"]}]"#,
+        )
+        .unwrap();
+
+        let merged = read_teams_files(&[ta1.clone(), ta2.clone()]).unwrap();
+        let team = |name: &str, members: &[&str]| {
+            StudentTeam::with_name(
+                name.to_string(),
+                members.iter().map(|m| m.to_string()).collect(),
+            )
+        };
+        // The team both files list identically is kept once
+        assert_eq!(
+            merged.teams,
+            vec![
+                team("team-1", &["alice", "bob"]),
+                team("team-2", &["carol"]),
+                team("team-3", &["bob", "dave"]),
+            ]
+        );
+        assert_eq!(
+            merged.conflicts,
+            vec![format!(
+                "bob is in team-1 ({}) and team-3 ({})",
+                ta1.display(),
+                ta2.display()
+            )]
+        );
+
+        // The order of the files does not matter
+        let reversed = read_teams_files(&[ta2.clone(), ta1.clone()]).unwrap();
+        assert_eq!(reversed.teams, merged.teams);
+        assert_eq!(reversed.conflicts, merged.conflicts);
+
+        std::fs::write(
+            &ta2,
+            r#"[{"name": "team-1", "members": ["alice", "erin"]}]"#,
+        )
+        .unwrap();
+        let merged = read_teams_files(&[ta1.clone(), ta2.clone()]).unwrap();
+        assert_eq!(
+            merged.conflicts[0],
+            format!(
+                "Team team-1 has different members in {} (alice, bob) and {} (alice, erin)",
+                ta1.display(),
+                ta2.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_team_notes_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("students.yaml");
+        let mut team = StudentTeam::with_name("team-1".to_string(), vec!["alice".to_string()]);
+        team.notes = Some("merged after drop".to_string());
+        let teams = vec![
+            team,
+            StudentTeam::with_name("team-2".to_string(), vec!["bob".to_string()]),
+        ];
+
+        write_yaml_file(&teams, &path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.matches("notes:").count(), 1);
+        assert_eq!(read_teams_file(&path).unwrap(), teams);
+
+        let json = serde_json::to_string(&teams).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<StudentTeam>>(&json).unwrap(),
+            teams
+        );
+    }
+
+    #[test]
+    fn test_preview_of_sample_students() {
+        let preview = preview_repobee_yaml(&sample_students(), &config()).unwrap();
+        let teams: Vec<StudentTeam> = serde_yaml::from_str(&preview.yaml).unwrap();
+        assert_eq!(teams.len(), 1);
+        assert_eq!(teams[0].name, "team-group-1");
+        assert_eq!(teams[0].members, vec!["ajansen", "bdevries"]);
+        assert!(!preview.notes.is_empty());
+
+        let mut config = config();
+        config.member_option = MemberOption::Email;
+        config.full_groups = false;
+        config.individual_teams = true;
+        let preview = preview_repobee_yaml(&sample_students(), &config).unwrap();
+        let teams: Vec<StudentTeam> = serde_yaml::from_str(&preview.yaml).unwrap();
+        assert_eq!(teams.len(), 3);
+        assert!(preview.yaml.contains("cbakker@student.uni.nl"));
+    }
+
+    #[test]
+    fn test_write_xlsx_file() {
+        use calamine::{open_workbook, Reader, Xlsx};
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("students.xlsx");
+        let students = vec![student("alice", Some("Group 1")), student("bob", None)];
+
+        write_xlsx_file(&students, &path).unwrap();
+
+        let mut workbook: Xlsx<_> = open_workbook(&path).unwrap();
+        let range = workbook.worksheet_range("Sheet1").unwrap();
+        let rows: Vec<Vec<String>> = range
+            .rows()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["Group", "FullName", "Name", "ID", "GitID", "Mail"],
+                vec![
+                    "Group 1",
+                    "alice Student",
+                    "alice",
+                    "alice",
+                    "alice",
+                    "alice@uni.nl"
+                ],
+                vec!["", "bob Student", "bob", "bob", "bob", "bob@uni.nl"],
+            ]
+        );
+    }
+}
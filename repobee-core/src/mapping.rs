@@ -0,0 +1,272 @@
+//! Student-to-repository mapping for gradebooks
+//!
+//! After setup, [`write_mapping_csv`] writes one row per student and repository, linking
+//! the student's git username to their team, repository and repository URL.
+
+use crate::error::{PlatformError, Result};
+use crate::setup::SetupResult;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Column of the mapping CSV
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MappingColumn {
+    GitId,
+    Team,
+    Assignment,
+    Repo,
+    Url,
+    /// `created` or `existing`
+    Status,
+}
+
+/// Columns written when none are configured
+pub const DEFAULT_MAPPING_COLUMNS: [MappingColumn; 4] = [
+    MappingColumn::GitId,
+    MappingColumn::Team,
+    MappingColumn::Repo,
+    MappingColumn::Url,
+];
+
+impl MappingColumn {
+    pub const ALL: [MappingColumn; 6] = [
+        MappingColumn::GitId,
+        MappingColumn::Team,
+        MappingColumn::Assignment,
+        MappingColumn::Repo,
+        MappingColumn::Url,
+        MappingColumn::Status,
+    ];
+
+    /// Header and configuration name of the column
+    pub fn name(self) -> &'static str {
+        match self {
+            MappingColumn::GitId => "git_id",
+            MappingColumn::Team => "team",
+            MappingColumn::Assignment => "assignment",
+            MappingColumn::Repo => "repo",
+            MappingColumn::Url => "url",
+            MappingColumn::Status => "status",
+        }
+    }
+
+    /// Parse a comma-separated column list, e.g. `git_id,team,url`
+    pub fn parse_list(s: &str) -> std::result::Result<Vec<Self>, String> {
+        let columns = s
+            .split(',')
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(str::parse)
+            .collect::<std::result::Result<Vec<Self>, _>>()?;
+        if columns.is_empty() {
+            return Err("At least one mapping column is required".to_string());
+        }
+        Ok(columns)
+    }
+}
+
+impl FromStr for MappingColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        MappingColumn::ALL
+            .into_iter()
+            .find(|c| c.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown mapping column: {} (expected one of: {})",
+                    s,
+                    MappingColumn::ALL.map(|c| c.name()).join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for MappingColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// One student's membership of one student repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappingRow {
+    pub git_id: String,
+    pub team: String,
+    pub assignment: String,
+    pub repo: String,
+    pub url: String,
+    pub status: &'static str,
+}
+
+impl MappingRow {
+    fn value(&self, column: MappingColumn) -> &str {
+        match column {
+            MappingColumn::GitId => &self.git_id,
+            MappingColumn::Team => &self.team,
+            MappingColumn::Assignment => &self.assignment,
+            MappingColumn::Repo => &self.repo,
+            MappingColumn::Url => &self.url,
+            MappingColumn::Status => self.status,
+        }
+    }
+}
+
+/// Rows of the mapping, one per team member of every created or existing repository
+///
+/// Rows are sorted by git username and repository name.
+pub fn mapping_rows(result: &SetupResult) -> Vec<MappingRow> {
+    let assignment_of = |repo_name: &str| {
+        result
+            .by_assignment
+            .iter()
+            .find(|(_, repos)| {
+                repos.created.iter().any(|r| r == repo_name)
+                    || repos.existing.iter().any(|r| r == repo_name)
+            })
+            .map(|(assignment, _)| assignment.clone())
+            .unwrap_or_default()
+    };
+
+    let repos = result
+        .successful_repos
+        .iter()
+        .map(|repo| (repo, "created"))
+        .chain(result.existing_repos.iter().map(|repo| (repo, "existing")));
+    let mut rows: Vec<MappingRow> = repos
+        .flat_map(|(repo, status)| {
+            let assignment = assignment_of(&repo.name);
+            repo.team.members.iter().map(move |member| MappingRow {
+                git_id: member.clone(),
+                team: repo.team.name.clone(),
+                assignment: assignment.clone(),
+                repo: repo.name.clone(),
+                url: repo.url.clone(),
+                status,
+            })
+        })
+        .collect();
+    rows.sort_by(|a, b| (&a.git_id, &a.repo).cmp(&(&b.git_id, &b.repo)));
+    rows
+}
+
+/// Write the student-to-repository mapping of a setup as CSV
+pub fn write_mapping_csv(
+    result: &SetupResult,
+    columns: &[MappingColumn],
+    file_path: &Path,
+) -> Result<()> {
+    let mut csv = String::new();
+    csv.push_str(&csv_line(columns.iter().map(|c| c.name())));
+    for row in mapping_rows(result) {
+        csv.push_str(&csv_line(columns.iter().map(|c| row.value(*c))));
+    }
+
+    std::fs::write(file_path, csv)
+        .map_err(|e| PlatformError::Other(format!("Failed to write mapping CSV: {}", e)))
+}
+
+pub(crate) fn csv_line<'a>(fields: impl Iterator<Item = &'a str>) -> String {
+    let mut line = fields.map(csv_field).collect::<Vec<_>>().join(",");
+    line.push('\n');
+    line
+}
+
+/// Quote a field if it contains a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::AssignmentRepos;
+    use crate::types::{StudentRepo, StudentTeam};
+    use tempfile::TempDir;
+
+    fn setup_result() -> SetupResult {
+        let team = StudentTeam::with_name(
+            "team-1".to_string(),
+            vec!["bob".to_string(), "alice".to_string()],
+        );
+        let mut result = SetupResult::new();
+        result.successful_repos.push(StudentRepo::new(
+            "team-1-task1".to_string(),
+            team.clone(),
+            "https://git.uni.nl/course/team-1-task1".to_string(),
+        ));
+        result.existing_repos.push(StudentRepo::new(
+            "team-1-task0".to_string(),
+            team,
+            "https://git.uni.nl/course/team-1-task0".to_string(),
+        ));
+        result.by_assignment.insert(
+            "task1".to_string(),
+            AssignmentRepos {
+                created: vec!["team-1-task1".to_string()],
+                existing: Vec::new(),
+            },
+        );
+        result.by_assignment.insert(
+            "task0".to_string(),
+            AssignmentRepos {
+                created: Vec::new(),
+                existing: vec!["team-1-task0".to_string()],
+            },
+        );
+        result
+    }
+
+    #[test]
+    fn test_mapping_rows() {
+        let rows = mapping_rows(&setup_result());
+        let summary: Vec<(&str, &str, &str)> = rows
+            .iter()
+            .map(|r| (r.git_id.as_str(), r.assignment.as_str(), r.status))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("alice", "task0", "existing"),
+                ("alice", "task1", "created"),
+                ("bob", "task0", "existing"),
+                ("bob", "task1", "created"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_mapping_csv() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("mapping.csv");
+
+        write_mapping_csv(&setup_result(), &DEFAULT_MAPPING_COLUMNS, &path).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "git_id,team,repo,url");
+        assert_eq!(
+            lines[1],
+            "alice,team-1,team-1-task0,https://git.uni.nl/course/team-1-task0"
+        );
+        assert_eq!(lines.len(), 5);
+
+        let columns = MappingColumn::parse_list("repo, git_id").unwrap();
+        write_mapping_csv(&setup_result(), &columns, &path).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        assert!(csv.starts_with("repo,git_id\nteam-1-task0,alice\n"));
+    }
+
+    #[test]
+    fn test_parse_mapping_columns() {
+        assert!(MappingColumn::parse_list("git_id,grade").is_err());
+        assert!(MappingColumn::parse_list(" , ").is_err());
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,200 @@
+//! Student repository names
+//!
+//! Every operation that refers to a student repository by name derives it with
+//! [`repo_name`], so setup and the operations after it always agree on the name.
+
+use crate::error::{PlatformError, Result};
+
+/// Template used when no naming scheme is configured
+pub const DEFAULT_REPO_NAME_TEMPLATE: &str = "{team}-{assignment}";
+
+/// Longest repository name accepted by all supported platforms
+const MAX_REPO_NAME_LENGTH: usize = 100;
+
+/// Naming scheme of student repositories
+///
+/// The template contains the placeholders `{team}` and `{assignment}`, e.g.
+/// `{team}-{assignment}` or `2025-{assignment}_{team}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoNaming {
+    template: String,
+}
+
+impl Default for RepoNaming {
+    fn default() -> Self {
+        Self {
+            template: DEFAULT_REPO_NAME_TEMPLATE.to_string(),
+        }
+    }
+}
+
+impl RepoNaming {
+    /// Naming scheme from a template with `{team}` and `{assignment}` placeholders
+    pub fn new(template: impl Into<String>) -> Result<Self> {
+        let template = template.into();
+        let mut rest = template.as_str();
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else {
+                return Err(invalid_template(&template, "unclosed '{'"));
+            };
+            let placeholder = &rest[start..start + len + 1];
+            if placeholder != "{team}" && placeholder != "{assignment}" {
+                return Err(invalid_template(
+                    &template,
+                    &format!("unknown placeholder {}", placeholder),
+                ));
+            }
+            rest = &rest[start + len + 1..];
+        }
+        for required in ["{team}", "{assignment}"] {
+            if !template.contains(required) {
+                return Err(invalid_template(
+                    &template,
+                    &format!("{} is required to keep names unique", required),
+                ));
+            }
+        }
+        Ok(Self { template })
+    }
+
+    /// Naming scheme `{prefix}{team}{separator}{assignment}{suffix}`
+    pub fn from_parts(prefix: &str, separator: &str, suffix: &str) -> Result<Self> {
+        Self::new(format!(
+            "{}{{team}}{}{{assignment}}{}",
+            prefix, separator, suffix
+        ))
+    }
+
+    /// The same scheme with `prefix` and `suffix` around every name
+    ///
+    /// Used to label throwaway repositories, e.g. `test-` for a trial run on a real
+    /// organization, so they never collide with the real ones and are easy to find later.
+    pub fn with_affixes(&self, prefix: &str, suffix: &str) -> Result<Self> {
+        Self::new(format!("{}{}{}", prefix, self.template, suffix))
+    }
+
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// Name of the repository of a team for an assignment, see [`repo_name`]
+    pub fn repo_name(&self, team: &str, assignment: &str) -> Result<String> {
+        repo_name(team, assignment, self)
+    }
+}
+
+/// Name of the repository of a team for an assignment
+///
+/// Fails when the name is not valid on every supported platform.
+pub fn repo_name(team: &str, assignment: &str, naming: &RepoNaming) -> Result<String> {
+    let name = naming
+        .template
+        .replace("{team}", team)
+        .replace("{assignment}", assignment);
+    validate_repo_name(&name)?;
+    Ok(name)
+}
+
+/// Check that a repository name is accepted by GitHub, GitLab and Gitea
+///
+/// Allowed are ASCII letters, digits, `.`, `_` and `-`, not starting with `.` or `-` and
+/// not ending with `.` or `.git`.
+pub fn validate_repo_name(name: &str) -> Result<()> {
+    let problem = if name.is_empty() {
+        Some("it is empty".to_string())
+    } else if name.len() > MAX_REPO_NAME_LENGTH {
+        Some(format!(
+            "it is longer than {} characters",
+            MAX_REPO_NAME_LENGTH
+        ))
+    } else if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')))
+    {
+        Some(format!("it contains '{}'", c))
+    } else if name.starts_with(['.', '-']) {
+        Some("it starts with '.' or '-'".to_string())
+    } else if name.ends_with('.') || name.ends_with(".git") {
+        Some("it ends with '.' or '.git'".to_string())
+    } else {
+        None
+    };
+
+    match problem {
+        Some(problem) => Err(PlatformError::Other(format!(
+            "Invalid repository name '{}': {}",
+            name, problem
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn invalid_template(template: &str, problem: &str) -> PlatformError {
+    PlatformError::Other(format!(
+        "Invalid repository name template '{}': {}",
+        template, problem
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_repo_name() {
+        let naming = RepoNaming::default();
+        assert_eq!(naming.repo_name("team-1", "task").unwrap(), "team-1-task");
+    }
+
+    #[test]
+    fn test_repo_name_templates() {
+        let cases = [
+            ("{assignment}-{team}", "task-team-1"),
+            ("2025-{team}_{assignment}", "2025-team-1_task"),
+            ("ip.{assignment}.{team}.v2", "ip.task.team-1.v2"),
+        ];
+        for (template, expected) in cases {
+            let naming = RepoNaming::new(template).unwrap();
+            assert_eq!(naming.repo_name("team-1", "task").unwrap(), expected);
+        }
+
+        let naming = RepoNaming::from_parts("ip-", "--", "-2025").unwrap();
+        assert_eq!(naming.template(), "ip-{team}--{assignment}-2025");
+    }
+
+    #[test]
+    fn test_affixes_apply_to_any_template() {
+        let naming = RepoNaming::new("{assignment}_{team}")
+            .unwrap()
+            .with_affixes("test-", "-tmp")
+            .unwrap();
+        assert_eq!(naming.template(), "test-{assignment}_{team}-tmp");
+        assert_eq!(
+            naming.repo_name("team-1", "task").unwrap(),
+            "test-task_team-1-tmp"
+        );
+
+        let naming = RepoNaming::default().with_affixes("", "").unwrap();
+        assert_eq!(naming, RepoNaming::default());
+        assert!(RepoNaming::default().with_affixes("{year}-", "").is_err());
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        assert!(RepoNaming::new("{team}").is_err());
+        assert!(RepoNaming::new("{team}-{assignment}-{year}").is_err());
+        assert!(RepoNaming::new("{team}-{assignment").is_err());
+    }
+
+    #[test]
+    fn test_generated_names_are_validated() {
+        let naming = RepoNaming::new("{team} {assignment}").unwrap();
+        assert!(naming.repo_name("team-1", "task").is_err());
+
+        let naming = RepoNaming::default();
+        assert!(naming.repo_name("team-1", "task.git").is_err());
+        assert!(naming.repo_name("-team", "task").is_err());
+        assert!(naming.repo_name("ß", "task").is_err());
+        assert!(naming.repo_name(&"a".repeat(100), "task").is_err());
+    }
+}
@@ -0,0 +1,280 @@
+//! Per-team notification data for telling students where their repositories are
+//!
+//! [`team_notifications`] combines a [`SetupResult`] with the students' email addresses
+//! into one record per team, which [`write_notifications`] writes as CSV or JSON for a
+//! mail merge. Sending the emails is left to the teacher's mail client.
+
+use crate::error::{PlatformError, Result};
+use crate::mapping::csv_line;
+use crate::setup::SetupResult;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Separator of list values in a CSV field, as accepted by mail clients
+const LIST_SEPARATOR: &str = "; ";
+
+/// Output format of the notification records
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NotificationFormat {
+    /// One row per team, list values separated by `;`
+    #[default]
+    Csv,
+    /// An array of [`TeamNotification`] records
+    Json,
+}
+
+impl FromStr for NotificationFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            other => Err(format!(
+                "Unknown notification format: {} (expected csv or json)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for NotificationFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Csv => write!(f, "csv"),
+            Self::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// A student repository in a notification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NotifiedRepo {
+    pub name: String,
+    pub url: String,
+}
+
+/// Everything needed to tell one team where its repositories are
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TeamNotification {
+    pub team: String,
+    /// Git usernames of the members
+    pub members: Vec<String>,
+    /// Email addresses of the members whose address is known
+    pub emails: Vec<String>,
+    /// Members without a known email address
+    pub missing_emails: Vec<String>,
+    pub repos: Vec<NotifiedRepo>,
+}
+
+/// One record per team with a created or existing repository, sorted by team name
+///
+/// `emails` maps git usernames to email addresses, e.g. from [`read_student_emails`].
+pub fn team_notifications(
+    result: &SetupResult,
+    emails: &HashMap<String, String>,
+) -> Vec<TeamNotification> {
+    let mut by_team: BTreeMap<&str, TeamNotification> = BTreeMap::new();
+    for repo in result.successful_repos.iter().chain(&result.existing_repos) {
+        let record = by_team.entry(&repo.team.name).or_insert_with(|| {
+            let (known, missing): (Vec<&String>, Vec<&String>) = repo
+                .team
+                .members
+                .iter()
+                .partition(|member| emails.contains_key(*member));
+            TeamNotification {
+                team: repo.team.name.clone(),
+                members: repo.team.members.clone(),
+                emails: known.into_iter().map(|m| emails[m].clone()).collect(),
+                missing_emails: missing.into_iter().cloned().collect(),
+                repos: Vec::new(),
+            }
+        });
+        record.repos.push(NotifiedRepo {
+            name: repo.name.clone(),
+            url: repo.url.clone(),
+        });
+    }
+
+    let mut records: Vec<TeamNotification> = by_team.into_values().collect();
+    for record in &mut records {
+        record.repos.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    records
+}
+
+/// Read git usernames and email addresses from a student CSV written by
+/// [`write_csv_file`](crate::lms::write_csv_file)
+///
+/// Students without an email address are left out.
+pub fn read_student_emails(file_path: &Path) -> Result<HashMap<String, String>> {
+    let content = std::fs::read_to_string(file_path).map_err(|e| {
+        PlatformError::FileError(format!(
+            "Failed to read student CSV {}: {}",
+            file_path.display(),
+            e
+        ))
+    })?;
+
+    let mut lines = content.lines();
+    let header: Vec<&str> = lines.next().unwrap_or_default().split(',').collect();
+    let column = |name: &str| {
+        header.iter().position(|h| h.trim() == name).ok_or_else(|| {
+            PlatformError::FileError(format!(
+                "Student CSV {} has no {} column",
+                file_path.display(),
+                name
+            ))
+        })
+    };
+    let git_id_column = column("GitID")?;
+    let mail_column = column("Mail")?;
+
+    Ok(lines
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let git_id = fields.get(git_id_column).filter(|g| !g.is_empty())?;
+            let email = fields.get(mail_column).filter(|e| !e.is_empty())?;
+            Some((git_id.to_string(), email.to_string()))
+        })
+        .collect())
+}
+
+/// Write notification records for a mail merge
+pub fn write_notifications(
+    records: &[TeamNotification],
+    format: NotificationFormat,
+    file_path: &Path,
+) -> Result<()> {
+    let content = match format {
+        NotificationFormat::Csv => {
+            let mut csv = csv_line(["team", "emails", "members", "repos", "urls"].into_iter());
+            for record in records {
+                let emails = record.emails.join(LIST_SEPARATOR);
+                let members = record.members.join(LIST_SEPARATOR);
+                let names: Vec<&str> = record.repos.iter().map(|r| r.name.as_str()).collect();
+                let urls: Vec<&str> = record.repos.iter().map(|r| r.url.as_str()).collect();
+                let (names, urls) = (names.join(LIST_SEPARATOR), urls.join(LIST_SEPARATOR));
+                csv.push_str(&csv_line(
+                    [
+                        record.team.as_str(),
+                        emails.as_str(),
+                        members.as_str(),
+                        names.as_str(),
+                        urls.as_str(),
+                    ]
+                    .into_iter(),
+                ));
+            }
+            csv
+        }
+        NotificationFormat::Json => serde_json::to_string_pretty(records).map_err(|e| {
+            PlatformError::Other(format!("Failed to serialize notifications: {}", e))
+        })?,
+    };
+
+    std::fs::write(file_path, content)
+        .map_err(|e| PlatformError::Other(format!("Failed to write notifications: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{StudentRepo, StudentTeam};
+    use tempfile::TempDir;
+
+    fn setup_result() -> SetupResult {
+        let team = StudentTeam::with_name(
+            "team-1".to_string(),
+            vec!["bob".to_string(), "alice".to_string()],
+        );
+        let solo = StudentTeam::with_name("carol".to_string(), vec!["carol".to_string()]);
+        let mut result = SetupResult::new();
+        result.successful_repos.push(StudentRepo::new(
+            "team-1-task2".to_string(),
+            team.clone(),
+            "https://git.uni.nl/course/team-1-task2".to_string(),
+        ));
+        result.successful_repos.push(StudentRepo::new(
+            "carol-task2".to_string(),
+            solo,
+            "https://git.uni.nl/course/carol-task2".to_string(),
+        ));
+        result.existing_repos.push(StudentRepo::new(
+            "team-1-task1".to_string(),
+            team,
+            "https://git.uni.nl/course/team-1-task1".to_string(),
+        ));
+        result
+    }
+
+    fn emails() -> HashMap<String, String> {
+        HashMap::from([
+            ("alice".to_string(), "alice@uni.nl".to_string()),
+            ("bob".to_string(), "bob@uni.nl".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_team_notifications() {
+        let records = team_notifications(&setup_result(), &emails());
+        assert_eq!(records.len(), 2);
+
+        assert_eq!(records[0].team, "carol");
+        assert!(records[0].emails.is_empty());
+        assert_eq!(records[0].missing_emails, vec!["carol"]);
+
+        assert_eq!(records[1].team, "team-1");
+        assert_eq!(records[1].emails, vec!["alice@uni.nl", "bob@uni.nl"]);
+        let repos: Vec<&str> = records[1].repos.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(repos, vec!["team-1-task1", "team-1-task2"]);
+    }
+
+    #[test]
+    fn test_write_notifications() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("notify.csv");
+        let records = team_notifications(&setup_result(), &emails());
+
+        write_notifications(&records, NotificationFormat::Csv, &path).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "team,emails,members,repos,urls");
+        assert_eq!(
+            lines[2],
+            "team-1,alice@uni.nl; bob@uni.nl,alice; bob,team-1-task1; team-1-task2,\
+             https://git.uni.nl/course/team-1-task1; https://git.uni.nl/course/team-1-task2"
+        );
+
+        write_notifications(&records, NotificationFormat::Json, &path).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            json[1]["repos"][0]["url"],
+            "https://git.uni.nl/course/team-1-task1"
+        );
+    }
+
+    #[test]
+    fn test_read_student_emails() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("students.csv");
+        std::fs::write(
+            &path,
+            "Group,FullName,Name,ID,GitID,Mail\n\
+             Group 1,Alice Doe,Doe,adoe,alice,alice@uni.nl\n\
+             Group 1,Bob Roe,Roe,broe,bob,\n",
+        )
+        .unwrap();
+
+        let emails = read_student_emails(&path).unwrap();
+        assert_eq!(emails.len(), 1);
+        assert_eq!(emails["alice"], "alice@uni.nl");
+
+        std::fs::write(&path, "GitID,Email\nalice,alice@uni.nl\n").unwrap();
+        assert!(read_student_emails(&path).is_err());
+    }
+}
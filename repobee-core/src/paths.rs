@@ -0,0 +1,367 @@
+//! Local directories for cloned repositories
+//!
+//! Windows refuses device names such as `con` or `aux` as file names, and by default limits
+//! directory paths to 248 characters. Directories for clones are built with [`work_path`],
+//! which renames reserved names and reports paths that are too long before git fails on
+//! them with a less helpful error.
+//!
+//! The work directory and target folder themselves are resolved with [`resolve_dir`], so a
+//! relative path never depends on where the GUI happened to be started from. Files written
+//! from LMS data get their path from [`resolve_output_file`] in the same way.
+
+use crate::error::{PlatformError, Result};
+use std::path::{Component, Path, PathBuf};
+
+/// Longest directory path Windows accepts without the `\\?\` prefix
+///
+/// `MAX_PATH` (260) minus room for an 8.3 file name.
+const WINDOWS_MAX_DIR_PATH: usize = 248;
+
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// System directories that never hold student repositories, checked with their subdirectories
+const SYSTEM_DIRS: &[&str] = if cfg!(windows) {
+    &[
+        r"C:\Windows",
+        r"C:\Program Files",
+        r"C:\Program Files (x86)",
+        r"C:\ProgramData",
+    ]
+} else {
+    &[
+        "/bin",
+        "/boot",
+        "/dev",
+        "/etc",
+        "/lib",
+        "/lib64",
+        "/proc",
+        "/sbin",
+        "/sys",
+        "/usr",
+        "/System",
+        "/Library",
+        "/Applications",
+    ]
+};
+
+/// Number of unrelated entries named in a warning
+const MAX_LISTED_ENTRIES: usize = 5;
+
+/// A work directory or target folder resolved to an absolute path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedDir {
+    pub path: PathBuf,
+    /// Problems that do not prevent using the directory
+    pub warnings: Vec<String>,
+}
+
+/// Default work directory for template clones, in the user's cache directory
+///
+/// Falls back to the system temporary directory when there is no cache directory.
+pub fn default_work_dir() -> PathBuf {
+    directories::ProjectDirs::from("", "", "repobee-tauri")
+        .map(|dirs| dirs.cache_dir().join("work"))
+        .unwrap_or_else(|| std::env::temp_dir().join("repobee-work"))
+}
+
+/// Resolve a work directory or target folder to an absolute path
+///
+/// A leading `~` is the home directory, other relative paths are relative to the current
+/// directory, and `.` and `..` are resolved without touching the file system. Filesystem
+/// roots and system directories such as `/usr` or `C:\Windows` are refused. When the
+/// directory already exists and contains entries other than `expected` (and hidden ones),
+/// a warning lists them.
+pub fn resolve_dir(path: &Path, expected: &[&str]) -> Result<ResolvedDir> {
+    if path.as_os_str().is_empty() {
+        return Err(PlatformError::FileError(
+            "No directory specified".to_string(),
+        ));
+    }
+    let resolved = absolute_path(path)?;
+
+    if resolved.parent().is_none() {
+        return Err(PlatformError::FileError(format!(
+            "Refusing to use the filesystem root {} for repositories",
+            resolved.display()
+        )));
+    }
+    if let Some(system_dir) = SYSTEM_DIRS.iter().find(|dir| resolved.starts_with(dir)) {
+        return Err(PlatformError::FileError(format!(
+            "Refusing to use {} for repositories, it is inside the system directory {}",
+            resolved.display(),
+            system_dir
+        )));
+    }
+
+    let mut warnings = Vec::new();
+    let unrelated = unrelated_entries(&resolved, expected);
+    if !unrelated.is_empty() {
+        let mut listed = unrelated
+            .iter()
+            .take(MAX_LISTED_ENTRIES)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if unrelated.len() > MAX_LISTED_ENTRIES {
+            listed.push_str(", ...");
+        }
+        warnings.push(format!(
+            "{} already contains {} unrelated entries: {}",
+            resolved.display(),
+            unrelated.len(),
+            listed
+        ));
+    }
+
+    Ok(ResolvedDir {
+        path: resolved,
+        warnings,
+    })
+}
+
+/// Resolve the path of a file written from LMS data, creating its folder when missing
+///
+/// An absolute `file`, e.g. one picked in a save dialog, is used as it is; a relative one
+/// is placed in `folder`. Both may start with `~` and may contain spaces or non-ASCII
+/// characters. Surrounding whitespace and quotes, as left by pasting a path, are removed.
+pub fn resolve_output_file(folder: &str, file: &str) -> Result<PathBuf> {
+    let unquote = |s: &str| s.trim().trim_matches(['"', '\'']).trim().to_string();
+    let file = PathBuf::from(unquote(file));
+    if file.as_os_str().is_empty() {
+        return Err(PlatformError::FileError(
+            "No file name specified".to_string(),
+        ));
+    }
+    let file = expand_home(&file)?;
+    let path = if file.is_absolute() {
+        file
+    } else {
+        expand_home(Path::new(&unquote(folder)))?.join(file)
+    };
+    let resolved = absolute_path(&path)?;
+
+    if let Some(parent) = resolved.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            PlatformError::FileError(format!("Cannot create folder {}: {}", parent.display(), e))
+        })?;
+    }
+    Ok(resolved)
+}
+
+/// Replace a leading `~` with the home directory
+fn expand_home(path: &Path) -> Result<PathBuf> {
+    match path.strip_prefix("~") {
+        Ok(rest) => Ok(dirs::home_dir()
+            .ok_or_else(|| PlatformError::FileError("Could not find home directory".to_string()))?
+            .join(rest)),
+        Err(_) => Ok(path.to_path_buf()),
+    }
+}
+
+/// Absolute form of `path` with `~` expanded and `.` and `..` removed
+fn absolute_path(path: &Path) -> Result<PathBuf> {
+    let absolute = std::path::absolute(expand_home(path)?).map_err(|e| {
+        PlatformError::FileError(format!("Cannot resolve {}: {}", path.display(), e))
+    })?;
+    Ok(normalize_lexically(&absolute))
+}
+
+/// Remove `.` and `..` components without following symbolic links
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Names of the visible entries of `dir` that are not in `expected`, sorted
+fn unrelated_entries(dir: &Path, expected: &[&str]) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut unrelated: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| !name.starts_with('.'))
+        .filter(|name| {
+            !expected
+                .iter()
+                .any(|e| *e == name.as_str() || sanitize_dir_name(e) == *name)
+        })
+        .collect();
+    unrelated.sort();
+    unrelated
+}
+
+/// Directory for a clone below `base`, safe to use on the current platform
+///
+/// On Windows, reserved names are sanitized with [`sanitize_dir_name`] and paths over the
+/// length limit are an error, unless `long_paths` is set, in which case the `\\?\` prefix
+/// is added. Elsewhere the components are joined as they are.
+pub fn work_path(base: &Path, components: &[&str], long_paths: bool) -> Result<PathBuf> {
+    if cfg!(windows) {
+        windows_work_path(base, components, long_paths)
+    } else {
+        Ok(components
+            .iter()
+            .fold(base.to_path_buf(), |path, c| path.join(c)))
+    }
+}
+
+/// Whether Windows reserves the name, e.g. `aux` or `com1.txt`
+pub fn is_windows_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// Make a directory name usable on Windows
+///
+/// Reserved names get a `_` appended (`aux` becomes `aux_`) and trailing dots and spaces,
+/// which Windows silently drops, are removed.
+pub fn sanitize_dir_name(name: &str) -> String {
+    let trimmed = name.trim_end_matches(['.', ' ']);
+    let trimmed = if trimmed.is_empty() { "_" } else { trimmed };
+    if is_windows_reserved_name(trimmed) {
+        match trimmed.split_once('.') {
+            Some((stem, extension)) => format!("{}_.{}", stem, extension),
+            None => format!("{}_", trimmed),
+        }
+    } else {
+        trimmed.to_string()
+    }
+}
+
+fn windows_work_path(base: &Path, components: &[&str], long_paths: bool) -> Result<PathBuf> {
+    let path = components.iter().fold(base.to_path_buf(), |path, c| {
+        path.join(sanitize_dir_name(c))
+    });
+
+    let absolute = std::path::absolute(&path).unwrap_or_else(|_| path.clone());
+    let length = absolute.as_os_str().len();
+    if length <= WINDOWS_MAX_DIR_PATH {
+        return Ok(path);
+    }
+    if long_paths {
+        return Ok(PathBuf::from(format!(r"\\?\{}", absolute.display())));
+    }
+    Err(PlatformError::FileError(format!(
+        "Path {} is {} characters long, over the Windows limit of {}. \
+         Choose a shorter target folder, e.g. C:\\repobee, or enable long paths.",
+        absolute.display(),
+        length,
+        WINDOWS_MAX_DIR_PATH
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_windows_reserved_name() {
+        assert!(is_windows_reserved_name("aux"));
+        assert!(is_windows_reserved_name("CON"));
+        assert!(is_windows_reserved_name("com1.txt"));
+        assert!(!is_windows_reserved_name("auxiliary"));
+        assert!(!is_windows_reserved_name("team-con"));
+    }
+
+    #[test]
+    fn test_sanitize_dir_name() {
+        assert_eq!(sanitize_dir_name("aux"), "aux_");
+        assert_eq!(sanitize_dir_name("Nul.tar"), "Nul_.tar");
+        assert_eq!(sanitize_dir_name("task1. "), "task1");
+        assert_eq!(sanitize_dir_name("team-1-task"), "team-1-task");
+    }
+
+    #[test]
+    fn test_resolve_dir() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path();
+        std::fs::create_dir(base.join("task1")).unwrap();
+        std::fs::create_dir(base.join(".cache")).unwrap();
+        std::fs::write(base.join("notes.txt"), "").unwrap();
+
+        let resolved = resolve_dir(&base.join("sub").join(".."), &["task1"]).unwrap();
+        assert_eq!(
+            resolved.path,
+            normalize_lexically(&std::path::absolute(base).unwrap())
+        );
+        assert_eq!(resolved.warnings.len(), 1);
+        assert!(resolved.warnings[0].contains("1 unrelated entries: notes.txt"));
+
+        let resolved = resolve_dir(&base.join("new"), &[]).unwrap();
+        assert!(resolved.path.is_absolute());
+        assert!(resolved.warnings.is_empty());
+
+        let resolved = resolve_dir(Path::new("repobee-work"), &[]).unwrap();
+        assert!(resolved.path.is_absolute());
+    }
+
+    #[test]
+    fn test_resolve_dir_refuses_system_dirs() {
+        assert!(resolve_dir(Path::new(""), &[]).is_err());
+        for dir in SYSTEM_DIRS {
+            let error = resolve_dir(&Path::new(dir).join("work"), &[]).unwrap_err();
+            assert!(error.to_string().contains("system directory"));
+        }
+        let root = std::path::absolute("/").unwrap();
+        assert!(resolve_dir(&root, &[]).is_err());
+        assert!(resolve_dir(&root.join("home").join("..").join(".."), &[]).is_err());
+    }
+
+    #[test]
+    fn test_resolve_output_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let folder = temp_dir.path().join("Cursus Ünïcode 2024");
+        let folder_str = folder.to_str().unwrap();
+
+        // A relative file goes into the folder, which is created
+        let path = resolve_output_file(folder_str, "students.yaml").unwrap();
+        assert_eq!(path, folder.join("students.yaml"));
+        assert!(folder.is_dir());
+
+        // A chosen absolute file ignores the folder
+        let chosen = temp_dir.path().join("gekozen map").join("lijst.csv");
+        let path = resolve_output_file(folder_str, chosen.to_str().unwrap()).unwrap();
+        assert_eq!(path, chosen);
+        assert!(chosen.parent().unwrap().is_dir());
+
+        // Pasted paths keep working with quotes and surrounding whitespace
+        let quoted = format!("  \"{}\" ", folder_str);
+        let path = resolve_output_file(&quoted, " 'info.csv'").unwrap();
+        assert_eq!(path, folder.join("info.csv"));
+
+        let path = resolve_output_file("", "students.yaml").unwrap();
+        assert!(path.is_absolute());
+
+        assert!(resolve_output_file(folder_str, "  ").is_err());
+    }
+
+    #[test]
+    fn test_windows_work_path() {
+        let base = Path::new("work");
+        let path = windows_work_path(base, &["con", "task"], false).unwrap();
+        assert_eq!(path, base.join("con_").join("task"));
+
+        let long = "a".repeat(WINDOWS_MAX_DIR_PATH);
+        let error = windows_work_path(base, &[long.as_str()], false).unwrap_err();
+        assert!(error.to_string().contains("Choose a shorter target folder"));
+
+        let path = windows_work_path(base, &[long.as_str()], true).unwrap();
+        assert!(path.to_string_lossy().starts_with(r"\\?\"));
+    }
+}
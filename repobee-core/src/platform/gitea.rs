@@ -1,8 +1,91 @@
 //! Gitea platform implementation
 
 use crate::error::{PlatformError, Result};
-use crate::platform::PlatformAPI;
-use crate::types::{Issue, IssueState, Repo, Team, TeamPermission};
+use crate::http::http_client;
+use crate::platform::paging::{paged, PageStyle, PagedStream};
+use crate::platform::{repo_path_from_url, PlatformAPI};
+use crate::types::{Issue, IssueState, Repo, RepoFile, RepoFileKind, Team, TeamPermission};
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Page size of Gitea list endpoints
+const PAGE_LIMIT: usize = 50;
+
+/// Repository units a student team gets access to
+const TEAM_UNITS: [&str; 4] = ["repo.code", "repo.issues", "repo.pulls", "repo.releases"];
+
+#[derive(Debug, Deserialize)]
+struct GiteaRepo {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    private: bool,
+    #[serde(default)]
+    html_url: String,
+    #[serde(default)]
+    empty: bool,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    updated_at: Option<DateTime<Utc>>,
+}
+
+/// Entry of the Gitea contents API
+#[derive(Debug, Deserialize)]
+struct GiteaContent {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaTeam {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    permission: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaUser {
+    login: String,
+}
+
+/// Body of team creation and team updates
+///
+/// Gitea sets permissions per team rather than per repository. Older servers read
+/// `permission` and `units`, newer ones the per-unit `units_map`.
+#[derive(Debug, Serialize)]
+struct TeamRequest {
+    name: String,
+    description: String,
+    permission: String,
+    units: Vec<String>,
+    units_map: HashMap<String, String>,
+    includes_all_repositories: bool,
+    can_create_org_repo: bool,
+}
+
+impl TeamRequest {
+    fn new(name: &str, permission: TeamPermission) -> Self {
+        let access = permission.to_gitea_str();
+        Self {
+            name: name.to_string(),
+            description: format!("Team {}", name),
+            permission: access.to_string(),
+            units: TEAM_UNITS.iter().map(|u| u.to_string()).collect(),
+            units_map: TEAM_UNITS
+                .iter()
+                .map(|u| (u.to_string(), access.to_string()))
+                .collect(),
+            includes_all_repositories: false,
+            can_create_org_repo: false,
+        }
+    }
+}
 
 /// Gitea API client
 #[derive(Debug)]
@@ -17,9 +100,7 @@ pub struct GiteaAPI {
 impl GiteaAPI {
     /// Create a new Gitea API client
     pub fn new(base_url: String, token: String, org_name: String, user: String) -> Result<Self> {
-        let client = reqwest::Client::builder()
-            .user_agent("repobee-rust/0.1.0")
-            .build()?;
+        let client = http_client()?;
 
         Ok(Self {
             base_url,
@@ -29,56 +110,289 @@ impl GiteaAPI {
             client,
         })
     }
+
+    /// Send an authenticated request against the Gitea API, failing on error statuses
+    async fn send<B: Serialize>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<reqwest::Response> {
+        self.send_url(method, &self.api_url(path), body).await
+    }
+
+    /// Full URL of an API path
+    fn api_url(&self, path: &str) -> String {
+        format!("{}/api/v1{}", self.base_url.trim_end_matches('/'), path)
+    }
+
+    /// Send an authenticated request to a full API URL, failing on error statuses
+    async fn send_url<B: Serialize>(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        body: Option<&B>,
+    ) -> Result<reqwest::Response> {
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("Authorization", format!("token {}", self.token));
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request.send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(match status.as_u16() {
+                404 => PlatformError::not_found(format!("Resource not found: {}", text)),
+                401 | 403 => {
+                    PlatformError::bad_credentials(format!("Authentication failed: {}", text))
+                }
+                code => PlatformError::unexpected(format!("HTTP {}: {}", code, text)),
+            });
+        }
+        Ok(response)
+    }
+
+    /// Parse the JSON body of a successful response
+    async fn json<T: serde::de::DeserializeOwned>(response: reqwest::Response) -> Result<T> {
+        response
+            .json()
+            .await
+            .map_err(|e| PlatformError::unexpected(format!("JSON parse error: {}", e)))
+    }
+
+    /// Make an authenticated GET request against the Gitea API
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let response = self.send::<()>(reqwest::Method::GET, path, None).await?;
+        Self::json(response).await
+    }
+
+    /// Make an authenticated GET request for every page of a list endpoint
+    async fn get_all<T>(&self, path: &str) -> Result<Vec<T>>
+    where
+        T: serde::de::DeserializeOwned + Send,
+    {
+        self.get_paged(path).try_collect().await
+    }
+
+    /// Make authenticated GET requests for every page of a list endpoint, as a stream
+    ///
+    /// Gitea has no `Link` header to follow; pages are counted until one comes back short.
+    fn get_paged<'a, T>(&'a self, path: &str) -> PagedStream<'a, T>
+    where
+        T: serde::de::DeserializeOwned + Send + 'a,
+    {
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let first = format!("{}{}limit={}&page=1", path, separator, PAGE_LIMIT);
+        let style = PageStyle::PageNumber {
+            per_page: PAGE_LIMIT,
+        };
+        paged(self.api_url(&first), style, move |url| async move {
+            let response = self
+                .send_url::<()>(reqwest::Method::GET, &url, None)
+                .await?;
+            let headers = response.headers().clone();
+            let items = Self::json(response).await?;
+            Ok((items, headers))
+        })
+    }
+
+    /// Stream all repositories of the organization, one page at a time
+    pub fn repo_stream(&self) -> PagedStream<'_, Repo> {
+        let repos = self.get_paged::<GiteaRepo>(&format!("/orgs/{}/repos", self.org_name));
+        Box::pin(
+            repos
+                .map_ok(|repo| Repo::new(repo.name, repo.description, repo.private, repo.html_url)),
+        )
+    }
+
+    /// All teams of the organization
+    async fn list_teams(&self) -> Result<Vec<GiteaTeam>> {
+        self.get_all(&format!("/orgs/{}/teams", self.org_name))
+            .await
+    }
+
+    /// Get team by name
+    async fn get_team_by_name(&self, team_name: &str) -> Result<Option<GiteaTeam>> {
+        Ok(self
+            .list_teams()
+            .await?
+            .into_iter()
+            .find(|t| t.name == team_name))
+    }
+
+    /// Get team by name, failing if it does not exist
+    async fn require_team(&self, team_name: &str) -> Result<GiteaTeam> {
+        self.get_team_by_name(team_name)
+            .await?
+            .ok_or_else(|| PlatformError::not_found(format!("Team '{}' not found", team_name)))
+    }
+
+    /// Get team members
+    async fn get_team_members(&self, team_id: u64) -> Result<Vec<String>> {
+        let members: Vec<GiteaUser> = self.get_all(&format!("/teams/{}/members", team_id)).await?;
+        Ok(members.into_iter().map(|m| m.login).collect())
+    }
+
+    /// Add members to a team, skipping those already in it
+    async fn add_team_members(
+        &self,
+        team_id: u64,
+        current: &[String],
+        members: &[String],
+    ) -> Result<()> {
+        for member in members {
+            if current.iter().any(|m| m.eq_ignore_ascii_case(member)) {
+                continue;
+            }
+            self.send::<()>(
+                reqwest::Method::PUT,
+                &format!("/teams/{}/members/{}", team_id, member),
+                None,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Give a team the access level of `permission` if it has a different one
+    async fn ensure_team_permission(
+        &self,
+        team: &GiteaTeam,
+        permission: TeamPermission,
+    ) -> Result<()> {
+        if team.permission == permission.to_gitea_str() {
+            return Ok(());
+        }
+        let request = TeamRequest::new(&team.name, permission);
+        self.send(
+            reqwest::Method::PATCH,
+            &format!("/teams/{}", team.id),
+            Some(&request),
+        )
+        .await?;
+        Ok(())
+    }
 }
 
 impl PlatformAPI for GiteaAPI {
     async fn create_team(
         &self,
-        _name: &str,
-        _members: Option<&[String]>,
-        _permission: TeamPermission,
+        name: &str,
+        members: Option<&[String]>,
+        permission: TeamPermission,
     ) -> Result<Team> {
-        Err(PlatformError::Other(
-            "Gitea implementation not yet implemented".to_string(),
-        ))
+        let members = members.unwrap_or_default();
+
+        // An existing team keeps its id; only missing members are added
+        if let Some(existing_team) = self.get_team_by_name(name).await? {
+            self.ensure_team_permission(&existing_team, permission)
+                .await?;
+            let mut current = self.get_team_members(existing_team.id).await?;
+            self.add_team_members(existing_team.id, &current, members)
+                .await?;
+            for member in members {
+                if !current.iter().any(|m| m.eq_ignore_ascii_case(member)) {
+                    current.push(member.clone());
+                }
+            }
+            return Ok(Team::new(
+                existing_team.name,
+                current,
+                existing_team.id.to_string(),
+            ));
+        }
+
+        let request = TeamRequest::new(name, permission);
+        let response = self
+            .send(
+                reqwest::Method::POST,
+                &format!("/orgs/{}/teams", self.org_name),
+                Some(&request),
+            )
+            .await?;
+        let team: GiteaTeam = Self::json(response).await?;
+        self.add_team_members(team.id, &[], members).await?;
+
+        Ok(Team::new(team.name, members.to_vec(), team.id.to_string()))
     }
 
-    async fn delete_team(&self, _team: &Team) -> Result<()> {
-        Err(PlatformError::Other(
-            "Gitea implementation not yet implemented".to_string(),
-        ))
+    async fn delete_team(&self, team: &Team) -> Result<()> {
+        let team_obj = self.require_team(&team.name).await?;
+        self.send::<()>(
+            reqwest::Method::DELETE,
+            &format!("/teams/{}", team_obj.id),
+            None,
+        )
+        .await?;
+        Ok(())
     }
 
-    async fn get_teams(&self, _team_names: Option<&[String]>) -> Result<Vec<Team>> {
-        Err(PlatformError::Other(
-            "Gitea implementation not yet implemented".to_string(),
-        ))
+    async fn get_teams(&self, team_names: Option<&[String]>) -> Result<Vec<Team>> {
+        let mut result_teams = Vec::new();
+        for team in self.list_teams().await? {
+            if let Some(names) = team_names {
+                if !names.contains(&team.name) {
+                    continue;
+                }
+            }
+
+            let members = self.get_team_members(team.id).await?;
+            result_teams.push(Team::new(team.name, members, team.id.to_string()));
+        }
+        Ok(result_teams)
     }
 
     async fn assign_repo(
         &self,
-        _team: &Team,
-        _repo: &Repo,
-        _permission: TeamPermission,
+        team: &Team,
+        repo: &Repo,
+        permission: TeamPermission,
     ) -> Result<()> {
-        Err(PlatformError::Other(
-            "Gitea implementation not yet implemented".to_string(),
-        ))
+        let team_obj = self.require_team(&team.name).await?;
+        self.ensure_team_permission(&team_obj, permission).await?;
+        self.send::<()>(
+            reqwest::Method::PUT,
+            &format!(
+                "/teams/{}/repos/{}/{}",
+                team_obj.id, self.org_name, repo.name
+            ),
+            None,
+        )
+        .await?;
+        Ok(())
     }
 
     async fn assign_members(
         &self,
-        _team: &Team,
-        _members: &[String],
+        team: &Team,
+        members: &[String],
         _permission: TeamPermission,
     ) -> Result<()> {
+        // Gitea grants access per team, so members get the permission of their team
+        let team_obj = self.require_team(&team.name).await?;
+        let current = self.get_team_members(team_obj.id).await?;
+        self.add_team_members(team_obj.id, &current, members).await
+    }
+
+    async fn create_repo(
+        &self,
+        _name: &str,
+        _description: &str,
+        _private: bool,
+        _team: Option<&Team>,
+    ) -> Result<Repo> {
         Err(PlatformError::Other(
             "Gitea implementation not yet implemented".to_string(),
         ))
     }
 
-    async fn create_repo(
+    async fn create_repo_from_template(
         &self,
+        _template_url: &str,
         _name: &str,
         _description: &str,
         _private: bool,
@@ -89,30 +403,109 @@ impl PlatformAPI for GiteaAPI {
         ))
     }
 
-    async fn delete_repo(&self, _repo: &Repo) -> Result<()> {
+    async fn delete_repo(&self, _name: &str) -> Result<()> {
         Err(PlatformError::Other(
             "Gitea implementation not yet implemented".to_string(),
         ))
     }
 
-    async fn get_repos(&self, _repo_urls: Option<&[String]>) -> Result<Vec<Repo>> {
+    async fn get_repos(&self, repo_urls: Option<&[String]>) -> Result<Vec<Repo>> {
+        self.repo_stream()
+            .try_filter(|repo| {
+                std::future::ready(repo_urls.is_none_or(|urls| urls.contains(&repo.url)))
+            })
+            .try_collect()
+            .await
+    }
+
+    async fn get_repo(&self, _repo_name: &str, _team_name: Option<&str>) -> Result<Repo> {
         Err(PlatformError::Other(
             "Gitea implementation not yet implemented".to_string(),
         ))
     }
 
-    async fn get_repo(&self, _repo_name: &str, _team_name: Option<&str>) -> Result<Repo> {
+    async fn repo_exists(&self, _name: &str) -> Result<bool> {
         Err(PlatformError::Other(
             "Gitea implementation not yet implemented".to_string(),
         ))
     }
 
+    async fn is_archived(&self, repo_url: &str) -> Result<bool> {
+        let path = repo_path_from_url(&self.base_url, repo_url)?;
+        let repo: GiteaRepo = self.get(&format!("/repos/{}", path)).await?;
+        Ok(repo.archived)
+    }
+
+    /// Repositories are not created on Gitea yet, so setup always clones and pushes
+    async fn is_template(&self, _repo_url: &str) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// The last update of an empty repository is its creation, so it reports `None`
+    async fn repo_last_activity(&self, name: &str) -> Result<Option<DateTime<Utc>>> {
+        let repo: GiteaRepo = self
+            .get(&format!("/repos/{}/{}", self.org_name, name))
+            .await?;
+        Ok(repo.updated_at.filter(|_| !repo.empty))
+    }
+
+    async fn user_exists(&self, username: &str) -> Result<bool> {
+        match self.get::<GiteaUser>(&format!("/users/{}", username)).await {
+            Ok(_) => Ok(true),
+            Err(PlatformError::NotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
     async fn get_team_repos(&self, _team: &Team) -> Result<Vec<Repo>> {
         Err(PlatformError::Other(
             "Gitea implementation not yet implemented".to_string(),
         ))
     }
 
+    async fn list_repo_files(&self, repo: &Repo, git_ref: Option<&str>) -> Result<Vec<RepoFile>> {
+        // The contents API fails on repositories without commits
+        let info: GiteaRepo = self
+            .get(&format!("/repos/{}/{}", self.org_name, repo.name))
+            .await?;
+        if info.empty {
+            return Ok(Vec::new());
+        }
+
+        let mut path = format!("/repos/{}/{}/contents", self.org_name, repo.name);
+        if let Some(git_ref) = git_ref {
+            path.push_str(&format!("?ref={}", git_ref));
+        }
+        let contents: Vec<GiteaContent> = self.get(&path).await?;
+
+        let mut files: Vec<RepoFile> = contents
+            .into_iter()
+            .map(|c| {
+                let kind = match c.kind.as_str() {
+                    "dir" => RepoFileKind::Dir,
+                    "symlink" => RepoFileKind::Symlink,
+                    "submodule" => RepoFileKind::Submodule,
+                    _ => RepoFileKind::File,
+                };
+                RepoFile::new(c.name, kind)
+            })
+            .collect();
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(files)
+    }
+
+    async fn set_ci_variable(
+        &self,
+        _repo: &Repo,
+        _key: &str,
+        _value: &str,
+        _masked: bool,
+    ) -> Result<()> {
+        Err(PlatformError::Other(
+            "Gitea implementation not yet implemented".to_string(),
+        ))
+    }
+
     fn get_repo_urls(
         &self,
         _assignment_names: &[String],
@@ -188,4 +581,188 @@ impl PlatformAPI for GiteaAPI {
     fn base_url(&self) -> &str {
         &self.base_url
     }
+
+    fn repo_size_limit(&self) -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::http::{MockServer, Response};
+
+    /// Serve one canned `(status, body)` response per connection
+    fn serve(responses: Vec<(u16, &str)>) -> MockServer {
+        MockServer::sequence(
+            responses
+                .into_iter()
+                .map(|(status, body)| {
+                    Response::status(status)
+                        .header("Content-Type", "application/json")
+                        .body(body)
+                })
+                .collect(),
+        )
+    }
+
+    fn api(server: &MockServer) -> GiteaAPI {
+        GiteaAPI::new(
+            server.url().to_string(),
+            "token".to_string(),
+            "course".to_string(),
+            "teacher".to_string(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_repo_stream_follows_pages() {
+        let repo = |i: usize| {
+            format!(r#"{{"name": "task-{i}", "html_url": "https://gitea.io/course/task-{i}"}}"#)
+        };
+        let full_page = format!(
+            "[{}]",
+            (0..PAGE_LIMIT).map(repo).collect::<Vec<_>>().join(",")
+        );
+        let last_page = format!("[{}]", repo(PAGE_LIMIT));
+        let server = serve(vec![(200, &full_page), (200, &last_page)]);
+
+        let repos: Vec<Repo> = api(&server).repo_stream().try_collect().await.unwrap();
+
+        assert_eq!(repos.len(), PAGE_LIMIT + 1);
+        assert_eq!(repos[PAGE_LIMIT].name, format!("task-{}", PAGE_LIMIT));
+        assert_eq!(
+            server.request_lines(),
+            vec![
+                "GET /api/v1/orgs/course/repos?limit=50&page=1",
+                "GET /api/v1/orgs/course/repos?limit=50&page=2",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_team() {
+        let server = serve(vec![
+            (200, "[]"),
+            (201, r#"{"id": 7, "name": "team-1", "permission": "write"}"#),
+            (204, ""),
+            (204, ""),
+        ]);
+
+        let members = vec!["alice".to_string(), "bob".to_string()];
+        let team = api(&server)
+            .create_team("team-1", Some(&members), TeamPermission::Push)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            team,
+            Team::new("team-1".to_string(), members, "7".to_string())
+        );
+        assert_eq!(
+            server.request_lines(),
+            vec![
+                "GET /api/v1/orgs/course/teams?limit=50&page=1",
+                "POST /api/v1/orgs/course/teams",
+                "PUT /api/v1/teams/7/members/alice",
+                "PUT /api/v1/teams/7/members/bob",
+            ]
+        );
+
+        let body: serde_json::Value = serde_json::from_str(&server.requests()[1].body).unwrap();
+        assert_eq!(body["name"], "team-1");
+        assert_eq!(body["permission"], "write");
+        assert_eq!(body["units_map"]["repo.code"], "write");
+        assert_eq!(body["includes_all_repositories"], false);
+    }
+
+    #[tokio::test]
+    async fn test_create_existing_team_updates_membership() {
+        let server = serve(vec![
+            (
+                200,
+                r#"[{"id": 7, "name": "team-1", "permission": "write"}]"#,
+            ),
+            (200, r#"[{"login": "alice"}]"#),
+            (204, ""),
+        ]);
+
+        let members = vec!["alice".to_string(), "bob".to_string()];
+        let team = api(&server)
+            .create_team("team-1", Some(&members), TeamPermission::Push)
+            .await
+            .unwrap();
+
+        assert_eq!(team.id, "7");
+        assert_eq!(team.members, members);
+        assert_eq!(
+            server.request_lines(),
+            vec![
+                "GET /api/v1/orgs/course/teams?limit=50&page=1",
+                "GET /api/v1/teams/7/members?limit=50&page=1",
+                "PUT /api/v1/teams/7/members/bob",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_assign_repo_with_other_permission() {
+        let server = serve(vec![
+            (
+                200,
+                r#"[{"id": 7, "name": "team-1", "permission": "write"}]"#,
+            ),
+            (200, r#"{"id": 7, "name": "team-1", "permission": "read"}"#),
+            (204, ""),
+        ]);
+
+        let team = Team::new("team-1".to_string(), Vec::new(), "7".to_string());
+        let repo = Repo::new(
+            "team-1-task1".to_string(),
+            String::new(),
+            true,
+            String::new(),
+        );
+        api(&server)
+            .assign_repo(&team, &repo, TeamPermission::Pull)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            server.request_lines(),
+            vec![
+                "GET /api/v1/orgs/course/teams?limit=50&page=1",
+                "PATCH /api/v1/teams/7",
+                "PUT /api/v1/teams/7/repos/course/team-1-task1",
+            ]
+        );
+        let body: serde_json::Value = serde_json::from_str(&server.requests()[1].body).unwrap();
+        assert_eq!(body["permission"], "read");
+        assert_eq!(body["units_map"]["repo.pulls"], "read");
+    }
+
+    #[tokio::test]
+    async fn test_delete_missing_team() {
+        let server = serve(vec![(200, "[]")]);
+
+        let team = Team::new("team-1".to_string(), Vec::new(), String::new());
+        let result = api(&server).delete_team(&team).await;
+        assert!(matches!(result, Err(PlatformError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_is_archived_looks_up_other_organization() {
+        let server = serve(vec![(
+            200,
+            r#"{"name": "task1", "html_url": "", "archived": true}"#,
+        )]);
+
+        let url = format!("{}/templates/task1.git", server.url());
+        assert!(api(&server).is_archived(&url).await.unwrap());
+        assert_eq!(
+            server.request_lines(),
+            vec!["GET /api/v1/repos/templates/task1"]
+        );
+    }
 }
@@ -1,8 +1,14 @@
 //! GitHub platform implementation using REST API
 
 use crate::error::{PlatformError, Result};
-use crate::platform::PlatformAPI;
-use crate::types::{Issue, IssueState, Repo, Team, TeamPermission};
+use crate::http::http_client;
+use crate::naming::{repo_name, RepoNaming};
+use crate::platform::paging::{paged, PageStyle, PagedStream};
+use crate::platform::token::{missing_scopes_error, missing_token_scopes, GitPlatformKind};
+use crate::platform::{repo_path_from_url, PlatformAPI};
+use crate::types::{Issue, IssueState, Repo, RepoFile, RepoFileKind, Team, TeamPermission};
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
 use serde::{Deserialize, Serialize};
 
 /// GitHub API client
@@ -35,6 +41,20 @@ struct GitHubRepo {
     description: Option<String>,
     private: bool,
     html_url: String,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    is_template: bool,
+    /// Missing for repositories nothing was pushed to
+    #[serde(default)]
+    pushed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct GitHubContent {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -61,6 +81,15 @@ struct CreateRepoRequest {
     private: bool,
 }
 
+#[derive(Debug, Serialize)]
+struct GenerateRepoRequest {
+    owner: String,
+    name: String,
+    description: String,
+    private: bool,
+    include_all_branches: bool,
+}
+
 #[derive(Debug, Serialize)]
 struct CreateIssueRequest {
     title: String,
@@ -77,9 +106,7 @@ struct UpdateIssueRequest {
 impl GitHubAPI {
     /// Create a new GitHub API client
     pub fn new(base_url: String, token: String, org_name: String, user: String) -> Result<Self> {
-        let client = reqwest::Client::builder()
-            .user_agent("repobee-rust/0.1.0")
-            .build()?;
+        let client = http_client()?;
 
         // Determine API URL
         let api_url = if base_url.contains("github.com") {
@@ -112,6 +139,42 @@ impl GitHubAPI {
         self.handle_response(response).await
     }
 
+    /// Make authenticated GET requests for every page of a list endpoint
+    ///
+    /// GitHub points at the next page with the `Link` header.
+    fn get_paged<'a, T>(&'a self, path: &str) -> PagedStream<'a, T>
+    where
+        T: serde::de::DeserializeOwned + Send + 'a,
+    {
+        let url = format!("{}{}", self.api_url, path);
+        paged(url, PageStyle::LinkHeader, move |url| async move {
+            let response = self
+                .client
+                .get(&url)
+                .header("Authorization", format!("token {}", self.token))
+                .header("Accept", "application/vnd.github.v3+json")
+                .send()
+                .await?;
+            let headers = response.headers().clone();
+            let items = self.handle_response(response).await?;
+            Ok((items, headers))
+        })
+    }
+
+    /// Stream all repositories of the organization, one page at a time
+    pub fn repo_stream(&self) -> PagedStream<'_, Repo> {
+        let repos =
+            self.get_paged::<GitHubRepo>(&format!("/orgs/{}/repos?per_page=100", self.org_name));
+        Box::pin(repos.map_ok(|repo| {
+            Repo::new(
+                repo.name,
+                repo.description.unwrap_or_default(),
+                repo.private,
+                repo.html_url,
+            )
+        }))
+    }
+
     /// Make an authenticated POST request
     async fn post<T: serde::de::DeserializeOwned, B: Serialize>(
         &self,
@@ -244,9 +307,43 @@ impl GitHubAPI {
         }
     }
 
+    /// Check that the token has the scopes RepoBee needs
+    ///
+    /// Classic tokens list their scopes in the `X-OAuth-Scopes` header. Fine-grained tokens
+    /// send no such header and are not checked here.
+    pub async fn verify_token_scopes(&self) -> Result<()> {
+        let response = self
+            .client
+            .get(format!("{}/user", self.api_url))
+            .header("Authorization", format!("token {}", self.token))
+            .header("Accept", "application/vnd.github.v3+json")
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return self.convert_error(status.as_u16(), &text);
+        }
+
+        let Some(header) = response.headers().get("x-oauth-scopes") else {
+            return Ok(());
+        };
+        let granted: Vec<&str> = header.to_str().unwrap_or_default().split(',').collect();
+        let missing = missing_token_scopes(GitPlatformKind::GitHub, &granted);
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing_scopes_error(GitPlatformKind::GitHub, &missing))
+        }
+    }
+
     /// Get team by name
     async fn get_team_by_name(&self, team_name: &str) -> Result<Option<GitHubTeam>> {
-        let teams: Vec<GitHubTeam> = self.get(&format!("/orgs/{}/teams", self.org_name)).await?;
+        let teams: Vec<GitHubTeam> = self
+            .get_paged(&format!("/orgs/{}/teams?per_page=100", self.org_name))
+            .try_collect()
+            .await?;
         Ok(teams.into_iter().find(|t| t.name == team_name))
     }
 
@@ -317,7 +414,10 @@ impl PlatformAPI for GitHubAPI {
     }
 
     async fn get_teams(&self, team_names: Option<&[String]>) -> Result<Vec<Team>> {
-        let teams: Vec<GitHubTeam> = self.get(&format!("/orgs/{}/teams", self.org_name)).await?;
+        let teams: Vec<GitHubTeam> = self
+            .get_paged(&format!("/orgs/{}/teams?per_page=100", self.org_name))
+            .try_collect()
+            .await?;
         let mut result_teams = Vec::new();
 
         for team in teams {
@@ -444,36 +544,59 @@ impl PlatformAPI for GitHubAPI {
         Ok(result_repo)
     }
 
-    async fn delete_repo(&self, repo: &Repo) -> Result<()> {
-        self.delete(&format!("/repos/{}/{}", self.org_name, repo.name))
-            .await
-    }
+    async fn create_repo_from_template(
+        &self,
+        template_url: &str,
+        name: &str,
+        description: &str,
+        private: bool,
+        team: Option<&Team>,
+    ) -> Result<Repo> {
+        // Returns the existing repository, assigned to the team
+        if self.repo_exists(name).await? {
+            return self.create_repo(name, description, private, team).await;
+        }
 
-    async fn get_repos(&self, repo_urls: Option<&[String]>) -> Result<Vec<Repo>> {
-        let repos: Vec<GitHubRepo> = self
-            .get(&format!("/orgs/{}/repos?per_page=100", self.org_name))
+        // Only the default branch is copied, like a setup that pushes a template clone
+        let template = repo_path_from_url(&self.base_url, template_url)?;
+        let request = GenerateRepoRequest {
+            owner: self.org_name.clone(),
+            name: name.to_string(),
+            description: description.to_string(),
+            private,
+            include_all_branches: false,
+        };
+        let repo: GitHubRepo = self
+            .post(&format!("/repos/{}/generate", template), &request)
             .await?;
-        let mut result_repos = Vec::new();
+        let result_repo = Repo::new(
+            repo.name,
+            repo.description.unwrap_or_default(),
+            repo.private,
+            repo.html_url,
+        );
 
-        for repo in repos {
-            let converted = Repo::new(
-                repo.name,
-                repo.description.unwrap_or_default(),
-                repo.private,
-                repo.html_url.clone(),
-            );
+        if let Some(t) = team {
+            self.assign_repo(t, &result_repo, TeamPermission::Push)
+                .await?;
+        }
 
-            // Filter by URLs if specified
-            if let Some(urls) = repo_urls {
-                if !urls.contains(&converted.url) {
-                    continue;
-                }
-            }
+        Ok(result_repo)
+    }
 
-            result_repos.push(converted);
-        }
+    async fn delete_repo(&self, name: &str) -> Result<()> {
+        self.delete(&format!("/repos/{}/{}", self.org_name, name))
+            .await
+    }
 
-        Ok(result_repos)
+    async fn get_repos(&self, repo_urls: Option<&[String]>) -> Result<Vec<Repo>> {
+        // Organizations with many students span several pages
+        self.repo_stream()
+            .try_filter(|repo| {
+                std::future::ready(repo_urls.is_none_or(|urls| urls.contains(&repo.url)))
+            })
+            .try_collect()
+            .await
     }
 
     async fn get_repo(&self, repo_name: &str, _team_name: Option<&str>) -> Result<Repo> {
@@ -488,6 +611,47 @@ impl PlatformAPI for GitHubAPI {
         ))
     }
 
+    async fn repo_exists(&self, name: &str) -> Result<bool> {
+        match self
+            .get::<GitHubRepo>(&format!("/repos/{}/{}", self.org_name, name))
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(PlatformError::NotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn is_archived(&self, repo_url: &str) -> Result<bool> {
+        let path = repo_path_from_url(&self.base_url, repo_url)?;
+        let repo: GitHubRepo = self.get(&format!("/repos/{}", path)).await?;
+        Ok(repo.archived)
+    }
+
+    async fn is_template(&self, repo_url: &str) -> Result<bool> {
+        let path = repo_path_from_url(&self.base_url, repo_url)?;
+        let repo: GitHubRepo = self.get(&format!("/repos/{}", path)).await?;
+        Ok(repo.is_template)
+    }
+
+    async fn repo_last_activity(&self, name: &str) -> Result<Option<DateTime<Utc>>> {
+        let repo: GitHubRepo = self
+            .get(&format!("/repos/{}/{}", self.org_name, name))
+            .await?;
+        Ok(repo.pushed_at)
+    }
+
+    async fn user_exists(&self, username: &str) -> Result<bool> {
+        match self
+            .get::<GitHubUser>(&format!("/users/{}", username))
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(PlatformError::NotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
     async fn get_team_repos(&self, team: &Team) -> Result<Vec<Repo>> {
         let team_obj = self
             .get_team_by_name(&team.name)
@@ -513,6 +677,49 @@ impl PlatformAPI for GitHubAPI {
             .collect())
     }
 
+    async fn list_repo_files(&self, repo: &Repo, git_ref: Option<&str>) -> Result<Vec<RepoFile>> {
+        let mut path = format!("/repos/{}/{}/contents", self.org_name, repo.name);
+        if let Some(git_ref) = git_ref {
+            path.push_str(&format!("?ref={}", git_ref));
+        }
+
+        let contents: Vec<GitHubContent> = match self.get(&path).await {
+            Ok(contents) => contents,
+            // GitHub answers 404 for repositories without commits
+            Err(PlatformError::NotFound(m)) if m.contains("repository is empty") => {
+                return Ok(Vec::new())
+            }
+            Err(e) => return Err(e),
+        };
+
+        let mut files: Vec<RepoFile> = contents
+            .into_iter()
+            .map(|c| {
+                let kind = match c.kind.as_str() {
+                    "dir" => RepoFileKind::Dir,
+                    "symlink" => RepoFileKind::Symlink,
+                    "submodule" => RepoFileKind::Submodule,
+                    _ => RepoFileKind::File,
+                };
+                RepoFile::new(c.name, kind)
+            })
+            .collect();
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(files)
+    }
+
+    async fn set_ci_variable(
+        &self,
+        _repo: &Repo,
+        _key: &str,
+        _value: &str,
+        _masked: bool,
+    ) -> Result<()> {
+        Err(PlatformError::Other(
+            "CI variables are not supported on GitHub".to_string(),
+        ))
+    }
+
     fn get_repo_urls(
         &self,
         assignment_names: &[String],
@@ -533,7 +740,7 @@ impl PlatformAPI for GitHubAPI {
             Some(teams) => {
                 for team in teams {
                     for assignment in assignment_names {
-                        let repo_name = format!("{}-{}", team, assignment);
+                        let repo_name = repo_name(team, assignment, &RepoNaming::default())?;
                         let url = format!("{}/{}/{}.git", base, org, repo_name);
                         urls.push(if insert_auth {
                             self.insert_auth(&url)?
@@ -624,10 +831,11 @@ impl PlatformAPI for GitHubAPI {
         };
 
         let issues: Vec<GitHubIssue> = self
-            .get(&format!(
+            .get_paged(&format!(
                 "/repos/{}/{}/issues?state={}&per_page=100",
                 self.org_name, repo.name, state_str
             ))
+            .try_collect()
             .await?;
 
         Ok(issues
@@ -686,6 +894,9 @@ impl PlatformAPI for GitHubAPI {
     }
 
     async fn verify_settings(&self) -> Result<()> {
+        // Report missing scopes before they cause confusing failures further on
+        self.verify_token_scopes().await?;
+
         // Verify we can access the organization
         #[derive(Deserialize)]
         struct OrgResponse {
@@ -713,4 +924,9 @@ impl PlatformAPI for GitHubAPI {
     fn base_url(&self) -> &str {
         &self.base_url
     }
+
+    /// GitHub strongly recommends keeping repositories below 5 GB
+    fn repo_size_limit(&self) -> Option<u64> {
+        Some(5 * 1024 * 1024 * 1024)
+    }
 }
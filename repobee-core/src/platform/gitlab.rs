@@ -1,8 +1,44 @@
 //! GitLab platform implementation
 
 use crate::error::{PlatformError, Result};
-use crate::platform::PlatformAPI;
-use crate::types::{Issue, IssueState, Repo, Team, TeamPermission};
+use crate::http::http_client;
+use crate::platform::token::{missing_scopes_error, missing_token_scopes, GitPlatformKind};
+use crate::platform::{repo_path_from_url, PlatformAPI};
+use crate::types::{Issue, IssueState, Repo, RepoFile, RepoFileKind, Team, TeamPermission};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+/// Entry of the GitLab repository tree API
+#[derive(Debug, Deserialize)]
+struct GitLabTreeEntry {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    mode: String,
+}
+
+/// Project fields of the GitLab projects API that are used
+#[derive(Debug, Deserialize)]
+struct GitLabProject {
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    empty_repo: bool,
+    #[serde(default)]
+    last_activity_at: Option<DateTime<Utc>>,
+}
+
+/// User fields of the GitLab users API that are used
+#[derive(Debug, Deserialize)]
+struct GitLabUser {
+    username: String,
+}
+
+/// Response of the GitLab API for the token in use
+#[derive(Debug, Deserialize)]
+struct GitLabTokenInfo {
+    scopes: Vec<String>,
+}
 
 /// GitLab API client
 #[derive(Debug)]
@@ -17,9 +53,7 @@ pub struct GitLabAPI {
 impl GitLabAPI {
     /// Create a new GitLab API client
     pub fn new(base_url: String, token: String, org_name: String, user: String) -> Result<Self> {
-        let client = reqwest::Client::builder()
-            .user_agent("repobee-rust/0.1.0")
-            .build()?;
+        let client = http_client()?;
 
         Ok(Self {
             base_url,
@@ -29,6 +63,77 @@ impl GitLabAPI {
             client,
         })
     }
+
+    /// Make an authenticated GET request against the GitLab API
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let url = format!("{}/api/v4{}", self.base_url.trim_end_matches('/'), path);
+        let response = self
+            .client
+            .get(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(match status.as_u16() {
+                404 => PlatformError::not_found(format!("Resource not found: {}", text)),
+                401 | 403 => {
+                    PlatformError::bad_credentials(format!("Authentication failed: {}", text))
+                }
+                code => PlatformError::unexpected(format!("HTTP {}: {}", code, text)),
+            });
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| PlatformError::unexpected(format!("JSON parse error: {}", e)))
+    }
+
+    /// Check that the token has the scopes RepoBee needs
+    ///
+    /// GitLab versions before 15.5 cannot report the scopes of a token, so the check is
+    /// skipped there.
+    pub async fn verify_token_scopes(&self) -> Result<()> {
+        let url = format!(
+            "{}/api/v4/personal_access_tokens/self",
+            self.base_url.trim_end_matches('/')
+        );
+        let response = self
+            .client
+            .get(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return match status.as_u16() {
+                404 => Ok(()),
+                401 | 403 => Err(PlatformError::bad_credentials(format!(
+                    "Authentication failed: {}",
+                    text
+                ))),
+                code => Err(PlatformError::unexpected(format!(
+                    "HTTP {}: {}",
+                    code, text
+                ))),
+            };
+        }
+
+        let token: GitLabTokenInfo = response
+            .json()
+            .await
+            .map_err(|e| PlatformError::unexpected(format!("JSON parse error: {}", e)))?;
+        let missing = missing_token_scopes(GitPlatformKind::GitLab, &token.scopes);
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing_scopes_error(GitPlatformKind::GitLab, &missing))
+        }
+    }
 }
 
 impl PlatformAPI for GitLabAPI {
@@ -89,7 +194,20 @@ impl PlatformAPI for GitLabAPI {
         ))
     }
 
-    async fn delete_repo(&self, _repo: &Repo) -> Result<()> {
+    async fn create_repo_from_template(
+        &self,
+        _template_url: &str,
+        _name: &str,
+        _description: &str,
+        _private: bool,
+        _team: Option<&Team>,
+    ) -> Result<Repo> {
+        Err(PlatformError::Other(
+            "GitLab implementation not yet implemented".to_string(),
+        ))
+    }
+
+    async fn delete_repo(&self, _name: &str) -> Result<()> {
         Err(PlatformError::Other(
             "GitLab implementation not yet implemented".to_string(),
         ))
@@ -107,12 +225,162 @@ impl PlatformAPI for GitLabAPI {
         ))
     }
 
+    async fn repo_exists(&self, _name: &str) -> Result<bool> {
+        Err(PlatformError::Other(
+            "GitLab implementation not yet implemented".to_string(),
+        ))
+    }
+
+    async fn is_archived(&self, repo_url: &str) -> Result<bool> {
+        // Projects in subgroups are addressed by their full, encoded path
+        let path = repo_path_from_url(&self.base_url, repo_url)?;
+        let project: GitLabProject = self
+            .get(&format!("/projects/{}", path.replace('/', "%2F")))
+            .await?;
+        Ok(project.archived)
+    }
+
+    /// Repositories are not created on GitLab yet, so setup always clones and pushes
+    async fn is_template(&self, _repo_url: &str) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// The last activity of an empty project is its creation, so it reports `None`
+    async fn repo_last_activity(&self, name: &str) -> Result<Option<DateTime<Utc>>> {
+        let path = format!("{}/{}", self.org_name, name);
+        let project: GitLabProject = self
+            .get(&format!("/projects/{}", path.replace('/', "%2F")))
+            .await?;
+        Ok(project.last_activity_at.filter(|_| !project.empty_repo))
+    }
+
+    async fn user_exists(&self, username: &str) -> Result<bool> {
+        let users: Vec<GitLabUser> = self.get(&format!("/users?username={}", username)).await?;
+        Ok(users
+            .iter()
+            .any(|user| user.username.eq_ignore_ascii_case(username)))
+    }
+
     async fn get_team_repos(&self, _team: &Team) -> Result<Vec<Repo>> {
         Err(PlatformError::Other(
             "GitLab implementation not yet implemented".to_string(),
         ))
     }
 
+    async fn list_repo_files(&self, repo: &Repo, git_ref: Option<&str>) -> Result<Vec<RepoFile>> {
+        let project = format!("{}/{}", self.org_name, repo.name).replace('/', "%2F");
+        let mut url = format!(
+            "{}/api/v4/projects/{}/repository/tree?per_page=100",
+            self.base_url.trim_end_matches('/'),
+            project
+        );
+        if let Some(git_ref) = git_ref {
+            url.push_str(&format!("&ref={}", git_ref));
+        }
+
+        let response = self
+            .client
+            .get(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return match status.as_u16() {
+                // GitLab has no tree for a repository without commits
+                404 if text.contains("Tree Not Found") => Ok(Vec::new()),
+                404 => Err(PlatformError::not_found(format!(
+                    "Repository '{}' not found",
+                    repo.name
+                ))),
+                401 | 403 => Err(PlatformError::bad_credentials(format!(
+                    "Authentication failed: {}",
+                    text
+                ))),
+                code => Err(PlatformError::unexpected(format!(
+                    "HTTP {}: {}",
+                    code, text
+                ))),
+            };
+        }
+
+        let entries: Vec<GitLabTreeEntry> = response
+            .json()
+            .await
+            .map_err(|e| PlatformError::unexpected(format!("JSON parse error: {}", e)))?;
+
+        let mut files: Vec<RepoFile> = entries
+            .into_iter()
+            .map(|e| {
+                let kind = match (e.kind.as_str(), e.mode.as_str()) {
+                    ("tree", _) => RepoFileKind::Dir,
+                    ("commit", _) => RepoFileKind::Submodule,
+                    (_, "120000") => RepoFileKind::Symlink,
+                    _ => RepoFileKind::File,
+                };
+                RepoFile::new(e.name, kind)
+            })
+            .collect();
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(files)
+    }
+
+    async fn set_ci_variable(
+        &self,
+        repo: &Repo,
+        key: &str,
+        value: &str,
+        masked: bool,
+    ) -> Result<()> {
+        let project = format!("{}/{}", self.org_name, repo.name).replace('/', "%2F");
+        let url = format!(
+            "{}/api/v4/projects/{}/variables",
+            self.base_url.trim_end_matches('/'),
+            project
+        );
+        let form = [
+            ("key", key),
+            ("value", value),
+            ("masked", if masked { "true" } else { "false" }),
+        ];
+
+        let response = self
+            .client
+            .post(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .form(&form)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let text = response.text().await.unwrap_or_default();
+        // GitLab rejects creating a variable that already exists, so update it instead
+        if status.as_u16() == 400 && text.contains("has already been taken") {
+            let response = self
+                .client
+                .put(format!("{}/{}", url, key))
+                .header("PRIVATE-TOKEN", &self.token)
+                .form(&form)
+                .send()
+                .await?;
+
+            let status = response.status();
+            if status.is_success() {
+                return Ok(());
+            }
+            let text = response.text().await.unwrap_or_default();
+            return Err(ci_variable_error(status.as_u16(), &repo.name, key, &text));
+        }
+
+        Err(ci_variable_error(status.as_u16(), &repo.name, key, &text))
+    }
+
     fn get_repo_urls(
         &self,
         _assignment_names: &[String],
@@ -172,9 +440,41 @@ impl PlatformAPI for GitLabAPI {
     }
 
     async fn verify_settings(&self) -> Result<()> {
-        Err(PlatformError::Other(
-            "GitLab implementation not yet implemented".to_string(),
-        ))
+        // Report missing scopes before they cause confusing failures further on
+        self.verify_token_scopes().await?;
+
+        // Verify we can access the group
+        let url = format!(
+            "{}/api/v4/groups/{}",
+            self.base_url.trim_end_matches('/'),
+            self.org_name.replace('/', "%2F")
+        );
+        let response = self
+            .client
+            .get(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let text = response.text().await.unwrap_or_default();
+        match status.as_u16() {
+            404 => Err(PlatformError::not_found(format!(
+                "Group '{}' not found",
+                self.org_name
+            ))),
+            401 | 403 => Err(PlatformError::bad_credentials(format!(
+                "Authentication failed: {}",
+                text
+            ))),
+            code => Err(PlatformError::unexpected(format!(
+                "HTTP {}: {}",
+                code, text
+            ))),
+        }
     }
 
     fn org_name(&self) -> &str {
@@ -188,4 +488,24 @@ impl PlatformAPI for GitLabAPI {
     fn base_url(&self) -> &str {
         &self.base_url
     }
+
+    /// Default repository size limit on GitLab.com; self-managed instances may differ
+    fn repo_size_limit(&self) -> Option<u64> {
+        Some(10 * 1024 * 1024 * 1024)
+    }
+}
+
+/// Convert a failed CI variable request into an error (the value is never included)
+fn ci_variable_error(status: u16, repo_name: &str, key: &str, message: &str) -> PlatformError {
+    match status {
+        404 => PlatformError::not_found(format!("Project '{}' not found", repo_name)),
+        401 | 403 => PlatformError::bad_credentials(format!(
+            "Not allowed to set CI variable '{}' on '{}': {}",
+            key, repo_name, message
+        )),
+        _ => PlatformError::unexpected(format!(
+            "Failed to set CI variable '{}' on '{}' (HTTP {}): {}",
+            key, repo_name, status, message
+        )),
+    }
 }
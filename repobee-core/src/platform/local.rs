@@ -19,8 +19,12 @@
 //! ```
 
 use crate::error::{PlatformError, Result};
+use crate::naming::{repo_name, RepoNaming};
 use crate::platform::PlatformAPI;
-use crate::types::{Issue, IssueState, Repo, Team, TeamPermission};
+use crate::types::{
+    CiVariable, Issue, IssueState, Repo, RepoFile, RepoFileKind, Team, TeamPermission,
+};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -98,6 +102,27 @@ impl LocalAPI {
         self.repos_dir().join(format!("{}.json", repo_name))
     }
 
+    /// Get the directory holding the bare git repository
+    fn repo_dir(&self, repo_name: &str) -> PathBuf {
+        self.org_dir().join(repo_name)
+    }
+
+    /// Get path for the CI variables file of a repository
+    fn ci_variables_path(&self, repo_name: &str) -> PathBuf {
+        self.org_dir()
+            .join("ci_variables")
+            .join(format!("{}.json", repo_name))
+    }
+
+    /// Get the CI variables set on a repository
+    pub fn get_ci_variables(&self, repo_name: &str) -> Result<Vec<CiVariable>> {
+        let path = self.ci_variables_path(repo_name);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        self.read_json(&path)
+    }
+
     /// Get path for an issue file
     fn issue_path(&self, repo_name: &str, issue_number: u32) -> PathBuf {
         self.issues_dir()
@@ -313,7 +338,7 @@ impl PlatformAPI for LocalAPI {
         let repo = Repo::new(name.to_string(), desc, private, self.repo_url(name));
 
         // Create the actual git repository directory (as a bare repo)
-        let repo_dir = self.base_dir.join("orgs").join(&self.org_name).join(name);
+        let repo_dir = self.repo_dir(name);
         if !repo_dir.exists() {
             git2::Repository::init_bare(&repo_dir).map_err(|e| PlatformError::GitError(e))?;
         }
@@ -322,15 +347,52 @@ impl PlatformAPI for LocalAPI {
         Ok(repo)
     }
 
-    async fn delete_repo(&self, repo: &Repo) -> Result<()> {
-        let repo_path = self.repo_path(&repo.name);
+    async fn create_repo_from_template(
+        &self,
+        template_url: &str,
+        name: &str,
+        description: &str,
+        private: bool,
+        team: Option<&Team>,
+    ) -> Result<Repo> {
+        let repo_path = self.repo_path(name);
+        if repo_path.exists() {
+            return self.read_json(&repo_path);
+        }
+
+        // A bare clone of the template, without the remote pointing back at it
+        let repo_dir = self.repo_dir(name);
+        let clone = git2::build::RepoBuilder::new()
+            .bare(true)
+            .clone(template_url, &repo_dir)
+            .map_err(|e| PlatformError::GitError(e))?;
+        clone
+            .remote_delete("origin")
+            .map_err(|e| PlatformError::GitError(e))?;
+
+        let mut desc = description.to_string();
+        if let Some(t) = team {
+            desc.push_str(&format!(" [team:{}]", t.name));
+        }
+        let repo = Repo::new(name.to_string(), desc, private, self.repo_url(name));
+        self.write_json(&repo_path, &repo)?;
+        Ok(repo)
+    }
+
+    async fn delete_repo(&self, name: &str) -> Result<()> {
+        let repo_path = self.repo_path(name);
         if !repo_path.exists() {
             return Err(PlatformError::not_found(format!(
                 "Repo '{}' not found",
-                repo.name
+                name
             )));
         }
 
+        let repo_dir = self.repo_dir(name);
+        if repo_dir.exists() {
+            fs::remove_dir_all(&repo_dir)
+                .map_err(|e| PlatformError::FileError(format!("Failed to delete repo: {}", e)))?;
+        }
         fs::remove_file(&repo_path)
             .map_err(|e| PlatformError::FileError(format!("Failed to delete repo: {}", e)))
     }
@@ -367,6 +429,50 @@ impl PlatformAPI for LocalAPI {
         self.read_json(&repo_path)
     }
 
+    async fn repo_exists(&self, name: &str) -> Result<bool> {
+        Ok(self.repo_dir(name).is_dir())
+    }
+
+    /// Local repositories cannot be archived
+    async fn is_archived(&self, _repo_url: &str) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// A local repository is a template when its git config sets `repobee.template`
+    async fn is_template(&self, repo_url: &str) -> Result<bool> {
+        let path = repo_url.strip_prefix("file://").unwrap_or(repo_url);
+        let repo = git2::Repository::open(path).map_err(|e| {
+            PlatformError::not_found(format!("Repo '{}' not found: {}", repo_url, e))
+        })?;
+        let config = repo.config().map_err(|e| PlatformError::GitError(e))?;
+        Ok(config.get_bool("repobee.template").unwrap_or(false))
+    }
+
+    /// Time of the newest commit on any branch of the bare repository
+    async fn repo_last_activity(&self, name: &str) -> Result<Option<DateTime<Utc>>> {
+        let repo = git2::Repository::open_bare(self.repo_dir(name))
+            .map_err(|e| PlatformError::not_found(format!("Repo '{}' not found: {}", name, e)))?;
+        let branches = repo
+            .branches(Some(git2::BranchType::Local))
+            .map_err(PlatformError::GitError)?;
+        let mut newest = None;
+        for branch in branches {
+            let (branch, _) = branch.map_err(PlatformError::GitError)?;
+            let commit = branch
+                .get()
+                .peel_to_commit()
+                .map_err(PlatformError::GitError)?;
+            let time = DateTime::from_timestamp(commit.time().seconds(), 0);
+            newest = newest.max(time);
+        }
+        Ok(newest)
+    }
+
+    /// The local platform has no accounts, every member name is accepted
+    async fn user_exists(&self, _username: &str) -> Result<bool> {
+        Ok(true)
+    }
+
     async fn get_team_repos(&self, team: &Team) -> Result<Vec<Repo>> {
         let files = self.list_json_files(&self.repos_dir())?;
         let mut repos = Vec::new();
@@ -381,6 +487,58 @@ impl PlatformAPI for LocalAPI {
         Ok(repos)
     }
 
+    async fn list_repo_files(&self, repo: &Repo, git_ref: Option<&str>) -> Result<Vec<RepoFile>> {
+        let git_repo = git2::Repository::open_bare(self.repo_dir(&repo.name)).map_err(|e| {
+            PlatformError::not_found(format!("Repo '{}' not found: {}", repo.name, e))
+        })?;
+
+        // A repository without commits has an unborn HEAD
+        if git_ref.is_none() && git_repo.head().is_err() {
+            return Ok(Vec::new());
+        }
+
+        let tree = git_repo
+            .revparse_single(git_ref.unwrap_or("HEAD"))
+            .and_then(|object| object.peel_to_tree())
+            .map_err(PlatformError::GitError)?;
+
+        let mut files: Vec<RepoFile> = tree
+            .iter()
+            .filter_map(|entry| {
+                let name = entry.name()?.to_string();
+                let kind = match entry.kind() {
+                    Some(git2::ObjectType::Tree) => RepoFileKind::Dir,
+                    Some(git2::ObjectType::Commit) => RepoFileKind::Submodule,
+                    _ if entry.filemode() == 0o120000 => RepoFileKind::Symlink,
+                    _ => RepoFileKind::File,
+                };
+                Some(RepoFile::new(name, kind))
+            })
+            .collect();
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(files)
+    }
+
+    async fn set_ci_variable(
+        &self,
+        repo: &Repo,
+        key: &str,
+        value: &str,
+        masked: bool,
+    ) -> Result<()> {
+        if !self.repo_path(&repo.name).exists() {
+            return Err(PlatformError::not_found(format!(
+                "Repo '{}' not found",
+                repo.name
+            )));
+        }
+
+        let mut variables = self.get_ci_variables(&repo.name)?;
+        variables.retain(|v| v.key != key);
+        variables.push(CiVariable::new(key.to_string(), value.to_string(), masked));
+        self.write_json(&self.ci_variables_path(&repo.name), &variables)
+    }
+
     fn get_repo_urls(
         &self,
         assignment_names: &[String],
@@ -395,7 +553,7 @@ impl PlatformAPI for LocalAPI {
             Some(teams) => {
                 for team in teams {
                     for assignment in assignment_names {
-                        let repo_name = format!("{}-{}", team, assignment);
+                        let repo_name = repo_name(team, assignment, &RepoNaming::default())?;
                         urls.push(format!("{}/{}/{}", self.base_url, org, repo_name));
                     }
                 }
@@ -547,6 +705,10 @@ impl PlatformAPI for LocalAPI {
     fn base_url(&self) -> &str {
         &self.base_url
     }
+
+    fn repo_size_limit(&self) -> Option<u64> {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -600,6 +762,79 @@ mod tests {
         assert_eq!(fetched_repo.name, "test-repo");
     }
 
+    #[tokio::test]
+    async fn test_repo_exists() {
+        let (api, _temp) = setup_test_api();
+
+        assert!(!api.repo_exists("test-repo").await.unwrap());
+
+        api.create_repo("test-repo", "Test repository", true, None)
+            .await
+            .unwrap();
+
+        assert!(api.repo_exists("test-repo").await.unwrap());
+        assert!(!api.repo_exists("other-repo").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_set_ci_variable() {
+        let (api, _temp) = setup_test_api();
+
+        let repo = api
+            .create_repo("test-repo", "Test repository", true, None)
+            .await
+            .unwrap();
+
+        api.set_ci_variable(&repo, "GRADER_TOKEN", "old", true)
+            .await
+            .unwrap();
+        api.set_ci_variable(&repo, "GRADER_TOKEN", "secret", true)
+            .await
+            .unwrap();
+
+        let variables = api.get_ci_variables("test-repo").unwrap();
+        assert_eq!(variables.len(), 1);
+        assert_eq!(variables[0].value, "secret");
+        assert!(variables[0].masked);
+    }
+
+    #[tokio::test]
+    async fn test_list_repo_files() {
+        let (api, temp) = setup_test_api();
+
+        let repo = api
+            .create_repo("test-repo", "Test repository", true, None)
+            .await
+            .unwrap();
+        assert!(api.list_repo_files(&repo, None).await.unwrap().is_empty());
+
+        // Commit a README and a src directory straight into the bare repository
+        let git_repo =
+            git2::Repository::open_bare(temp.path().join("orgs/test-org/test-repo")).unwrap();
+        let readme = git_repo.blob(b"# Task").unwrap();
+        let main = git_repo.blob(b"fn main() {}").unwrap();
+        let mut src = git_repo.treebuilder(None).unwrap();
+        src.insert("main.rs", main, 0o100644).unwrap();
+        let src = src.write().unwrap();
+        let mut root = git_repo.treebuilder(None).unwrap();
+        root.insert("src", src, 0o040000).unwrap();
+        root.insert("README.md", readme, 0o100644).unwrap();
+        let tree = git_repo.find_tree(root.write().unwrap()).unwrap();
+        let signature = git2::Signature::now("test", "test@example.com").unwrap();
+        git_repo
+            .commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+
+        let files = api.list_repo_files(&repo, None).await.unwrap();
+        assert_eq!(
+            files,
+            vec![
+                RepoFile::new("README.md".to_string(), RepoFileKind::File),
+                RepoFile::new("src".to_string(), RepoFileKind::Dir),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_assign_repo_to_team() {
         let (api, _temp) = setup_test_api();
@@ -1,19 +1,124 @@
 //! Platform abstraction layer for GitHub, GitLab, Gitea, and Local (filesystem-based)
 
-use crate::error::Result;
-use crate::types::{Issue, IssueState, Repo, Team, TeamPermission};
-use std::path::PathBuf;
+use crate::error::{PlatformError, Result};
+use crate::secrets::resolve_token;
+use crate::types::{Issue, IssueState, Repo, RepoFile, Team, TeamPermission};
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, StreamExt};
+use std::path::{Path, PathBuf};
 
 pub mod gitea;
 pub mod github;
 pub mod gitlab;
 pub mod local;
+pub mod paging;
+pub mod token;
 
 // Re-export platform implementations
 pub use gitea::GiteaAPI;
 pub use github::GitHubAPI;
 pub use gitlab::GitLabAPI;
 pub use local::LocalAPI;
+pub use paging::{paged, PageStyle, PagedStream};
+pub use token::{
+    git_token_generation_instructions, git_token_generation_url, missing_token_scopes,
+    GitPlatformKind,
+};
+
+/// Path of a repository below the platform root, e.g. `course/task-1` for
+/// `https://github.com/course/task-1.git`
+///
+/// The part of the URL after `base_url` is used when the URL starts with it, so instances
+/// served below a path prefix work; otherwise the whole URL path.
+pub(crate) fn repo_path_from_url(base_url: &str, repo_url: &str) -> Result<String> {
+    let base_url = base_url.trim_end_matches('/');
+    let path = match repo_url.strip_prefix(base_url) {
+        Some(rest) if !base_url.is_empty() && rest.starts_with('/') => rest.to_string(),
+        _ => reqwest::Url::parse(repo_url)
+            .map_err(|e| PlatformError::invalid_url(format!("{}: {}", repo_url, e)))?
+            .path()
+            .to_string(),
+    };
+    let path = path.trim_matches('/').trim_end_matches(".git");
+    if !path.contains('/') {
+        return Err(PlatformError::invalid_url(format!(
+            "{} does not name an owner and a repository",
+            repo_url
+        )));
+    }
+    Ok(path.to_string())
+}
+
+/// Check the settings a hosted platform is created with
+///
+/// The organization, user and token must not be empty, and `base_url` must be an
+/// `http(s)` URL that does not point at another platform, e.g. a GitLab URL configured
+/// for GitHub. URLs that do not name a platform, such as self-hosted instances, are
+/// accepted for every platform.
+pub fn validate_config(
+    kind: GitPlatformKind,
+    base_url: &str,
+    token: &str,
+    org_name: &str,
+    user: &str,
+) -> Result<()> {
+    let url = reqwest::Url::parse(base_url.trim()).map_err(|e| {
+        PlatformError::invalid_config(format!(
+            "{} base URL '{}' is not a URL: {}",
+            kind, base_url, e
+        ))
+    })?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(PlatformError::invalid_config(format!(
+            "{} base URL '{}' must start with https:// or http://",
+            kind, base_url
+        )));
+    }
+    if let Some(other) = GitPlatformKind::from_base_url(base_url).filter(|k| *k != kind) {
+        return Err(PlatformError::invalid_config(format!(
+            "{} base URL '{}' looks like a {} URL",
+            kind, base_url, other
+        )));
+    }
+    if token.trim().is_empty() {
+        return Err(PlatformError::invalid_config(format!(
+            "{} needs an access token",
+            kind
+        )));
+    }
+    validate_names(org_name, Some(user))
+}
+
+/// Check the settings the local platform is created with
+///
+/// The local platform needs no token or user, but a directory and an organization.
+pub fn validate_local_config(base_dir: &Path, org_name: &str) -> Result<()> {
+    let dir = base_dir.to_string_lossy();
+    if dir.trim().is_empty() {
+        return Err(PlatformError::invalid_config(
+            "The local platform needs a base directory",
+        ));
+    }
+    if dir.contains("://") {
+        return Err(PlatformError::invalid_config(format!(
+            "Local base directory '{}' is a URL, not a directory",
+            dir
+        )));
+    }
+    validate_names(org_name, None)
+}
+
+fn validate_names(org_name: &str, user: Option<&str>) -> Result<()> {
+    if org_name.trim().is_empty() {
+        return Err(PlatformError::invalid_config(
+            "The student organization (group) is empty",
+        ));
+    }
+    if user.is_some_and(|user| user.trim().is_empty()) {
+        return Err(PlatformError::invalid_config("The git user is empty"));
+    }
+    Ok(())
+}
 
 // ============================================================================
 // Platform Enum (Enum + Trait Pattern)
@@ -30,7 +135,12 @@ pub enum Platform {
 
 impl Platform {
     /// Create a new GitHub platform instance
+    ///
+    /// `token` may be a `file:` reference, see [`crate::secrets`]. The same holds for the
+    /// other hosted platforms, which all check their settings with [`validate_config`].
     pub fn github(base_url: String, token: String, org_name: String, user: String) -> Result<Self> {
+        let token = resolve_token(&token)?;
+        validate_config(GitPlatformKind::GitHub, &base_url, &token, &org_name, &user)?;
         Ok(Self::GitHub(GitHubAPI::new(
             base_url, token, org_name, user,
         )?))
@@ -38,6 +148,8 @@ impl Platform {
 
     /// Create a new GitLab platform instance
     pub fn gitlab(base_url: String, token: String, org_name: String, user: String) -> Result<Self> {
+        let token = resolve_token(&token)?;
+        validate_config(GitPlatformKind::GitLab, &base_url, &token, &org_name, &user)?;
         Ok(Self::GitLab(GitLabAPI::new(
             base_url, token, org_name, user,
         )?))
@@ -45,13 +157,37 @@ impl Platform {
 
     /// Create a new Gitea platform instance
     pub fn gitea(base_url: String, token: String, org_name: String, user: String) -> Result<Self> {
+        let token = resolve_token(&token)?;
+        validate_config(GitPlatformKind::Gitea, &base_url, &token, &org_name, &user)?;
         Ok(Self::Gitea(GiteaAPI::new(base_url, token, org_name, user)?))
     }
 
     /// Create a new Local (filesystem-based) platform instance
+    ///
+    /// The settings are checked with [`validate_local_config`].
     pub fn local(base_dir: PathBuf, org_name: String, user: String) -> Result<Self> {
+        validate_local_config(&base_dir, &org_name)?;
         Ok(Self::Local(LocalAPI::new(base_dir, org_name, user)?))
     }
+
+    /// Stream all repositories of the organization, one page at a time
+    ///
+    /// Platforms without paged listings yield the whole listing after a single request.
+    pub fn repo_stream(&self) -> PagedStream<'_, Repo> {
+        match self {
+            Platform::GitHub(api) => api.repo_stream(),
+            Platform::Gitea(api) => api.repo_stream(),
+            Platform::GitLab(_) | Platform::Local(_) => {
+                let repos = async move {
+                    match self.get_repos(None).await {
+                        Ok(repos) => stream::iter(repos.into_iter().map(Ok)).left_stream(),
+                        Err(e) => stream::once(async { Err(e) }).right_stream(),
+                    }
+                };
+                Box::pin(stream::once(repos).flatten())
+            }
+        }
+    }
 }
 
 // ============================================================================
@@ -121,8 +257,31 @@ pub trait PlatformAPI {
         team: Option<&Team>,
     ) -> Result<Repo>;
 
-    /// Delete a repository
-    async fn delete_repo(&self, repo: &Repo) -> Result<()>;
+    /// Create a new repository with the content of a template, copied by the platform
+    ///
+    /// Unlike cloning the template and pushing it, nothing passes through this machine.
+    /// Only templates for which [`is_template`](Self::is_template) is `true` can be used.
+    /// An existing repository is returned as is, like [`create_repo`](Self::create_repo).
+    ///
+    /// # Arguments
+    /// * `template_url` - URL of the template repository
+    /// * `name` - Repository name
+    /// * `description` - Repository description
+    /// * `private` - Whether the repository should be private
+    /// * `team` - Optional team to assign the repository to
+    async fn create_repo_from_template(
+        &self,
+        template_url: &str,
+        name: &str,
+        description: &str,
+        private: bool,
+        team: Option<&Team>,
+    ) -> Result<Repo>;
+
+    /// Delete the repository `name` of the organization
+    ///
+    /// A repository that does not exist is a [`PlatformError::NotFound`].
+    async fn delete_repo(&self, name: &str) -> Result<()>;
 
     /// Get repositories by URL. If `repo_urls` is None, returns all repos in the organization.
     async fn get_repos(&self, repo_urls: Option<&[String]>) -> Result<Vec<Repo>>;
@@ -134,11 +293,62 @@ pub trait PlatformAPI {
     /// * `team_name` - Optional team name (used by some platforms for namespacing)
     async fn get_repo(&self, repo_name: &str, team_name: Option<&str>) -> Result<Repo>;
 
+    /// Check whether a repository with the given name exists in the organization
+    async fn repo_exists(&self, name: &str) -> Result<bool>;
+
+    /// Check whether the repository at `repo_url` is archived (read-only)
+    ///
+    /// The repository may belong to another organization on the same platform, such as a
+    /// template group. Platforms without archiving report `false`.
+    async fn is_archived(&self, repo_url: &str) -> Result<bool>;
+
+    /// Check whether the repository at `repo_url` is marked as a template on the platform
+    ///
+    /// Platforms that cannot create repositories from templates report `false`.
+    async fn is_template(&self, repo_url: &str) -> Result<bool>;
+
+    /// Time of the last push to the repository `name` of the organization
+    ///
+    /// `None` when nothing was pushed since the repository was created. Platforms report
+    /// different things: GitHub the last push, GitLab the last activity of any kind and
+    /// Gitea the last update. A repository that does not exist is a
+    /// [`PlatformError::NotFound`].
+    async fn repo_last_activity(&self, name: &str) -> Result<Option<DateTime<Utc>>>;
+
+    /// Check whether an account with the given username exists on the platform
+    async fn user_exists(&self, username: &str) -> Result<bool>;
+
     /// Get all repositories assigned to a team
     async fn get_team_repos(&self, team: &Team) -> Result<Vec<Repo>>;
 
+    /// List the top-level files and directories of a repository, sorted by name
+    ///
+    /// # Arguments
+    /// * `repo` - Repository to inspect
+    /// * `git_ref` - Branch, tag or commit (uses the default branch if None)
+    ///
+    /// An empty repository has no files, so this returns an empty list rather than an error.
+    async fn list_repo_files(&self, repo: &Repo, git_ref: Option<&str>) -> Result<Vec<RepoFile>>;
+
+    /// Set a CI/CD variable on a repository, overwriting any existing value
+    ///
+    /// # Arguments
+    /// * `repo` - Target repository
+    /// * `key` - Variable name
+    /// * `value` - Variable value
+    /// * `masked` - Whether the value should be masked in job logs
+    async fn set_ci_variable(
+        &self,
+        repo: &Repo,
+        key: &str,
+        value: &str,
+        masked: bool,
+    ) -> Result<()>;
+
     /// Generate repository URLs for the given assignment names and teams
     ///
+    /// Student repository names follow the default [`RepoNaming`](crate::naming::RepoNaming).
+    ///
     /// # Arguments
     /// * `assignment_names` - List of assignment/template names
     /// * `org_name` - Optional organization name (uses default if None)
@@ -211,6 +421,9 @@ pub trait PlatformAPI {
 
     /// Get the base URL
     fn base_url(&self) -> &str;
+
+    /// Known maximum repository size in bytes, if the platform enforces one
+    fn repo_size_limit(&self) -> Option<u64>;
 }
 
 // ============================================================================
@@ -299,13 +512,42 @@ impl PlatformAPI for Platform {
         }
     }
 
-    async fn delete_repo(&self, repo: &Repo) -> Result<()> {
+    async fn create_repo_from_template(
+        &self,
+        template_url: &str,
+        name: &str,
+        description: &str,
+        private: bool,
+        team: Option<&Team>,
+    ) -> Result<Repo> {
+        match self {
+            Platform::GitHub(api) => {
+                api.create_repo_from_template(template_url, name, description, private, team)
+                    .await
+            }
+            Platform::GitLab(api) => {
+                api.create_repo_from_template(template_url, name, description, private, team)
+                    .await
+            }
+            Platform::Gitea(api) => {
+                api.create_repo_from_template(template_url, name, description, private, team)
+                    .await
+            }
+
+            Platform::Local(api) => {
+                api.create_repo_from_template(template_url, name, description, private, team)
+                    .await
+            }
+        }
+    }
+
+    async fn delete_repo(&self, name: &str) -> Result<()> {
         match self {
-            Platform::GitHub(api) => api.delete_repo(repo).await,
-            Platform::GitLab(api) => api.delete_repo(repo).await,
-            Platform::Gitea(api) => api.delete_repo(repo).await,
+            Platform::GitHub(api) => api.delete_repo(name).await,
+            Platform::GitLab(api) => api.delete_repo(name).await,
+            Platform::Gitea(api) => api.delete_repo(name).await,
 
-            Platform::Local(api) => api.delete_repo(repo).await,
+            Platform::Local(api) => api.delete_repo(name).await,
         }
     }
 
@@ -329,6 +571,56 @@ impl PlatformAPI for Platform {
         }
     }
 
+    async fn repo_exists(&self, name: &str) -> Result<bool> {
+        match self {
+            Platform::GitHub(api) => api.repo_exists(name).await,
+            Platform::GitLab(api) => api.repo_exists(name).await,
+            Platform::Gitea(api) => api.repo_exists(name).await,
+
+            Platform::Local(api) => api.repo_exists(name).await,
+        }
+    }
+
+    async fn is_archived(&self, repo_url: &str) -> Result<bool> {
+        match self {
+            Platform::GitHub(api) => api.is_archived(repo_url).await,
+            Platform::GitLab(api) => api.is_archived(repo_url).await,
+            Platform::Gitea(api) => api.is_archived(repo_url).await,
+
+            Platform::Local(api) => api.is_archived(repo_url).await,
+        }
+    }
+
+    async fn is_template(&self, repo_url: &str) -> Result<bool> {
+        match self {
+            Platform::GitHub(api) => api.is_template(repo_url).await,
+            Platform::GitLab(api) => api.is_template(repo_url).await,
+            Platform::Gitea(api) => api.is_template(repo_url).await,
+
+            Platform::Local(api) => api.is_template(repo_url).await,
+        }
+    }
+
+    async fn repo_last_activity(&self, name: &str) -> Result<Option<DateTime<Utc>>> {
+        match self {
+            Platform::GitHub(api) => api.repo_last_activity(name).await,
+            Platform::GitLab(api) => api.repo_last_activity(name).await,
+            Platform::Gitea(api) => api.repo_last_activity(name).await,
+
+            Platform::Local(api) => api.repo_last_activity(name).await,
+        }
+    }
+
+    async fn user_exists(&self, username: &str) -> Result<bool> {
+        match self {
+            Platform::GitHub(api) => api.user_exists(username).await,
+            Platform::GitLab(api) => api.user_exists(username).await,
+            Platform::Gitea(api) => api.user_exists(username).await,
+
+            Platform::Local(api) => api.user_exists(username).await,
+        }
+    }
+
     async fn get_team_repos(&self, team: &Team) -> Result<Vec<Repo>> {
         match self {
             Platform::GitHub(api) => api.get_team_repos(team).await,
@@ -339,6 +631,32 @@ impl PlatformAPI for Platform {
         }
     }
 
+    async fn list_repo_files(&self, repo: &Repo, git_ref: Option<&str>) -> Result<Vec<RepoFile>> {
+        match self {
+            Platform::GitHub(api) => api.list_repo_files(repo, git_ref).await,
+            Platform::GitLab(api) => api.list_repo_files(repo, git_ref).await,
+            Platform::Gitea(api) => api.list_repo_files(repo, git_ref).await,
+
+            Platform::Local(api) => api.list_repo_files(repo, git_ref).await,
+        }
+    }
+
+    async fn set_ci_variable(
+        &self,
+        repo: &Repo,
+        key: &str,
+        value: &str,
+        masked: bool,
+    ) -> Result<()> {
+        match self {
+            Platform::GitHub(api) => api.set_ci_variable(repo, key, value, masked).await,
+            Platform::GitLab(api) => api.set_ci_variable(repo, key, value, masked).await,
+            Platform::Gitea(api) => api.set_ci_variable(repo, key, value, masked).await,
+
+            Platform::Local(api) => api.set_ci_variable(repo, key, value, masked).await,
+        }
+    }
+
     fn get_repo_urls(
         &self,
         assignment_names: &[String],
@@ -458,6 +776,16 @@ impl PlatformAPI for Platform {
         }
     }
 
+    fn repo_size_limit(&self) -> Option<u64> {
+        match self {
+            Platform::GitHub(api) => api.repo_size_limit(),
+            Platform::GitLab(api) => api.repo_size_limit(),
+            Platform::Gitea(api) => api.repo_size_limit(),
+
+            Platform::Local(api) => api.repo_size_limit(),
+        }
+    }
+
     fn base_url(&self) -> &str {
         match self {
             Platform::GitHub(api) => api.base_url(),
@@ -468,3 +796,138 @@ impl PlatformAPI for Platform {
         }
     }
 }
+
+/// Delete one repository of the organization after checking that it exists
+///
+/// Returns the deleted repository. A missing repository is reported as
+/// [`PlatformError::NotFound`] without deleting anything.
+pub async fn delete_single_repo<P: PlatformAPI>(api: &P, name: &str) -> Result<Repo> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(PlatformError::Other("No repository name given".to_string()));
+    }
+    let repo = match api.get_repo(name, None).await {
+        Ok(repo) => repo,
+        Err(PlatformError::NotFound(_)) => {
+            return Err(PlatformError::not_found(format!(
+                "Repository '{}' does not exist in '{}', nothing was deleted",
+                name,
+                api.org_name()
+            )))
+        }
+        Err(e) => return Err(e),
+    };
+    api.delete_repo(&repo.name).await?;
+    log::info!("Deleted repository {} from {}", repo.name, api.org_name());
+    Ok(repo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_path_from_url() {
+        let path = |base_url, url| repo_path_from_url(base_url, url).unwrap();
+        assert_eq!(
+            path("https://github.com", "https://github.com/course/task-1.git"),
+            "course/task-1"
+        );
+        assert_eq!(
+            path(
+                "https://git.uni.edu/gitlab/",
+                "https://git.uni.edu/gitlab/dept/course/task-1"
+            ),
+            "dept/course/task-1"
+        );
+        // API base URLs differ from the web URLs of repositories
+        assert_eq!(
+            path(
+                "https://api.github.com",
+                "https://github.com/course/task-1/"
+            ),
+            "course/task-1"
+        );
+        assert!(repo_path_from_url("https://github.com", "https://github.com/task-1").is_err());
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let valid = |kind, url| validate_config(kind, url, "token", "course", "teacher");
+        assert!(valid(GitPlatformKind::GitHub, "https://github.com").is_ok());
+        assert!(valid(GitPlatformKind::GitLab, "https://git.uni.edu").is_ok());
+        assert!(valid(GitPlatformKind::Gitea, "http://localhost:3000").is_ok());
+
+        let error = |result: Result<()>| result.unwrap_err().to_string();
+        assert_eq!(
+            error(valid(GitPlatformKind::GitHub, "https://gitlab.tue.nl")),
+            "Invalid configuration: GitHub base URL 'https://gitlab.tue.nl' looks like a \
+             GitLab URL"
+        );
+        assert!(error(valid(GitPlatformKind::GitLab, "gitlab.tue.nl")).contains("is not a URL"));
+        assert!(error(valid(GitPlatformKind::Gitea, "ssh://gitea.uni.edu"))
+            .contains("must start with https://"));
+        assert!(error(validate_config(
+            GitPlatformKind::GitLab,
+            "https://gitlab.tue.nl",
+            " ",
+            "course",
+            "teacher"
+        ))
+        .contains("GitLab needs an access token"));
+        assert!(error(validate_config(
+            GitPlatformKind::GitHub,
+            "https://github.com",
+            "token",
+            "",
+            "teacher"
+        ))
+        .contains("organization (group) is empty"));
+        assert!(error(validate_config(
+            GitPlatformKind::GitHub,
+            "https://github.com",
+            "token",
+            "course",
+            ""
+        ))
+        .contains("git user is empty"));
+    }
+
+    #[test]
+    fn test_validate_local_config() {
+        assert!(validate_local_config(Path::new("/srv/repos"), "course").is_ok());
+        assert!(validate_local_config(Path::new(""), "course").is_err());
+        assert!(validate_local_config(Path::new("https://github.com"), "course").is_err());
+        assert!(validate_local_config(Path::new("/srv/repos"), " ").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_delete_single_repo() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let api = Platform::local(
+            temp_dir.path().to_path_buf(),
+            "test-org".to_string(),
+            "teacher".to_string(),
+        )
+        .unwrap();
+        api.create_repo("team1-task-1", "", true, None)
+            .await
+            .unwrap();
+        api.create_repo("team2-task-1", "", true, None)
+            .await
+            .unwrap();
+
+        let deleted = delete_single_repo(&api, "team1-task-1").await.unwrap();
+        assert_eq!(deleted.name, "team1-task-1");
+        assert!(!api.repo_exists("team1-task-1").await.unwrap());
+        assert!(api.repo_exists("team2-task-1").await.unwrap());
+
+        match delete_single_repo(&api, "team1-task-1").await {
+            Err(PlatformError::NotFound(message)) => {
+                assert!(message.contains("does not exist in 'test-org'"))
+            }
+            other => panic!("expected NotFound, got {:?}", other),
+        }
+        assert!(api.repo_exists("team2-task-1").await.unwrap());
+    }
+}
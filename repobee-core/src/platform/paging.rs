@@ -0,0 +1,152 @@
+//! Paginated listings as streams
+//!
+//! The hosted platforms split long listings into pages and point at the next page in
+//! different ways: GitHub and GitLab send a `Link` header, Gitea expects the caller to
+//! count pages until one comes back short. [`paged`] hides that behind a stream of
+//! items, so callers can process a listing as it arrives or collect it with
+//! [`TryStreamExt::try_collect`](futures_util::TryStreamExt::try_collect).
+
+use crate::error::Result;
+use crate::http::next_link;
+use futures_util::stream::{self, Stream, StreamExt};
+use reqwest::header::HeaderMap;
+use std::future::Future;
+use std::pin::Pin;
+
+/// Items of a paginated listing, fetched one page at a time as the stream is polled
+///
+/// An error ends the stream.
+pub type PagedStream<'a, T> = Pin<Box<dyn Stream<Item = Result<T>> + Send + 'a>>;
+
+/// How an endpoint tells where the next page of a listing is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageStyle {
+    /// The `rel="next"` URL of the `Link` header, as GitHub and GitLab send
+    LinkHeader,
+    /// The `page` query parameter counts up until a page has fewer than `per_page` items
+    PageNumber { per_page: usize },
+}
+
+impl PageStyle {
+    /// URL of the page after the one at `url`, `None` on the last page
+    fn next_url(self, url: &str, items: usize, headers: &HeaderMap) -> Option<String> {
+        match self {
+            Self::LinkHeader => next_link(headers),
+            Self::PageNumber { per_page } if items < per_page.max(1) => None,
+            Self::PageNumber { .. } => next_page_number(url),
+        }
+    }
+}
+
+/// Stream the items of every page, starting at `first_url`
+///
+/// `fetch` requests one page and returns its items and response headers; platform
+/// specific authentication and error handling belong there.
+pub fn paged<'a, T, F, Fut>(first_url: String, style: PageStyle, fetch: F) -> PagedStream<'a, T>
+where
+    T: Send + 'a,
+    F: FnMut(String) -> Fut + Send + 'a,
+    Fut: Future<Output = Result<(Vec<T>, HeaderMap)>> + Send + 'a,
+{
+    let pages = stream::unfold(
+        (Some(first_url), fetch),
+        move |(next, mut fetch)| async move {
+            let url = next?;
+            let (items, next) = match fetch(url.clone()).await {
+                Ok((items, headers)) => {
+                    let next = style.next_url(&url, items.len(), &headers);
+                    (items.into_iter().map(Ok).collect(), next)
+                }
+                Err(e) => (vec![Err(e)], None),
+            };
+            Some((stream::iter(items), (next, fetch)))
+        },
+    );
+    Box::pin(pages.flatten())
+}
+
+/// `url` with its `page` query parameter counted up, starting from page 1
+fn next_page_number(url: &str) -> Option<String> {
+    let mut url = reqwest::Url::parse(url).ok()?;
+    let mut found = false;
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| {
+            if name == "page" {
+                found = true;
+                let page = value.parse::<u32>().unwrap_or(1) + 1;
+                (name.into_owned(), page.to_string())
+            } else {
+                (name.into_owned(), value.into_owned())
+            }
+        })
+        .collect();
+    let mut query = url.query_pairs_mut();
+    query.clear().extend_pairs(pairs);
+    if !found {
+        query.append_pair("page", "2");
+    }
+    drop(query);
+    Some(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PlatformError;
+    use futures_util::TryStreamExt;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_next_page_number() {
+        assert_eq!(
+            next_page_number("https://gitea.io/api/v1/orgs/c/teams?limit=2&page=1").as_deref(),
+            Some("https://gitea.io/api/v1/orgs/c/teams?limit=2&page=2")
+        );
+        assert_eq!(
+            next_page_number("https://gitea.io/api/v1/orgs/c/teams").as_deref(),
+            Some("https://gitea.io/api/v1/orgs/c/teams?page=2")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_page_numbers_until_short_page() {
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let urls = requested.clone();
+        let items: Vec<u32> = paged(
+            "https://gitea.io/teams?limit=2&page=1".to_string(),
+            PageStyle::PageNumber { per_page: 2 },
+            move |url| {
+                urls.lock().unwrap().push(url.clone());
+                async move {
+                    let page = match url.rsplit_once("page=").unwrap().1 {
+                        "1" => vec![1, 2],
+                        "2" => vec![3, 4],
+                        _ => vec![5],
+                    };
+                    Ok((page, HeaderMap::new()))
+                }
+            },
+        )
+        .try_collect()
+        .await
+        .unwrap();
+
+        assert_eq!(items, vec![1, 2, 3, 4, 5]);
+        assert_eq!(requested.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_error_ends_stream() {
+        let mut stream = paged(
+            "https://github.com/orgs/c/repos".to_string(),
+            PageStyle::LinkHeader,
+            |_| async { Err::<(Vec<u32>, _), _>(PlatformError::not_found("org c")) },
+        );
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(PlatformError::NotFound(_)))
+        ));
+        assert!(stream.next().await.is_none());
+    }
+}
@@ -0,0 +1,238 @@
+//! Where and how to create an access token on the hosted git platforms
+//!
+//! The git counterpart of the LMS token helpers in `lms_common`. Base URLs are the web
+//! addresses used everywhere else, e.g. `https://gitlab.tue.nl`.
+
+use crate::error::PlatformError;
+use crate::suggest::did_you_mean;
+use std::fmt;
+use std::str::FromStr;
+
+/// Hosted git platform that needs an access token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitPlatformKind {
+    GitHub,
+    GitLab,
+    Gitea,
+}
+
+impl GitPlatformKind {
+    /// Detect the platform from its base URL, the same way the GUI picks a platform
+    pub fn from_base_url(base_url: &str) -> Option<Self> {
+        let url = base_url.to_lowercase();
+        if url.contains("github") {
+            Some(Self::GitHub)
+        } else if url.contains("gitlab") {
+            Some(Self::GitLab)
+        } else if url.contains("gitea") {
+            Some(Self::Gitea)
+        } else {
+            None
+        }
+    }
+
+    /// Token scopes RepoBee needs for setting up student repositories
+    ///
+    /// Gitea does not report the scopes of a token, so nothing is checked there.
+    pub fn required_scopes(self) -> &'static [&'static str] {
+        match self {
+            Self::GitHub => &["repo", "admin:org"],
+            Self::GitLab => &["api"],
+            Self::Gitea => &[],
+        }
+    }
+}
+
+impl fmt::Display for GitPlatformKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GitHub => write!(f, "GitHub"),
+            Self::GitLab => write!(f, "GitLab"),
+            Self::Gitea => write!(f, "Gitea"),
+        }
+    }
+}
+
+impl FromStr for GitPlatformKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "github" => Ok(Self::GitHub),
+            "gitlab" => Ok(Self::GitLab),
+            "gitea" => Ok(Self::Gitea),
+            _ => Err(format!(
+                "Unknown git platform: {}.{} Supported: GitHub, GitLab, Gitea",
+                s,
+                did_you_mean(s, &["GitHub", "GitLab", "Gitea"])
+            )),
+        }
+    }
+}
+
+/// Page for creating a personal access token, with the scopes RepoBee needs filled in
+/// where the platform supports that
+pub fn git_token_generation_url(base_url: &str, kind: GitPlatformKind) -> String {
+    let base = base_url.trim().trim_end_matches('/');
+    match kind {
+        GitPlatformKind::GitHub => {
+            let web = if base.contains("github.com") {
+                "https://github.com"
+            } else {
+                base.trim_end_matches("/api/v3")
+            };
+            format!(
+                "{}/settings/tokens/new?scopes=repo,admin:org,delete_repo&description=RepoBee",
+                web
+            )
+        }
+        GitPlatformKind::GitLab => format!(
+            "{}/-/user_settings/personal_access_tokens?name=RepoBee&scopes=api",
+            base.trim_end_matches("/api/v4")
+        ),
+        GitPlatformKind::Gitea => format!(
+            "{}/user/settings/applications",
+            base.trim_end_matches("/api/v1")
+        ),
+    }
+}
+
+/// Step-by-step instructions for creating a personal access token
+pub fn git_token_generation_instructions(kind: GitPlatformKind) -> &'static str {
+    match kind {
+        GitPlatformKind::GitHub => {
+            "1. Sign in to GitHub as the teacher account\n\
+             2. Open Settings > Developer settings > Personal access tokens > Tokens (classic)\n\
+             3. Click 'Generate new token (classic)'\n\
+             4. Select the scopes 'repo', 'admin:org' and 'delete_repo'\n\
+             5. Set an expiration date that covers the course\n\
+             6. Click 'Generate token' and copy it, it is only shown once"
+        }
+        GitPlatformKind::GitLab => {
+            "1. Sign in to GitLab as the teacher account\n\
+             2. Open Preferences > Access tokens\n\
+             3. Click 'Add new token' and give it a name, e.g. RepoBee\n\
+             4. Select the 'api' scope\n\
+             5. Set an expiration date that covers the course\n\
+             6. Click 'Create personal access token' and copy it, it is only shown once"
+        }
+        GitPlatformKind::Gitea => {
+            "1. Sign in to Gitea as the teacher account\n\
+             2. Open Settings > Applications\n\
+             3. Under 'Manage Access Tokens', enter a token name, e.g. RepoBee\n\
+             4. Give read and write access to 'organization', 'repository' and 'issue'\n\
+             5. Click 'Generate Token' and copy it, it is only shown once"
+        }
+    }
+}
+
+/// Required scopes of the platform that are not among the granted scopes
+pub fn missing_token_scopes<S: AsRef<str>>(
+    kind: GitPlatformKind,
+    granted: &[S],
+) -> Vec<&'static str> {
+    kind.required_scopes()
+        .iter()
+        .copied()
+        .filter(|required| {
+            !granted
+                .iter()
+                .any(|scope| scope.as_ref().trim() == *required)
+        })
+        .collect()
+}
+
+/// Error for a token that lacks required scopes, naming what is missing
+pub(crate) fn missing_scopes_error(kind: GitPlatformKind, missing: &[&str]) -> PlatformError {
+    PlatformError::bad_credentials(format!(
+        "The {} token is missing the scope(s) {}. Create a token with the scopes {}",
+        kind,
+        missing.join(", "),
+        kind.required_scopes().join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_base_url() {
+        assert_eq!(
+            GitPlatformKind::from_base_url("https://github.com"),
+            Some(GitPlatformKind::GitHub)
+        );
+        assert_eq!(
+            GitPlatformKind::from_base_url("https://gitlab.tue.nl/"),
+            Some(GitPlatformKind::GitLab)
+        );
+        assert_eq!(
+            GitPlatformKind::from_base_url("https://gitea.example.org"),
+            Some(GitPlatformKind::Gitea)
+        );
+        assert_eq!(GitPlatformKind::from_base_url("/tmp/repos"), None);
+    }
+
+    #[test]
+    fn test_unknown_platform_suggests_closest() {
+        let error = "gitlba".parse::<GitPlatformKind>().unwrap_err();
+        assert_eq!(
+            error,
+            "Unknown git platform: gitlba. Did you mean 'GitLab'? Supported: GitHub, GitLab, Gitea"
+        );
+        let error = "svn".parse::<GitPlatformKind>().unwrap_err();
+        assert_eq!(
+            error,
+            "Unknown git platform: svn. Supported: GitHub, GitLab, Gitea"
+        );
+    }
+
+    #[test]
+    fn test_git_token_generation_url() {
+        assert_eq!(
+            git_token_generation_url("https://github.com/", GitPlatformKind::GitHub),
+            "https://github.com/settings/tokens/new?scopes=repo,admin:org,delete_repo&description=RepoBee"
+        );
+        assert!(
+            git_token_generation_url("https://github.uni.nl/api/v3", GitPlatformKind::GitHub)
+                .starts_with("https://github.uni.nl/settings/tokens/new?")
+        );
+        assert_eq!(
+            git_token_generation_url("https://gitlab.tue.nl", GitPlatformKind::GitLab),
+            "https://gitlab.tue.nl/-/user_settings/personal_access_tokens?name=RepoBee&scopes=api"
+        );
+        assert_eq!(
+            git_token_generation_url("https://git.uni.nl/gitlab/", GitPlatformKind::GitLab),
+            "https://git.uni.nl/gitlab/-/user_settings/personal_access_tokens?name=RepoBee&scopes=api"
+        );
+        assert_eq!(
+            git_token_generation_url("https://gitea.example.org/api/v1", GitPlatformKind::Gitea),
+            "https://gitea.example.org/user/settings/applications"
+        );
+    }
+
+    #[test]
+    fn test_git_token_generation_instructions_name_scopes() {
+        assert!(git_token_generation_instructions(GitPlatformKind::GitHub).contains("'repo'"));
+        assert!(git_token_generation_instructions(GitPlatformKind::GitLab).contains("'api'"));
+        assert!(git_token_generation_instructions(GitPlatformKind::Gitea).contains("'repository'"));
+    }
+
+    #[test]
+    fn test_missing_token_scopes() {
+        let granted = "repo, read:org, gist".split(',').collect::<Vec<_>>();
+        assert_eq!(
+            missing_token_scopes(GitPlatformKind::GitHub, &granted),
+            vec!["admin:org"]
+        );
+        assert!(missing_token_scopes(GitPlatformKind::GitHub, &["repo", "admin:org"]).is_empty());
+        assert_eq!(
+            missing_token_scopes(GitPlatformKind::GitLab, &["read_api", "read_repository"]),
+            vec!["api"]
+        );
+        assert!(missing_token_scopes::<&str>(GitPlatformKind::Gitea, &[]).is_empty());
+
+        let error = missing_scopes_error(GitPlatformKind::GitHub, &["admin:org"]);
+        assert!(error.to_string().contains("missing the scope(s) admin:org"));
+    }
+}
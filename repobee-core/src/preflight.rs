@@ -0,0 +1,410 @@
+//! Checks run before setup creates anything
+//!
+//! [`preflight_setup`] collects every reason a setup would fail, or do something the
+//! teacher does not expect, into a single [`PreflightReport`]. Nothing is created or
+//! changed on the platform, so it is safe to run as often as needed.
+
+use crate::error::Result;
+use crate::lms::validate_repobee_yaml;
+use crate::naming::RepoNaming;
+use crate::platform::PlatformAPI;
+use crate::reconcile::expected_repos;
+use crate::templates::check_assignments;
+use crate::types::StudentTeam;
+use futures_util::stream::{self, StreamExt};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+/// Number of account lookups running at the same time
+const LOOKUP_CONCURRENCY: usize = 8;
+
+/// Outcome of a single pre-flight check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Passed,
+    /// Setup works, but probably not the way the teacher expects
+    Warning,
+    /// Setup would fail
+    Failed,
+    /// The check could not be done, e.g. because the platform does not support it
+    Skipped,
+}
+
+/// A pre-flight check with its outcome
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PreflightCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    /// One line per affected item, e.g. each unknown account
+    pub details: Vec<String>,
+}
+
+impl PreflightCheck {
+    fn new(name: &str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+            details: Vec::new(),
+        }
+    }
+
+    fn with_details(mut self, details: Vec<String>) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Outcome of all pre-flight checks, in the order they were run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PreflightReport {
+    pub checks: Vec<PreflightCheck>,
+}
+
+impl PreflightReport {
+    /// Whether setup can run, i.e. no check failed; warnings and skipped checks are allowed
+    pub fn is_ready(&self) -> bool {
+        self.checks.iter().all(|c| c.status != CheckStatus::Failed)
+    }
+
+    /// Checks with the given outcome
+    pub fn with_status(&self, status: CheckStatus) -> impl Iterator<Item = &PreflightCheck> {
+        self.checks.iter().filter(move |c| c.status == status)
+    }
+}
+
+impl fmt::Display for PreflightReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            let symbol = match check.status {
+                CheckStatus::Passed => "✓",
+                CheckStatus::Warning => "⚠",
+                CheckStatus::Failed => "✗",
+                CheckStatus::Skipped => "-",
+            };
+            writeln!(f, "{} {}: {}", symbol, check.name, check.message)?;
+            for detail in &check.details {
+                writeln!(f, "    {}", detail)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run every check setup depends on, without creating anything
+///
+/// # Arguments
+/// * `api` - Platform set up with the student organization
+/// * `assignments` - Assignment (template) names
+/// * `templates` - Templates in the template group, or the error listing them
+/// * `student_teams` - Teams to set up
+/// * `naming` - Naming scheme of the student repositories
+///
+/// The checks are, in order: platform access (authentication, organization access and
+/// the permissions [`PlatformAPI::verify_settings`] checks), templates, the teams file,
+/// member accounts and repository names. The platform checks are skipped when the
+/// platform cannot be accessed.
+pub async fn preflight_setup<P: PlatformAPI>(
+    api: &P,
+    assignments: &[String],
+    templates: Result<Vec<String>>,
+    student_teams: &[StudentTeam],
+    naming: &RepoNaming,
+) -> PreflightReport {
+    let mut report = PreflightReport::default();
+
+    let platform_ok = match api.verify_settings().await {
+        Ok(()) => {
+            report.checks.push(PreflightCheck::new(
+                "Platform access",
+                CheckStatus::Passed,
+                format!("Signed in with access to {}", api.org_name()),
+            ));
+            true
+        }
+        Err(e) => {
+            report.checks.push(PreflightCheck::new(
+                "Platform access",
+                CheckStatus::Failed,
+                e.to_string(),
+            ));
+            false
+        }
+    };
+
+    report.checks.push(match templates {
+        Ok(templates) if assignments.is_empty() => PreflightCheck::new(
+            "Templates",
+            CheckStatus::Failed,
+            format!(
+                "No assignments given, {} templates available",
+                templates.len()
+            ),
+        ),
+        Ok(templates) => match check_assignments(assignments, &templates) {
+            Ok(()) => PreflightCheck::new(
+                "Templates",
+                CheckStatus::Passed,
+                format!("All {} assignments have a template", assignments.len()),
+            ),
+            Err(e) => PreflightCheck::new("Templates", CheckStatus::Failed, e.to_string()),
+        },
+        Err(e) => PreflightCheck::new(
+            "Templates",
+            CheckStatus::Failed,
+            format!("Cannot list the templates: {}", e),
+        ),
+    });
+
+    let problems = validate_repobee_yaml(student_teams);
+    report.checks.push(if student_teams.is_empty() {
+        PreflightCheck::new("Teams", CheckStatus::Failed, "No teams to set up")
+    } else if problems.is_empty() {
+        PreflightCheck::new(
+            "Teams",
+            CheckStatus::Passed,
+            format!("{} teams without problems", student_teams.len()),
+        )
+    } else {
+        PreflightCheck::new(
+            "Teams",
+            CheckStatus::Failed,
+            format!("{} problems in the teams", problems.len()),
+        )
+        .with_details(problems)
+    });
+
+    if platform_ok {
+        report.checks.push(check_accounts(api, student_teams).await);
+    } else {
+        report.checks.push(PreflightCheck::new(
+            "Student accounts",
+            CheckStatus::Skipped,
+            "The platform cannot be accessed",
+        ));
+    }
+
+    report
+        .checks
+        .push(check_repo_names(api, assignments, student_teams, naming, platform_ok).await);
+
+    report
+}
+
+/// Every team member must have an account, or adding them to their team fails
+async fn check_accounts<P: PlatformAPI>(api: &P, student_teams: &[StudentTeam]) -> PreflightCheck {
+    let members: BTreeSet<&str> = student_teams
+        .iter()
+        .flat_map(|team| team.members.iter())
+        .map(String::as_str)
+        .collect();
+
+    let lookups: Vec<(&str, Result<bool>)> = stream::iter(members.iter().copied())
+        .map(|member| async move { (member, api.user_exists(member).await) })
+        .buffered(LOOKUP_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut missing = Vec::new();
+    for (member, lookup) in lookups {
+        match lookup {
+            Ok(true) => {}
+            Ok(false) => missing.push(member.to_string()),
+            Err(e) => {
+                return PreflightCheck::new(
+                    "Student accounts",
+                    CheckStatus::Skipped,
+                    format!("Cannot look up accounts: {}", e),
+                )
+            }
+        }
+    }
+
+    if missing.is_empty() {
+        PreflightCheck::new(
+            "Student accounts",
+            CheckStatus::Passed,
+            format!("All {} members have an account", members.len()),
+        )
+    } else {
+        PreflightCheck::new(
+            "Student accounts",
+            CheckStatus::Failed,
+            format!(
+                "{} of {} members have no account",
+                missing.len(),
+                members.len()
+            ),
+        )
+        .with_details(missing)
+    }
+}
+
+/// Names must be valid and unique; repositories that already exist are left as they are
+async fn check_repo_names<P: PlatformAPI>(
+    api: &P,
+    assignments: &[String],
+    student_teams: &[StudentTeam],
+    naming: &RepoNaming,
+    platform_ok: bool,
+) -> PreflightCheck {
+    const NAME: &str = "Repository names";
+    let expected = match expected_repos(assignments, student_teams, naming) {
+        Ok(expected) => expected,
+        Err(e) => return PreflightCheck::new(NAME, CheckStatus::Failed, e.to_string()),
+    };
+
+    // Platforms compare repository names case-insensitively
+    let mut seen: HashMap<String, &str> = HashMap::new();
+    let mut collisions = Vec::new();
+    for repo in &expected {
+        if let Some(team) = seen.insert(repo.name.to_lowercase(), &repo.team) {
+            collisions.push(format!(
+                "{} is the name for both {} and {}",
+                repo.name, team, repo.team
+            ));
+        }
+    }
+    if !collisions.is_empty() {
+        return PreflightCheck::new(
+            NAME,
+            CheckStatus::Failed,
+            format!("{} repository names are not unique", collisions.len()),
+        )
+        .with_details(collisions);
+    }
+
+    if !platform_ok {
+        return PreflightCheck::new(
+            NAME,
+            CheckStatus::Passed,
+            format!(
+                "{} valid, unique names; existing repositories not checked",
+                expected.len()
+            ),
+        );
+    }
+    let existing: BTreeSet<String> = match api.get_repos(None).await {
+        Ok(repos) => repos.into_iter().map(|r| r.name.to_lowercase()).collect(),
+        Err(e) => {
+            return PreflightCheck::new(
+                NAME,
+                CheckStatus::Warning,
+                format!("Cannot check for existing repositories: {}", e),
+            )
+        }
+    };
+    let taken: Vec<String> = expected
+        .iter()
+        .filter(|repo| existing.contains(&repo.name.to_lowercase()))
+        .map(|repo| repo.name.clone())
+        .collect();
+    if taken.is_empty() {
+        PreflightCheck::new(
+            NAME,
+            CheckStatus::Passed,
+            format!("{} repositories to create, none exist yet", expected.len()),
+        )
+    } else {
+        PreflightCheck::new(
+            NAME,
+            CheckStatus::Warning,
+            format!(
+                "{} of {} repositories already exist and are left as they are",
+                taken.len(),
+                expected.len()
+            ),
+        )
+        .with_details(taken)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PlatformError;
+    use crate::platform::Platform;
+    use tempfile::TempDir;
+
+    fn teams() -> Vec<StudentTeam> {
+        vec![
+            StudentTeam::new(vec!["alice".to_string()]),
+            StudentTeam::new(vec!["bob".to_string()]),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_preflight_ready_on_local_platform() {
+        let temp_dir = TempDir::new().unwrap();
+        let api = Platform::local(
+            temp_dir.path().to_path_buf(),
+            "course".to_string(),
+            "teacher".to_string(),
+        )
+        .unwrap();
+        api.create_repo("alice-task1", "", true, None)
+            .await
+            .unwrap();
+
+        let report = preflight_setup(
+            &api,
+            &["task1".to_string()],
+            Ok(vec!["task1".to_string()]),
+            &teams(),
+            &RepoNaming::default(),
+        )
+        .await;
+
+        assert!(report.is_ready(), "{}", report);
+        let statuses: Vec<CheckStatus> = report.checks.iter().map(|c| c.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                CheckStatus::Passed,
+                CheckStatus::Passed,
+                CheckStatus::Passed,
+                CheckStatus::Passed,
+                CheckStatus::Warning,
+            ]
+        );
+        assert_eq!(report.checks[4].details, vec!["alice-task1"]);
+    }
+
+    #[tokio::test]
+    async fn test_preflight_reports_every_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_dir = temp_dir.path().join("platform");
+        let api = Platform::local(
+            base_dir.clone(),
+            "course".to_string(),
+            "teacher".to_string(),
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&base_dir).unwrap();
+        let mut teams = teams();
+        teams[1].name = teams[0].name.clone();
+
+        let report = preflight_setup(
+            &api,
+            &["task1".to_string()],
+            Err(PlatformError::not_found("template group")),
+            &teams,
+            &RepoNaming::default(),
+        )
+        .await;
+
+        assert!(!report.is_ready());
+        let failed: Vec<&str> = report
+            .with_status(CheckStatus::Failed)
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(
+            failed,
+            vec!["Platform access", "Templates", "Teams", "Repository names"]
+        );
+        assert!(report.to_string().contains("- Student accounts"));
+    }
+}
@@ -0,0 +1,215 @@
+//! Progress of long-running operations
+//!
+//! Fetching students, setting up repositories, cloning them and opening issues all report
+//! through [`Progress`], so a frontend needs a single event channel and a single handler.
+//! Each operation has a `*_with_progress` variant taking an `FnMut(Progress)` callback.
+
+use crate::lms::FetchProgress;
+use std::fmt;
+
+/// Progress event of any long-running operation
+#[derive(Debug, Clone)]
+pub enum Progress {
+    /// Fetching students from the LMS
+    Fetch(FetchProgress),
+    /// Setting up student repositories
+    Setup(SetupProgress),
+    /// Cloning student repositories
+    Clone(CloneProgress),
+    /// Opening an issue in student repositories
+    Issue(IssueProgress),
+}
+
+/// Phases of [`setup_student_repos_with_progress`](crate::setup::setup_student_repos_with_progress)
+#[derive(Debug, Clone)]
+pub enum SetupProgress {
+    CloningTemplates {
+        count: usize,
+    },
+    SettingUpTeams {
+        count: usize,
+    },
+    CreatingRepos {
+        count: usize,
+    },
+    /// Pushing template content to a new student repository
+    PushingTemplate {
+        current: usize,
+        total: usize,
+        repo_name: String,
+    },
+    SettingCiVariables {
+        count: usize,
+    },
+    /// Checking that the new student repositories received the template content
+    VerifyingPushes {
+        count: usize,
+    },
+}
+
+/// Phases of [`clone_student_repos_with_progress`](crate::clone::clone_student_repos_with_progress)
+#[derive(Debug, Clone)]
+pub enum CloneProgress {
+    /// Looking up the repositories and their destinations
+    Resolving { count: usize },
+    /// A clone finished, successfully or not
+    Cloned {
+        current: usize,
+        total: usize,
+        repo_name: String,
+    },
+}
+
+/// Progress of [`open_issue_in_repos_with_progress`](crate::issues::open_issue_in_repos_with_progress)
+#[derive(Debug, Clone)]
+pub enum IssueProgress {
+    /// The issue was opened in a repository, or failed to
+    Opened {
+        current: usize,
+        total: usize,
+        repo_name: String,
+    },
+}
+
+impl Progress {
+    /// Whether the event is a counter that replaces the previous counter line, instead of
+    /// a line of its own
+    pub fn is_inline(&self) -> bool {
+        matches!(
+            self,
+            Self::Fetch(FetchProgress::FetchingGroupMembers { .. })
+                | Self::Setup(SetupProgress::PushingTemplate { .. })
+                | Self::Clone(CloneProgress::Cloned { .. })
+                | Self::Issue(IssueProgress::Opened { .. })
+        )
+    }
+}
+
+impl From<FetchProgress> for Progress {
+    fn from(progress: FetchProgress) -> Self {
+        Self::Fetch(progress)
+    }
+}
+
+impl From<SetupProgress> for Progress {
+    fn from(progress: SetupProgress) -> Self {
+        Self::Setup(progress)
+    }
+}
+
+impl From<CloneProgress> for Progress {
+    fn from(progress: CloneProgress) -> Self {
+        Self::Clone(progress)
+    }
+}
+
+impl From<IssueProgress> for Progress {
+    fn from(progress: IssueProgress) -> Self {
+        Self::Issue(progress)
+    }
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fetch(progress) => match progress {
+                FetchProgress::FetchingUsers => write!(f, "Fetching students..."),
+                FetchProgress::FetchingGroups => write!(f, "Fetching groups..."),
+                FetchProgress::FetchedUsers { count } => write!(f, "Retrieved {} students", count),
+                FetchProgress::FetchedGroups { count } => write!(f, "Retrieved {} groups", count),
+                FetchProgress::FetchedSections { count } => {
+                    write!(f, "Retrieved {} sections", count)
+                }
+                FetchProgress::FetchingGroupMembers {
+                    current,
+                    total,
+                    group_name,
+                } => write!(
+                    f,
+                    "Fetching group memberships {}/{}: {}",
+                    current,
+                    total.max(&1),
+                    group_name
+                ),
+            },
+            Self::Setup(progress) => match progress {
+                SetupProgress::CloningTemplates { count } => {
+                    write!(f, "Cloning {} template repositories...", count)
+                }
+                SetupProgress::SettingUpTeams { count } => {
+                    write!(f, "Setting up {} teams...", count)
+                }
+                SetupProgress::CreatingRepos { count } => {
+                    write!(f, "Creating {} student repositories...", count)
+                }
+                SetupProgress::PushingTemplate {
+                    current,
+                    total,
+                    repo_name,
+                } => write!(f, "Pushing templates {}/{}: {}", current, total, repo_name),
+                SetupProgress::SettingCiVariables { count } => {
+                    write!(f, "Setting CI variables on {} repositories...", count)
+                }
+                SetupProgress::VerifyingPushes { count } => {
+                    write!(f, "Checking {} repositories for content...", count)
+                }
+            },
+            Self::Clone(progress) => match progress {
+                CloneProgress::Resolving { count } => {
+                    write!(f, "Looking up {} repositories...", count)
+                }
+                CloneProgress::Cloned {
+                    current,
+                    total,
+                    repo_name,
+                } => write!(f, "Cloned {}/{}: {}", current, total, repo_name),
+            },
+            Self::Issue(progress) => match progress {
+                IssueProgress::Opened {
+                    current,
+                    total,
+                    repo_name,
+                } => write!(f, "Opening issues {}/{}: {}", current, total, repo_name),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_messages() {
+        let progress = Progress::from(FetchProgress::FetchingGroupMembers {
+            current: 0,
+            total: 0,
+            group_name: "Group 1".to_string(),
+        });
+        assert!(progress.is_inline());
+        assert_eq!(
+            progress.to_string(),
+            "Fetching group memberships 0/1: Group 1"
+        );
+
+        let progress = Progress::from(SetupProgress::CreatingRepos { count: 6 });
+        assert!(!progress.is_inline());
+        assert_eq!(progress.to_string(), "Creating 6 student repositories...");
+
+        let progress = Progress::from(CloneProgress::Cloned {
+            current: 2,
+            total: 3,
+            repo_name: "team-1-task1".to_string(),
+        });
+        assert!(progress.is_inline());
+        assert_eq!(progress.to_string(), "Cloned 2/3: team-1-task1");
+
+        let progress = Progress::from(IssueProgress::Opened {
+            current: 1,
+            total: 4,
+            repo_name: "team-1-task1".to_string(),
+        });
+        assert!(progress.is_inline());
+        assert_eq!(progress.to_string(), "Opening issues 1/4: team-1-task1");
+    }
+}
@@ -0,0 +1,187 @@
+//! Auditing the student repositories on a platform against the roster
+//!
+//! [`reconcile`] derives the repositories setup should have created from the teams and
+//! assignments, lists the repositories that actually exist in the organization, and
+//! reports which expected repositories are missing and which existing ones are unexpected.
+
+use crate::error::Result;
+use crate::naming::RepoNaming;
+use crate::platform::PlatformAPI;
+use crate::types::{Repo, StudentTeam};
+use std::collections::HashMap;
+
+/// A student repository the roster calls for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedRepo {
+    pub name: String,
+    pub team: String,
+    pub assignment: String,
+    /// Members of the team
+    pub members: Vec<String>,
+}
+
+/// Outcome of comparing the expected repositories with the organization
+#[derive(Debug, Clone, Default)]
+pub struct Reconciliation {
+    /// Expected repositories that exist, with their URL
+    pub matched: Vec<(ExpectedRepo, String)>,
+    /// Expected repositories that do not exist
+    pub missing: Vec<ExpectedRepo>,
+    /// Repositories in the organization that no team and assignment accounts for
+    pub extra: Vec<Repo>,
+}
+
+impl Reconciliation {
+    /// Whether every expected repository exists
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// The repositories setup creates for `teams` and `assignments`, in setup order
+pub fn expected_repos(
+    assignments: &[String],
+    teams: &[StudentTeam],
+    naming: &RepoNaming,
+) -> Result<Vec<ExpectedRepo>> {
+    let mut expected = Vec::new();
+    for assignment in assignments {
+        for team in teams {
+            expected.push(ExpectedRepo {
+                name: naming.repo_name(&team.name, assignment)?,
+                team: team.name.clone(),
+                assignment: assignment.clone(),
+                members: team.members.clone(),
+            });
+        }
+    }
+    Ok(expected)
+}
+
+/// Compare the repositories the roster calls for with those in the organization
+///
+/// Every page of repositories is listed. Names are compared case-insensitively, as the
+/// platforms do. With a `filter`, only repositories whose name contains it
+/// (case-insensitively) are compared, e.g. a single assignment.
+pub async fn reconcile<P: PlatformAPI>(
+    assignments: &[String],
+    teams: &[StudentTeam],
+    api: &P,
+    naming: &RepoNaming,
+    filter: Option<&str>,
+) -> Result<Reconciliation> {
+    let filter = filter
+        .map(|f| f.trim().to_lowercase())
+        .filter(|f| !f.is_empty());
+    let included = |name: &str| {
+        filter
+            .as_ref()
+            .is_none_or(|f| name.to_lowercase().contains(f.as_str()))
+    };
+
+    let mut actual: HashMap<String, Repo> = api
+        .get_repos(None)
+        .await?
+        .into_iter()
+        .filter(|repo| included(&repo.name))
+        .map(|repo| (repo.name.to_lowercase(), repo))
+        .collect();
+
+    let mut result = Reconciliation::default();
+    for expected in expected_repos(assignments, teams, naming)? {
+        if !included(&expected.name) {
+            continue;
+        }
+        match actual.remove(&expected.name.to_lowercase()) {
+            Some(repo) => result.matched.push((expected, repo.url)),
+            None => result.missing.push(expected),
+        }
+    }
+
+    result.extra = actual.into_values().collect();
+    result.extra.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Platform;
+    use tempfile::TempDir;
+
+    fn teams() -> Vec<StudentTeam> {
+        vec![
+            StudentTeam::with_name("alice".to_string(), vec!["alice".to_string()]),
+            StudentTeam::with_name("bob".to_string(), vec!["bob".to_string()]),
+        ]
+    }
+
+    async fn platform(temp_dir: &TempDir, repos: &[&str]) -> Platform {
+        let api = Platform::local(
+            temp_dir.path().to_path_buf(),
+            "course".to_string(),
+            "teacher".to_string(),
+        )
+        .unwrap();
+        for name in repos {
+            api.create_repo(name, "", true, None).await.unwrap();
+        }
+        api
+    }
+
+    #[tokio::test]
+    async fn test_reconcile() {
+        let temp_dir = TempDir::new().unwrap();
+        let api = platform(
+            &temp_dir,
+            &["alice-task1", "Bob-task1", "carol-task1", "alice-task2"],
+        )
+        .await;
+
+        let assignments = vec!["task1".to_string(), "task2".to_string()];
+        let result = reconcile(&assignments, &teams(), &api, &RepoNaming::default(), None)
+            .await
+            .unwrap();
+
+        let matched: Vec<&str> = result
+            .matched
+            .iter()
+            .map(|(r, _)| r.name.as_str())
+            .collect();
+        assert_eq!(matched, vec!["alice-task1", "bob-task1", "alice-task2"]);
+        assert_eq!(
+            result.missing,
+            vec![ExpectedRepo {
+                name: "bob-task2".to_string(),
+                team: "bob".to_string(),
+                assignment: "task2".to_string(),
+                members: vec!["bob".to_string()],
+            }]
+        );
+        let extra: Vec<&str> = result.extra.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(extra, vec!["carol-task1"]);
+        assert!(!result.is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_with_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        let api = platform(&temp_dir, &["alice-task1", "bob-task1", "carol-task2"]).await;
+
+        let assignments = vec!["task1".to_string(), "task2".to_string()];
+        let result = reconcile(
+            &assignments,
+            &teams(),
+            &api,
+            &RepoNaming::default(),
+            Some("TASK1"),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.matched.len(), 2);
+        assert!(result.missing.is_empty());
+        assert!(result.extra.is_empty());
+        assert!(result.is_complete());
+    }
+}
@@ -0,0 +1,131 @@
+//! Comparing student rosters
+//!
+//! Used to review what would change before syncing a freshly fetched roster.
+
+use crate::types::StudentTeam;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Changes between an old and a new roster, sorted by team name and member
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RosterDiff {
+    /// Teams only in the new roster
+    pub teams_added: Vec<StudentTeam>,
+    /// Teams only in the old roster
+    pub teams_removed: Vec<StudentTeam>,
+    /// Teams in both rosters whose members differ
+    pub teams_changed: Vec<TeamMemberChanges>,
+}
+
+/// Member changes of a team present in both rosters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TeamMemberChanges {
+    pub team: String,
+    pub members_added: Vec<String>,
+    pub members_removed: Vec<String>,
+}
+
+impl RosterDiff {
+    /// Whether the rosters are identical
+    pub fn is_empty(&self) -> bool {
+        self.teams_added.is_empty()
+            && self.teams_removed.is_empty()
+            && self.teams_changed.is_empty()
+    }
+}
+
+/// Compare two rosters, matching teams by name
+pub fn diff_roster(old: &[StudentTeam], new: &[StudentTeam]) -> RosterDiff {
+    let old_teams: BTreeMap<&str, &StudentTeam> =
+        old.iter().map(|t| (t.name.as_str(), t)).collect();
+    let new_teams: BTreeMap<&str, &StudentTeam> =
+        new.iter().map(|t| (t.name.as_str(), t)).collect();
+
+    let mut diff = RosterDiff::default();
+
+    for (name, new_team) in &new_teams {
+        let Some(old_team) = old_teams.get(name) else {
+            diff.teams_added.push(sorted_team(new_team));
+            continue;
+        };
+
+        let old_members: BTreeSet<&String> = old_team.members.iter().collect();
+        let new_members: BTreeSet<&String> = new_team.members.iter().collect();
+        let members_added: Vec<String> = new_members
+            .difference(&old_members)
+            .map(|m| m.to_string())
+            .collect();
+        let members_removed: Vec<String> = old_members
+            .difference(&new_members)
+            .map(|m| m.to_string())
+            .collect();
+
+        if !members_added.is_empty() || !members_removed.is_empty() {
+            diff.teams_changed.push(TeamMemberChanges {
+                team: name.to_string(),
+                members_added,
+                members_removed,
+            });
+        }
+    }
+
+    for (name, old_team) in &old_teams {
+        if !new_teams.contains_key(name) {
+            diff.teams_removed.push(sorted_team(old_team));
+        }
+    }
+
+    diff
+}
+
+fn sorted_team(team: &StudentTeam) -> StudentTeam {
+    StudentTeam {
+        notes: team.notes.clone(),
+        ..StudentTeam::with_name(team.name.clone(), team.members.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn team(name: &str, members: &[&str]) -> StudentTeam {
+        StudentTeam::with_name(
+            name.to_string(),
+            members.iter().map(|m| m.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn test_diff_roster() {
+        let old = vec![
+            team("team-1", &["alice", "bob"]),
+            team("team-2", &["carol"]),
+            team("team-3", &["dave"]),
+        ];
+        let new = vec![
+            team("team-4", &["frank", "erin"]),
+            team("team-1", &["bob", "zoe", "alice"]),
+            team("team-2", &["carol"]),
+        ];
+
+        let diff = diff_roster(&old, &new);
+
+        assert_eq!(diff.teams_added, vec![team("team-4", &["erin", "frank"])]);
+        assert_eq!(diff.teams_removed, vec![team("team-3", &["dave"])]);
+        assert_eq!(
+            diff.teams_changed,
+            vec![TeamMemberChanges {
+                team: "team-1".to_string(),
+                members_added: vec!["zoe".to_string()],
+                members_removed: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_identical_rosters_is_empty() {
+        let roster = vec![team("team-1", &["alice", "bob"])];
+        assert!(diff_roster(&roster, &roster).is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+//! Access tokens stored outside the settings
+//!
+//! A token setting such as `lms_access_token` or `git_access_token` may contain a
+//! reference of the form `file:/path/to/token` instead of the token itself. The file is
+//! only read when a client is created, so the secret never ends up in the settings JSON.
+
+use crate::error::{PlatformError, Result};
+use std::path::Path;
+
+/// Prefix marking a token setting as a reference to a token file
+pub const TOKEN_FILE_PREFIX: &str = "file:";
+
+/// Path of the token file a setting refers to, if it is a `file:` reference
+pub fn token_file(value: &str) -> Option<&Path> {
+    value
+        .strip_prefix(TOKEN_FILE_PREFIX)
+        .map(|path| Path::new(path.trim()))
+}
+
+/// The token a setting stands for
+///
+/// Plain tokens are returned as they are. For a `file:` reference the file is checked with
+/// [`check_token_file`] and its content is returned without surrounding whitespace.
+pub fn resolve_token(value: &str) -> Result<String> {
+    let Some(path) = token_file(value) else {
+        return Ok(value.to_string());
+    };
+    check_token_file(path)?;
+
+    let token = std::fs::read_to_string(path).map_err(|e| {
+        PlatformError::FileError(format!(
+            "Failed to read token file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let token = token.trim();
+    if token.is_empty() {
+        return Err(PlatformError::FileError(format!(
+            "Token file {} is empty",
+            path.display()
+        )));
+    }
+    Ok(token.to_string())
+}
+
+/// Check that a token file exists, is readable, and is private to its owner
+///
+/// On Unix a file that other users can access is refused, like SSH does for private keys.
+pub fn check_token_file(path: &Path) -> Result<()> {
+    let metadata = std::fs::metadata(path).map_err(|e| {
+        PlatformError::FileError(format!(
+            "Cannot access token file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    if !metadata.is_file() {
+        return Err(PlatformError::FileError(format!(
+            "Token file {} is not a file",
+            path.display()
+        )));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o077 != 0 {
+            return Err(PlatformError::FileError(format!(
+                "Token file {} is accessible by other users. Restrict it with `chmod 600 {}`",
+                path.display(),
+                path.display()
+            )));
+        }
+    }
+
+    std::fs::File::open(path).map_err(|e| {
+        PlatformError::FileError(format!(
+            "Token file {} is not readable: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_token_file(dir: &Path, content: &str) -> std::path::PathBuf {
+        let path = dir.join("token");
+        std::fs::write(&path, content).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn test_plain_token_is_unchanged() {
+        assert_eq!(resolve_token("glpat-abc").unwrap(), "glpat-abc");
+        assert_eq!(resolve_token("").unwrap(), "");
+        assert!(token_file("glpat-abc").is_none());
+    }
+
+    #[test]
+    fn test_resolve_token_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = write_token_file(temp_dir.path(), "glpat-abc\n");
+
+        let value = format!("file:{}", path.display());
+        assert_eq!(token_file(&value), Some(path.as_path()));
+        assert_eq!(resolve_token(&value).unwrap(), "glpat-abc");
+    }
+
+    #[test]
+    fn test_missing_or_empty_token_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = format!("file:{}", temp_dir.path().join("missing").display());
+        assert!(matches!(
+            resolve_token(&missing),
+            Err(PlatformError::FileError(_))
+        ));
+
+        let path = write_token_file(temp_dir.path(), "  \n");
+        let error = resolve_token(&format!("file:{}", path.display())).unwrap_err();
+        assert!(error.to_string().contains("is empty"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_token_file_readable_by_others_is_refused() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = write_token_file(temp_dir.path(), "glpat-abc");
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let error = check_token_file(&path).unwrap_err();
+        assert!(error.to_string().contains("chmod 600"));
+    }
+}
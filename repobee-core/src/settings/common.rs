@@ -1,7 +1,14 @@
-use super::enums::{DirectoryLayout, LmsUrlOption, MemberOption};
-use super::normalization::{normalize_string, normalize_url, Normalize};
+use super::enums::{
+    DirectoryLayout, ExtensionCheck, GitIdSource, LmsUrlOption, MemberOption, NameExtraction,
+    SurnameSource,
+};
+use super::normalization::{normalize_string, normalize_url, FileFormat, Normalize};
+use crate::error::Result;
+use crate::naming::{RepoNaming, DEFAULT_REPO_NAME_TEMPLATE};
+use crate::secrets::token_file;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Common settings shared between GUI and CLI
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -23,12 +30,23 @@ pub struct CommonSettings {
     #[serde(default)]
     pub lms_access_token: String,
 
+    /// Moodle site; the custom URL is used while this is empty
+    #[serde(default)]
+    pub moodle_base_url: String,
+
+    /// Moodle web service token; the LMS access token is used while this is empty
+    #[serde(default)]
+    pub moodle_access_token: String,
+
     #[serde(default)]
     pub lms_course_id: String,
 
     #[serde(default)]
     pub lms_course_name: String,
 
+    #[serde(default)]
+    pub lms_course_term: String, // Substituted for {term} in git_org_template
+
     #[serde(default = "defaults::lms_yaml_file")]
     pub lms_yaml_file: String,
 
@@ -44,6 +62,18 @@ pub struct CommonSettings {
     #[serde(default = "defaults::lms_member_option")]
     pub lms_member_option: MemberOption, // EmailAndGitId, Email, GitId
 
+    #[serde(default = "defaults::lms_git_id_source")]
+    pub lms_git_id_source: GitIdSource, // SisUserId, LoginId, EmailLocalPart
+
+    #[serde(default = "defaults::lms_surname_source")]
+    pub lms_surname_source: SurnameSource, // SortableName, Email
+
+    #[serde(default)]
+    pub lms_name_extraction: NameExtraction, // AfterLastDot, BeforeFirstDot, FullLocalPart, Custom
+
+    #[serde(default = "defaults::lms_include_roles")]
+    pub lms_include_roles: Vec<String>, // Canvas enrollment roles, e.g. TaEnrollment
+
     #[serde(default = "defaults::lms_include_group")]
     pub lms_include_group: bool,
 
@@ -56,6 +86,9 @@ pub struct CommonSettings {
     #[serde(default = "defaults::lms_full_groups")]
     pub lms_full_groups: bool,
 
+    #[serde(default)]
+    pub lms_include_test_students: bool,
+
     #[serde(default)]
     pub lms_output_csv: bool,
 
@@ -78,9 +111,30 @@ pub struct CommonSettings {
     #[serde(default)]
     pub git_student_repos_group: String,
 
+    #[serde(default)]
+    pub git_org_template: String, // e.g. {course_code}-{term}, fills git_student_repos_group
+
     #[serde(default)]
     pub git_template_group: String,
 
+    #[serde(default = "defaults::git_private_repos")]
+    pub git_private_repos: bool,
+
+    #[serde(default)]
+    pub git_public_fallback: bool, // Create public repos when private ones are not allowed
+
+    #[serde(default)]
+    pub git_max_network_operations: u32, // Crate-wide cap on clones, fetches and pushes, 0 = no cap
+
+    #[serde(default)]
+    pub git_max_kib_per_second: u32, // Combined transfer rate of git operations, 0 = unlimited
+
+    #[serde(default)]
+    pub http_headers: BTreeMap<String, String>, // Sent on LMS and git platform API requests
+
+    #[serde(default)]
+    pub http_user_agent: String, // Replaces the repobee-tauri/<version> user agent when set
+
     // ===== Repository Setup Settings =====
     #[serde(default = "defaults::yaml_file")]
     pub yaml_file: String,
@@ -94,6 +148,12 @@ pub struct CommonSettings {
     #[serde(default = "defaults::directory_layout")]
     pub directory_layout: DirectoryLayout, // ByTeam, Flat, ByTask
 
+    #[serde(default = "defaults::repo_name_template")]
+    pub repo_name_template: String, // {team} and {assignment} placeholders, e.g. {team}-{assignment}
+
+    #[serde(default)]
+    pub file_extension_check: ExtensionCheck, // Warn, Fix, Ignore
+
     // ===== Logging Settings =====
     #[serde(default = "defaults::log_info")]
     pub log_info: bool,
@@ -119,17 +179,25 @@ impl Default for CommonSettings {
             lms_custom_url: String::new(),
             lms_url_option: defaults::lms_url_option(),
             lms_access_token: String::new(),
+            moodle_base_url: String::new(),
+            moodle_access_token: String::new(),
             lms_course_id: String::new(),
             lms_course_name: String::new(),
+            lms_course_term: String::new(),
             lms_yaml_file: defaults::lms_yaml_file(),
             lms_info_folder: String::new(),
             lms_csv_file: defaults::lms_csv_file(),
             lms_xlsx_file: defaults::lms_xlsx_file(),
             lms_member_option: defaults::lms_member_option(),
+            lms_git_id_source: defaults::lms_git_id_source(),
+            lms_surname_source: defaults::lms_surname_source(),
+            lms_name_extraction: NameExtraction::default(),
+            lms_include_roles: defaults::lms_include_roles(),
             lms_include_group: defaults::lms_include_group(),
             lms_include_member: defaults::lms_include_member(),
             lms_include_initials: false,
             lms_full_groups: defaults::lms_full_groups(),
+            lms_include_test_students: false,
             lms_output_csv: false,
             lms_output_xlsx: false,
             lms_output_yaml: defaults::lms_output_yaml(),
@@ -139,13 +207,22 @@ impl Default for CommonSettings {
             git_access_token: String::new(),
             git_user: String::new(),
             git_student_repos_group: String::new(),
+            git_org_template: String::new(),
             git_template_group: String::new(),
+            git_private_repos: defaults::git_private_repos(),
+            git_public_fallback: false,
+            git_max_network_operations: 0,
+            git_max_kib_per_second: 0,
+            http_headers: BTreeMap::new(),
+            http_user_agent: String::new(),
 
             // Repository setup settings
             yaml_file: defaults::yaml_file(),
             target_folder: String::new(),
             assignments: String::new(),
             directory_layout: defaults::directory_layout(),
+            repo_name_template: defaults::repo_name_template(),
+            file_extension_check: ExtensionCheck::default(),
 
             // Logging settings
             log_info: defaults::log_info(),
@@ -158,7 +235,10 @@ impl Default for CommonSettings {
 
 /// Default values for settings
 mod defaults {
-    use super::{DirectoryLayout, LmsUrlOption, MemberOption};
+    use super::{
+        DirectoryLayout, GitIdSource, LmsUrlOption, MemberOption, SurnameSource,
+        DEFAULT_REPO_NAME_TEMPLATE,
+    };
 
     pub fn lms_type() -> String {
         "Canvas".to_string()
@@ -188,6 +268,18 @@ mod defaults {
         MemberOption::EmailAndGitId
     }
 
+    pub fn lms_git_id_source() -> GitIdSource {
+        GitIdSource::SisUserId
+    }
+
+    pub fn lms_surname_source() -> SurnameSource {
+        SurnameSource::SortableName
+    }
+
+    pub fn lms_include_roles() -> Vec<String> {
+        vec!["StudentEnrollment".to_string()]
+    }
+
     pub fn lms_include_group() -> bool {
         true
     }
@@ -208,6 +300,10 @@ mod defaults {
         "https://gitlab.tue.nl".to_string()
     }
 
+    pub fn git_private_repos() -> bool {
+        true
+    }
+
     pub fn yaml_file() -> String {
         "students.yaml".to_string()
     }
@@ -216,6 +312,10 @@ mod defaults {
         DirectoryLayout::Flat
     }
 
+    pub fn repo_name_template() -> String {
+        DEFAULT_REPO_NAME_TEMPLATE.to_string()
+    }
+
     pub fn log_info() -> bool {
         true
     }
@@ -229,6 +329,112 @@ mod defaults {
     }
 }
 
+impl CommonSettings {
+    /// Reset the fields that belong to one course, for moving on to the next course
+    ///
+    /// The course, its output files, the include flags and the student repository settings
+    /// go back to their defaults. Platforms, base URLs, tokens, the git user and preferences
+    /// such as the member option and logging are kept.
+    pub fn reset_course_settings(&mut self) {
+        let defaults = Self::default();
+
+        // Course
+        self.lms_course_id = defaults.lms_course_id;
+        self.lms_course_name = defaults.lms_course_name;
+        self.lms_course_term = defaults.lms_course_term;
+
+        // Output files
+        self.lms_yaml_file = defaults.lms_yaml_file;
+        self.lms_info_folder = defaults.lms_info_folder;
+        self.lms_csv_file = defaults.lms_csv_file;
+        self.lms_xlsx_file = defaults.lms_xlsx_file;
+        self.lms_output_csv = defaults.lms_output_csv;
+        self.lms_output_xlsx = defaults.lms_output_xlsx;
+        self.lms_output_yaml = defaults.lms_output_yaml;
+
+        // Include flags
+        self.lms_include_roles = defaults.lms_include_roles;
+        self.lms_include_group = defaults.lms_include_group;
+        self.lms_include_member = defaults.lms_include_member;
+        self.lms_include_initials = defaults.lms_include_initials;
+        self.lms_full_groups = defaults.lms_full_groups;
+        self.lms_include_test_students = defaults.lms_include_test_students;
+
+        // Student repositories of the course
+        self.git_student_repos_group = defaults.git_student_repos_group;
+        self.yaml_file = defaults.yaml_file;
+        self.target_folder = defaults.target_folder;
+        self.assignments = defaults.assignments;
+    }
+
+    /// Naming scheme of the student repositories, shared by every operation on them
+    pub fn repo_naming(&self) -> Result<RepoNaming> {
+        RepoNaming::new(self.repo_name_template.as_str())
+    }
+
+    /// Copy for showing the settings, with the access tokens replaced by `***`
+    ///
+    /// `file:` references are kept, they only tell where the token is stored.
+    pub fn redacted(&self) -> Self {
+        let mut settings = self.clone();
+        for token in [
+            &mut settings.lms_access_token,
+            &mut settings.git_access_token,
+        ] {
+            if !token.is_empty() && token_file(token).is_none() {
+                *token = "***".to_string();
+            }
+        }
+        settings
+    }
+
+    /// File settings with the format of their contents
+    fn output_files(&self) -> [(&'static str, &String, FileFormat); 4] {
+        [
+            ("lms_yaml_file", &self.lms_yaml_file, FileFormat::Yaml),
+            ("lms_csv_file", &self.lms_csv_file, FileFormat::Csv),
+            ("lms_xlsx_file", &self.lms_xlsx_file, FileFormat::Xlsx),
+            ("yaml_file", &self.yaml_file, FileFormat::Yaml),
+        ]
+    }
+
+    /// Warnings for files whose extension does not match their format
+    ///
+    /// Only reported with [`ExtensionCheck::Warn`]; with [`ExtensionCheck::Fix`]
+    /// normalization corrects the file names instead.
+    pub fn extension_warnings(&self) -> Vec<String> {
+        if self.file_extension_check != ExtensionCheck::Warn {
+            return Vec::new();
+        }
+
+        self.output_files()
+            .into_iter()
+            .filter(|(_, file, format)| !file.is_empty() && !format.matches(file))
+            .map(|(field, file, format)| {
+                format!(
+                    "{}: {} does not have the .{} extension of a {} file",
+                    field,
+                    file,
+                    format.extensions()[0],
+                    format
+                )
+            })
+            .collect()
+    }
+
+    /// Give the files the extension of their format
+    fn fix_extensions(&mut self) {
+        for (file, format) in [
+            (&mut self.lms_yaml_file, FileFormat::Yaml),
+            (&mut self.lms_csv_file, FileFormat::Csv),
+            (&mut self.lms_xlsx_file, FileFormat::Xlsx),
+            (&mut self.yaml_file, FileFormat::Yaml),
+        ] {
+            *file = format.with_extension(file);
+        }
+    }
+}
+
 impl Normalize for CommonSettings {
     fn normalize(&mut self) {
         // Normalize URL fields
@@ -240,6 +446,7 @@ impl Normalize for CommonSettings {
         normalize_string(&mut self.lms_access_token);
         normalize_string(&mut self.lms_course_id);
         normalize_string(&mut self.lms_course_name);
+        normalize_string(&mut self.lms_course_term);
         normalize_string(&mut self.lms_yaml_file);
         normalize_string(&mut self.lms_info_folder);
         normalize_string(&mut self.lms_csv_file);
@@ -247,9 +454,125 @@ impl Normalize for CommonSettings {
         normalize_string(&mut self.git_access_token);
         normalize_string(&mut self.git_user);
         normalize_string(&mut self.git_student_repos_group);
+        normalize_string(&mut self.git_org_template);
         normalize_string(&mut self.git_template_group);
         normalize_string(&mut self.yaml_file);
         normalize_string(&mut self.target_folder);
         normalize_string(&mut self.assignments);
+        normalize_string(&mut self.repo_name_template);
+
+        if self.file_extension_check == ExtensionCheck::Fix {
+            self.fix_extensions();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reset_course_settings_keeps_credentials() {
+        let mut settings = CommonSettings {
+            lms_base_url: "https://canvas.uni.nl".to_string(),
+            lms_access_token: "lms-token".to_string(),
+            lms_course_id: "123".to_string(),
+            lms_course_name: "Programming".to_string(),
+            lms_csv_file: "programming.csv".to_string(),
+            lms_full_groups: false,
+            lms_member_option: MemberOption::GitId,
+            git_base_url: "https://gitlab.uni.nl".to_string(),
+            git_access_token: "git-token".to_string(),
+            git_user: "teacher".to_string(),
+            git_student_repos_group: "programming-2024".to_string(),
+            assignments: "task1,task2".to_string(),
+            ..Default::default()
+        };
+
+        settings.reset_course_settings();
+
+        assert_eq!(settings.lms_base_url, "https://canvas.uni.nl");
+        assert_eq!(settings.lms_access_token, "lms-token");
+        assert_eq!(settings.git_base_url, "https://gitlab.uni.nl");
+        assert_eq!(settings.git_access_token, "git-token");
+        assert_eq!(settings.git_user, "teacher");
+        assert_eq!(settings.lms_member_option, MemberOption::GitId);
+
+        assert!(settings.lms_course_id.is_empty());
+        assert!(settings.lms_course_name.is_empty());
+        assert_eq!(settings.lms_csv_file, defaults::lms_csv_file());
+        assert!(settings.lms_full_groups);
+        assert!(settings.git_student_repos_group.is_empty());
+        assert!(settings.assignments.is_empty());
+    }
+
+    #[test]
+    fn test_redacted_hides_tokens() {
+        let settings = CommonSettings {
+            lms_access_token: "lms-token".to_string(),
+            git_access_token: "file:/home/teacher/.gitlab-token".to_string(),
+            git_user: "teacher".to_string(),
+            ..Default::default()
+        };
+
+        let redacted = settings.redacted();
+        assert_eq!(redacted.lms_access_token, "***");
+        assert_eq!(
+            redacted.git_access_token,
+            "file:/home/teacher/.gitlab-token"
+        );
+        assert_eq!(redacted.git_user, "teacher");
+        assert!(CommonSettings::default()
+            .redacted()
+            .git_access_token
+            .is_empty());
+    }
+
+    #[test]
+    fn test_extension_warnings() {
+        let mut settings = CommonSettings {
+            lms_yaml_file: "students.csv".to_string(),
+            lms_csv_file: "student-info.xlsx".to_string(),
+            lms_xlsx_file: "student-info".to_string(),
+            yaml_file: "students.yml".to_string(),
+            ..Default::default()
+        };
+
+        let warnings = settings.extension_warnings();
+        assert_eq!(warnings.len(), 3);
+        assert_eq!(
+            warnings[0],
+            "lms_yaml_file: students.csv does not have the .yaml extension of a YAML file"
+        );
+
+        settings.file_extension_check = ExtensionCheck::Ignore;
+        assert!(settings.extension_warnings().is_empty());
+        assert!(CommonSettings::default().extension_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_normalize_fixes_extensions() {
+        let settings = CommonSettings {
+            lms_yaml_file: "students.csv".to_string(),
+            lms_csv_file: "student-info.xlsx".to_string(),
+            lms_xlsx_file: "student-info".to_string(),
+            yaml_file: "students.yml".to_string(),
+            file_extension_check: ExtensionCheck::Fix,
+            ..Default::default()
+        }
+        .normalized();
+
+        assert_eq!(settings.lms_yaml_file, "students.yaml");
+        assert_eq!(settings.lms_csv_file, "student-info.csv");
+        assert_eq!(settings.lms_xlsx_file, "student-info.xlsx");
+        assert_eq!(settings.yaml_file, "students.yml");
+        assert!(settings.extension_warnings().is_empty());
+
+        let settings = CommonSettings {
+            lms_yaml_file: "students.csv".to_string(),
+            ..Default::default()
+        }
+        .normalized();
+        assert_eq!(settings.lms_yaml_file, "students.csv");
     }
 }
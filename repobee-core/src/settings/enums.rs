@@ -78,6 +78,135 @@ impl FromStr for MemberOption {
     }
 }
 
+/// LMS user field used as a student's git username
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GitIdSource {
+    /// SIS user id, falling back to the login id when it is not set
+    SisUserId,
+    LoginId,
+    /// Part of the email address before the `@`
+    EmailLocalPart,
+}
+
+impl Default for GitIdSource {
+    fn default() -> Self {
+        Self::SisUserId
+    }
+}
+
+impl fmt::Display for GitIdSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SisUserId => write!(f, "sis_user_id"),
+            Self::LoginId => write!(f, "login_id"),
+            Self::EmailLocalPart => write!(f, "email_local_part"),
+        }
+    }
+}
+
+impl FromStr for GitIdSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "sis_user_id" | "sis-user-id" | "sis" => Ok(Self::SisUserId),
+            "login_id" | "login-id" | "login" => Ok(Self::LoginId),
+            "email_local_part" | "email-local-part" | "email" => Ok(Self::EmailLocalPart),
+            _ => Err(format!("Unknown git id source: {}", s)),
+        }
+    }
+}
+
+/// LMS user field a student's surname is taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SurnameSource {
+    /// Part before the comma of the "Last, First" sortable name, falling back to the email
+    SortableName,
+    /// Last dot-separated part of the email address before the `@`
+    Email,
+}
+
+impl Default for SurnameSource {
+    fn default() -> Self {
+        Self::SortableName
+    }
+}
+
+impl fmt::Display for SurnameSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SortableName => write!(f, "sortable_name"),
+            Self::Email => write!(f, "email"),
+        }
+    }
+}
+
+impl FromStr for SurnameSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "sortable_name" | "sortable-name" | "sortable" => Ok(Self::SortableName),
+            "email" => Ok(Self::Email),
+            _ => Err(format!("Unknown surname source: {}", s)),
+        }
+    }
+}
+
+/// How a surname is extracted from an email address when it is not taken from the sortable
+/// name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NameExtraction {
+    /// `firstname.lastname@`: the part after the last dot of the local part
+    AfterLastDot,
+    /// `lastname.firstname@`: the part before the first dot of the local part
+    BeforeFirstDot,
+    /// `s1234567@` or `jdoe@`: the whole local part
+    FullLocalPart,
+    /// Regular expression matched against the local part; the first capture group, or the
+    /// whole match without one, is the surname
+    Custom(String),
+}
+
+impl Default for NameExtraction {
+    fn default() -> Self {
+        Self::AfterLastDot
+    }
+}
+
+impl fmt::Display for NameExtraction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AfterLastDot => write!(f, "after_last_dot"),
+            Self::BeforeFirstDot => write!(f, "before_first_dot"),
+            Self::FullLocalPart => write!(f, "full_local_part"),
+            Self::Custom(pattern) => write!(f, "custom:{}", pattern),
+        }
+    }
+}
+
+impl FromStr for NameExtraction {
+    type Err = String;
+
+    /// Parse a strategy name, or `custom:<regex>` for a custom pattern
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(pattern) = s.trim().strip_prefix("custom:") {
+            regex::Regex::new(pattern)
+                .map_err(|e| format!("Invalid name extraction pattern: {}", e))?;
+            return Ok(Self::Custom(pattern.to_string()));
+        }
+        match s.trim().to_lowercase().as_str() {
+            "after_last_dot" | "after-last-dot" => Ok(Self::AfterLastDot),
+            "before_first_dot" | "before-first-dot" => Ok(Self::BeforeFirstDot),
+            "full_local_part" | "full-local-part" => Ok(Self::FullLocalPart),
+            _ => Err(format!("Unknown name extraction: {}", s)),
+        }
+    }
+}
+
 /// Directory layout for cloned repositories
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
@@ -151,6 +280,46 @@ impl FromStr for ActiveTab {
     }
 }
 
+/// What to do when an output file name does not have the extension of its format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExtensionCheck {
+    /// Keep the file name and report the mismatch
+    Warn,
+    /// Replace or append the extension when the settings are normalized
+    Fix,
+    Ignore,
+}
+
+impl Default for ExtensionCheck {
+    fn default() -> Self {
+        Self::Warn
+    }
+}
+
+impl fmt::Display for ExtensionCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warn => write!(f, "warn"),
+            Self::Fix => write!(f, "fix"),
+            Self::Ignore => write!(f, "ignore"),
+        }
+    }
+}
+
+impl FromStr for ExtensionCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "warn" => Ok(Self::Warn),
+            "fix" => Ok(Self::Fix),
+            "ignore" => Ok(Self::Ignore),
+            _ => Err(format!("Unknown extension check: {}", s)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -186,6 +355,51 @@ mod tests {
         assert_eq!("git_id".parse::<MemberOption>().unwrap(), MemberOption::GitId);
     }
 
+    #[test]
+    fn test_git_id_source_from_str() {
+        assert_eq!(
+            "sis_user_id".parse::<GitIdSource>().unwrap(),
+            GitIdSource::SisUserId
+        );
+        assert_eq!("login_id".parse::<GitIdSource>().unwrap(), GitIdSource::LoginId);
+        assert_eq!(
+            "email_local_part".parse::<GitIdSource>().unwrap(),
+            GitIdSource::EmailLocalPart
+        );
+        assert!("student_number".parse::<GitIdSource>().is_err());
+    }
+
+    #[test]
+    fn test_surname_source_from_str() {
+        assert_eq!(
+            "sortable_name".parse::<SurnameSource>().unwrap(),
+            SurnameSource::SortableName
+        );
+        assert_eq!(
+            "email".parse::<SurnameSource>().unwrap(),
+            SurnameSource::Email
+        );
+        assert_eq!(SurnameSource::default(), SurnameSource::SortableName);
+        assert!("full_name".parse::<SurnameSource>().is_err());
+    }
+
+    #[test]
+    fn test_name_extraction_from_str() {
+        assert_eq!(
+            "before-first-dot".parse::<NameExtraction>().unwrap(),
+            NameExtraction::BeforeFirstDot
+        );
+        let custom = "custom:^s(\\d+)$".parse::<NameExtraction>().unwrap();
+        assert_eq!(custom, NameExtraction::Custom("^s(\\d+)$".to_string()));
+        assert_eq!(
+            custom.to_string().parse::<NameExtraction>().unwrap(),
+            custom
+        );
+        assert_eq!(NameExtraction::default(), NameExtraction::AfterLastDot);
+        assert!("custom:(unclosed".parse::<NameExtraction>().is_err());
+        assert!("last_word".parse::<NameExtraction>().is_err());
+    }
+
     #[test]
     fn test_directory_layout_display() {
         assert_eq!(DirectoryLayout::ByTeam.to_string(), "by-team");
@@ -223,4 +437,24 @@ mod tests {
         let tab: ActiveTab = serde_json::from_str("\"repo\"").unwrap();
         assert_eq!(tab, ActiveTab::Repo);
     }
+
+    #[test]
+    fn test_extension_check_from_str() {
+        assert_eq!(
+            "warn".parse::<ExtensionCheck>().unwrap(),
+            ExtensionCheck::Warn
+        );
+        assert_eq!(
+            " FIX ".parse::<ExtensionCheck>().unwrap(),
+            ExtensionCheck::Fix
+        );
+        assert_eq!(
+            "ignore".parse::<ExtensionCheck>().unwrap(),
+            ExtensionCheck::Ignore
+        );
+        assert!("append".parse::<ExtensionCheck>().is_err());
+
+        let json = serde_json::to_string(&ExtensionCheck::Fix).unwrap();
+        assert_eq!(json, "\"fix\"");
+    }
 }
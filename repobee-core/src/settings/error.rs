@@ -119,11 +119,71 @@ impl ConfigError {
                         .join("\n")
                 )
             }
+            (ConfigError::ReadError { path, source }, _) => {
+                let hint = match source.kind() {
+                    std::io::ErrorKind::NotFound => "The file does not exist.",
+                    std::io::ErrorKind::PermissionDenied => {
+                        "You do not have permission to read it."
+                    }
+                    _ => "Check that the file exists and can be read.",
+                };
+                format!(
+                    "Could not read the settings file {}: {}\n{}",
+                    path.display(),
+                    source,
+                    hint
+                )
+            }
+            (ConfigError::WriteError { path, source }, _) => {
+                format!(
+                    "Could not save the settings to {}: {}\n\
+                     Check that the folder exists and that you can write to it.",
+                    path.display(),
+                    source
+                )
+            }
+            (ConfigError::JsonParseError { path, source }, interface) => {
+                let problem = if source.is_data() {
+                    "has a setting with a value of the wrong type"
+                } else {
+                    "is not valid JSON"
+                };
+                let fix = match interface {
+                    Interface::CLI => "Fix the file, or run `settings reset` to start over.",
+                    Interface::GUI => "Fix the file, or reset the settings to start over.",
+                };
+                format!(
+                    "The settings file {} {} (line {}, column {}): {}\n{}",
+                    path.display(),
+                    problem,
+                    source.line(),
+                    source.column(),
+                    json_error_reason(source),
+                    fix
+                )
+            }
+            (ConfigError::CreateDirError { path, source }, _) => {
+                format!(
+                    "Could not create the folder {}: {}\n\
+                     Check that you can write to its parent folder.",
+                    path.display(),
+                    source
+                )
+            }
             _ => self.to_string(),
         }
     }
 }
 
+/// Message of a JSON error without the position that serde_json appends
+fn json_error_reason(error: &serde_json::Error) -> String {
+    let message = error.to_string();
+    match message.rfind(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message,
+    }
+}
+
 /// Interface type for context-specific error messages
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Interface {
@@ -255,6 +315,41 @@ mod tests {
         assert!(cli_message.contains("Validation failed"));
     }
 
+    #[test]
+    fn test_user_message_json_parse_error_has_position() {
+        let source =
+            serde_json::from_str::<serde_json::Value>("{\n  \"git_user\": \n}").unwrap_err();
+        let error = ConfigError::JsonParseError {
+            path: PathBuf::from("/path/to/repobee.json"),
+            source,
+        };
+
+        let message = error.user_message(Interface::GUI);
+        assert!(message.contains("/path/to/repobee.json is not valid JSON"));
+        assert!(message.contains("(line 3, column 1)"));
+        assert!(!message.contains("at line"));
+
+        let source = serde_json::from_str::<Vec<u32>>("[1, \"two\"]").unwrap_err();
+        let error = ConfigError::JsonParseError {
+            path: PathBuf::from("/path/to/repobee.json"),
+            source,
+        };
+        assert!(error
+            .user_message(Interface::CLI)
+            .contains("has a setting with a value of the wrong type"));
+    }
+
+    #[test]
+    fn test_user_message_read_error_explains_cause() {
+        let error = ConfigError::ReadError {
+            path: PathBuf::from("/path/to/repobee.json"),
+            source: std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied"),
+        };
+        let message = error.user_message(Interface::GUI);
+        assert!(message.contains("/path/to/repobee.json"));
+        assert!(message.contains("permission to read"));
+    }
+
     #[test]
     fn test_config_error_from_io_error() {
         let path = PathBuf::from("/test/path.json");
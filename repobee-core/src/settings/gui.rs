@@ -4,6 +4,16 @@ use super::normalization::Normalize;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+/// Maximum number of courses kept in [`GuiSettings::recent_courses`]
+pub const MAX_RECENT_COURSES: usize = 8;
+
+/// A course that was verified before, offered for quickly switching courses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RecentCourse {
+    pub id: String,
+    pub name: String,
+}
+
 /// GUI-specific settings (extends CommonSettings)
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GuiSettings {
@@ -32,6 +42,10 @@ pub struct GuiSettings {
 
     #[serde(default)]
     pub window_y: i32,
+
+    /// Recently verified courses, most recent first
+    #[serde(default)]
+    pub recent_courses: Vec<RecentCourse>,
 }
 
 impl Default for GuiSettings {
@@ -45,6 +59,7 @@ impl Default for GuiSettings {
             window_height: 0,
             window_x: 0,
             window_y: 0,
+            recent_courses: Vec::new(),
         }
     }
 }
@@ -62,6 +77,30 @@ impl GuiSettings {
             ..Default::default()
         }
     }
+
+    /// Copy for showing the settings, see [`CommonSettings::redacted`]
+    pub fn redacted(&self) -> Self {
+        Self {
+            common: self.common.redacted(),
+            ..self.clone()
+        }
+    }
+
+    /// Put a course at the front of the recent courses
+    ///
+    /// An earlier entry with the same id is replaced, and the list is kept at
+    /// [`MAX_RECENT_COURSES`] entries.
+    pub fn remember_course(&mut self, id: &str, name: &str) {
+        self.recent_courses.retain(|course| course.id != id);
+        self.recent_courses.insert(
+            0,
+            RecentCourse {
+                id: id.to_string(),
+                name: name.to_string(),
+            },
+        );
+        self.recent_courses.truncate(MAX_RECENT_COURSES);
+    }
 }
 
 mod defaults {
@@ -81,3 +120,30 @@ impl Normalize for GuiSettings {
         // No string normalization needed for GUI-only fields currently
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remember_course() {
+        let mut settings = GuiSettings::default();
+        settings.remember_course("1", "Programming");
+        settings.remember_course("2", "Databases");
+        settings.remember_course("1", "Programming 2024");
+
+        let ids: Vec<&str> = settings
+            .recent_courses
+            .iter()
+            .map(|c| c.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["1", "2"]);
+        assert_eq!(settings.recent_courses[0].name, "Programming 2024");
+
+        for i in 0..20 {
+            settings.remember_course(&i.to_string(), "Course");
+        }
+        assert_eq!(settings.recent_courses.len(), MAX_RECENT_COURSES);
+        assert_eq!(settings.recent_courses[0].id, "19");
+    }
+}
@@ -98,7 +98,14 @@ impl SettingsManager {
         let mut errors = Vec::new();
         if let Err(validation_errors) = compiled.validate(json_value) {
             for error in validation_errors {
-                errors.push(format!("{} at {}", error, error.instance_path));
+                // Name the offending field the way `ValidationErrors::add_field` does
+                let pointer = error.instance_path.to_string();
+                let field = pointer.trim_start_matches('/').replace('/', ".");
+                if field.is_empty() {
+                    errors.push(error.to_string());
+                } else {
+                    errors.push(format!("{}: {}", field, error));
+                }
             }
         }
 
@@ -148,6 +155,10 @@ impl SettingsManager {
         // Validate the settings
         settings.validate()?;
 
+        for warning in settings.common.extension_warnings() {
+            log::warn!("{}", warning);
+        }
+
         Ok(settings)
     }
 
@@ -250,6 +261,26 @@ impl SettingsManager {
         Ok(settings)
     }
 
+    /// Reset the course-specific settings, keeping URLs and tokens
+    ///
+    /// See [`CommonSettings::reset_course_settings`].
+    pub fn reset_course_settings(&self) -> ConfigResult<GuiSettings> {
+        let mut settings = self.load()?;
+        settings.common.reset_course_settings();
+        self.save(&settings)?;
+        Ok(settings)
+    }
+
+    /// Add a course to the recent courses in the saved settings
+    ///
+    /// See [`GuiSettings::remember_course`].
+    pub fn remember_course(&self, id: &str, name: &str) -> ConfigResult<GuiSettings> {
+        let mut settings = self.load()?;
+        settings.remember_course(id, name);
+        self.save(&settings)?;
+        Ok(settings)
+    }
+
     /// Get the path to the settings file
     pub fn settings_file_path(&self) -> PathBuf {
         self.config_dir.join("repobee.json")
@@ -552,6 +583,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_invalid_member_option_fails_validation() {
+        let manager = SettingsManager::new().unwrap();
+
+        let mut json_value = serde_json::to_value(GuiSettings::default()).unwrap();
+        json_value["lms_member_option"] = serde_json::json!("git_id");
+        assert!(manager.validate_settings(&json_value).unwrap().is_empty());
+
+        // A typo is reported instead of silently becoming "(email, gitid)"
+        json_value["lms_member_option"] = serde_json::json!("gitid ");
+        let errors = manager.validate_settings(&json_value).unwrap();
+        assert!(errors.iter().any(|e| e.contains("lms_member_option")));
+        assert!(serde_json::from_value::<GuiSettings>(json_value).is_err());
+    }
+
+    #[test]
+    fn test_invalid_directory_layout_fails_validation() {
+        let manager = SettingsManager::new().unwrap();
+
+        let mut json_value = serde_json::to_value(GuiSettings::default()).unwrap();
+        json_value["directory_layout"] = serde_json::json!("by-task");
+        assert!(manager.validate_settings(&json_value).unwrap().is_empty());
+
+        // A typo is reported instead of silently becoming the flat layout
+        json_value["directory_layout"] = serde_json::json!("by-tema");
+        let errors = manager.validate_settings(&json_value).unwrap();
+        assert!(errors.iter().any(|e| e.contains("directory_layout")));
+        assert!(serde_json::from_value::<GuiSettings>(json_value).is_err());
+    }
+
     // Note: Tests for save, save_to, and load_from behavior are omitted
     // because they require file system access to the user's config directory,
     // which causes permission issues in unit tests.
@@ -25,14 +25,17 @@ mod validation;
 pub use atomic::{atomic_write, atomic_write_json, atomic_write_string};
 pub use cli::CLIConfig;
 pub use common::CommonSettings;
-pub use enums::{ActiveTab, DirectoryLayout, LmsUrlOption, MemberOption};
+pub use enums::{
+    ActiveTab, DirectoryLayout, ExtensionCheck, GitIdSource, LmsUrlOption, MemberOption,
+    NameExtraction, SurnameSource,
+};
 pub use error::{ConfigError, ConfigResult, Interface};
-pub use gui::GuiSettings;
+pub use gui::{GuiSettings, RecentCourse, MAX_RECENT_COURSES};
 pub use location::{LocationManager, SettingsLocation};
 pub use manager::SettingsManager;
 pub use normalization::{
     join_comma_separated, normalize_path, normalize_paths, normalize_string, normalize_string_vec,
-    normalize_url, parse_comma_separated, path_to_posix_string, Normalize,
+    normalize_url, parse_comma_separated, path_to_posix_string, FileFormat, Normalize,
 };
 pub use validation::{
     validate_date, validate_date_range, validate_glob_pattern, validate_path,
@@ -85,6 +85,69 @@ pub fn normalize_url(url: &mut String) {
     *url = url.trim().trim_end_matches('/').to_string();
 }
 
+/// Format of a file that RepoBee reads or writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Yaml,
+    Csv,
+    Xlsx,
+    Json,
+}
+
+impl FileFormat {
+    const ALL: [FileFormat; 4] = [Self::Yaml, Self::Csv, Self::Xlsx, Self::Json];
+
+    /// Extensions of this format, the first one is used when correcting a file name
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::Yaml => &["yaml", "yml"],
+            Self::Csv => &["csv"],
+            Self::Xlsx => &["xlsx"],
+            Self::Json => &["json"],
+        }
+    }
+
+    /// Whether the file name has an extension of this format (case-insensitive)
+    pub fn matches(self, file: &str) -> bool {
+        file_extension(file).is_some_and(|ext| self.has_extension(&ext))
+    }
+
+    /// The file name with an extension of this format
+    ///
+    /// The extension of another format is replaced (`students.csv` becomes `students.yaml`),
+    /// any other extension is kept and the right one appended. Empty names are left alone.
+    pub fn with_extension(self, file: &str) -> String {
+        if file.is_empty() || self.matches(file) {
+            return file.to_string();
+        }
+
+        let stem = match file_extension(file) {
+            Some(ext) if Self::ALL.iter().any(|format| format.has_extension(&ext)) => {
+                &file[..file.len() - ext.len() - 1]
+            }
+            _ => file,
+        };
+        format!("{}.{}", stem, self.extensions()[0])
+    }
+
+    fn has_extension(self, ext: &str) -> bool {
+        self.extensions().contains(&ext.to_lowercase().as_str())
+    }
+}
+
+impl std::fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.extensions()[0].to_uppercase())
+    }
+}
+
+/// Extension of the file name, without the dot
+fn file_extension(file: &str) -> Option<String> {
+    Path::new(file)
+        .extension()
+        .map(|ext| ext.to_string_lossy().into_owned())
+}
+
 /// Clean and normalize boolean from various string representations
 pub fn parse_bool_flexible(s: &str) -> Result<bool, String> {
     match s.trim().to_lowercase().as_str() {
@@ -391,6 +454,56 @@ mod tests {
         assert_eq!(normalized.len(), 0);
     }
 
+    // ===== File Format Tests =====
+
+    #[test]
+    fn test_file_format_matches() {
+        assert!(FileFormat::Yaml.matches("students.yaml"));
+        assert!(FileFormat::Yaml.matches("students.YML"));
+        assert!(FileFormat::Csv.matches("out/student-info.csv"));
+        assert!(FileFormat::Xlsx.matches("student-info.xlsx"));
+        assert!(FileFormat::Json.matches("notify.json"));
+
+        assert!(!FileFormat::Yaml.matches("students.csv"));
+        assert!(!FileFormat::Csv.matches("student-info.xlsx"));
+        assert!(!FileFormat::Xlsx.matches("student-info.csv"));
+        assert!(!FileFormat::Json.matches("notify"));
+    }
+
+    #[test]
+    fn test_file_format_with_extension_replaces_other_format() {
+        assert_eq!(
+            FileFormat::Yaml.with_extension("students.csv"),
+            "students.yaml"
+        );
+        assert_eq!(FileFormat::Csv.with_extension("info.XLSX"), "info.csv");
+        assert_eq!(FileFormat::Xlsx.with_extension("info.json"), "info.xlsx");
+        assert_eq!(FileFormat::Json.with_extension("notify.yml"), "notify.json");
+    }
+
+    #[test]
+    fn test_file_format_with_extension_appends() {
+        assert_eq!(FileFormat::Yaml.with_extension("students"), "students.yaml");
+        assert_eq!(FileFormat::Csv.with_extension("info.2024"), "info.2024.csv");
+        assert_eq!(
+            FileFormat::Xlsx.with_extension("dir.v2/info"),
+            "dir.v2/info.xlsx"
+        );
+        assert_eq!(
+            FileFormat::Json.with_extension("notify.txt"),
+            "notify.txt.json"
+        );
+    }
+
+    #[test]
+    fn test_file_format_with_extension_keeps_matching_and_empty() {
+        assert_eq!(
+            FileFormat::Yaml.with_extension("students.yml"),
+            "students.yml"
+        );
+        assert_eq!(FileFormat::Csv.with_extension(""), "");
+    }
+
     // ===== Normalize Trait Tests =====
 
     #[test]
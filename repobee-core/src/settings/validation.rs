@@ -1,6 +1,8 @@
 use super::common::CommonSettings;
+use super::enums::NameExtraction;
 use super::error::{ConfigError, ConfigResult};
 use super::gui::GuiSettings;
+use crate::secrets::{check_token_file, token_file};
 use chrono::NaiveDate;
 use std::path::Path;
 
@@ -56,11 +58,38 @@ impl Validate for CommonSettings {
             errors.add_field("lms_custom_url", "must be a valid URL");
         }
 
+        if !self.moodle_base_url.is_empty() && !is_valid_url(&self.moodle_base_url) {
+            errors.add_field("moodle_base_url", "must be a valid URL");
+        }
+
+        if let NameExtraction::Custom(pattern) = &self.lms_name_extraction {
+            if let Err(e) = regex::Regex::new(pattern) {
+                errors.add_field("lms_name_extraction", &format!("invalid pattern: {}", e));
+            }
+        }
+
         // Validate Git settings
         if !self.git_base_url.is_empty() && !is_valid_url(&self.git_base_url) {
             errors.add_field("git_base_url", "must be a valid URL");
         }
 
+        if let Err(e) = self.repo_naming() {
+            errors.add_field("repo_name_template", &e.to_string());
+        }
+
+        // Tokens stored in a file must point to a usable file
+        for (field, value) in [
+            ("lms_access_token", &self.lms_access_token),
+            ("moodle_access_token", &self.moodle_access_token),
+            ("git_access_token", &self.git_access_token),
+        ] {
+            if let Some(path) = token_file(value) {
+                if let Err(e) = check_token_file(path) {
+                    errors.add_field(field, &e.to_string());
+                }
+            }
+        }
+
         errors.into_result(())
     }
 }
@@ -360,6 +389,31 @@ mod tests {
         assert!(settings.validate().is_err());
     }
 
+    #[test]
+    fn test_validate_common_settings_invalid_moodle_url() {
+        let mut settings = CommonSettings::default();
+        settings.moodle_base_url = "moodle.uni.nl".to_string();
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_common_settings_invalid_name_pattern() {
+        let mut settings = CommonSettings::default();
+        settings.lms_name_extraction = NameExtraction::Custom("(unclosed".to_string());
+        assert!(settings.validate().is_err());
+        settings.lms_name_extraction = NameExtraction::Custom("^([a-z]+)\\d*$".to_string());
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_common_settings_invalid_repo_name_template() {
+        let mut settings = CommonSettings::default();
+        settings.repo_name_template = "{team}".to_string();
+        assert!(settings.validate().is_err());
+        settings.repo_name_template = "2025-{assignment}_{team}".to_string();
+        assert!(settings.validate().is_ok());
+    }
+
     #[test]
     fn test_validate_common_settings_invalid_git_url() {
         let mut settings = CommonSettings::default();
@@ -391,6 +445,20 @@ mod tests {
         assert!(settings.validate().is_ok());
     }
 
+    #[test]
+    fn test_validate_common_settings_missing_token_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut settings = CommonSettings::default();
+        settings.git_access_token = format!("file:{}", temp_dir.path().join("token").display());
+
+        if let Err(ConfigError::InvalidConfig { errors }) = settings.validate() {
+            assert_eq!(errors.len(), 1);
+            assert!(errors[0].starts_with("git_access_token: "));
+        } else {
+            panic!("Expected InvalidConfig error for the missing token file");
+        }
+    }
+
     #[test]
     fn test_validate_gui_settings_default() {
         let settings = GuiSettings::default();
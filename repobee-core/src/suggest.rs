@@ -0,0 +1,65 @@
+//! Did-you-mean suggestions for mistyped names
+//!
+//! Used where a name has to match one of a known set, such as assignments and templates or
+//! platform names, so an error can point at the name that was probably meant.
+
+/// The candidate closest to `input`, if it is close enough to be a likely typo
+///
+/// Comparison ignores case. A candidate is close enough when at most a third of its
+/// characters (and at least one) have to be changed. Nothing is suggested when the input is
+/// one of the candidates.
+pub fn closest_match<'a, S: AsRef<str>>(input: &str, candidates: &'a [S]) -> Option<&'a str> {
+    let input = input.trim().to_lowercase();
+    if candidates
+        .iter()
+        .any(|candidate| candidate.as_ref().to_lowercase() == input)
+    {
+        return None;
+    }
+    candidates
+        .iter()
+        .map(AsRef::as_ref)
+        .map(|candidate| {
+            let distance = strsim::damerau_levenshtein(&input, &candidate.to_lowercase());
+            (distance, candidate)
+        })
+        .filter(|(distance, candidate)| *distance <= (candidate.chars().count() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// `" Did you mean 'x'?"` for the closest candidate, or an empty string, for appending to
+/// an error message
+pub fn did_you_mean<S: AsRef<str>>(input: &str, candidates: &[S]) -> String {
+    closest_match(input, candidates)
+        .map(|candidate| format!(" Did you mean '{}'?", candidate))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_match() {
+        let templates = ["task1", "task2", "project-intro"];
+        assert_eq!(closest_match("taks1", &templates), Some("task1"));
+        assert_eq!(
+            closest_match("Project-Itro", &templates),
+            Some("project-intro")
+        );
+        assert_eq!(closest_match("task1", &templates), None);
+        assert_eq!(closest_match("exam", &templates), None);
+        assert_eq!(closest_match::<&str>("task1", &[]), None);
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        let platforms = ["github", "gitlab", "gitea"];
+        assert_eq!(
+            did_you_mean("gitlba", &platforms),
+            " Did you mean 'gitlab'?"
+        );
+        assert_eq!(did_you_mean("bitbucket", &platforms), "");
+    }
+}
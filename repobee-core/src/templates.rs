@@ -0,0 +1,159 @@
+//! Discovering template repositories
+//!
+//! Templates are the repositories in the template group. On the Local platform the template
+//! group is a directory with one subdirectory per template, which is read directly.
+
+use crate::error::{PlatformError, Result};
+use crate::platform::PlatformAPI;
+use crate::suggest::closest_match;
+use crate::types::{RepoFile, RepoFileKind};
+use std::path::Path;
+
+/// List the names of the template repositories, sorted
+///
+/// `api` must be set up with the template group as its organization.
+pub async fn list_templates<P: PlatformAPI>(api: &P) -> Result<Vec<String>> {
+    let mut names: Vec<String> = api
+        .get_repos(None)
+        .await?
+        .into_iter()
+        .map(|repo| repo.name)
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Check that every assignment names one of the templates
+///
+/// Unknown assignments are reported together, each with the closest template name when it
+/// looks like a typo.
+pub fn check_assignments<S: AsRef<str>>(assignments: &[S], templates: &[String]) -> Result<()> {
+    let unknown: Vec<String> = assignments
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|assignment| !templates.iter().any(|t| t == assignment))
+        .map(|assignment| match closest_match(assignment, templates) {
+            Some(template) => format!("{} (did you mean '{}'?)", assignment, template),
+            None => assignment.to_string(),
+        })
+        .collect();
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(PlatformError::not_found(format!(
+            "No template for assignment(s) {}",
+            unknown.join(", ")
+        )))
+    }
+}
+
+/// List the templates in a local template directory, sorted
+///
+/// Every non-hidden subdirectory counts as a template.
+pub fn list_local_templates(template_dir: &Path) -> Result<Vec<String>> {
+    let entries = std::fs::read_dir(template_dir).map_err(|e| {
+        PlatformError::FileError(format!(
+            "Failed to read template directory {}: {}",
+            template_dir.display(),
+            e
+        ))
+    })?;
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// List the top-level files of a local template, sorted by name
+///
+/// The `.git` directory is not part of the template content and is skipped.
+pub fn list_local_template_files(template_path: &Path) -> Result<Vec<RepoFile>> {
+    let entries = std::fs::read_dir(template_path).map_err(|e| {
+        PlatformError::FileError(format!(
+            "Failed to read template {}: {}",
+            template_path.display(),
+            e
+        ))
+    })?;
+
+    let mut files = Vec::new();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        if name == ".git" {
+            continue;
+        }
+        let kind = match entry.file_type() {
+            Ok(t) if t.is_symlink() => RepoFileKind::Symlink,
+            // Submodules have a `.git` file or directory of their own
+            Ok(t) if t.is_dir() && entry.path().join(".git").exists() => RepoFileKind::Submodule,
+            Ok(t) if t.is_dir() => RepoFileKind::Dir,
+            _ => RepoFileKind::File,
+        };
+        files.push(RepoFile::new(name, kind));
+    }
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_list_local_templates() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("task2")).unwrap();
+        fs::create_dir(temp_dir.path().join("task1")).unwrap();
+        fs::create_dir(temp_dir.path().join(".git")).unwrap();
+        fs::write(temp_dir.path().join("README.md"), "templates").unwrap();
+
+        let templates = list_local_templates(temp_dir.path()).unwrap();
+        assert_eq!(templates, vec!["task1", "task2"]);
+    }
+
+    #[test]
+    fn test_list_local_template_files() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join(".git")).unwrap();
+        fs::create_dir(temp_dir.path().join("src")).unwrap();
+        fs::write(temp_dir.path().join("README.md"), "# Task").unwrap();
+
+        let files = list_local_template_files(temp_dir.path()).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                RepoFile::new("README.md".to_string(), RepoFileKind::File),
+                RepoFile::new("src".to_string(), RepoFileKind::Dir),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_local_templates_missing_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = list_local_templates(&temp_dir.path().join("missing"));
+        assert!(matches!(result, Err(PlatformError::FileError(_))));
+    }
+
+    #[test]
+    fn test_check_assignments() {
+        let templates = vec!["task1".to_string(), "task2".to_string()];
+        assert!(check_assignments(&["task1", "task2"], &templates).is_ok());
+
+        let error = check_assignments(&["taks1", "task2", "exam"], &templates).unwrap_err();
+        assert!(matches!(error, PlatformError::NotFound(_)));
+        assert_eq!(
+            error.to_string(),
+            "Resource not found: No template for assignment(s) taks1 (did you mean 'task1'?), exam"
+        );
+    }
+}
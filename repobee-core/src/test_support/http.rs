@@ -0,0 +1,231 @@
+//! Mock HTTP server for tests of the LMS, platform and git clients
+//!
+//! The server runs on its own threads, so it serves blocking clients like git2 as well as
+//! async ones. Every connection carries one request and is closed after the response.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Request received by a [`MockServer`]
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    /// Path with the query string
+    pub path: String,
+    /// Headers by lowercase name
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl Request {
+    /// Value of a header, by case-insensitive name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    /// Method and path, e.g. `GET /api/v1/courses/42/users?per_page=100`
+    pub fn request_line(&self) -> String {
+        format!("{} {}", self.method, self.path)
+    }
+}
+
+/// Canned response of a [`MockServer`]
+///
+/// `{base}` in a header is replaced by the server's base URL, for `Link` headers.
+#[derive(Debug, Clone)]
+pub struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Response {
+    /// 200 response with a JSON body
+    pub fn json(body: impl Into<String>) -> Self {
+        Self::status(200)
+            .header("Content-Type", "application/json")
+            .body(body)
+    }
+
+    /// Response with an empty body
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
+
+    /// Add a header, replacing an earlier one with the same name
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Link to the next page, `path` being relative to the server
+    pub fn next_page(self, path: &str) -> Self {
+        self.header("Link", &format!("<{{base}}{}>; rel=\"next\"", path))
+    }
+
+    fn write_to(&self, stream: &mut TcpStream, base_url: &str) {
+        let mut response = format!("HTTP/1.1 {} {}\r\n", self.status, reason(self.status));
+        for (name, value) in &self.headers {
+            let value = value.replace("{base}", base_url);
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str(&format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.body.len(),
+            self.body
+        ));
+        // The client may hang up early, e.g. after a rejected request
+        let _ = stream.write_all(response.as_bytes());
+    }
+}
+
+/// Local HTTP server answering with canned responses and recording every request
+pub struct MockServer {
+    url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    /// Answer the connections one at a time with `responses`, in order
+    ///
+    /// The server stops once every response is sent.
+    pub fn sequence(responses: Vec<Response>) -> Self {
+        let (listener, server) = Self::bind();
+        let (base_url, requests) = (server.url.clone(), server.requests.clone());
+        thread::spawn(move || {
+            for response in responses {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    return;
+                };
+                requests.lock().unwrap().push(read_request(&stream));
+                response.write_to(&mut stream, &base_url);
+            }
+        });
+        server
+    }
+
+    /// Answer every request with the response `handler` picks for it
+    ///
+    /// Connections are handled concurrently, each on its own thread.
+    pub fn route<F>(handler: F) -> Self
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        let (listener, server) = Self::bind();
+        let (base_url, requests) = (server.url.clone(), server.requests.clone());
+        let handler = Arc::new(handler);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    return;
+                };
+                let (base_url, requests, handler) =
+                    (base_url.clone(), requests.clone(), handler.clone());
+                thread::spawn(move || {
+                    let request = read_request(&stream);
+                    requests.lock().unwrap().push(request.clone());
+                    handler(&request).write_to(&mut stream, &base_url);
+                });
+            }
+        });
+        server
+    }
+
+    fn bind() -> (TcpListener, Self) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = Self {
+            url,
+            requests: Arc::default(),
+        };
+        (listener, server)
+    }
+
+    /// Base URL of the server, without a trailing slash
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Port the server listens on
+    pub fn port(&self) -> u16 {
+        self.url.rsplit(':').next().unwrap().parse().unwrap()
+    }
+
+    /// Requests received so far, in the order they arrived
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Method and path of the requests received so far
+    pub fn request_lines(&self) -> Vec<String> {
+        self.requests().iter().map(Request::request_line).collect()
+    }
+}
+
+/// Base URL that nothing listens on, for connection errors
+pub fn closed_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+fn read_request(stream: &TcpStream) -> Request {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap() == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let content_length = headers
+        .get("content-length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).unwrap();
+    Request {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        422 => "Unprocessable Entity",
+        429 => "Too Many Requests",
+        _ => "Status",
+    }
+}
@@ -0,0 +1,3 @@
+//! Helpers shared by the unit tests of several modules
+
+pub mod http;
@@ -0,0 +1,196 @@
+//! Crate-wide throttle for git network operations
+//!
+//! Clones, fetches and pushes from setup and clone all pass through one global throttle,
+//! so a large class does not saturate a shared network. It caps the number of network
+//! operations running at the same time, and the combined transfer rate of those
+//! operations. Both limits are off until [`set_git_throttle`] is called, usually with
+//! [`GitThrottle::from_settings`].
+//!
+//! Git LFS transfers run in a separate `git lfs` process: they take a slot, but their
+//! rate is not limited.
+//!
+//! The throttle sits below the per-operation concurrency such as
+//! [`CloneOptions::concurrency`](crate::CloneOptions::concurrency): a clone run starts up
+//! to that many tasks, but each task waits for a free slot here before touching the
+//! network. The effective parallelism is therefore the smaller of the two, and it is
+//! shared with every other operation running in the same process.
+
+use crate::settings::CommonSettings;
+use git2::RemoteCallbacks;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Limits applied to all git network operations, 0 meaning unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GitThrottle {
+    /// Maximum number of clones, fetches and pushes running at the same time
+    pub max_operations: u32,
+    /// Maximum combined transfer rate in KiB per second
+    pub max_kib_per_second: u32,
+}
+
+impl GitThrottle {
+    /// Throttle configured by `git_max_network_operations` and `git_max_kib_per_second`
+    pub fn from_settings(settings: &CommonSettings) -> Self {
+        Self {
+            max_operations: settings.git_max_network_operations,
+            max_kib_per_second: settings.git_max_kib_per_second,
+        }
+    }
+}
+
+struct State {
+    throttle: GitThrottle,
+    running: u32,
+    /// When the bytes transferred so far are paid off at the configured rate
+    paid_until: Option<Instant>,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    throttle: GitThrottle {
+        max_operations: 0,
+        max_kib_per_second: 0,
+    },
+    running: 0,
+    paid_until: None,
+});
+static SLOT_FREED: Condvar = Condvar::new();
+
+fn state() -> std::sync::MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Replace the throttle for all later git network operations
+///
+/// Operations that are already running keep their slot; a lower limit takes effect as
+/// they finish.
+pub fn set_git_throttle(throttle: GitThrottle) {
+    let mut state = state();
+    state.throttle = throttle;
+    if throttle.max_kib_per_second == 0 {
+        state.paid_until = None;
+    }
+    drop(state);
+    SLOT_FREED.notify_all();
+}
+
+/// The throttle currently in effect
+pub fn git_throttle() -> GitThrottle {
+    state().throttle
+}
+
+/// A running git network operation, freeing its slot when dropped
+pub(crate) struct NetworkSlot(());
+
+impl Drop for NetworkSlot {
+    fn drop(&mut self) {
+        let mut state = state();
+        state.running = state.running.saturating_sub(1);
+        drop(state);
+        SLOT_FREED.notify_one();
+    }
+}
+
+/// Wait, blocking the thread, until another git network operation may start
+pub(crate) fn network_slot() -> NetworkSlot {
+    let mut state = state();
+    while state.throttle.max_operations > 0 && state.running >= state.throttle.max_operations {
+        state = SLOT_FREED.wait(state).unwrap_or_else(|e| e.into_inner());
+    }
+    state.running += 1;
+    NetworkSlot(())
+}
+
+/// Pause until `bytes` more fit in the configured transfer rate
+///
+/// Every operation books its bytes on the same schedule, so the rate is shared rather
+/// than granted to each operation. Idle time is not saved up for later bursts.
+fn pace(bytes: u64) {
+    let wait = {
+        let mut state = state();
+        let rate = u64::from(state.throttle.max_kib_per_second) * 1024;
+        if rate == 0 || bytes == 0 {
+            return;
+        }
+        let now = Instant::now();
+        let start = state.paid_until.map_or(now, |paid| paid.max(now));
+        let end = start + Duration::from_secs_f64(bytes as f64 / rate as f64);
+        state.paid_until = Some(end);
+        end.saturating_duration_since(now)
+    };
+    if !wait.is_zero() {
+        thread::sleep(wait);
+    }
+}
+
+/// Slow down the transfers of a fetch or push to the configured rate
+///
+/// libgit2 stops reading from or writing to the connection while a progress callback
+/// runs, so sleeping in the callback throttles the transfer itself. A fetch is cancelled
+/// once `deadline` has passed.
+pub(crate) fn pace_transfers(callbacks: &mut RemoteCallbacks<'_>, deadline: Option<Instant>) {
+    let mut received = 0;
+    callbacks.transfer_progress(move |progress| {
+        let total = progress.received_bytes() as u64;
+        pace(total.saturating_sub(received));
+        received = total;
+        !deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    });
+    let mut sent = 0;
+    callbacks.push_transfer_progress(move |_current, _total, bytes| {
+        let total = bytes as u64;
+        pace(total.saturating_sub(sent));
+        sent = total;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    // The throttle is global, so one test covers it to keep parallel tests apart
+    #[test]
+    fn test_network_slots_and_pacing() {
+        set_git_throttle(GitThrottle {
+            max_operations: 2,
+            max_kib_per_second: 0,
+        });
+        let running = Arc::new(AtomicU32::new(0));
+        let peak = Arc::new(AtomicU32::new(0));
+        let workers: Vec<_> = (0..6)
+            .map(|_| {
+                let (running, peak) = (Arc::clone(&running), Arc::clone(&peak));
+                thread::spawn(move || {
+                    let _slot = network_slot();
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(20));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        // Clones in other tests may hold slots too, so only the cap is certain
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+
+        // 64 KiB at 256 KiB/s takes at least a quarter of a second, split or not
+        set_git_throttle(GitThrottle {
+            max_operations: 0,
+            max_kib_per_second: 256,
+        });
+        let start = Instant::now();
+        pace(32 * 1024);
+        pace(32 * 1024);
+        assert!(start.elapsed() >= Duration::from_millis(240));
+
+        set_git_throttle(GitThrottle::default());
+        let start = Instant::now();
+        pace(1024 * 1024);
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+}
@@ -81,6 +81,16 @@ impl IssueState {
     }
 }
 
+/// Kind of entry in a repository tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RepoFileKind {
+    File,
+    Dir,
+    Symlink,
+    Submodule,
+}
+
 // ============================================================================
 // Platform API Response Types (wrappers for platform-specific objects)
 // ============================================================================
@@ -156,6 +166,21 @@ impl Issue {
     }
 }
 
+/// Entry in the top-level tree of a repository
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoFile {
+    /// File or directory name
+    pub name: String,
+    /// Entry kind
+    pub kind: RepoFileKind,
+}
+
+impl RepoFile {
+    pub fn new(name: String, kind: RepoFileKind) -> Self {
+        Self { name, kind }
+    }
+}
+
 // ============================================================================
 // Local/User-facing Types
 // ============================================================================
@@ -167,6 +192,9 @@ pub struct StudentTeam {
     pub members: Vec<String>,
     /// Team name (defaults to members joined by "-" if empty)
     pub name: String,
+    /// Free-form remarks of the teacher, e.g. "merged after drop"; not used by setup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
 }
 
 impl StudentTeam {
@@ -178,6 +206,7 @@ impl StudentTeam {
         Self {
             members: sorted_members,
             name,
+            notes: None,
         }
     }
 
@@ -188,6 +217,7 @@ impl StudentTeam {
         Self {
             name,
             members: sorted_members,
+            notes: None,
         }
     }
 }
@@ -230,6 +260,13 @@ pub struct TemplateRepo {
     pub url: String,
     /// Local path to the repository (if cloned)
     pub path: Option<PathBuf>,
+    /// Default branch detected after cloning
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    /// Student repositories are created from this template by the platform, see
+    /// [`PlatformAPI::create_repo_from_template`](crate::PlatformAPI::create_repo_from_template)
+    #[serde(default)]
+    pub platform_template: bool,
 }
 
 impl TemplateRepo {
@@ -238,6 +275,8 @@ impl TemplateRepo {
             name,
             url,
             path: None,
+            default_branch: None,
+            platform_template: false,
         }
     }
 
@@ -246,3 +285,39 @@ impl TemplateRepo {
         self
     }
 }
+
+/// CI/CD variable to set on a student repository
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CiVariable {
+    /// Variable name
+    pub key: String,
+    /// Variable value
+    pub value: String,
+    /// Whether the platform should mask the value in job logs
+    pub masked: bool,
+}
+
+impl CiVariable {
+    pub fn new(key: String, value: String, masked: bool) -> Self {
+        Self { key, value, masked }
+    }
+
+    /// Value to show in logs and messages (masked values are redacted)
+    pub fn display_value(&self) -> &str {
+        if self.masked {
+            "[masked]"
+        } else {
+            &self.value
+        }
+    }
+}
+
+impl std::fmt::Debug for CiVariable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CiVariable")
+            .field("key", &self.key)
+            .field("value", &self.display_value())
+            .field("masked", &self.masked)
+            .finish()
+    }
+}
@@ -1,14 +1,29 @@
 use repobee_core::{
-    create_lms_client_with_params, generate_repobee_yaml_with_progress,
-    get_student_info_with_progress, get_token_generation_instructions, open_token_generation_url,
-    write_csv_file, write_yaml_file, FetchProgress, GuiSettings, LmsClientTrait, LmsCommonType,
-    LmsMemberOption, Platform, PlatformAPI, SettingsManager, StudentTeam, YamlConfig,
+    check_assignments, clone_student_repos_with_progress, course_name_differs,
+    create_lms_client_with_params, default_lms_cache_dir, default_work_dir, delete_single_repo,
+    did_you_mean, expected_repos, generate_repobee_yaml_with_progress, get_course_sections,
+    get_group_sets, get_student_info_by_sections_with_progress, get_student_info_with_progress,
+    get_token_generation_instructions, get_token_generation_url, git_token_generation_instructions,
+    git_token_generation_url, group_set_file, open_issue_in_repos_with_progress,
+    open_token_generation_url, org_from_course, parse_window_date, preview_repobee_yaml,
+    read_issue_body, read_teams_file, reconcile, repo_activity, resolve_dir, resolve_output_file,
+    resolve_token, sample_students, set_git_throttle, set_http_settings, trace_student,
+    validate_repobee_yaml, write_csv_file, write_xlsx_file, write_yaml_file, CachedLmsClient,
+    CanvasEnrollmentClient, CloneOptions, ConfigError, CourseDataSource, DirectoryLayout,
+    DistributionMode, EmptyTeamPolicy, EnrollmentRole, FetchProgress, GitIdSource, GitPlatformKind,
+    GitThrottle, GroupingSource, GuiSettings, Interface, IssueTemplate, LmsClientTrait,
+    LmsCommonType, MemberFormat, MemberOption, MoodleClient, NameExtraction, Platform, PlatformAPI,
+    PlatformError, PreflightReport, Progress, RecentCourse, RepoFile, RepoNaming, SettingsManager,
+    SetupOptions, StudentFetchOptions, StudentInfo, StudentTeam, SurnameSource, WorkDirCleanup,
+    YamlConfig, YamlPreview,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::io::{self, Write};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use tauri::ipc::Channel;
+use tauri_plugin_dialog::DialogExt;
 
 const PROGRESS_PREFIX: &str = "[PROGRESS]";
 
@@ -57,13 +72,28 @@ fn emit_inline_message(channel: &Channel<String>, state: &mut InlineCliState, me
     state.update(message);
 }
 
+/// Send a progress event of any operation: counters replace the previous counter line,
+/// everything else gets a line of its own
+fn emit_progress(channel: &Channel<String>, state: &Mutex<InlineCliState>, update: Progress) {
+    let message = update.to_string();
+    if let Ok(mut state) = state.lock() {
+        if update.is_inline() {
+            emit_inline_message(channel, &mut state, &message);
+        } else {
+            state.finalize();
+            emit_standard_message(channel, &message);
+        }
+    }
+}
+
 fn parse_lms_type(lms_type: &str) -> Result<LmsCommonType, String> {
     match lms_type {
         "Canvas" => Ok(LmsCommonType::Canvas),
         "Moodle" => Ok(LmsCommonType::Moodle),
         other => Err(format!(
-            "Unknown LMS type: {}. Supported: Canvas, Moodle",
-            other
+            "Unknown LMS type: {}.{} Supported: Canvas, Moodle",
+            other,
+            did_you_mean(other, &["Canvas", "Moodle"])
         )),
     }
 }
@@ -82,6 +112,14 @@ struct VerifyCourseParams {
     access_token: String,
     course_id: String,
     lms_type: String,
+    /// Template deriving the student repos group from the course, skipped when empty
+    #[serde(default)]
+    org_template: String,
+    #[serde(default)]
+    term: String,
+    /// Course name stored in the settings, compared with the name of the fetched course
+    #[serde(default)]
+    course_name: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -94,7 +132,8 @@ struct GenerateFilesParams {
     info_file_folder: String,
     csv_file: String,
     xlsx_file: String,
-    member_option: String,
+    /// `(email, gitid)`, `email` or `git_id`, as in the settings
+    member_option: MemberOption,
     include_group: bool,
     include_member: bool,
     include_initials: bool,
@@ -102,6 +141,69 @@ struct GenerateFilesParams {
     csv: bool,
     xlsx: bool,
     yaml: bool,
+    #[serde(default)]
+    grouping: GroupingSource,
+    #[serde(default)]
+    individual_teams: bool,
+    #[serde(default)]
+    team_name_template: Option<String>,
+    #[serde(default)]
+    git_id_source: GitIdSource,
+    /// Where surnames come from, the "Last, First" sortable name by default
+    #[serde(default)]
+    surname_source: SurnameSource,
+    /// How surnames are extracted from email addresses, e.g. `lastname.firstname@`
+    #[serde(default)]
+    name_extraction: NameExtraction,
+    /// Canvas enrollment roles to fetch, students only when empty
+    #[serde(default)]
+    include_roles: Vec<String>,
+    #[serde(default)]
+    member_format: MemberFormat,
+    /// Keep the Canvas "Test Student" account in the roster
+    #[serde(default)]
+    include_test_students: bool,
+    /// Fetch everything from the LMS instead of using the data cached by an earlier run
+    #[serde(default)]
+    refresh: bool,
+    /// Write one YAML file per Canvas group set instead of one for all groups
+    #[serde(default)]
+    per_group_set: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PreviewYamlParams {
+    /// `(email, gitid)`, `email` or `git_id`, as in the settings
+    member_option: MemberOption,
+    include_group: bool,
+    include_member: bool,
+    include_initials: bool,
+    full_groups: bool,
+    #[serde(default)]
+    grouping: GroupingSource,
+    #[serde(default)]
+    individual_teams: bool,
+    #[serde(default)]
+    team_name_template: Option<String>,
+    #[serde(default)]
+    member_format: MemberFormat,
+    /// Code for `{course_code}` in the team name template, `course` when not given
+    #[serde(default)]
+    course_code: Option<String>,
+    /// Students to preview, e.g. one fetched team; a built-in sample when empty
+    #[serde(default)]
+    students: Vec<StudentInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DebugStudentParams {
+    #[serde(flatten)]
+    lms: GenerateFilesParams,
+    /// Login id, git id, email or full name of the student
+    identifier: String,
+    /// Comma-separated assignments to derive repository names for
+    #[serde(default)]
+    assignments: String,
 }
 
 // Git platform related parameters
@@ -119,6 +221,30 @@ struct SetupParams {
     config: ConfigParams,
     yaml_file: String,
     assignments: String,
+    /// Directory for template clones, the user's cache directory when empty
+    #[serde(default)]
+    work_dir: String,
+    /// Remove the template working copies after a successful setup
+    #[serde(default)]
+    clean_work_dir: bool,
+    /// Create private repositories, the default when not given
+    #[serde(default)]
+    private: Option<bool>,
+    /// Create public repositories when the platform does not allow private ones
+    #[serde(default)]
+    public_fallback: bool,
+    /// Create repositories for teams without members instead of skipping those teams
+    #[serde(default)]
+    create_empty_teams: bool,
+    /// Let the platform create repositories from templates marked as such there
+    #[serde(default)]
+    platform_templates: bool,
+    /// Check after the setup that every new repository received the template content
+    #[serde(default)]
+    verify_pushes: bool,
+    /// Cancel the setup after this many minutes
+    #[serde(default)]
+    timeout_minutes: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -127,7 +253,55 @@ struct CloneParams {
     yaml_file: String,
     assignments: String,
     target_folder: String,
-    directory_layout: String,
+    directory_layout: DirectoryLayout,
+    /// Fast-forward repositories cloned before to their new commits
+    #[serde(default)]
+    update_existing: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ReconcileParams {
+    config: ConfigParams,
+    yaml_file: String,
+    assignments: String,
+    /// Only compare repositories whose name contains this text
+    #[serde(default)]
+    filter: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RepoActivityParams {
+    config: ConfigParams,
+    yaml_file: String,
+    assignments: String,
+    /// Setup date, repositories without activity after it are flagged
+    #[serde(default)]
+    since: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OpenIssueParams {
+    config: ConfigParams,
+    yaml_file: String,
+    assignment: String,
+    title: String,
+    #[serde(default)]
+    body: String,
+    /// Markdown file with the issue body, used instead of `body` when set
+    #[serde(default)]
+    body_file: String,
+    /// Values of `{{variable}}` placeholders next to the per-repository ones
+    #[serde(default)]
+    variables: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DeleteRepoParams {
+    config: ConfigParams,
+    name: String,
+    /// The repository name typed again by the user, the repository is only deleted when
+    /// it matches `name`
+    confirmation: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -139,6 +313,19 @@ struct CommandResult {
 
 // ===== Settings Commands =====
 
+/// Message for a settings error, naming the settings file for validation errors
+fn settings_error(action: &str, manager: &SettingsManager, error: ConfigError) -> String {
+    match error {
+        ConfigError::ValidationError { .. } | ConfigError::InvalidConfig { .. } => format!(
+            "{} ({}): {}",
+            action,
+            manager.settings_file_path().display(),
+            error.user_message(Interface::GUI)
+        ),
+        _ => format!("{}: {}", action, error.user_message(Interface::GUI)),
+    }
+}
+
 /// Load settings from disk
 #[tauri::command]
 async fn load_settings() -> Result<GuiSettings, String> {
@@ -147,7 +334,9 @@ async fn load_settings() -> Result<GuiSettings, String> {
 
     let settings = manager
         .load()
-        .map_err(|e| format!("Failed to load settings: {}", e))?;
+        .map_err(|e| settings_error("Failed to load settings", &manager, e))?;
+    set_git_throttle(GitThrottle::from_settings(&settings.common));
+    set_http_settings(&settings.common).map_err(|e| format!("Invalid HTTP settings: {}", e))?;
 
     Ok(settings)
 }
@@ -160,7 +349,9 @@ async fn save_settings(settings: GuiSettings) -> Result<(), String> {
 
     manager
         .save(&settings)
-        .map_err(|e| format!("Failed to save settings: {}", e))?;
+        .map_err(|e| settings_error("Failed to save settings", &manager, e))?;
+    set_git_throttle(GitThrottle::from_settings(&settings.common));
+    set_http_settings(&settings.common).map_err(|e| format!("Invalid HTTP settings: {}", e))?;
 
     Ok(())
 }
@@ -174,6 +365,21 @@ async fn reset_settings() -> Result<GuiSettings, String> {
     let settings = manager
         .reset()
         .map_err(|e| format!("Failed to reset settings: {}", e))?;
+    set_git_throttle(GitThrottle::from_settings(&settings.common));
+    set_http_settings(&settings.common).map_err(|e| format!("Invalid HTTP settings: {}", e))?;
+
+    Ok(settings)
+}
+
+/// Reset the course-specific settings, keeping URLs and tokens
+#[tauri::command]
+async fn reset_course_settings() -> Result<GuiSettings, String> {
+    let manager = SettingsManager::new()
+        .map_err(|e| format!("Failed to create settings manager: {}", e))?;
+
+    let settings = manager
+        .reset_course_settings()
+        .map_err(|e| format!("Failed to reset course settings: {}", e))?;
 
     Ok(settings)
 }
@@ -229,6 +435,19 @@ async fn get_settings_schema() -> Result<serde_json::Value, String> {
         .map_err(|e| format!("Failed to get schema: {}", e))
 }
 
+/// Settings in effect, with the tokens redacted, for troubleshooting
+#[tauri::command]
+async fn get_effective_settings() -> Result<GuiSettings, String> {
+    let manager = SettingsManager::new()
+        .map_err(|e| format!("Failed to create settings manager: {}", e))?;
+
+    let settings = manager
+        .load()
+        .map_err(|e| format!("Failed to load settings: {}", e))?;
+
+    Ok(settings.redacted())
+}
+
 /// Load settings or return defaults (never fails)
 #[tauri::command]
 async fn load_settings_or_default() -> Result<GuiSettings, String> {
@@ -324,6 +543,83 @@ async fn open_token_url(base_url: String, lms_type: String) -> Result<(), String
     Ok(())
 }
 
+/// Get token generation instructions for a git platform
+#[tauri::command]
+async fn get_git_token_instructions(platform: String) -> Result<String, String> {
+    let kind: GitPlatformKind = platform.parse()?;
+    Ok(git_token_generation_instructions(kind).to_string())
+}
+
+/// Open the token generation page of a git platform in the browser
+///
+/// The page is derived from `base_url`, so self-hosted GitLab and Gitea instances work too.
+#[tauri::command]
+async fn open_git_token_url(base_url: String, platform: String) -> Result<(), String> {
+    let kind: GitPlatformKind = platform.parse()?;
+
+    tauri_plugin_opener::open_url(git_token_generation_url(&base_url, kind), None::<&str>)
+        .map_err(|e| format!("Failed to open token URL: {}", e))?;
+
+    Ok(())
+}
+
+/// Where and how to create an access token for an LMS or git platform
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TokenGuide {
+    /// Display name of the service, e.g. Canvas or GitLab
+    service: String,
+    url: String,
+    instructions: String,
+    /// Whether the token page was opened in the browser
+    opened: bool,
+}
+
+/// Get the token page and instructions for an LMS or git platform in one response
+///
+/// `service` is an LMS type (Canvas, Moodle) or a git platform (GitHub, GitLab, Gitea). When
+/// it is empty, the git platform is detected from `base_url`. With `open` the page is also
+/// opened in the browser.
+#[tauri::command]
+async fn get_token_guide(
+    service: String,
+    base_url: String,
+    open: bool,
+) -> Result<TokenGuide, String> {
+    let (service, url, instructions) = match parse_lms_type(&service) {
+        Ok(lms_type) => (
+            lms_display_name(&service).to_string(),
+            get_token_generation_url(&base_url, lms_type),
+            get_token_generation_instructions(lms_type).to_string(),
+        ),
+        Err(_) => {
+            let kind = if service.is_empty() {
+                GitPlatformKind::from_base_url(&base_url).ok_or_else(|| {
+                    format!("Cannot tell the git platform from the URL {}", base_url)
+                })?
+            } else {
+                service.parse::<GitPlatformKind>()?
+            };
+            (
+                kind.to_string(),
+                git_token_generation_url(&base_url, kind),
+                git_token_generation_instructions(kind).to_string(),
+            )
+        }
+    };
+
+    if open {
+        tauri_plugin_opener::open_url(&url, None::<&str>)
+            .map_err(|e| format!("Failed to open token URL: {}", e))?;
+    }
+
+    Ok(TokenGuide {
+        service,
+        url,
+        instructions,
+        opened: open,
+    })
+}
+
 // ===== LMS Commands =====
 
 /// Verify LMS course credentials and fetch course information
@@ -343,18 +639,159 @@ async fn verify_lms_course(params: VerifyCourseParams) -> Result<CommandResult,
         .await
         .map_err(|e| format!("Failed to verify course: {}", e))?;
 
+    let mut details = format!(
+        "Course ID: {}\nCourse Name: {}\nCourse Code: {}",
+        course.id,
+        course.name,
+        course.course_code.as_deref().unwrap_or("N/A")
+    );
+    if course_name_differs(&params.course_name, &course.name) {
+        details.push_str(&format!(
+            "\n⚠ Course name mismatch: the settings say '{}' but course {} is '{}'; \
+             check the course id, or update the stored name",
+            params.course_name.trim(),
+            course.id,
+            course.name
+        ));
+    }
+    if !params.org_template.trim().is_empty() {
+        match org_from_course(&course, &params.org_template, &params.term) {
+            Ok(org) => details.push_str(&format!("\nStudent Repos Group: {}", org)),
+            Err(e) => details.push_str(&format!("\n⚠ Cannot derive student repos group: {}", e)),
+        }
+    }
+
+    // Offer the course in the recent courses; a settings problem must not fail the verification
+    let remembered = SettingsManager::new()
+        .and_then(|manager| manager.remember_course(&course.id.to_string(), &course.name));
+    if let Err(e) = remembered {
+        details.push_str(&format!(
+            "\n⚠ Could not add the course to the recent courses: {}",
+            e
+        ));
+    }
+
     Ok(CommandResult {
         success: true,
         message: format!("✓ {} course verified: {}", lms_label, course.name),
-        details: Some(format!(
-            "Course ID: {}\nCourse Name: {}\nCourse Code: {}",
-            course.id,
-            course.name,
-            course.course_code.as_deref().unwrap_or("N/A")
-        )),
+        details: Some(details),
+    })
+}
+
+/// Remove the cached LMS data of a course, or of all courses without a course id
+#[tauri::command]
+async fn clear_lms_cache(course_id: Option<String>) -> Result<CommandResult, String> {
+    let removed = repobee_core::clear_lms_cache(&default_lms_cache_dir(), course_id.as_deref())
+        .map_err(|e| e.to_string())?;
+    Ok(CommandResult {
+        success: true,
+        message: format!("✓ Removed the cached LMS data of {} courses", removed),
+        details: None,
     })
 }
 
+/// Recently verified courses, most recent first
+#[tauri::command]
+async fn get_recent_courses() -> Result<Vec<RecentCourse>, String> {
+    let manager = SettingsManager::new()
+        .map_err(|e| format!("Failed to create settings manager: {}", e))?;
+
+    let settings = manager
+        .load()
+        .map_err(|e| format!("Failed to load settings: {}", e))?;
+
+    Ok(settings.recent_courses)
+}
+
+/// Course data source for an LMS type; Moodle is read through its Web Services API
+fn course_data_source<'a>(
+    lms_type: &str,
+    base_url: &'a str,
+    access_token: &'a str,
+) -> Result<CourseDataSource<'a>, String> {
+    if lms_type == "Moodle" {
+        MoodleClient::new(base_url, access_token)
+            .map(CourseDataSource::Moodle)
+            .map_err(|e| format!("Failed to create Moodle client: {}", e))
+    } else {
+        Ok(CourseDataSource::Canvas(CanvasEnrollmentClient::new(
+            base_url,
+            access_token,
+        )))
+    }
+}
+
+/// Where generated student files go
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum OutputPathKind {
+    Folder,
+    Yaml,
+    Csv,
+    Xlsx,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ChooseOutputPathParams {
+    kind: OutputPathKind,
+    /// Current value of the field, the dialog starts there
+    #[serde(default)]
+    current: String,
+    /// Folder that a relative `current` file is in
+    #[serde(default)]
+    folder: String,
+}
+
+/// Let the user pick an output folder or file with the native dialog
+///
+/// Files are picked with a save dialog, so they do not have to exist yet. Returns the
+/// absolute path, or `None` when the dialog was cancelled.
+#[tauri::command]
+async fn choose_output_path(
+    app: tauri::AppHandle,
+    params: ChooseOutputPathParams,
+) -> Result<Option<String>, String> {
+    let current = PathBuf::from(params.current.trim());
+    let start = if current.is_absolute() || params.folder.trim().is_empty() {
+        current
+    } else {
+        PathBuf::from(params.folder.trim()).join(current)
+    };
+
+    let mut dialog = app.dialog().file();
+    let chosen = match params.kind {
+        OutputPathKind::Folder => {
+            if start.is_dir() {
+                dialog = dialog.set_directory(&start);
+            }
+            dialog.blocking_pick_folder()
+        }
+        kind => {
+            let (name, extensions): (&str, &[&str]) = match kind {
+                OutputPathKind::Yaml => ("YAML Files", &["yaml", "yml"]),
+                OutputPathKind::Csv => ("CSV Files", &["csv"]),
+                _ => ("Excel Files", &["xlsx"]),
+            };
+            dialog = dialog.add_filter(name, extensions);
+            if let Some(parent) = start.parent().filter(|p| p.is_dir()) {
+                dialog = dialog.set_directory(parent);
+            }
+            if let Some(file_name) = start.file_name() {
+                dialog = dialog.set_file_name(file_name.to_string_lossy());
+            }
+            dialog.blocking_save_file()
+        }
+    };
+
+    chosen
+        .map(|path| {
+            path.into_path()
+                .map(|p| p.display().to_string())
+                .map_err(|e| format!("Unusable path from the dialog: {}", e))
+        })
+        .transpose()
+}
+
 /// Generate student files from an LMS course
 #[tauri::command]
 async fn generate_lms_files(
@@ -362,8 +799,24 @@ async fn generate_lms_files(
     progress: Channel<String>,
 ) -> Result<CommandResult, String> {
     let lms_label = lms_display_name(&params.lms_type);
-    let client = create_lms_client_with_params(&params.lms_type, params.base_url, params.access_token)
-        .map_err(|e| format!("Failed to create LMS client: {}", e))?;
+    let per_group_set = params.yaml && params.per_group_set;
+    if per_group_set && (params.lms_type != "Canvas" || params.grouping != GroupingSource::Groups) {
+        return Err("One file per group set needs Canvas groups as the grouping".to_string());
+    }
+    let include_roles = EnrollmentRole::parse_list(&params.include_roles)?;
+    let access_token = resolve_token(&params.access_token).map_err(|e| e.to_string())?;
+    let lms_client = create_lms_client_with_params(
+        &params.lms_type,
+        params.base_url.clone(),
+        access_token.clone(),
+    )
+    .map_err(|e| format!("Failed to create LMS client: {}", e))?;
+    let client = CachedLmsClient::new(
+        course_data_source(&params.lms_type, &params.base_url, &access_token)?,
+        &default_lms_cache_dir(),
+        &params.course_id,
+    )
+    .refresh(params.refresh);
 
     let cli_progress = Arc::new(Mutex::new(InlineCliState::default()));
 
@@ -371,61 +824,84 @@ async fn generate_lms_files(
     let fetch_progress_state = Arc::clone(&cli_progress);
     let fetch_progress_channel = progress.clone();
     let course_id = params.course_id.clone();
-    let students = get_student_info_with_progress(&client, &course_id, move |update| {
-            match update {
-                FetchProgress::FetchingUsers => {
-                    emit_standard_message(
-                        &fetch_progress_channel,
-                        &format!("Fetching students from {}...", lms_label),
-                    );
-                }
-                FetchProgress::FetchingGroups => {
-                    emit_standard_message(
-                        &fetch_progress_channel,
-                        &format!("Fetching groups from {}...", lms_label),
-                    );
-                }
-                FetchProgress::FetchedUsers { count } => {
-                    emit_standard_message(
-                        &fetch_progress_channel,
-                        &format!("Retrieved {} students", count),
-                    );
-                }
-                FetchProgress::FetchedGroups { count } => {
-                    emit_standard_message(
-                        &fetch_progress_channel,
-                        &format!("Retrieved {} groups", count),
-                    );
-                }
-                FetchProgress::FetchingGroupMembers {
-                    current,
-                    total,
-                    group_name,
-                } => {
-                    if let Ok(mut state) = fetch_progress_state.lock() {
-                        emit_inline_message(
-                            &fetch_progress_channel,
-                            &mut state,
-                            &format!(
-                                "Fetching {} group memberships {}/{}: {}",
-                                lms_label,
-                                current,
-                                total.max(1),
-                                group_name
-                            ),
-                        );
-                    }
-                }
+    let fetch_options = StudentFetchOptions {
+        git_id_source: params.git_id_source,
+        surname_source: params.surname_source,
+        name_extraction: params.name_extraction.clone(),
+        include_roles,
+        include_test_students: params.include_test_students,
+        ..Default::default()
+    };
+    let on_progress = move |update: FetchProgress| {
+        emit_progress(
+            &fetch_progress_channel,
+            &fetch_progress_state,
+            update.into(),
+        )
+    };
+
+    let fetched = match params.grouping {
+        GroupingSource::Groups => {
+            get_student_info_with_progress(&client, &course_id, &fetch_options, on_progress)
+                .await
+                .map_err(|e| format!("Failed to fetch student info: {}", e))?
+        }
+        GroupingSource::Sections => {
+            if params.lms_type != "Canvas" {
+                return Err(format!(
+                    "Section grouping is only supported for Canvas, not {}",
+                    lms_label
+                ));
             }
-        })
-        .await
-        .map_err(|e| format!("Failed to fetch student info: {}", e))?;
+            emit_standard_message(&progress, "Fetching sections from Canvas...");
+            let sections = get_course_sections(&params.base_url, &access_token, &course_id)
+                .await
+                .map_err(|e| format!("Failed to fetch sections: {}", e))?;
+            get_student_info_by_sections_with_progress(
+                &client,
+                &course_id,
+                &sections,
+                &fetch_options,
+                on_progress,
+            )
+            .await
+            .map_err(|e| format!("Failed to fetch student info: {}", e))?
+        }
+    };
 
     if let Ok(mut state) = cli_progress.lock() {
         state.finalize();
     }
 
+    let students = fetched.students;
     let student_count = students.len();
+    let mut notes = Vec::new();
+
+    if !fetched.warnings.is_empty() {
+        let missing_git_id = fetched.warnings.iter().filter(|w| w.missing_git_id).count();
+        let missing_email = fetched.warnings.iter().filter(|w| w.missing_email).count();
+        let mut summary = Vec::new();
+        if missing_git_id > 0 {
+            summary.push(format!("{} students have no git username", missing_git_id));
+        }
+        if missing_email > 0 {
+            summary.push(format!("{} students have no email", missing_email));
+        }
+        emit_standard_message(&progress, &format!("⚠ {}", summary.join(", ")));
+        for warning in &fetched.warnings {
+            emit_standard_message(&progress, &format!("  - {}", warning));
+        }
+        notes.push(summary.join(", "));
+    }
+
+    if fetched.excluded_test_students > 0 {
+        let note = format!(
+            "Left out {} Canvas Test Student account(s)",
+            fetched.excluded_test_students
+        );
+        emit_standard_message(&progress, &note);
+        notes.push(note);
+    }
 
     let fetched_message = format!(
         "Fetched {} students from {}. Preparing files...",
@@ -436,84 +912,329 @@ async fn generate_lms_files(
 
     // Generate YAML file if requested
     if params.yaml {
+        let team_name_template = params
+            .team_name_template
+            .clone()
+            .filter(|t| !t.trim().is_empty());
+
+        // Only look up the course when the template needs its code
+        let course_code = match &team_name_template {
+            Some(template) if template.contains("{course_code}") => {
+                lms_client
+                    .get_course(&course_id)
+                    .await
+                    .map_err(|e| format!("Failed to fetch course: {}", e))?
+                    .course_code
+            }
+            _ => None,
+        };
+
         let config = YamlConfig {
-            member_option: LmsMemberOption::from_str(&params.member_option),
+            member_option: params.member_option.into(),
             include_group: params.include_group,
             include_member: params.include_member,
             include_initials: params.include_initials,
             full_groups: params.full_groups,
+            grouping: params.grouping,
+            individual_teams: params.individual_teams,
+            team_name_template,
+            course_code,
+            member_format: params.member_format.clone(),
         };
 
-        let yaml_progress_state = Arc::clone(&cli_progress);
-        let yaml_progress_channel = progress.clone();
-        let teams = generate_repobee_yaml_with_progress(
-            &students,
-            &config,
-            move |current, total, group_name| {
-                let message = format!("Processing group {}/{}: {}", current, total, group_name);
-                if let Ok(mut state) = yaml_progress_state.lock() {
-                    emit_inline_message(&yaml_progress_channel, &mut state, &message);
-                }
-            },
-        )
-        .map_err(|e| format!("Failed to generate YAML: {}", e))?;
-
-        if let Ok(mut state) = cli_progress.lock() {
-            state.finalize();
+        if per_group_set {
+            emit_standard_message(&progress, "Fetching group sets from Canvas...");
+            let group_sets = get_group_sets(&params.base_url, &access_token, &course_id)
+                .await
+                .map_err(|e| format!("Failed to fetch group sets: {}", e))?;
+            if group_sets.is_empty() {
+                return Err("The course has no group sets".to_string());
+            }
+            for set in &group_sets {
+                // The course data is cached, so only the first fetch reaches Canvas
+                let set_options = StudentFetchOptions {
+                    group_ids: Some(set.group_ids.iter().cloned().collect()),
+                    ..fetch_options.clone()
+                };
+                let set_students =
+                    get_student_info_with_progress(&client, &course_id, &set_options, |_| {})
+                        .await
+                        .map_err(|e| format!("Failed to fetch student info: {}", e))?
+                        .students;
+                let yaml_file = group_set_file(&params.yaml_file, &set.name);
+                let (yaml_path, team_count, set_notes) = write_team_yaml(
+                    &set_students,
+                    &config,
+                    &params.info_file_folder,
+                    &yaml_file,
+                    &progress,
+                    &cli_progress,
+                )?;
+                notes.extend(
+                    set_notes
+                        .into_iter()
+                        .map(|n| format!("{}: {}", set.name, n)),
+                );
+                generated_files.push(format!(
+                    "YAML ({}): {} ({} teams)",
+                    set.name,
+                    yaml_path.display(),
+                    team_count
+                ));
+            }
+        } else {
+            let (yaml_path, team_count, yaml_notes) = write_team_yaml(
+                &students,
+                &config,
+                &params.info_file_folder,
+                &params.yaml_file,
+                &progress,
+                &cli_progress,
+            )?;
+            notes.extend(yaml_notes);
+            generated_files.push(format!(
+                "YAML: {} ({} teams)",
+                yaml_path.display(),
+                team_count
+            ));
         }
-
-        let yaml_path = PathBuf::from(&params.info_file_folder).join(&params.yaml_file);
-        write_yaml_file(&teams, &yaml_path)
-            .map_err(|e| format!("Failed to write YAML file: {}", e))?;
-
-        // Get absolute path for display
-        let absolute_yaml_path = yaml_path.canonicalize().unwrap_or(yaml_path.clone());
-        generated_files.push(format!(
-            "YAML: {} ({} teams)",
-            absolute_yaml_path.display(),
-            teams.len()
-        ));
     }
 
     // Generate CSV file if requested
     if params.csv {
-        let csv_path = PathBuf::from(&params.info_file_folder).join(&params.csv_file);
+        let csv_path = resolve_output_file(&params.info_file_folder, &params.csv_file)
+            .map_err(|e| format!("Invalid CSV file location: {}", e))?;
         write_csv_file(&students, &csv_path)
             .map_err(|e| format!("Failed to write CSV file: {}", e))?;
 
-        // Get absolute path for display
-        let absolute_csv_path = csv_path.canonicalize().unwrap_or(csv_path.clone());
-        generated_files.push(format!("CSV: {}", absolute_csv_path.display()));
+        generated_files.push(format!("CSV: {}", csv_path.display()));
     }
 
-    // Generate Excel file if requested (TODO: implement Excel writer)
+    // Generate Excel file if requested
     if params.xlsx {
-        return Err("Excel file generation not yet implemented".to_string());
+        let xlsx_path = resolve_output_file(&params.info_file_folder, &params.xlsx_file)
+            .map_err(|e| format!("Invalid Excel file location: {}", e))?;
+        write_xlsx_file(&students, &xlsx_path)
+            .map_err(|e| format!("Failed to write Excel file: {}", e))?;
+
+        generated_files.push(format!("Excel: {}", xlsx_path.display()));
+    }
+
+    let mut details = format!(
+        "Students processed: {}\n\nGenerated files:\n{}",
+        student_count,
+        generated_files.join("\n")
+    );
+    if !notes.is_empty() {
+        details.push_str(&format!("\n\nNotes:\n{}", notes.join("\n")));
     }
 
     Ok(CommandResult {
         success: true,
         message: format!("✓ Successfully generated {} file(s)", generated_files.len()),
-        details: Some(format!(
-            "Students processed: {}\n\nGenerated files:\n{}",
-            student_count,
-            generated_files.join("\n")
-        )),
+        details: Some(details),
     })
 }
 
-/// Verify platform configuration and authentication
+/// Generate the teams of `students` and write them to a YAML file
+///
+/// Returns the file written, the number of teams and the generation notes, which are also
+/// sent to `progress`.
+fn write_team_yaml(
+    students: &[StudentInfo],
+    config: &YamlConfig,
+    folder: &str,
+    file: &str,
+    progress: &Channel<String>,
+    cli_progress: &Arc<Mutex<InlineCliState>>,
+) -> Result<(PathBuf, usize, Vec<String>), String> {
+    let yaml_progress_state = Arc::clone(cli_progress);
+    let yaml_progress_channel = progress.clone();
+    let generation =
+        generate_repobee_yaml_with_progress(students, config, move |current, total, group_name| {
+            let message = format!("Processing group {}/{}: {}", current, total, group_name);
+            if let Ok(mut state) = yaml_progress_state.lock() {
+                emit_inline_message(&yaml_progress_channel, &mut state, &message);
+            }
+        })
+        .map_err(|e| format!("Failed to generate YAML: {}", e))?;
+
+    if let Ok(mut state) = cli_progress.lock() {
+        state.finalize();
+    }
+
+    for note in &generation.notes {
+        emit_standard_message(progress, note);
+    }
+
+    let yaml_path = resolve_output_file(folder, file)
+        .map_err(|e| format!("Invalid YAML file location: {}", e))?;
+    write_yaml_file(&generation.teams, &yaml_path)
+        .map_err(|e| format!("Failed to write YAML file: {}", e))?;
+    Ok((yaml_path, generation.teams.len(), generation.notes))
+}
+
+/// Render the YAML a few students would get with the given options
+///
+/// Nothing is fetched from the LMS, so the preview can follow every change of the options.
 #[tauri::command]
-async fn verify_config(params: ConfigParams) -> Result<CommandResult, String> {
-    // Determine platform from base_url
-    let platform = if params.base_url.starts_with('/') || params.base_url.contains("local") {
+async fn preview_lms_yaml(params: PreviewYamlParams) -> Result<YamlPreview, String> {
+    let config = YamlConfig {
+        member_option: params.member_option.into(),
+        include_group: params.include_group,
+        include_member: params.include_member,
+        include_initials: params.include_initials,
+        full_groups: params.full_groups,
+        grouping: params.grouping,
+        individual_teams: params.individual_teams,
+        team_name_template: params.team_name_template.filter(|t| !t.trim().is_empty()),
+        course_code: params.course_code.or_else(|| Some("course".to_string())),
+        member_format: params.member_format,
+    };
+    let students = if params.students.is_empty() {
+        sample_students()
+    } else {
+        params.students
+    };
+
+    preview_repobee_yaml(&students, &config).map_err(|e| format!("Failed to preview YAML: {}", e))
+}
+
+/// Trace one student from the LMS to the repository names setup would create
+#[tauri::command]
+async fn debug_student(params: DebugStudentParams) -> Result<CommandResult, String> {
+    let lms = &params.lms;
+    let include_roles = EnrollmentRole::parse_list(&lms.include_roles)?;
+    let access_token = resolve_token(&lms.access_token).map_err(|e| e.to_string())?;
+    let lms_client =
+        create_lms_client_with_params(&lms.lms_type, lms.base_url.clone(), access_token.clone())
+            .map_err(|e| format!("Failed to create LMS client: {}", e))?;
+    let client = CachedLmsClient::new(
+        course_data_source(&lms.lms_type, &lms.base_url, &access_token)?,
+        &default_lms_cache_dir(),
+        &lms.course_id,
+    )
+    .refresh(lms.refresh);
+
+    let team_name_template = lms
+        .team_name_template
+        .clone()
+        .filter(|t| !t.trim().is_empty());
+    let course_code = match &team_name_template {
+        Some(template) if template.contains("{course_code}") => {
+            lms_client
+                .get_course(&lms.course_id)
+                .await
+                .map_err(|e| format!("Failed to fetch course: {}", e))?
+                .course_code
+        }
+        _ => None,
+    };
+    let config = YamlConfig {
+        member_option: lms.member_option.into(),
+        include_group: lms.include_group,
+        include_member: lms.include_member,
+        include_initials: lms.include_initials,
+        full_groups: lms.full_groups,
+        grouping: lms.grouping,
+        individual_teams: lms.individual_teams,
+        team_name_template,
+        course_code,
+        member_format: lms.member_format.clone(),
+    };
+    let fetch_options = StudentFetchOptions {
+        git_id_source: lms.git_id_source,
+        surname_source: lms.surname_source,
+        name_extraction: lms.name_extraction.clone(),
+        include_roles,
+        include_test_students: lms.include_test_students,
+        ..Default::default()
+    };
+    let assignments: Vec<String> = params
+        .assignments
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let naming = repo_naming()?;
+
+    let trace = match lms.grouping {
+        GroupingSource::Groups => repobee_core::debug_student(
+            &client,
+            &lms.course_id,
+            &params.identifier,
+            &fetch_options,
+            &config,
+            &assignments,
+            &naming,
+        )
+        .await
+        .map_err(|e| format!("Failed to trace student: {}", e))?,
+        GroupingSource::Sections => {
+            let sections = get_course_sections(&lms.base_url, &access_token, &lms.course_id)
+                .await
+                .map_err(|e| format!("Failed to fetch sections: {}", e))?;
+            let fetched = get_student_info_by_sections_with_progress(
+                &client,
+                &lms.course_id,
+                &sections,
+                &fetch_options,
+                |_| {},
+            )
+            .await
+            .map_err(|e| format!("Failed to fetch student info: {}", e))?;
+            trace_student(
+                &fetched.students,
+                &params.identifier,
+                &config,
+                &assignments,
+                &naming,
+            )
+            .map_err(|e| format!("Failed to trace student: {}", e))?
+        }
+    };
+
+    Ok(CommandResult {
+        success: trace.problems.is_empty(),
+        message: if trace.problems.is_empty() {
+            format!("✓ {} resolves without problems", trace.student.full_name)
+        } else {
+            format!(
+                "⚠ Found {} problem(s) for {}",
+                trace.problems.len(),
+                trace.student.full_name
+            )
+        },
+        details: Some(trace.to_string()),
+    })
+}
+
+/// Naming scheme of the student repositories from the saved settings
+///
+/// Every command that refers to student repositories by name uses it, so they always
+/// find the repositories under the names the setup gave them.
+fn repo_naming() -> Result<RepoNaming, String> {
+    let manager =
+        SettingsManager::new().map_err(|e| format!("Failed to create settings manager: {}", e))?;
+    let settings = manager
+        .load()
+        .map_err(|e| settings_error("Failed to load settings", &manager, e))?;
+    settings
+        .common
+        .repo_naming()
+        .map_err(|e| format!("Invalid repository naming: {}", e))
+}
+
+/// Create the git platform a configuration points at, based on its base URL
+fn create_platform(params: &ConfigParams) -> Result<Platform, String> {
+    if params.base_url.starts_with('/') || params.base_url.contains("local") {
         // Local filesystem platform
         Platform::local(
             PathBuf::from(&params.base_url),
             params.student_repos_group.clone(),
             params.user.clone(),
         )
-        .map_err(|e| format!("Failed to create Local platform: {}", e))?
+        .map_err(|e| format!("Failed to create Local platform: {}", e))
     } else if params.base_url.contains("github") {
         Platform::github(
             params.base_url.clone(),
@@ -521,7 +1242,7 @@ async fn verify_config(params: ConfigParams) -> Result<CommandResult, String> {
             params.student_repos_group.clone(),
             params.user.clone(),
         )
-        .map_err(|e| format!("Failed to create GitHub platform: {}", e))?
+        .map_err(|e| format!("Failed to create GitHub platform: {}", e))
     } else if params.base_url.contains("gitlab") {
         Platform::gitlab(
             params.base_url.clone(),
@@ -529,7 +1250,7 @@ async fn verify_config(params: ConfigParams) -> Result<CommandResult, String> {
             params.student_repos_group.clone(),
             params.user.clone(),
         )
-        .map_err(|e| format!("Failed to create GitLab platform: {}", e))?
+        .map_err(|e| format!("Failed to create GitLab platform: {}", e))
     } else if params.base_url.contains("gitea") {
         Platform::gitea(
             params.base_url.clone(),
@@ -537,10 +1258,16 @@ async fn verify_config(params: ConfigParams) -> Result<CommandResult, String> {
             params.student_repos_group.clone(),
             params.user.clone(),
         )
-        .map_err(|e| format!("Failed to create Gitea platform: {}", e))?
+        .map_err(|e| format!("Failed to create Gitea platform: {}", e))
     } else {
-        return Err("Unknown platform. URL must contain 'github', 'gitlab', 'gitea', or be a filesystem path".to_string());
-    };
+        Err("Unknown platform. URL must contain 'github', 'gitlab', 'gitea', or be a filesystem path".to_string())
+    }
+}
+
+/// Verify platform configuration and authentication
+#[tauri::command]
+async fn verify_config(params: ConfigParams) -> Result<CommandResult, String> {
+    let platform = create_platform(&params)?;
 
     // Verify settings
     platform
@@ -567,9 +1294,142 @@ async fn verify_config(params: ConfigParams) -> Result<CommandResult, String> {
     })
 }
 
+/// Group holding the templates, with the same fallback as setup_repos
+fn template_group(config: &ConfigParams) -> &str {
+    if config.template_group.is_empty() {
+        &config.student_repos_group
+    } else {
+        &config.template_group
+    }
+}
+
+/// Directory holding the templates on the Local platform
+fn local_template_dir(config: &ConfigParams) -> PathBuf {
+    let group = template_group(config);
+    if group.starts_with('/') {
+        PathBuf::from(group)
+    } else {
+        PathBuf::from(&config.base_url).join(group)
+    }
+}
+
+/// Create a hosted platform client for the template group
+fn template_platform(config: &ConfigParams) -> Result<Platform, String> {
+    let group = template_group(config);
+    if config.base_url.contains("github") {
+        Platform::github(
+            config.base_url.clone(),
+            config.access_token.clone(),
+            group.to_string(),
+            config.user.clone(),
+        )
+        .map_err(|e| format!("Failed to create GitHub platform: {}", e))
+    } else if config.base_url.contains("gitlab") {
+        Platform::gitlab(
+            config.base_url.clone(),
+            config.access_token.clone(),
+            group.to_string(),
+            config.user.clone(),
+        )
+        .map_err(|e| format!("Failed to create GitLab platform: {}", e))
+    } else if config.base_url.contains("gitea") {
+        Platform::gitea(
+            config.base_url.clone(),
+            config.access_token.clone(),
+            group.to_string(),
+            config.user.clone(),
+        )
+        .map_err(|e| format!("Failed to create Gitea platform: {}", e))
+    } else {
+        Err("Unknown platform. URL must contain 'github', 'gitlab', 'gitea', or be a filesystem path".to_string())
+    }
+}
+
+/// List the template repositories in the template group
+#[tauri::command]
+async fn list_templates(config: ConfigParams) -> Result<Vec<String>, String> {
+    if config.base_url.starts_with('/') || config.base_url.contains("local") {
+        // Local templates are plain git directories, so list the subdirectories
+        return repobee_core::list_local_templates(&local_template_dir(&config))
+            .map_err(|e| e.to_string());
+    }
+
+    let platform = template_platform(&config)?;
+    repobee_core::list_templates(&platform)
+        .await
+        .map_err(|e| format!("Failed to list templates: {}", e))
+}
+
+/// Fail on assignments without a template, suggesting the closest template name
+///
+/// Skipped when the templates cannot be listed, setup then reports the missing template.
+async fn check_assignment_templates(
+    config: &ConfigParams,
+    assignments: &[String],
+) -> Result<(), String> {
+    match list_templates(config.clone()).await {
+        Ok(templates) if !templates.is_empty() => {
+            check_assignments(assignments, &templates).map_err(|e| e.to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// List the top-level files of a template, for previewing it before setup
+#[tauri::command]
+async fn list_template_files(
+    config: ConfigParams,
+    template: String,
+    git_ref: Option<String>,
+) -> Result<Vec<RepoFile>, String> {
+    if config.base_url.starts_with('/') || config.base_url.contains("local") {
+        let template_path = local_template_dir(&config).join(&template);
+        return repobee_core::list_local_template_files(&template_path).map_err(|e| e.to_string());
+    }
+
+    let platform = template_platform(&config)?;
+    let repo = platform
+        .get_repo(&template, None)
+        .await
+        .map_err(|e| format!("Failed to get template {}: {}", template, e))?;
+    platform
+        .list_repo_files(&repo, git_ref.as_deref())
+        .await
+        .map_err(|e| format!("Failed to list files of {}: {}", template, e))
+}
+
+/// Run every setup pre-check without creating anything, to gate the setup button
+#[tauri::command]
+async fn preflight_setup(params: SetupParams) -> Result<PreflightReport, String> {
+    let student_teams =
+        read_teams_file(std::path::Path::new(&params.yaml_file)).map_err(|e| e.to_string())?;
+    let assignments: Vec<String> = params
+        .assignments
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let templates = list_templates(params.config.clone())
+        .await
+        .map_err(PlatformError::Other);
+    let platform = create_platform(&params.config)?;
+    Ok(repobee_core::preflight_setup(
+        &platform,
+        &assignments,
+        templates,
+        &student_teams,
+        &repo_naming()?,
+    )
+    .await)
+}
+
 /// Create student repositories from templates
 #[tauri::command]
-async fn setup_repos(params: SetupParams) -> Result<CommandResult, String> {
+async fn setup_repos(
+    params: SetupParams,
+    progress: Channel<String>,
+) -> Result<CommandResult, String> {
     // Parse YAML file to get student teams
     let yaml_content = std::fs::read_to_string(&params.yaml_file)
         .map_err(|e| format!("Failed to read YAML file: {}", e))?;
@@ -588,6 +1448,7 @@ async fn setup_repos(params: SetupParams) -> Result<CommandResult, String> {
     if assignments.is_empty() {
         return Err("No assignments specified".to_string());
     }
+    check_assignment_templates(&params.config, &assignments).await?;
 
     // Create template URLs from assignments and template group
     let template_urls: Vec<String> = assignments
@@ -615,68 +1476,128 @@ async fn setup_repos(params: SetupParams) -> Result<CommandResult, String> {
         })
         .collect();
 
-    // Determine platform
-    let platform = if params.config.base_url.starts_with('/')
-        || params.config.base_url.contains("local")
-    {
-        // Local filesystem platform
-        Platform::local(
-            PathBuf::from(&params.config.base_url),
-            params.config.student_repos_group.clone(),
-            params.config.user.clone(),
-        )
-        .map_err(|e| format!("Failed to create Local platform: {}", e))?
-    } else if params.config.base_url.contains("github") {
-        Platform::github(
-            params.config.base_url.clone(),
-            params.config.access_token.clone(),
-            params.config.student_repos_group.clone(),
-            params.config.user.clone(),
-        )
-        .map_err(|e| format!("Failed to create GitHub platform: {}", e))?
-    } else if params.config.base_url.contains("gitlab") {
-        Platform::gitlab(
-            params.config.base_url.clone(),
-            params.config.access_token.clone(),
-            params.config.student_repos_group.clone(),
-            params.config.user.clone(),
-        )
-        .map_err(|e| format!("Failed to create GitLab platform: {}", e))?
-    } else if params.config.base_url.contains("gitea") {
-        Platform::gitea(
-            params.config.base_url.clone(),
-            params.config.access_token.clone(),
-            params.config.student_repos_group.clone(),
-            params.config.user.clone(),
-        )
-        .map_err(|e| format!("Failed to create Gitea platform: {}", e))?
+    let platform = create_platform(&params.config)?;
+
+    // Git needs the token itself, not a `file:` reference to it
+    let access_token = resolve_token(&params.config.access_token).map_err(|e| e.to_string())?;
+
+    // Resolve the work directory, which must not depend on where the app was started from
+    let work_dir = if params.work_dir.trim().is_empty() {
+        default_work_dir()
     } else {
-        return Err("Unknown platform. URL must contain 'github', 'gitlab', 'gitea', or be a filesystem path".to_string());
+        PathBuf::from(params.work_dir.trim())
     };
-
-    // Create work directory
-    let work_dir = PathBuf::from("./repobee-work");
-    std::fs::create_dir_all(&work_dir)
+    let template_names: Vec<&str> = assignments.iter().map(String::as_str).collect();
+    let work_dir = resolve_dir(&work_dir, &template_names).map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&work_dir.path)
         .map_err(|e| format!("Failed to create work directory: {}", e))?;
 
+    let options = SetupOptions {
+        work_dir_cleanup: if params.clean_work_dir {
+            WorkDirCleanup::Remove
+        } else {
+            WorkDirCleanup::Keep
+        },
+        public_fallback: params.public_fallback,
+        empty_teams: if params.create_empty_teams {
+            EmptyTeamPolicy::Create
+        } else {
+            EmptyTeamPolicy::Skip
+        },
+        distribution: if params.platform_templates {
+            DistributionMode::PlatformTemplate
+        } else {
+            DistributionMode::ClonePush
+        },
+        verify_pushes: params.verify_pushes,
+        timeout: params
+            .timeout_minutes
+            .map(|minutes| std::time::Duration::from_secs(minutes * 60)),
+        naming: repo_naming()?,
+        ..Default::default()
+    };
+
     // Run setup
-    let result = repobee_core::setup_student_repos(
+    let cli_progress = Mutex::new(InlineCliState::default());
+    let result = repobee_core::setup_student_repos_with_progress(
         &template_urls,
         &student_teams,
         &platform,
-        &work_dir,
-        true, // private repos
-        Some(&params.config.access_token),
+        &work_dir.path,
+        params.private.unwrap_or(true),
+        Some(&access_token),
+        &options,
+        |update| emit_progress(&progress, &cli_progress, update),
     )
     .await
     .map_err(|e| format!("Setup failed: {}", e))?;
+    if let Ok(mut state) = cli_progress.lock() {
+        state.finalize();
+    }
 
-    let details = format!(
+    let mut details = format!(
         "Successfully created: {} repositories\nAlready existed: {} repositories\nErrors: {}",
         result.successful_repos.len(),
         result.existing_repos.len(),
         result.errors.len()
     );
+    let mut copied: Vec<&str> = result
+        .distribution
+        .iter()
+        .filter(|(_, mode)| **mode == DistributionMode::PlatformTemplate)
+        .map(|(name, _)| name.as_str())
+        .collect();
+    if !copied.is_empty() {
+        copied.sort_unstable();
+        details.push_str(&format!(
+            "\nCreated from platform templates: {}",
+            copied.join(", ")
+        ));
+    }
+    if !result.empty_repos.is_empty() {
+        details.push_str(&format!(
+            "\nEmpty after the setup: {}",
+            result.empty_repos.join(", ")
+        ));
+    }
+    if result.timed_out {
+        details.push_str(&format!(
+            "\nTimed out, unfinished: {}",
+            result.unfinished_repos.join(", ")
+        ));
+    }
+    if !result.skipped_teams.is_empty() {
+        details.push_str(&format!(
+            "\nSkipped {} teams without members: {}",
+            result.skipped_teams.len(),
+            result.skipped_teams.join(", ")
+        ));
+    }
+    details.push_str(&format!("\nWork directory: {}", result.work_dir.display()));
+    for warning in &work_dir.warnings {
+        details.push_str(&format!("\n⚠ {}", warning));
+    }
+    if !result.cleanup.removed.is_empty() {
+        details.push_str(&format!("\n{}", result.cleanup.summary()));
+    }
+    let mut branches: Vec<_> = result.template_branches.iter().collect();
+    branches.sort();
+    for (template, branch) in branches {
+        details.push_str(&format!("\nTemplate {} uses branch: {}", template, branch));
+    }
+    for warning in &result.warnings {
+        details.push_str(&format!("\n⚠ {}", warning));
+    }
+    details.push_str(&format!("\nTime: {}", result.timings.summary()));
+    for (assignment, repos) in &result.by_assignment {
+        details.push_str(&format!(
+            "\nAssignment {}: {} created, {} already existed, {} errors",
+            assignment,
+            repos.created.len(),
+            repos.existing.len(),
+            result.assignment_errors(assignment).len()
+        ));
+    }
 
     if result.is_success() {
         Ok(CommandResult {
@@ -694,18 +1615,339 @@ async fn setup_repos(params: SetupParams) -> Result<CommandResult, String> {
 
         Ok(CommandResult {
             success: false,
-            message: format!("Setup completed with {} errors", result.errors.len()),
+            message: if result.timed_out {
+                format!(
+                    "Setup timed out with {} repositories unfinished",
+                    result.unfinished_repos.len()
+                )
+            } else {
+                format!("Setup completed with {} errors", result.errors.len())
+            },
             details: Some(format!("{}\n\nErrors:\n{}", details, error_details)),
         })
     }
 }
 
-/// Clone student repositories (stub for now)
+/// Check a teams file (YAML or JSON) without running setup, returning its problems
+#[tauri::command]
+async fn validate_teams_file(path: String) -> Result<Vec<String>, String> {
+    let teams = read_teams_file(std::path::Path::new(&path)).map_err(|e| e.to_string())?;
+    Ok(validate_repobee_yaml(&teams))
+}
+
+/// Compare the student repositories on the platform with the roster
+#[tauri::command]
+async fn reconcile_repos(params: ReconcileParams) -> Result<CommandResult, String> {
+    let yaml_content = std::fs::read_to_string(&params.yaml_file)
+        .map_err(|e| format!("Failed to read YAML file: {}", e))?;
+    let student_teams: Vec<StudentTeam> = serde_yaml::from_str(&yaml_content)
+        .map_err(|e| format!("Failed to parse YAML file: {}", e))?;
+
+    let assignments: Vec<String> = params
+        .assignments
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if assignments.is_empty() {
+        return Err("No assignments specified".to_string());
+    }
+
+    let platform = create_platform(&params.config)?;
+    let filter = Some(params.filter.as_str()).filter(|f| !f.trim().is_empty());
+    let result = reconcile(
+        &assignments,
+        &student_teams,
+        &platform,
+        &repo_naming()?,
+        filter,
+    )
+    .await
+    .map_err(|e| format!("Reconcile failed: {}", e))?;
+
+    let mut details = format!(
+        "Present: {} repositories\nMissing: {} repositories\nNot in the roster: {} repositories",
+        result.matched.len(),
+        result.missing.len(),
+        result.extra.len()
+    );
+    for repo in &result.missing {
+        details.push_str(&format!(
+            "\n✗ Missing {} ({}, {})",
+            repo.name, repo.team, repo.assignment
+        ));
+    }
+    for repo in &result.extra {
+        details.push_str(&format!("\n⚠ Not in the roster: {}", repo.name));
+    }
+
+    Ok(CommandResult {
+        success: result.is_complete(),
+        message: if result.is_complete() {
+            "✓ All expected repositories exist".to_string()
+        } else {
+            format!(
+                "✗ {} expected repositories are missing",
+                result.missing.len()
+            )
+        },
+        details: Some(details),
+    })
+}
+
+/// List the student repositories by last activity, least recently active first
 #[tauri::command]
-async fn clone_repos(_params: CloneParams) -> Result<CommandResult, String> {
-    // TODO: Implement clone functionality
-    // For now, return a stub response
-    Err("Clone functionality not yet implemented".to_string())
+async fn list_repo_activity(params: RepoActivityParams) -> Result<CommandResult, String> {
+    let since = Some(params.since.trim())
+        .filter(|since| !since.is_empty())
+        .map(|since| parse_window_date(since, true))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    let student_teams =
+        read_teams_file(std::path::Path::new(&params.yaml_file)).map_err(|e| e.to_string())?;
+    let assignments: Vec<String> = params
+        .assignments
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if assignments.is_empty() {
+        return Err("No assignments specified".to_string());
+    }
+    let repos =
+        expected_repos(&assignments, &student_teams, &repo_naming()?).map_err(|e| e.to_string())?;
+
+    let platform = create_platform(&params.config)?;
+    let report = repo_activity(&platform, &repos).await;
+
+    let inactive = report.inactive(since).len();
+    let mut details = String::new();
+    for activity in &report.repos {
+        let last = match activity.last_activity {
+            Some(time) => time.format("%Y-%m-%d %H:%M UTC").to_string(),
+            None => "never".to_string(),
+        };
+        let marker = if activity.is_inactive(since) {
+            "⚠"
+        } else {
+            "✓"
+        };
+        details.push_str(&format!(
+            "{} {} ({}): {}\n",
+            marker, activity.repo.name, activity.repo.team, last
+        ));
+    }
+    for error in &report.errors {
+        details.push_str(&format!("✗ {}: {}\n", error.repo_name, error.error));
+    }
+
+    Ok(CommandResult {
+        success: report.errors.is_empty(),
+        message: if report.errors.is_empty() {
+            format!(
+                "✓ {} repositories, {} without activity",
+                report.repos.len(),
+                inactive
+            )
+        } else {
+            format!(
+                "✗ The activity of {} repositories could not be looked up",
+                report.errors.len()
+            )
+        },
+        details: Some(details.trim_end().to_string()),
+    })
+}
+
+/// Open the same issue in every student repository of an assignment
+#[tauri::command]
+async fn open_issues(
+    params: OpenIssueParams,
+    progress: Channel<String>,
+) -> Result<CommandResult, String> {
+    let body = if params.body_file.trim().is_empty() {
+        params.body.clone()
+    } else {
+        read_issue_body(std::path::Path::new(params.body_file.trim())).map_err(|e| e.to_string())?
+    };
+    let student_teams =
+        read_teams_file(std::path::Path::new(&params.yaml_file)).map_err(|e| e.to_string())?;
+    let repos = expected_repos(
+        &[params.assignment.trim().to_string()],
+        &student_teams,
+        &repo_naming()?,
+    )
+    .map_err(|e| e.to_string())?;
+    let issue = IssueTemplate {
+        title: params.title.clone(),
+        body,
+        variables: params.variables.clone(),
+    };
+
+    let platform = create_platform(&params.config)?;
+    let cli_progress = Mutex::new(InlineCliState::default());
+    let on_progress = |update: Progress| emit_progress(&progress, &cli_progress, update);
+    let result = open_issue_in_repos_with_progress(&platform, &repos, &issue, on_progress)
+        .await
+        .map_err(|e| format!("Failed to open issues: {}", e))?;
+    if let Ok(mut state) = cli_progress.lock() {
+        state.finalize();
+    }
+
+    let mut details = format!(
+        "Opened: {} issues\nFailed: {} repositories",
+        result.opened.len(),
+        result.errors.len()
+    );
+    for error in &result.errors {
+        details.push_str(&format!("\n✗ {}: {}", error.repo_name, error.error));
+    }
+    if !result.unknown_variables.is_empty() {
+        details.push_str(&format!(
+            "\n⚠ Unknown variables left as written: {}",
+            result.unknown_variables.join(", ")
+        ));
+    }
+
+    Ok(CommandResult {
+        success: result.is_success(),
+        message: if result.is_success() {
+            format!("✓ Opened the issue in {} repositories", result.opened.len())
+        } else {
+            format!(
+                "✗ The issue could not be opened in {} repositories",
+                result.errors.len()
+            )
+        },
+        details: Some(details),
+    })
+}
+
+/// Delete one student repository after the user confirmed its name
+#[tauri::command]
+async fn delete_repo(params: DeleteRepoParams) -> Result<CommandResult, String> {
+    let name = params.name.trim();
+    if params.confirmation.trim() != name {
+        return Err(format!(
+            "The confirmation does not match '{}', nothing was deleted",
+            name
+        ));
+    }
+
+    let platform = create_platform(&params.config)?;
+    let repo = delete_single_repo(&platform, name)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(CommandResult {
+        success: true,
+        message: format!("✓ Deleted {}", repo.name),
+        details: Some(format!(
+            "Deleted {}/{}",
+            params.config.student_repos_group, repo.name
+        )),
+    })
+}
+
+/// Clone the student repositories of the assignments into the target folder
+#[tauri::command]
+async fn clone_repos(
+    params: CloneParams,
+    progress: Channel<String>,
+) -> Result<CommandResult, String> {
+    let student_teams =
+        read_teams_file(std::path::Path::new(&params.yaml_file)).map_err(|e| e.to_string())?;
+    let assignments: Vec<String> = params
+        .assignments
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if assignments.is_empty() {
+        return Err("No assignments specified".to_string());
+    }
+
+    let options = CloneOptions {
+        layout: params.directory_layout,
+        update_existing: params.update_existing,
+        naming: repo_naming()?,
+        ..Default::default()
+    };
+    let target_folder = if params.target_folder.trim().is_empty() {
+        default_work_dir()
+    } else {
+        PathBuf::from(params.target_folder.trim())
+    };
+    // Depending on the layout, entries are named after repositories, teams or assignments
+    let repo_names = assignments
+        .iter()
+        .flat_map(|a| {
+            student_teams
+                .iter()
+                .map(|t| options.naming.repo_name(&t.name, a))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let expected: Vec<&str> = repo_names
+        .iter()
+        .chain(&assignments)
+        .map(String::as_str)
+        .chain(student_teams.iter().map(|t| t.name.as_str()))
+        .collect();
+    let target_folder = resolve_dir(&target_folder, &expected).map_err(|e| e.to_string())?;
+
+    let platform = create_platform(&params.config)?;
+    let access_token = resolve_token(&params.config.access_token).map_err(|e| e.to_string())?;
+    let token = Some(access_token.as_str()).filter(|token| !token.is_empty());
+
+    let cli_progress = Mutex::new(InlineCliState::default());
+    let on_progress = |update: Progress| emit_progress(&progress, &cli_progress, update);
+    let result = clone_student_repos_with_progress(
+        &assignments,
+        &student_teams,
+        &platform,
+        &target_folder.path,
+        token,
+        &options,
+        on_progress,
+    )
+    .await
+    .map_err(|e| format!("Clone failed: {}", e))?;
+    if let Ok(mut state) = cli_progress.lock() {
+        state.finalize();
+    }
+
+    let mut details = format!(
+        "Cloned: {} repositories\nUpdated: {} repositories\nAlready cloned: {} repositories\nErrors: {}",
+        result.successful_repos.len() + result.resumed_repos.len(),
+        result.updated_repos.len(),
+        result.existing_repos.len(),
+        result.errors.len()
+    );
+    details.push_str(&format!(
+        "\nTarget folder: {} ({})",
+        result.target_dir.display(),
+        params.directory_layout
+    ));
+    for warning in &target_folder.warnings {
+        details.push_str(&format!("\n⚠ {}", warning));
+    }
+    for error in &result.errors {
+        details.push_str(&format!(
+            "\n✗ {}/{}: {}",
+            error.team_name, error.repo_name, error.error
+        ));
+    }
+
+    Ok(CommandResult {
+        success: result.is_success(),
+        message: if result.is_success() {
+            format!("✓ Cloned {} repositories", result.total_repos())
+        } else {
+            format!("✗ {} repositories could not be cloned", result.errors.len())
+        },
+        details: Some(details),
+    })
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -717,12 +1959,14 @@ pub fn run() {
             load_settings,
             save_settings,
             reset_settings,
+            reset_course_settings,
             get_settings_path,
             settings_exist,
             import_settings,
             export_settings,
             get_settings_schema,
             load_settings_or_default,
+            get_effective_settings,
             list_profiles,
             get_active_profile,
             load_profile,
@@ -731,10 +1975,26 @@ pub fn run() {
             rename_profile,
             get_token_instructions,
             open_token_url,
+            get_git_token_instructions,
+            open_git_token_url,
+            get_token_guide,
             verify_lms_course,
+            get_recent_courses,
+            clear_lms_cache,
+            preview_lms_yaml,
+            choose_output_path,
             generate_lms_files,
+            debug_student,
             verify_config,
+            list_templates,
+            list_template_files,
+            preflight_setup,
             setup_repos,
+            reconcile_repos,
+            list_repo_activity,
+            open_issues,
+            delete_repo,
+            validate_teams_file,
             clone_repos
         ])
         .run(tauri::generate_context!())
@@ -1,6 +1,6 @@
 import { useState, useEffect, useRef } from "react";
 import { invoke, Channel } from "@tauri-apps/api/core";
-import { message, open } from "@tauri-apps/plugin-dialog";
+import { ask, message, open } from "@tauri-apps/plugin-dialog";
 import { getCurrentWindow, type Window } from "@tauri-apps/api/window";
 import {
   Form,
@@ -21,7 +21,7 @@ import {
   App as AntApp
 } from "antd";
 import { SettingsMenu } from "./components/SettingsMenu";
-import type { GuiSettings } from "./types/settings";
+import type { GuiSettings, LmsGitIdSource, LmsNameExtraction, LmsSurnameSource, RecentCourse } from "./types/settings";
 import "./App.css";
 
 interface FormState {
@@ -29,11 +29,14 @@ interface FormState {
   user: string;
   baseUrl: string;
   studentReposGroup: string;
+  orgTemplate: string;
   templateGroup: string;
   yamlFile: string;
   targetFolder: string;
   assignments: string;
   directoryLayout: "by-team" | "flat" | "by-task";
+  privateRepos: boolean;
+  publicFallback: boolean;
   logLevels: {
     info: boolean;
     debug: boolean;
@@ -48,8 +51,11 @@ interface LmsFormState {
   customUrl: string;
   urlOption: "TUE" | "CUSTOM";
   accessToken: string;
+  moodleUrl: string;
+  moodleToken: string;
   courseId: string;
   courseName: string;
+  courseTerm: string;
   yamlFile: string;
   infoFileFolder: string;
   csvFile: string;
@@ -59,13 +65,43 @@ interface LmsFormState {
   includeMember: boolean;
   includeInitials: boolean;
   fullGroups: boolean;
+  includeTestStudents: boolean;
+  gitIdSource: LmsGitIdSource;
+  surnameSource: LmsSurnameSource;
+  nameExtraction: LmsNameExtraction;
+  includeRoles: string[];
   csv: boolean;
   xlsx: boolean;
   yaml: boolean;
 }
 
+interface YamlPreview {
+  yaml: string;
+  notes: string[];
+}
+
+interface PreflightCheck {
+  name: string;
+  status: "passed" | "warning" | "failed" | "skipped";
+  message: string;
+  details: string[];
+}
+
 type TabType = "lms" | "repo";
 
+/** Base URL of the selected LMS; Moodle falls back to the custom URL of older settings */
+function lmsBaseUrl(form: LmsFormState): string {
+  if (form.lmsType === "Moodle") {
+    return form.moodleUrl || form.customUrl;
+  }
+  return form.urlOption === "TUE" ? form.baseUrl : form.customUrl;
+}
+
+/** Access token of the selected LMS; Moodle falls back to the token of older settings */
+function lmsAccessToken(form: LmsFormState): string {
+  return form.lmsType === "Moodle" ? form.moodleToken || form.accessToken : form.accessToken;
+}
+
 function App() {
   const settingsLoadedRef = useRef(false);
   const isDirtyRef = useRef(false);
@@ -76,6 +112,7 @@ function App() {
 const [activeTab, setActiveTab] = useState<TabType>("lms");
   const [configLocked, setConfigLocked] = useState(true);
   const [optionsLocked, setOptionsLocked] = useState(true);
+  const [recentCourses, setRecentCourses] = useState<RecentCourse[]>([]);
   const [outputText, setOutputText] = useState("");
   const [tokenDialogOpen, setTokenDialogOpen] = useState(false);
   const [tokenDialogValue, setTokenDialogValue] = useState("");
@@ -85,14 +122,21 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
   const [tokenInstructions, setTokenInstructions] = useState("");
   const [settingsMenuOpen, setSettingsMenuOpen] = useState(false);
   const [currentGuiSettings, setCurrentGuiSettings] = useState<GuiSettings | null>(null);
+  const [availableTemplates, setAvailableTemplates] = useState<string[]>([]);
+  const [refreshLmsCache, setRefreshLmsCache] = useState(false);
+  const [perGroupSet, setPerGroupSet] = useState(false);
+  const [yamlPreview, setYamlPreview] = useState<YamlPreview | null>(null);
   const [lmsForm, setLmsForm] = useState<LmsFormState>({
     lmsType: "Canvas",
     baseUrl: "https://canvas.tue.nl",
     customUrl: "",
     urlOption: "TUE",
     accessToken: "",
+    moodleUrl: "",
+    moodleToken: "",
     courseId: "",
     courseName: "",
+    courseTerm: "",
     yamlFile: "students.yaml",
     infoFileFolder: "",
     csvFile: "student-info.csv",
@@ -102,6 +146,11 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
     includeMember: true,
     includeInitials: false,
     fullGroups: true,
+    includeTestStudents: false,
+    gitIdSource: "sis_user_id",
+    surnameSource: "sortable_name",
+    nameExtraction: "after_last_dot",
+    includeRoles: ["StudentEnrollment"],
     csv: false,
     xlsx: false,
     yaml: true,
@@ -111,11 +160,14 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
     user: "",
     baseUrl: "https://gitlab.tue.nl",
     studentReposGroup: "",
+    orgTemplate: "",
     templateGroup: "",
     yamlFile: "",
     targetFolder: "",
     assignments: "",
     directoryLayout: "flat",
+    privateRepos: true,
+    publicFallback: false,
     logLevels: {
       info: true,
       debug: false,
@@ -138,8 +190,11 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
       customUrl: "",
       urlOption: "TUE",
       accessToken: "",
+      moodleUrl: "",
+      moodleToken: "",
       courseId: "",
       courseName: "",
+      courseTerm: "",
       yamlFile: "students.yaml",
       infoFileFolder: "",
       csvFile: "student-info.csv",
@@ -149,6 +204,11 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
       includeMember: true,
       includeInitials: false,
       fullGroups: true,
+      includeTestStudents: false,
+      gitIdSource: "sis_user_id",
+      surnameSource: "sortable_name",
+      nameExtraction: "after_last_dot",
+      includeRoles: ["StudentEnrollment"],
       csv: false,
       xlsx: false,
       yaml: true,
@@ -158,11 +218,14 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
       user: "",
       baseUrl: "https://gitlab.tue.nl",
       studentReposGroup: "",
+      orgTemplate: "",
       templateGroup: "",
       yamlFile: "",
       targetFolder: "",
       assignments: "",
       directoryLayout: "flat",
+      privateRepos: true,
+      publicFallback: false,
       logLevels: {
         info: true,
         debug: false,
@@ -204,6 +267,27 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
     setClosePromptVisible(true);
   };
 
+  // Show what the member and naming options do to a few sample students
+  useEffect(() => {
+    invoke<YamlPreview>("preview_lms_yaml", {
+      params: {
+        member_option: lmsForm.memberOption,
+        include_group: lmsForm.includeGroup,
+        include_member: lmsForm.includeMember,
+        include_initials: lmsForm.includeInitials,
+        full_groups: lmsForm.fullGroups,
+      },
+    })
+      .then(setYamlPreview)
+      .catch((error) => setYamlPreview({ yaml: "", notes: [String(error)] }));
+  }, [
+    lmsForm.memberOption,
+    lmsForm.includeGroup,
+    lmsForm.includeMember,
+    lmsForm.includeInitials,
+    lmsForm.fullGroups,
+  ]);
+
   // Load settings on startup (only once, even with React StrictMode)
   useEffect(() => {
     if (!settingsLoadedRef.current) {
@@ -307,8 +391,11 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
         customUrl: settings.lms_custom_url || "",
         urlOption: (settings.lms_url_option || "TUE") as "TUE" | "CUSTOM",
         accessToken: settings.lms_access_token || "",
+        moodleUrl: settings.moodle_base_url || "",
+        moodleToken: settings.moodle_access_token || "",
         courseId: settings.lms_course_id || "",
         courseName: settings.lms_course_name || "",
+        courseTerm: settings.lms_course_term || "",
         yamlFile: settings.lms_yaml_file || "students.yaml",
         infoFileFolder: settings.lms_info_folder || "",
         csvFile: settings.lms_csv_file || "student-info.csv",
@@ -318,6 +405,11 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
         includeMember: settings.lms_include_member ?? true,
         includeInitials: settings.lms_include_initials ?? false,
         fullGroups: settings.lms_full_groups ?? true,
+        includeTestStudents: settings.lms_include_test_students ?? false,
+        gitIdSource: settings.lms_git_id_source || "sis_user_id",
+        surnameSource: settings.lms_surname_source || "sortable_name",
+        nameExtraction: settings.lms_name_extraction || "after_last_dot",
+        includeRoles: settings.lms_include_roles ?? ["StudentEnrollment"],
         csv: settings.lms_output_csv ?? false,
         xlsx: settings.lms_output_xlsx ?? false,
         yaml: settings.lms_output_yaml ?? true,
@@ -335,11 +427,14 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
         user: settings.git_user || "",
         baseUrl: settings.git_base_url || "https://gitlab.tue.nl",
         studentReposGroup: settings.git_student_repos_group || "",
+        orgTemplate: settings.git_org_template || "",
         templateGroup: settings.git_template_group || "",
         yamlFile: settings.yaml_file || "",
         targetFolder: settings.target_folder || "",
         assignments: settings.assignments || "",
         directoryLayout: (settings.directory_layout || "flat") as "by-team" | "flat" | "by-task",
+        privateRepos: settings.git_private_repos ?? true,
+        publicFallback: settings.git_public_fallback ?? false,
         logLevels: {
           info: settings.log_info ?? true,
           debug: settings.log_debug ?? false,
@@ -359,6 +454,7 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
       setActiveTab(savedTab as TabType);
       setConfigLocked(savedConfigLocked);
       setOptionsLocked(savedOptionsLocked);
+      setRecentCourses(settings.recent_courses ?? []);
 
       // Store current state as last saved state (deep copy to prevent reference issues)
       setLastSavedState({
@@ -397,8 +493,11 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
       customUrl: settings.lms_custom_url || "",
       urlOption: (settings.lms_url_option || "TUE") as "TUE" | "CUSTOM",
       accessToken: settings.lms_access_token || "",
+      moodleUrl: settings.moodle_base_url || "",
+      moodleToken: settings.moodle_access_token || "",
       courseId: settings.lms_course_id || "",
       courseName: settings.lms_course_name || "",
+      courseTerm: settings.lms_course_term || "",
       yamlFile: settings.lms_yaml_file || "students.yaml",
       infoFileFolder: settings.lms_info_folder || "",
       csvFile: settings.lms_csv_file || "student-info.csv",
@@ -408,6 +507,11 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
       includeMember: settings.lms_include_member ?? true,
       includeInitials: settings.lms_include_initials ?? false,
       fullGroups: settings.lms_full_groups ?? true,
+      includeTestStudents: settings.lms_include_test_students ?? false,
+      gitIdSource: settings.lms_git_id_source || "sis_user_id",
+      surnameSource: settings.lms_surname_source || "sortable_name",
+      nameExtraction: settings.lms_name_extraction || "after_last_dot",
+      includeRoles: settings.lms_include_roles ?? ["StudentEnrollment"],
       csv: settings.lms_output_csv ?? false,
       xlsx: settings.lms_output_xlsx ?? false,
       yaml: settings.lms_output_yaml ?? true,
@@ -420,11 +524,14 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
       user: settings.git_user || "",
       baseUrl: settings.git_base_url || "https://gitlab.tue.nl",
       studentReposGroup: settings.git_student_repos_group || "",
+      orgTemplate: settings.git_org_template || "",
       templateGroup: settings.git_template_group || "",
       yamlFile: settings.yaml_file || "students.yaml",
       targetFolder: settings.target_folder || "",
       assignments: settings.assignments || "",
       directoryLayout: (settings.directory_layout || "flat") as "by-team" | "flat" | "by-task",
+      privateRepos: settings.git_private_repos ?? true,
+      publicFallback: settings.git_public_fallback ?? false,
       logLevels: {
         info: settings.log_info ?? true,
         debug: settings.log_debug ?? false,
@@ -441,6 +548,7 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
     const newOptionsLocked = settings.options_locked ?? true;
     setConfigLocked(newConfigLocked);
     setOptionsLocked(newOptionsLocked);
+    setRecentCourses(settings.recent_courses ?? []);
 
     // Update last saved state (deep copy to prevent reference issues)
     setLastSavedState({
@@ -463,8 +571,11 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
         lms_custom_url: lmsForm.customUrl,
         lms_url_option: lmsForm.urlOption,
         lms_access_token: lmsForm.accessToken,
+        moodle_base_url: lmsForm.moodleUrl,
+        moodle_access_token: lmsForm.moodleToken,
         lms_course_id: lmsForm.courseId,
         lms_course_name: lmsForm.courseName,
+        lms_course_term: lmsForm.courseTerm,
         lms_yaml_file: lmsForm.yamlFile,
         lms_info_folder: lmsForm.infoFileFolder,
         lms_csv_file: lmsForm.csvFile,
@@ -474,6 +585,11 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
         lms_include_member: lmsForm.includeMember,
         lms_include_initials: lmsForm.includeInitials,
         lms_full_groups: lmsForm.fullGroups,
+        lms_include_test_students: lmsForm.includeTestStudents,
+        lms_git_id_source: lmsForm.gitIdSource,
+        lms_surname_source: lmsForm.surnameSource,
+        lms_name_extraction: lmsForm.nameExtraction,
+        lms_include_roles: lmsForm.includeRoles,
         lms_output_csv: lmsForm.csv,
         lms_output_xlsx: lmsForm.xlsx,
         lms_output_yaml: lmsForm.yaml,
@@ -483,13 +599,22 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
         git_access_token: form.accessToken,
         git_user: form.user,
         git_student_repos_group: form.studentReposGroup,
+        git_org_template: form.orgTemplate,
         git_template_group: form.templateGroup,
+        git_private_repos: form.privateRepos,
+        git_public_fallback: form.publicFallback,
+        git_max_network_operations: currentGuiSettings?.git_max_network_operations ?? 0,
+        git_max_kib_per_second: currentGuiSettings?.git_max_kib_per_second ?? 0,
+        http_headers: currentGuiSettings?.http_headers ?? {},
+        http_user_agent: currentGuiSettings?.http_user_agent ?? "",
 
         // Repository setup settings
         yaml_file: form.yamlFile,
         target_folder: form.targetFolder,
         assignments: form.assignments,
         directory_layout: form.directoryLayout,
+        repo_name_template: currentGuiSettings?.repo_name_template ?? "{team}-{assignment}",
+        file_extension_check: currentGuiSettings?.file_extension_check ?? "warn",
 
         // Logging settings
         log_info: form.logLevels.info,
@@ -505,6 +630,7 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
         window_height: 0,
         window_x: 0,
         window_y: 0,
+        recent_courses: recentCourses,
       };
 
       await invoke("save_settings", { settings });
@@ -547,7 +673,7 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
   };
 
   const openLmsTokenDialog = async () => {
-    setLmsTokenDialogValue(lmsForm.accessToken);
+    setLmsTokenDialogValue(lmsAccessToken(lmsForm));
     setLmsTokenDialogOpen(true);
 
     // Load instructions
@@ -567,13 +693,13 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
   };
 
   const saveLmsToken = () => {
-    updateLmsForm("accessToken", lmsTokenDialogValue);
+    updateLmsForm(lmsForm.lmsType === "Moodle" ? "moodleToken" : "accessToken", lmsTokenDialogValue);
     setLmsTokenDialogOpen(false);
   };
 
   const openLmsTokenUrl = async () => {
     try {
-      const baseUrl = lmsForm.urlOption === "TUE" ? lmsForm.baseUrl : lmsForm.customUrl;
+      const baseUrl = lmsBaseUrl(lmsForm);
       await invoke("open_token_url", {
         base_url: baseUrl,
         lms_type: lmsForm.lmsType,
@@ -589,22 +715,21 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
     }
   };
 
+  // Output locations use a save dialog, so the files do not have to exist yet
   const browseLmsYamlFile = async () => {
-    const selected = await open({
-      multiple: false,
-      filters: [{ name: "YAML Files", extensions: ["yaml", "yml"] }],
+    const selected = await invoke<string | null>("choose_output_path", {
+      params: { kind: "yaml", current: lmsForm.yamlFile, folder: lmsForm.infoFileFolder },
     });
-    if (selected && typeof selected === "string") {
+    if (selected) {
       updateLmsForm("yamlFile", selected);
     }
   };
 
   const browseLmsInfoFolder = async () => {
-    const selected = await open({
-      directory: true,
-      multiple: false,
+    const selected = await invoke<string | null>("choose_output_path", {
+      params: { kind: "folder", current: lmsForm.infoFileFolder },
     });
-    if (selected && typeof selected === "string") {
+    if (selected) {
       updateLmsForm("infoFileFolder", selected);
     }
   };
@@ -642,10 +767,13 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
         "verify_lms_course",
         {
           params: {
-            base_url: lmsForm.urlOption === "TUE" ? lmsForm.baseUrl : lmsForm.customUrl,
-            access_token: lmsForm.accessToken,
+            base_url: lmsBaseUrl(lmsForm),
+            access_token: lmsAccessToken(lmsForm),
             course_id: lmsForm.courseId,
             lms_type: lmsForm.lmsType,
+            org_template: form.orgTemplate,
+            term: lmsForm.courseTerm,
+            course_name: lmsForm.courseName,
           },
         }
       );
@@ -659,53 +787,92 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
       if (result.details) {
         const match = result.details.match(/Course Name: (.+)/);
         if (match) {
-          updateLmsForm("courseName", match[1]);
+          // A stored name that differs points at a stale or mistyped course id
+          const mismatch = result.details.includes("Course name mismatch");
+          const update =
+            !mismatch ||
+            (await ask(
+              `The stored course name "${lmsForm.courseName}" differs from "${match[1]}". ` +
+                "Check the course id if this is not the course you meant.\n\nUpdate the stored name?",
+              { title: "Course name mismatch", kind: "warning" }
+            ));
+          if (update) {
+            updateLmsForm("courseName", match[1]);
+          }
         }
+        // Pre-fill the student repos group derived from the course
+        const groupMatch = result.details.match(/Student Repos Group: (.+)/);
+        if (groupMatch && !form.studentReposGroup) {
+          updateForm("studentReposGroup", groupMatch[1]);
+        }
+      }
+
+      if (result.success) {
+        setRecentCourses(await invoke<RecentCourse[]>("get_recent_courses"));
       }
     } catch (error) {
       appendOutput(`✗ Error: ${error}`);
     }
   };
 
+  // Progress of fetch, setup and clone: "[PROGRESS]" counters replace the previous
+  // counter line, other messages are appended
+  const createProgressChannel = () => {
+    const PROGRESS_PREFIX = "[PROGRESS]";
+    const PROGRESS_DISPLAY_PREFIX = "(progress) ";
+    const channel = new Channel<string>();
+    channel.onmessage = (message) => {
+      if (message.startsWith(PROGRESS_PREFIX)) {
+        const progressText = message.slice(PROGRESS_PREFIX.length).trimStart();
+        const displayLine = `${PROGRESS_DISPLAY_PREFIX}${progressText}`;
+        setOutputText((prev) => {
+          const lines = prev.split("\n");
+          while (lines.length && lines[lines.length - 1].trim() === "") {
+            lines.pop();
+          }
+          if (
+            lines.length > 0 &&
+            lines[lines.length - 1].startsWith(PROGRESS_DISPLAY_PREFIX)
+          ) {
+            lines[lines.length - 1] = displayLine;
+          } else {
+            lines.push(displayLine);
+          }
+          return lines.join("\n");
+        });
+        return;
+      }
+
+      appendOutput(message);
+    };
+
+    return channel;
+  };
+
+  const clearLmsCache = async () => {
+    try {
+      const result = await invoke<{ success: boolean; message: string }>("clear_lms_cache", {
+        courseId: null,
+      });
+      appendOutput(result.message);
+    } catch (error) {
+      appendOutput(`✗ Error: ${error}`);
+    }
+  };
+
   const generateLmsFiles = async () => {
     try {
       const lmsLabel = lmsForm.lmsType || "LMS";
       appendOutput(`Generating files from ${lmsLabel}...`);
 
-      const PROGRESS_PREFIX = "[PROGRESS]";
-      const PROGRESS_DISPLAY_PREFIX = "(progress) ";
-      const progressChannel = new Channel<string>();
-      progressChannel.onmessage = (message) => {
-        if (message.startsWith(PROGRESS_PREFIX)) {
-          const progressText = message.slice(PROGRESS_PREFIX.length).trimStart();
-          const displayLine = `${PROGRESS_DISPLAY_PREFIX}${progressText}`;
-          setOutputText((prev) => {
-            const lines = prev.split("\n");
-            while (lines.length && lines[lines.length - 1].trim() === "") {
-              lines.pop();
-            }
-            if (
-              lines.length > 0 &&
-              lines[lines.length - 1].startsWith(PROGRESS_DISPLAY_PREFIX)
-            ) {
-              lines[lines.length - 1] = displayLine;
-            } else {
-              lines.push(displayLine);
-            }
-            return lines.join("\n");
-          });
-          return;
-        }
-
-        appendOutput(message);
-      };
+      const progressChannel = createProgressChannel();
 
       const result = await invoke<{ success: boolean; message: string; details?: string }>(
         "generate_lms_files",
         {
           params: {
-            base_url: lmsForm.urlOption === "TUE" ? lmsForm.baseUrl : lmsForm.customUrl,
-            access_token: lmsForm.accessToken,
+            base_url: lmsBaseUrl(lmsForm),
+            access_token: lmsAccessToken(lmsForm),
             course_id: lmsForm.courseId,
             lms_type: lmsForm.lmsType,
             yaml_file: lmsForm.yamlFile,
@@ -717,6 +884,13 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
             include_member: lmsForm.includeMember,
             include_initials: lmsForm.includeInitials,
             full_groups: lmsForm.fullGroups,
+            include_test_students: lmsForm.includeTestStudents,
+            refresh: refreshLmsCache,
+            per_group_set: perGroupSet,
+            git_id_source: lmsForm.gitIdSource,
+            surname_source: lmsForm.surnameSource,
+            name_extraction: lmsForm.nameExtraction,
+            include_roles: lmsForm.includeRoles,
             csv: lmsForm.csv,
             xlsx: lmsForm.xlsx,
             yaml: lmsForm.yaml,
@@ -766,6 +940,52 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
     }
   };
 
+  const loadTemplates = async () => {
+    try {
+      const templates = await invoke<string[]>("list_templates", {
+        config: {
+          access_token: form.accessToken,
+          user: form.user,
+          base_url: form.baseUrl,
+          student_repos_group: form.studentReposGroup,
+          template_group: form.templateGroup,
+        },
+      });
+      setAvailableTemplates(templates);
+      appendOutput(`Found ${templates.length} templates`);
+    } catch (error) {
+      appendOutput(`✗ Error: ${error}`);
+    }
+  };
+
+  const previewTemplates = async () => {
+    const templates = form.assignments.split(",").map((a) => a.trim()).filter((a) => a);
+    for (const template of templates) {
+      try {
+        const files = await invoke<{ name: string; kind: string }[]>("list_template_files", {
+          config: {
+            access_token: form.accessToken,
+            user: form.user,
+            base_url: form.baseUrl,
+            student_repos_group: form.studentReposGroup,
+            template_group: form.templateGroup,
+          },
+          template,
+          gitRef: null,
+        });
+        appendOutput(`${template}:`);
+        if (files.length === 0) {
+          appendOutput("  (empty repository)");
+        }
+        for (const file of files) {
+          appendOutput(`  ${file.name}${file.kind === "dir" ? "/" : ""}`);
+        }
+      } catch (error) {
+        appendOutput(`✗ Error: ${error}`);
+      }
+    }
+  };
+
   const createStudentRepos = async () => {
     try {
       appendOutput("Creating student repositories...");
@@ -773,21 +993,36 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
       appendOutput(`Assignments: ${form.assignments}`);
       appendOutput("");
 
+      const params = {
+        config: {
+          access_token: form.accessToken,
+          user: form.user,
+          base_url: form.baseUrl,
+          student_repos_group: form.studentReposGroup,
+          template_group: form.templateGroup,
+        },
+        yaml_file: form.yamlFile,
+        assignments: form.assignments,
+        private: form.privateRepos,
+        public_fallback: form.publicFallback,
+      };
+
+      // Check everything setup needs before creating anything
+      const report = await invoke<{ checks: PreflightCheck[] }>("preflight_setup", { params });
+      const symbols = { passed: "✓", warning: "⚠", failed: "✗", skipped: "-" };
+      report.checks.forEach((check) => {
+        appendOutput(`${symbols[check.status]} ${check.name}: ${check.message}`);
+        check.details.forEach((detail) => appendOutput(`    ${detail}`));
+      });
+      if (report.checks.some((check) => check.status === "failed")) {
+        appendOutput("✗ Setup not started, fix the failed checks first");
+        return;
+      }
+      appendOutput("");
+
       const result = await invoke<{ success: boolean; message: string; details?: string }>(
         "setup_repos",
-        {
-          params: {
-            config: {
-              access_token: form.accessToken,
-              user: form.user,
-              base_url: form.baseUrl,
-              student_repos_group: form.studentReposGroup,
-              template_group: form.templateGroup,
-            },
-            yaml_file: form.yamlFile,
-            assignments: form.assignments,
-          },
-        }
+        { params, progress: createProgressChannel() }
       );
 
       appendOutput("");
@@ -800,6 +1035,21 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
     }
   };
 
+  const validateTeamsFile = async () => {
+    try {
+      appendOutput(`Validating ${form.yamlFile}...`);
+      const problems = await invoke<string[]>("validate_teams_file", { path: form.yamlFile });
+      if (problems.length === 0) {
+        appendOutput("✓ No problems found");
+      } else {
+        problems.forEach((problem) => appendOutput(`  ✗ ${problem}`));
+        appendOutput(`✗ ${problems.length} problems found`);
+      }
+    } catch (error) {
+      appendOutput(`✗ Error: ${error}`);
+    }
+  };
+
   const cloneRepos = async () => {
     try {
       appendOutput("Cloning repositories...");
@@ -820,6 +1070,7 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
             target_folder: form.targetFolder,
             directory_layout: form.directoryLayout,
           },
+          progress: createProgressChannel(),
         }
       );
 
@@ -882,9 +1133,36 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
     showClosePrompt(win);
   };
 
-  const openTokenDialog = () => {
+  const openTokenDialog = async () => {
     setTokenDialogValue(form.accessToken);
     setTokenDialogOpen(true);
+
+    // Load instructions, the platform is detected from the base URL
+    setTokenInstructions("");
+    try {
+      const guide = await invoke<{ service: string; url: string; instructions: string }>(
+        "get_token_guide",
+        { service: "", base_url: form.baseUrl, open: false }
+      );
+      setTokenInstructions(guide.instructions);
+      setShowTokenInstructions(false); // Start collapsed
+    } catch (error) {
+      // Local platforms need no token
+      console.error("Failed to load token instructions:", error);
+    }
+  };
+
+  const openGitTokenUrl = async () => {
+    try {
+      await invoke("get_token_guide", { service: "", base_url: form.baseUrl, open: true });
+      setShowTokenInstructions(true);
+    } catch (error) {
+      console.error("Failed to open token URL:", error);
+      await message(`Failed to open token URL: ${error}`, {
+        title: "Open token URL failed",
+        kind: "error",
+      });
+    }
   };
 
   const closeTokenDialog = () => {
@@ -959,10 +1237,14 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
                     <Select.Option value="CUSTOM">Custom</Select.Option>
                   </Select>
                   <Input
-                    value={lmsForm.urlOption === "TUE" ? lmsForm.baseUrl : lmsForm.customUrl}
+                    value={lmsBaseUrl(lmsForm)}
                     onChange={(e) =>
                       updateLmsForm(
-                        lmsForm.urlOption === "TUE" ? "baseUrl" : "customUrl",
+                        lmsForm.lmsType === "Moodle"
+                          ? "moodleUrl"
+                          : lmsForm.urlOption === "TUE"
+                            ? "baseUrl"
+                            : "customUrl",
                         e.target.value
                       )
                     }
@@ -981,13 +1263,13 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
               >
                 <Space.Compact style={{ width: "100%" }}>
                   <Input.Password
-                    value={lmsForm.accessToken}
+                    value={lmsAccessToken(lmsForm)}
                     readOnly
                     placeholder="Click Set to add token"
                     style={{ flex: 1 }}
                   />
                   <Button onClick={openLmsTokenDialog}>
-                    {lmsForm.accessToken ? "Edit" : "Set"}
+                    {lmsAccessToken(lmsForm) ? "Edit" : "Set"}
                   </Button>
                 </Space.Compact>
               </Form.Item>
@@ -1006,6 +1288,23 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
                     placeholder="Enter course ID"
                     style={{ flex: 1 }}
                   />
+                  {recentCourses.length > 0 && (
+                    <Select
+                      value={null}
+                      placeholder="Recent"
+                      style={{ width: 140 }}
+                      popupMatchSelectWidth={false}
+                      options={recentCourses.map((course) => ({
+                        value: course.id,
+                        label: `${course.name} (${course.id})`,
+                      }))}
+                      onChange={(id: string) => {
+                        const course = recentCourses.find((c) => c.id === id);
+                        updateLmsForm("courseId", id);
+                        updateLmsForm("courseName", course?.name ?? "");
+                      }}
+                    />
+                  )}
                   <Button onClick={verifyLmsCourse}>Verify</Button>
                 </Space.Compact>
               </Form.Item>
@@ -1018,6 +1317,20 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
                   />
                 </Form.Item>
               )}
+
+              <Form.Item
+                label={
+                  <Tooltip mouseEnterDelay={0.6} title="The term of the course, e.g. '2024' or '2024-Q3'. Used for {term} in the organization template on the repository tab.">
+                    <span style={{ borderBottom: "1px dashed #666" }}>Course Term</span>
+                  </Tooltip>
+                }
+              >
+                <Input
+                  value={lmsForm.courseTerm}
+                  onChange={(e) => updateLmsForm("courseTerm", e.target.value)}
+                  placeholder="e.g. 2024"
+                />
+              </Form.Item>
             </Form>
           </Card>
 
@@ -1134,10 +1447,47 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
                   >
                     Full Groups Only
                   </Checkbox>
+                  <Tooltip mouseEnterDelay={0.6} title="Canvas creates a Test Student account for its Student View. It is left out of the roster unless this is checked.">
+                    <Checkbox
+                      checked={lmsForm.includeTestStudents}
+                      onChange={(e) => updateLmsForm("includeTestStudents", e.target.checked)}
+                    >
+                      Include Test Student
+                    </Checkbox>
+                  </Tooltip>
+                  <Tooltip mouseEnterDelay={0.6} title="Fetched course data is kept on disk for a day, so generating the files again does not fetch the course again. Check this to fetch everything from the LMS.">
+                    <Checkbox
+                      checked={refreshLmsCache}
+                      onChange={(e) => setRefreshLmsCache(e.target.checked)}
+                    >
+                      Refresh Cached Data
+                    </Checkbox>
+                  </Tooltip>
+                  <Tooltip mouseEnterDelay={0.6} title="Write one YAML file per Canvas group set, e.g. students-projects.yaml and students-labs.yaml, for courses that use a different group set per assignment.">
+                    <Checkbox
+                      checked={perGroupSet}
+                      disabled={lmsForm.lmsType !== "Canvas"}
+                      onChange={(e) => setPerGroupSet(e.target.checked)}
+                    >
+                      One File per Group Set
+                    </Checkbox>
+                  </Tooltip>
                 </Card>
               </Col>
             </Row>
           </Card>
+
+          {/* YAML Preview */}
+          <Card title="YAML preview (sample students)" size="small" style={{ marginBottom: 8 }}>
+            <pre style={{ margin: 0, fontSize: "11px", maxHeight: 160, overflow: "auto" }}>
+              {yamlPreview?.yaml || "(no teams)"}
+            </pre>
+            {yamlPreview?.notes.map((note) => (
+              <div key={note} style={{ fontSize: "11px", color: "#8c8c8c" }}>
+                {note}
+              </div>
+            ))}
+          </Card>
           </div>
 
           {/* Action Buttons */}
@@ -1147,6 +1497,9 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
                 Generate Files
               </Button>
             </Tooltip>
+            <Tooltip mouseEnterDelay={0.6} title="Removes the course data kept on disk from earlier runs, for all courses.">
+              <Button onClick={clearLmsCache}>Clear Cache</Button>
+            </Tooltip>
             <div style={{ flex: 1 }} />
             <Button onClick={() => setSettingsMenuOpen(true)}>Settings...</Button>
             <Button onClick={saveSettingsToDisk}>Save Settings</Button>
@@ -1253,6 +1606,21 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
                 />
               </Form.Item>
 
+              <Form.Item
+                label={
+                  <Tooltip mouseEnterDelay={0.6} title="Template used to fill in an empty Student Repos Group when a course is verified, with {course_code}, {course_id} and {term} placeholders (e.g., 'courses/{term}/{course_code}'). Leave empty to type the group manually.">
+                    <span style={{ borderBottom: "1px dashed #666" }}>Organization Template</span>
+                  </Tooltip>
+                }
+              >
+                <Input
+                  value={form.orgTemplate}
+                  onChange={(e) => updateForm("orgTemplate", e.target.value)}
+                  placeholder="e.g. {course_code}-{term}"
+                  disabled={configLocked}
+                />
+              </Form.Item>
+
               <Form.Item
                 label={
                   <Tooltip mouseEnterDelay={0.6} title="The GitLab group path or GitHub organization containing template repositories. Each assignment name you specify should have a corresponding template repository in this group. For example, if this is 'courses/2024/templates' and you create 'lab1', it will copy from 'courses/2024/templates/lab1'.">
@@ -1286,6 +1654,7 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
                 style={{ flex: 1 }}
               />
               <Button onClick={browseYamlFile}>Browse</Button>
+              <Button onClick={validateTeamsFile}>Validate</Button>
             </Space.Compact>
           </Form.Item>
 
@@ -1318,10 +1687,18 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
               </Tooltip>
             }
           >
-            <Input
-              value={form.assignments}
-              onChange={(e) => updateForm("assignments", e.target.value)}
-            />
+            <Space.Compact style={{ width: "100%" }}>
+              <Select
+                mode="tags"
+                value={form.assignments.split(",").map((a) => a.trim()).filter((a) => a)}
+                onChange={(values: string[]) => updateForm("assignments", values.join(","))}
+                options={availableTemplates.map((t) => ({ value: t, label: t }))}
+                tokenSeparators={[","]}
+                style={{ flex: 1 }}
+              />
+              <Button onClick={loadTemplates}>Load</Button>
+              <Button onClick={previewTemplates}>Preview</Button>
+            </Space.Compact>
           </Form.Item>
         </Form>
       </Card>
@@ -1382,6 +1759,27 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
               </Col>
             </Row>
           </Form.Item>
+
+          <Form.Item label="Setup">
+            <Space>
+              <Checkbox
+                checked={form.privateRepos}
+                onChange={(e) => updateForm("privateRepos", e.target.checked)}
+                disabled={optionsLocked}
+              >
+                Private repositories
+              </Checkbox>
+              <Tooltip mouseEnterDelay={0.6} title="Some self-hosted instances and free organizations cannot create private repositories. When checked, setup then creates public repositories with a warning instead of failing.">
+                <Checkbox
+                  checked={form.publicFallback}
+                  onChange={(e) => updateForm("publicFallback", e.target.checked)}
+                  disabled={optionsLocked || !form.privateRepos}
+                >
+                  Fall back to public
+                </Checkbox>
+              </Tooltip>
+            </Space>
+          </Form.Item>
         </Form>
       </Card>
       </div>
@@ -1439,22 +1837,64 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
       <Modal
         title={`${form.accessToken ? "Edit" : "Set"} Git Access Token`}
         open={tokenDialogOpen}
-        onOk={saveToken}
         onCancel={closeTokenDialog}
-        okText="OK"
-        cancelText="Cancel"
+        footer={[
+          <Button key="get" onClick={openGitTokenUrl} disabled={!tokenInstructions}>
+            Get Token
+          </Button>,
+          <Button key="ok" type="primary" onClick={saveToken}>
+            OK
+          </Button>,
+          <Button key="cancel" onClick={closeTokenDialog}>
+            Cancel
+          </Button>
+        ]}
+        width={500}
       >
-        <Input
-          value={tokenDialogValue}
-          onChange={(e) => setTokenDialogValue(e.target.value)}
-          placeholder="Enter access token"
-          autoFocus
-        />
+        <Space direction="vertical" style={{ width: "100%" }}>
+          {tokenInstructions && (
+            <Collapse
+              activeKey={showTokenInstructions ? ["instructions"] : []}
+              onChange={(keys) => setShowTokenInstructions(keys.includes("instructions"))}
+              items={[
+                {
+                  key: "instructions",
+                  label: "How to Get Token",
+                  children: (
+                    <div
+                      style={{
+                        backgroundColor: "#f5f5f5",
+                        padding: "12px",
+                        borderRadius: "4px",
+                        fontSize: "12px",
+                        whiteSpace: "pre-wrap",
+                        maxHeight: "200px",
+                        overflowY: "auto",
+                        fontFamily: "monospace",
+                        lineHeight: "1.5",
+                      }}
+                    >
+                      <strong>Note: Click the "Get Token" button below to open the access token creation page in your browser</strong>
+                      {"\n\n"}
+                      {tokenInstructions}
+                    </div>
+                  )
+                }
+              ]}
+            />
+          )}
+          <Input
+            value={tokenDialogValue}
+            onChange={(e) => setTokenDialogValue(e.target.value)}
+            placeholder="Enter access token"
+            autoFocus={!showTokenInstructions}
+          />
+        </Space>
       </Modal>
 
       {/* LMS Token Edit Dialog */}
       <Modal
-        title={`${lmsForm.accessToken ? "Edit" : "Set"} ${lmsForm.lmsType} Access Token`}
+        title={`${lmsAccessToken(lmsForm) ? "Edit" : "Set"} ${lmsForm.lmsType} Access Token`}
         open={lmsTokenDialogOpen}
         onCancel={closeLmsTokenDialog}
         footer={[
@@ -1521,8 +1961,11 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
           lms_custom_url: lmsForm.customUrl,
           lms_url_option: lmsForm.urlOption,
           lms_access_token: lmsForm.accessToken,
+          moodle_base_url: lmsForm.moodleUrl,
+          moodle_access_token: lmsForm.moodleToken,
           lms_course_id: lmsForm.courseId,
           lms_course_name: lmsForm.courseName,
+          lms_course_term: lmsForm.courseTerm,
           lms_yaml_file: lmsForm.yamlFile,
           lms_info_folder: lmsForm.infoFileFolder,
           lms_csv_file: lmsForm.csvFile,
@@ -1532,6 +1975,11 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
           lms_include_member: lmsForm.includeMember,
           lms_include_initials: lmsForm.includeInitials,
           lms_full_groups: lmsForm.fullGroups,
+          lms_include_test_students: lmsForm.includeTestStudents,
+          lms_git_id_source: lmsForm.gitIdSource,
+          lms_surname_source: lmsForm.surnameSource,
+          lms_name_extraction: lmsForm.nameExtraction,
+          lms_include_roles: lmsForm.includeRoles,
           lms_output_csv: lmsForm.csv,
           lms_output_xlsx: lmsForm.xlsx,
           lms_output_yaml: lmsForm.yaml,
@@ -1539,11 +1987,20 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
           git_access_token: form.accessToken,
           git_user: form.user,
           git_student_repos_group: form.studentReposGroup,
+          git_org_template: form.orgTemplate,
           git_template_group: form.templateGroup,
+          git_private_repos: form.privateRepos,
+          git_public_fallback: form.publicFallback,
+          git_max_network_operations: 0,
+          git_max_kib_per_second: 0,
+          http_headers: {},
+          http_user_agent: "",
           yaml_file: form.yamlFile,
           target_folder: form.targetFolder,
           assignments: form.assignments,
           directory_layout: form.directoryLayout,
+          repo_name_template: "{team}-{assignment}",
+          file_extension_check: "warn",
           log_info: form.logLevels.info,
           log_debug: form.logLevels.debug,
           log_warning: form.logLevels.warning,
@@ -1555,6 +2012,7 @@ const [activeTab, setActiveTab] = useState<TabType>("lms");
           window_height: 0,
           window_x: 0,
           window_y: 0,
+          recent_courses: recentCourses,
         }}
         onSettingsLoaded={handleSettingsLoaded}
         onMessage={appendOutput}
@@ -193,6 +193,28 @@ export function SettingsMenu({
     });
   };
 
+  const handleResetCourse = async () => {
+    modal.confirm({
+      title: "Reset Course Settings",
+      content: "Reset the course, output files, include options and student repository settings?\nURLs and tokens are kept.",
+      okText: "Reset",
+      okType: "danger",
+      cancelText: "Cancel",
+      centered: true,
+      onOk: async () => {
+        try {
+          const settings = await invoke<GuiSettings>("reset_course_settings");
+          onSettingsLoaded(settings);
+          showSuccessFlash();
+          onMessage("✓ Course settings reset to defaults");
+          onClose();
+        } catch (error) {
+          onMessage(`✗ Failed to reset course settings: ${error}`);
+        }
+      },
+    });
+  };
+
   const handleViewSchema = async () => {
     if (!schema) {
       try {
@@ -333,11 +355,17 @@ export function SettingsMenu({
         {/* Reset */}
         <div>
           <Title level={5} style={{ marginBottom: 8 }}>Reset</Title>
-          <Button danger onClick={handleReset} size="small" style={{ marginBottom: 8 }}>
-            Reset to Defaults
-          </Button>
+          <Space style={{ marginBottom: 8 }}>
+            <Button onClick={handleResetCourse} size="small">
+              Reset Course Settings
+            </Button>
+            <Button danger onClick={handleReset} size="small">
+              Reset to Defaults
+            </Button>
+          </Space>
           <Paragraph style={{ fontSize: 11, margin: 0, color: "#666" }}>
-            Reset and save all settings to default values.
+            Reset the course-specific settings when moving to another course, keeping URLs and
+            tokens, or reset and save all settings to default values.
           </Paragraph>
         </div>
 
@@ -11,8 +11,22 @@ export type LmsUrlOption = "TUE" | "CUSTOM";
 /** LMS member identifier format */
 export type LmsMemberOption = "(email, gitid)" | "email" | "git_id";
 
+/** LMS user field used as git username */
+export type LmsGitIdSource = "sis_user_id" | "login_id" | "email_local_part";
+
+/** LMS user field the surname is taken from */
+export type LmsSurnameSource = "sortable_name" | "email";
+
+/** Part of the email address a surname is extracted from; `custom` holds a regex */
+export type LmsNameExtraction =
+  | "after_last_dot"
+  | "before_first_dot"
+  | "full_local_part"
+  | { custom: string };
+
 /** Directory layout for cloned repositories */
 export type DirectoryLayout = "flat" | "by-team" | "by-task";
+export type ExtensionCheck = "warn" | "fix" | "ignore";
 
 /** Active tab in the GUI */
 export type ActiveTab = "lms" | "repo";
@@ -30,17 +44,25 @@ export interface CommonSettings {
   lms_custom_url: string;
   lms_url_option: LmsUrlOption;
   lms_access_token: string;
+  moodle_base_url: string;
+  moodle_access_token: string;
   lms_course_id: string;
   lms_course_name: string;
+  lms_course_term: string;
   lms_yaml_file: string;
   lms_info_folder: string;
   lms_csv_file: string;
   lms_xlsx_file: string;
   lms_member_option: LmsMemberOption;
+  lms_git_id_source: LmsGitIdSource;
+  lms_surname_source: LmsSurnameSource;
+  lms_name_extraction: LmsNameExtraction;
+  lms_include_roles: string[];
   lms_include_group: boolean;
   lms_include_member: boolean;
   lms_include_initials: boolean;
   lms_full_groups: boolean;
+  lms_include_test_students: boolean;
   lms_output_csv: boolean;
   lms_output_xlsx: boolean;
   lms_output_yaml: boolean;
@@ -50,13 +72,22 @@ export interface CommonSettings {
   git_access_token: string;
   git_user: string;
   git_student_repos_group: string;
+  git_org_template: string;
   git_template_group: string;
+  git_private_repos: boolean;
+  git_public_fallback: boolean;
+  git_max_network_operations: number; // 0 = no cap
+  git_max_kib_per_second: number; // 0 = unlimited
+  http_headers: Record<string, string>; // Sent on LMS and git platform API requests
+  http_user_agent: string; // Empty = repobee-tauri/<version>
 
   // Repository setup settings
   yaml_file: string;
   target_folder: string;
   assignments: string;
   directory_layout: DirectoryLayout;
+  repo_name_template: string; // {team} and {assignment} placeholders
+  file_extension_check: ExtensionCheck;
 
   // Logging settings
   log_info: boolean;
@@ -65,6 +96,12 @@ export interface CommonSettings {
   log_error: boolean;
 }
 
+/** A course that was verified recently */
+export interface RecentCourse {
+  id: string;
+  name: string;
+}
+
 /** GUI-specific settings (includes common settings via flattening) */
 export interface GuiSettings extends CommonSettings {
   // GUI-specific fields
@@ -75,6 +112,7 @@ export interface GuiSettings extends CommonSettings {
   window_height: number;
   window_x: number;
   window_y: number;
+  recent_courses: RecentCourse[];
 }
 
 // ===== Default Values =====
@@ -87,17 +125,25 @@ export const DEFAULT_COMMON_SETTINGS: CommonSettings = {
   lms_custom_url: "",
   lms_url_option: "TUE",
   lms_access_token: "",
+  moodle_base_url: "",
+  moodle_access_token: "",
   lms_course_id: "",
   lms_course_name: "",
+  lms_course_term: "",
   lms_yaml_file: "students.yaml",
   lms_info_folder: "",
   lms_csv_file: "student-info.csv",
   lms_xlsx_file: "student-info.xlsx",
   lms_member_option: "(email, gitid)",
+  lms_git_id_source: "sis_user_id",
+  lms_surname_source: "sortable_name",
+  lms_name_extraction: "after_last_dot",
+  lms_include_roles: ["StudentEnrollment"],
   lms_include_group: true,
   lms_include_member: true,
   lms_include_initials: false,
   lms_full_groups: true,
+  lms_include_test_students: false,
   lms_output_csv: false,
   lms_output_xlsx: false,
   lms_output_yaml: true,
@@ -107,13 +153,22 @@ export const DEFAULT_COMMON_SETTINGS: CommonSettings = {
   git_access_token: "",
   git_user: "",
   git_student_repos_group: "",
+  git_org_template: "",
   git_template_group: "",
+  git_private_repos: true,
+  git_public_fallback: false,
+  git_max_network_operations: 0,
+  git_max_kib_per_second: 0,
+  http_headers: {},
+  http_user_agent: "",
 
   // Repository setup settings
   yaml_file: "students.yaml",
   target_folder: "",
   assignments: "",
   directory_layout: "flat",
+  repo_name_template: "{team}-{assignment}",
+  file_extension_check: "warn",
 
   // Logging settings
   log_info: true,
@@ -132,6 +187,7 @@ export const DEFAULT_GUI_SETTINGS: GuiSettings = {
   window_height: 0,
   window_x: 0,
   window_y: 0,
+  recent_courses: [],
 };
 
 // ===== Helper Functions =====